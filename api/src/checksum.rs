@@ -0,0 +1,100 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+    checksum.rs
+
+Abstract:
+
+    File contains the checksum used by checksummed mailbox requests and
+    responses.
+
+--*/
+
+/// Calculate the checksum of a mailbox request or response
+///
+/// The checksum is defined as `0 - (SUM(cmd bytes) + SUM(data bytes))`, where
+/// `data` is everything following the checksum field. Responses use a
+/// command code of zero.
+///
+/// # Arguments
+///
+/// * `cmd` - Command code
+/// * `data` - Request or response bytes following the checksum field
+///
+/// # Returns
+///
+/// * `u32` - Checksum
+pub fn calc_checksum(cmd: u32, data: &[u8]) -> u32 {
+    let mut sum = 0u32;
+    for byte in cmd.to_le_bytes().iter().chain(data) {
+        sum = sum.wrapping_add(*byte as u32);
+    }
+    0u32.wrapping_sub(sum)
+}
+
+/// Extend a checksum with `data` following the bytes it was calculated over
+///
+/// Used for requests followed by data too large to be checksummed in one
+/// buffer.
+///
+/// # Arguments
+///
+/// * `chksum` - Checksum of the preceding bytes
+/// * `data` - Following bytes
+///
+/// # Returns
+///
+/// * `u32` - Checksum
+pub fn extend_checksum(chksum: u32, data: &[u8]) -> u32 {
+    data.iter()
+        .fold(chksum, |chksum, byte| chksum.wrapping_sub(*byte as u32))
+}
+
+/// Verify the checksum of a mailbox request or response
+///
+/// # Arguments
+///
+/// * `chksum` - Checksum to verify
+/// * `cmd` - Command code
+/// * `data` - Request or response bytes following the checksum field
+///
+/// # Returns
+///
+/// * `bool` - True if the checksum matches
+pub fn verify_checksum(chksum: u32, cmd: u32, data: &[u8]) -> bool {
+    calc_checksum(cmd, data) == chksum
+}
+
+#[cfg(all(test, target_family = "unix"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checksum_empty() {
+        assert_eq!(calc_checksum(0, &[]), 0);
+        assert_eq!(calc_checksum(0x0000_0001, &[]), 0xFFFF_FFFF);
+    }
+
+    #[test]
+    fn test_checksum_round_trip() {
+        let data = [0xABu8; 52];
+        let chksum = calc_checksum(0x4D45_4153, &data);
+        assert!(verify_checksum(chksum, 0x4D45_4153, &data));
+        assert!(!verify_checksum(chksum, 0x4D45_4153, &data[1..]));
+        assert!(!verify_checksum(chksum, 0, &data));
+    }
+
+    #[test]
+    fn test_checksum_extend() {
+        let data: Vec<u8> = (0..=255u8).collect();
+        let (head, tail) = data.split_at(100);
+        assert_eq!(
+            extend_checksum(calc_checksum(0x5349_4756, head), tail),
+            calc_checksum(0x5349_4756, &data)
+        );
+        assert_eq!(extend_checksum(0x1234, &[]), 0x1234);
+    }
+}
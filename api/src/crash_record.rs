@@ -0,0 +1,105 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+    crash_record.rs
+
+Abstract:
+
+    File contains the crash record published to the SoC by the fatal error
+    handler of ROM, FMC and Runtime Firmware.
+
+--*/
+
+/// Marker identifying a valid crash record ("CRSH")
+pub const CRASH_RECORD_MARKER: u32 = 0x4352_5348;
+
+/// Crash record written to CPTRA_FW_EXTENDED_ERROR_INFO when the firmware
+/// stops on a fatal error
+#[repr(C)]
+#[derive(Default, Debug, Clone, Copy, Eq, PartialEq)]
+pub struct CrashRecord {
+    /// `CRASH_RECORD_MARKER` if the record is valid
+    pub marker: u32,
+
+    /// Firmware error code
+    pub error_code: u32,
+
+    /// Machine cause register, zero if the error was not caused by a trap
+    pub mcause: u32,
+
+    /// Machine secondary cause register, zero if the error was not caused by
+    /// a trap
+    pub mscause: u32,
+
+    /// Machine exception program counter, zero if the error was not caused
+    /// by a trap
+    pub mepc: u32,
+
+    /// Machine trap value register, zero if the error was not caused by a
+    /// trap
+    pub mtval: u32,
+
+    /// Value of CPTRA_BOOT_STATUS when the error occurred
+    pub boot_status: u32,
+
+    /// Value of CPTRA_RESET_REASON when the error occurred
+    pub reset_reason: u32,
+}
+
+impl CrashRecord {
+    /// Returns true if the record was written by the fatal error handler
+    pub fn is_valid(&self) -> bool {
+        self.marker == CRASH_RECORD_MARKER
+    }
+}
+
+impl From<[u32; 8]> for CrashRecord {
+    fn from(value: [u32; 8]) -> Self {
+        Self {
+            marker: value[0],
+            error_code: value[1],
+            mcause: value[2],
+            mscause: value[3],
+            mepc: value[4],
+            mtval: value[5],
+            boot_status: value[6],
+            reset_reason: value[7],
+        }
+    }
+}
+
+impl From<CrashRecord> for [u32; 8] {
+    fn from(value: CrashRecord) -> Self {
+        [
+            value.marker,
+            value.error_code,
+            value.mcause,
+            value.mscause,
+            value.mepc,
+            value.mtval,
+            value.boot_status,
+            value.reset_reason,
+        ]
+    }
+}
+
+#[cfg(all(test, target_family = "unix"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crash_record_round_trip() {
+        let words = [CRASH_RECORD_MARKER, 0x0100_0002, 2, 0, 0x100, 0, 0x20, 1];
+        let record = CrashRecord::from(words);
+        assert!(record.is_valid());
+        assert_eq!(record.error_code, 0x0100_0002);
+        assert_eq!(record.mepc, 0x100);
+        assert_eq!(record.reset_reason, 1);
+        assert_eq!(<[u32; 8]>::from(record), words);
+
+        assert!(!CrashRecord::from([0; 8]).is_valid());
+    }
+}
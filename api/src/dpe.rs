@@ -0,0 +1,343 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+    dpe.rs
+
+Abstract:
+
+    File contains the DICE Protection Environment (DPE) command and response
+    structures carried by the INVOKE_DPE_COMMAND mailbox command.
+
+--*/
+
+use zerocopy::{AsBytes, FromBytes};
+
+/// Size of a DPE context handle
+pub const CONTEXT_HANDLE_SIZE: usize = 20;
+
+/// DPE context handle
+pub type ContextHandle = [u8; CONTEXT_HANDLE_SIZE];
+
+/// Handle of the default context
+pub const DEFAULT_CONTEXT_HANDLE: ContextHandle = [0u8; CONTEXT_HANDLE_SIZE];
+
+/// Handle returned in place of a context which no longer exists
+pub const INVALID_CONTEXT_HANDLE: ContextHandle = [0xFFu8; CONTEXT_HANDLE_SIZE];
+
+/// Profile implemented by Caliptra (`DPE_PROFILE_IROT_P384_SHA384`)
+pub const DPE_PROFILE_P384_SHA384: u32 = 2;
+
+/// Maximum number of TCI nodes
+pub const MAX_TCI_NODES: usize = 24;
+
+/// TCI type of the node measuring Runtime Firmware ("RTJM")
+pub const TCI_TYPE_RT_JOURNEY: u32 = u32::from_le_bytes(*b"RTJM");
+
+/// DPE command identifiers
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum DpeCommandId {
+    InitializeContext = 0x7,
+    DeriveChild = 0x8,
+    CertifyKey = 0x9,
+    Sign = 0xA,
+    RotateContextHandle = 0xE,
+    DestroyContext = 0xF,
+}
+
+impl From<DpeCommandId> for u32 {
+    fn from(val: DpeCommandId) -> u32 {
+        val as u32
+    }
+}
+
+impl TryFrom<u32> for DpeCommandId {
+    type Error = ();
+    fn try_from(val: u32) -> Result<Self, Self::Error> {
+        match val {
+            0x7 => Ok(DpeCommandId::InitializeContext),
+            0x8 => Ok(DpeCommandId::DeriveChild),
+            0x9 => Ok(DpeCommandId::CertifyKey),
+            0xA => Ok(DpeCommandId::Sign),
+            0xE => Ok(DpeCommandId::RotateContextHandle),
+            0xF => Ok(DpeCommandId::DestroyContext),
+            _ => Err(()),
+        }
+    }
+}
+
+/// DPE response status codes
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum DpeStatus {
+    Ok = 0x0,
+    InternalError = 0x1,
+    InvalidCommand = 0x2,
+    InvalidArgument = 0x3,
+    ArgumentNotSupported = 0x4,
+    InvalidHandle = 0x1000,
+    MaxTcis = 0x1003,
+}
+
+impl From<DpeStatus> for u32 {
+    fn from(val: DpeStatus) -> u32 {
+        val as u32
+    }
+}
+
+/// Header common to every DPE command
+#[repr(C)]
+#[derive(Debug, Clone, Copy, AsBytes, FromBytes, PartialEq, Eq)]
+pub struct CommandHdr {
+    /// `CommandHdr::MAGIC`
+    pub magic: u32,
+
+    /// Command identifier (`DpeCommandId`)
+    pub cmd_id: u32,
+
+    /// Profile the command was built for
+    pub profile: u32,
+}
+
+impl CommandHdr {
+    /// Command magic ("DPEC")
+    pub const MAGIC: u32 = 0x4450_4543;
+
+    /// Header for command `cmd_id`
+    pub fn new(cmd_id: DpeCommandId) -> Self {
+        Self {
+            magic: Self::MAGIC,
+            cmd_id: cmd_id.into(),
+            profile: DPE_PROFILE_P384_SHA384,
+        }
+    }
+}
+
+/// Header common to every DPE response
+#[repr(C)]
+#[derive(Debug, Clone, Copy, AsBytes, FromBytes, PartialEq, Eq)]
+pub struct ResponseHdr {
+    /// `ResponseHdr::MAGIC`
+    pub magic: u32,
+
+    /// Status code (`DpeStatus`)
+    pub status: u32,
+
+    /// Profile implemented by Caliptra
+    pub profile: u32,
+}
+
+impl ResponseHdr {
+    /// Response magic ("DPER")
+    pub const MAGIC: u32 = 0x4450_4552;
+
+    /// Header for a response with `status`
+    pub fn new(status: DpeStatus) -> Self {
+        Self {
+            magic: Self::MAGIC,
+            status: status.into(),
+            profile: DPE_PROFILE_P384_SHA384,
+        }
+    }
+}
+
+/// InitializeContext command
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, AsBytes, FromBytes, PartialEq, Eq)]
+pub struct InitCtxCmd {
+    /// `InitCtxCmd::FLAG_*`
+    pub flags: u32,
+}
+
+impl InitCtxCmd {
+    /// Create a simulation context
+    pub const FLAG_SIMULATION: u32 = 1 << 31;
+
+    /// Create the default context
+    pub const FLAG_DEFAULT: u32 = 1 << 30;
+}
+
+/// Response to InitializeContext and RotateContextHandle
+#[repr(C)]
+#[derive(Debug, Clone, Copy, AsBytes, FromBytes, PartialEq, Eq)]
+pub struct NewHandleResp {
+    pub resp_hdr: ResponseHdr,
+
+    /// Handle of the context
+    pub handle: ContextHandle,
+}
+
+/// DeriveChild command
+#[repr(C)]
+#[derive(Debug, Clone, Copy, AsBytes, FromBytes, PartialEq, Eq)]
+pub struct DeriveChildCmd {
+    /// Handle of the parent context
+    pub handle: ContextHandle,
+
+    /// Measurement of the child (`INPUT_DATA`)
+    pub data: [u8; 48],
+
+    /// `DeriveChildCmd::FLAG_*`
+    pub flags: u32,
+
+    /// 4-byte type of the child TCI
+    pub tci_type: u32,
+}
+
+impl DeriveChildCmd {
+    /// Keep the parent context usable
+    pub const FLAG_RETAIN_PARENT: u32 = 1 << 29;
+
+    /// Make the child the default context
+    pub const FLAG_MAKE_DEFAULT: u32 = 1 << 28;
+}
+
+/// DeriveChild response
+#[repr(C)]
+#[derive(Debug, Clone, Copy, AsBytes, FromBytes, PartialEq, Eq)]
+pub struct DeriveChildResp {
+    pub resp_hdr: ResponseHdr,
+
+    /// Handle of the child context
+    pub handle: ContextHandle,
+
+    /// New handle of the parent context, `INVALID_CONTEXT_HANDLE` if the
+    /// parent was not retained
+    pub parent_handle: ContextHandle,
+}
+
+/// CertifyKey command
+#[repr(C)]
+#[derive(Debug, Clone, Copy, AsBytes, FromBytes, PartialEq, Eq)]
+pub struct CertifyKeyCmd {
+    /// Handle of the context to certify
+    pub handle: ContextHandle,
+
+    /// Reserved, must be 0
+    pub flags: u32,
+
+    /// Key derivation label
+    pub label: [u8; 48],
+}
+
+/// CertifyKey response
+#[repr(C)]
+#[derive(Debug, Clone, Copy, AsBytes, FromBytes, PartialEq, Eq)]
+pub struct CertifyKeyResp {
+    pub resp_hdr: ResponseHdr,
+
+    /// New handle of the context
+    pub new_context_handle: ContextHandle,
+
+    /// X coordinate of the certified public key
+    pub derived_pubkey_x: [u8; 48],
+
+    /// Y coordinate of the certified public key
+    pub derived_pubkey_y: [u8; 48],
+
+    /// Number of valid bytes in `cert`
+    pub cert_size: u32,
+
+    /// DER-encoded leaf certificate, zero padded
+    pub cert: [u8; CertifyKeyResp::CERT_MAX_SIZE],
+}
+
+impl CertifyKeyResp {
+    /// Maximum size of a DER-encoded leaf certificate
+    pub const CERT_MAX_SIZE: usize = 1024;
+
+    /// DER-encoded leaf certificate
+    pub fn cert(&self) -> Option<&[u8]> {
+        self.cert.get(..self.cert_size as usize)
+    }
+}
+
+/// Sign command
+#[repr(C)]
+#[derive(Debug, Clone, Copy, AsBytes, FromBytes, PartialEq, Eq)]
+pub struct SignCmd {
+    /// Handle of the context whose key signs `digest`
+    pub handle: ContextHandle,
+
+    /// Key derivation label
+    pub label: [u8; 48],
+
+    /// `SignCmd::FLAG_*`
+    pub flags: u32,
+
+    /// SHA-384 digest to sign
+    pub digest: [u8; 48],
+}
+
+impl SignCmd {
+    /// Produce an HMAC instead of an ECDSA signature
+    pub const FLAG_IS_SYMMETRIC: u32 = 1 << 30;
+}
+
+/// Sign response
+#[repr(C)]
+#[derive(Debug, Clone, Copy, AsBytes, FromBytes, PartialEq, Eq)]
+pub struct SignResp {
+    pub resp_hdr: ResponseHdr,
+
+    /// New handle of the context
+    pub new_context_handle: ContextHandle,
+
+    /// R portion of the signature
+    pub sig_r: [u8; 48],
+
+    /// S portion of the signature
+    pub sig_s: [u8; 48],
+}
+
+/// RotateContextHandle command
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, AsBytes, FromBytes, PartialEq, Eq)]
+pub struct RotateCtxCmd {
+    /// Handle of the context
+    pub handle: ContextHandle,
+
+    /// `RotateCtxCmd::FLAG_*`
+    pub flags: u32,
+}
+
+impl RotateCtxCmd {
+    /// Make the context the default context
+    pub const FLAG_TARGET_IS_DEFAULT: u32 = 1 << 31;
+}
+
+/// DestroyContext command
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, AsBytes, FromBytes, PartialEq, Eq)]
+pub struct DestroyCtxCmd {
+    /// Handle of the context to destroy along with its descendants
+    pub handle: ContextHandle,
+}
+
+#[cfg(all(test, target_family = "unix"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_id() {
+        for id in [
+            DpeCommandId::InitializeContext,
+            DpeCommandId::DeriveChild,
+            DpeCommandId::CertifyKey,
+            DpeCommandId::Sign,
+            DpeCommandId::RotateContextHandle,
+            DpeCommandId::DestroyContext,
+        ] {
+            assert_eq!(DpeCommandId::try_from(u32::from(id)), Ok(id));
+        }
+        assert_eq!(DpeCommandId::try_from(0), Err(()));
+    }
+
+    #[test]
+    fn test_tci_type() {
+        assert_eq!(TCI_TYPE_RT_JOURNEY.to_le_bytes(), *b"RTJM");
+    }
+}
@@ -0,0 +1,25 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+    lib.rs
+
+Abstract:
+
+    File contains the mailbox and crash record API shared by Caliptra
+    firmware and host software.
+
+--*/
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+mod checksum;
+mod crash_record;
+pub mod dpe;
+pub mod mailbox;
+
+pub use checksum::{calc_checksum, extend_checksum, verify_checksum};
+pub use crash_record::{CrashRecord, CRASH_RECORD_MARKER};
+pub use mailbox::{CommandId, MailboxReq, MailboxReqHeader, MailboxResp, MailboxRespHeader};
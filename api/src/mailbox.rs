@@ -0,0 +1,617 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+    mailbox.rs
+
+Abstract:
+
+    File contains mailbox command identifiers and the request and response
+    structures exchanged with ROM and Runtime Firmware.
+
+--*/
+
+use crate::checksum::{calc_checksum, extend_checksum, verify_checksum};
+use crate::dpe::CertifyKeyResp;
+use core::mem::size_of;
+use zerocopy::{AsBytes, FromBytes};
+
+/// Mailbox command identifiers
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum CommandId {
+    /// Load a firmware image bundle ("FWLD"). Handled by ROM on cold boot and
+    /// by Runtime Firmware for impactless updates. The request is the raw
+    /// image bundle and is not checksummed.
+    FirmwareLoad = 0x4657_4C44,
+
+    /// Image sections following a manifest-only firmware load ("FWCH").
+    /// Images larger than the mailbox are loaded as a FIRMWARE_LOAD request
+    /// carrying only the manifest, followed by the rest of the image in order
+    /// in requests of this command. The requests are not checksummed.
+    FirmwareLoadChunk = 0x4657_4348,
+
+    /// IDevID CSR sent by ROM to the SoC ("IDEV"). The request is the raw DER
+    /// CSR and is not checksummed.
+    GetIdevCsr = 0x4944_4556,
+
+    /// IDevID certificate provisioned by the SoC to ROM ("IDCP"). The request
+    /// is the 96-byte manufacturing HSM public key followed by the raw DER
+    /// certificate, and is not checksummed.
+    ProvisionIdevCert = 0x4944_4350,
+
+    /// Debug unlock challenge sent by ROM to the SoC ("DBGC"). The request is
+    /// the raw 48-byte challenge and is not checksummed.
+    DebugUnlockChallenge = 0x4442_4743,
+
+    /// Debug unlock token sent by the SoC to ROM ("DBGT"). The request is the
+    /// owner public keys followed by the owner ECC signature over
+    /// `DEBUG_UNLOCK_LABEL` and the challenge, and is not checksummed.
+    DebugUnlockToken = 0x4442_4754,
+
+    /// ECDSA-384 signature verification ("SIGV")
+    Ecdsa384Verify = 0x5349_4756,
+
+    /// LMS signature verification ("LMSV")
+    LmsVerify = 0x4C4D_5356,
+
+    /// Stash a measurement ("MEAS")
+    StashMeasurement = 0x4D45_4153,
+
+    /// Invoke a DPE command ("DPEC")
+    InvokeDpe = 0x4450_4543,
+
+    /// Get the provisioned IDevID certificate from Runtime Firmware ("GEID")
+    GetIdevIdCert = 0x4745_4944,
+
+    /// Get the LDevID certificate from Runtime Firmware ("GELD")
+    GetLdevIdCert = 0x4745_4C44,
+
+    /// Get the FMC Alias certificate ("CERF")
+    GetFmcAliasCert = 0x4345_5246,
+
+    /// Get the RT Alias certificate ("CERR")
+    GetRtAliasCert = 0x4345_5252,
+
+    /// Get a signed quote of the PCRs ("PCRQ")
+    QuotePcrs = 0x5043_5251,
+}
+
+impl From<CommandId> for u32 {
+    fn from(val: CommandId) -> u32 {
+        val as u32
+    }
+}
+
+impl TryFrom<u32> for CommandId {
+    type Error = ();
+    fn try_from(val: u32) -> Result<Self, Self::Error> {
+        match val {
+            0x4657_4C44 => Ok(CommandId::FirmwareLoad),
+            0x4657_4348 => Ok(CommandId::FirmwareLoadChunk),
+            0x4944_4556 => Ok(CommandId::GetIdevCsr),
+            0x4944_4350 => Ok(CommandId::ProvisionIdevCert),
+            0x4442_4743 => Ok(CommandId::DebugUnlockChallenge),
+            0x4442_4754 => Ok(CommandId::DebugUnlockToken),
+            0x5349_4756 => Ok(CommandId::Ecdsa384Verify),
+            0x4C4D_5356 => Ok(CommandId::LmsVerify),
+            0x4D45_4153 => Ok(CommandId::StashMeasurement),
+            0x4450_4543 => Ok(CommandId::InvokeDpe),
+            0x4745_4944 => Ok(CommandId::GetIdevIdCert),
+            0x4745_4C44 => Ok(CommandId::GetLdevIdCert),
+            0x4345_5246 => Ok(CommandId::GetFmcAliasCert),
+            0x4345_5252 => Ok(CommandId::GetRtAliasCert),
+            0x5043_5251 => Ok(CommandId::QuotePcrs),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Prefix of the data signed in a debug unlock token, followed by the
+/// challenge. It keeps other signatures made with the owner key from being
+/// used as a token.
+pub const DEBUG_UNLOCK_LABEL: [u8; 21] = *b"Caliptra Debug Unlock";
+
+/// A checksummed mailbox request
+pub trait MailboxReq: AsBytes + FromBytes {
+    /// Command the request is sent with
+    const ID: CommandId;
+
+    /// Response returned by the command
+    type Resp: MailboxResp;
+
+    /// Calculate and store the request checksum
+    fn populate_chksum(&mut self) {
+        let chksum = calc_checksum(
+            Self::ID.into(),
+            self.as_bytes()
+                .get(size_of::<MailboxReqHeader>()..)
+                .unwrap_or_default(),
+        );
+        if let Some(hdr) = self.as_bytes_mut().get_mut(..size_of::<u32>()) {
+            hdr.copy_from_slice(&chksum.to_le_bytes());
+        }
+    }
+
+    /// Calculate and store the checksum of the request followed by `data`
+    ///
+    /// Used by commands which accept data after the request, whose checksum
+    /// covers that data.
+    fn populate_chksum_with_data(&mut self, data: &[u8]) {
+        let chksum = calc_checksum(
+            Self::ID.into(),
+            self.as_bytes()
+                .get(size_of::<MailboxReqHeader>()..)
+                .unwrap_or_default(),
+        );
+        let chksum = extend_checksum(chksum, data);
+        if let Some(hdr) = self.as_bytes_mut().get_mut(..size_of::<u32>()) {
+            hdr.copy_from_slice(&chksum.to_le_bytes());
+        }
+    }
+
+    /// Check the request checksum
+    fn verify_chksum(&self) -> bool {
+        let Some(hdr) = MailboxReqHeader::read_from_prefix(self.as_bytes()) else {
+            return false;
+        };
+        verify_checksum(
+            hdr.chksum,
+            Self::ID.into(),
+            self.as_bytes()
+                .get(size_of::<MailboxReqHeader>()..)
+                .unwrap_or_default(),
+        )
+    }
+}
+
+/// A checksummed mailbox response
+pub trait MailboxResp: AsBytes + FromBytes {
+    /// Calculate and store the response checksum
+    fn populate_chksum(&mut self) {
+        let chksum = calc_checksum(
+            0,
+            self.as_bytes().get(size_of::<u32>()..).unwrap_or_default(),
+        );
+        if let Some(hdr) = self.as_bytes_mut().get_mut(..size_of::<u32>()) {
+            hdr.copy_from_slice(&chksum.to_le_bytes());
+        }
+    }
+
+    /// Check the response checksum
+    fn verify_chksum(&self) -> bool {
+        let Some(hdr) = MailboxRespHeader::read_from_prefix(self.as_bytes()) else {
+            return false;
+        };
+        verify_checksum(
+            hdr.chksum,
+            0,
+            self.as_bytes().get(size_of::<u32>()..).unwrap_or_default(),
+        )
+    }
+}
+
+/// Header common to every checksummed mailbox request
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, AsBytes, FromBytes, PartialEq, Eq)]
+pub struct MailboxReqHeader {
+    /// Checksum over the command code and the rest of the request
+    pub chksum: u32,
+}
+
+/// Header common to every checksummed mailbox response
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, AsBytes, FromBytes, PartialEq, Eq)]
+pub struct MailboxRespHeader {
+    /// Checksum over the rest of the response
+    pub chksum: u32,
+
+    /// Result code
+    pub result: u32,
+}
+
+impl MailboxRespHeader {
+    /// Result code: success
+    pub const RESULT_SUCCESS: u32 = 0x0000_0000;
+
+    /// Result code: signature verification failed ("BSIG")
+    pub const RESULT_BAD_SIG: u32 = 0x4253_4947;
+}
+
+impl MailboxResp for MailboxRespHeader {}
+
+/// ECDSA384_SIGNATURE_VERIFY request
+#[repr(C)]
+#[derive(Debug, Clone, Copy, AsBytes, FromBytes, PartialEq, Eq)]
+pub struct EcdsaVerifyReq {
+    pub hdr: MailboxReqHeader,
+
+    /// Verification flags (`EcdsaVerifyReq::FLAG_*`)
+    pub flags: u32,
+
+    pub digest: [u8; 48],
+    pub pub_key_x: [u8; 48],
+    pub pub_key_y: [u8; 48],
+    pub signature_r: [u8; 48],
+    pub signature_s: [u8; 48],
+}
+
+impl EcdsaVerifyReq {
+    /// Verify the SHA-384 digest of the data following the request instead
+    /// of `digest`. The digest is computed by Caliptra over mailbox SRAM.
+    pub const FLAG_SHA384_MESSAGE: u32 = 1 << 0;
+}
+
+impl MailboxReq for EcdsaVerifyReq {
+    const ID: CommandId = CommandId::Ecdsa384Verify;
+    type Resp = MailboxRespHeader;
+}
+
+/// LMS_SIGNATURE_VERIFY request
+#[repr(C)]
+#[derive(Debug, Clone, Copy, AsBytes, FromBytes, PartialEq, Eq)]
+pub struct LmsVerifyReq {
+    pub hdr: MailboxReqHeader,
+
+    /// Verification flags (`LmsVerifyReq::FLAG_*`)
+    pub flags: u32,
+
+    /// Serialized LMS public key (RFC 8554 section 5.3), zero padded
+    pub pub_key: [u8; LmsVerifyReq::PUB_KEY_MAX_SIZE],
+
+    /// Number of valid bytes in `signature`
+    pub signature_size: u32,
+
+    /// Serialized LMS signature (RFC 8554 section 5.4), zero padded
+    pub signature: [u8; LmsVerifyReq::SIGNATURE_MAX_SIZE],
+}
+
+impl LmsVerifyReq {
+    /// Verify the SHA-384 digest of the message instead of the message
+    /// itself. The digest is computed by Caliptra over mailbox SRAM.
+    pub const FLAG_SHA384_MESSAGE: u32 = 1 << 0;
+
+    /// Size of a serialized public key with 32-byte hashes
+    pub const PUB_KEY_MAX_SIZE: usize = 56;
+
+    /// Size of a serialized LMOTS_SHA256_N32_W4 / LMS_SHA256_M32_H25 signature
+    pub const SIGNATURE_MAX_SIZE: usize = 2988;
+}
+
+impl Default for LmsVerifyReq {
+    fn default() -> Self {
+        Self {
+            hdr: MailboxReqHeader::default(),
+            flags: 0,
+            pub_key: [0u8; LmsVerifyReq::PUB_KEY_MAX_SIZE],
+            signature_size: 0,
+            signature: [0u8; LmsVerifyReq::SIGNATURE_MAX_SIZE],
+        }
+    }
+}
+
+impl MailboxReq for LmsVerifyReq {
+    const ID: CommandId = CommandId::LmsVerify;
+    type Resp = MailboxRespHeader;
+}
+
+/// STASH_MEASUREMENT request
+#[repr(C)]
+#[derive(Debug, Clone, Copy, AsBytes, FromBytes, PartialEq, Eq)]
+pub struct StashMeasurementReq {
+    pub hdr: MailboxReqHeader,
+    pub metadata: [u8; 4],
+    pub measurement: [u8; 48],
+    pub svn: u32,
+}
+
+impl MailboxReq for StashMeasurementReq {
+    const ID: CommandId = CommandId::StashMeasurement;
+    type Resp = StashMeasurementResp;
+}
+
+/// STASH_MEASUREMENT response
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, AsBytes, FromBytes, PartialEq, Eq)]
+pub struct StashMeasurementResp {
+    pub hdr: MailboxRespHeader,
+    pub dpe_result: u32,
+}
+
+impl MailboxResp for StashMeasurementResp {}
+
+/// INVOKE_DPE_COMMAND request
+#[repr(C)]
+#[derive(Debug, Clone, Copy, AsBytes, FromBytes, PartialEq, Eq)]
+pub struct InvokeDpeReq {
+    pub hdr: MailboxReqHeader,
+
+    /// Number of valid bytes in `data`
+    pub data_size: u32,
+
+    /// DPE command header followed by the command, zero padded
+    pub data: [u8; InvokeDpeReq::DATA_MAX_SIZE],
+}
+
+impl InvokeDpeReq {
+    /// Maximum size of a DPE command
+    pub const DATA_MAX_SIZE: usize = 512;
+
+    /// Build a request carrying `cmd`, or `None` if it does not fit
+    pub fn new(cmd: &[u8]) -> Option<Self> {
+        let mut req = Self::default();
+        req.data.get_mut(..cmd.len())?.copy_from_slice(cmd);
+        req.data_size = cmd.len() as u32;
+        Some(req)
+    }
+}
+
+impl Default for InvokeDpeReq {
+    fn default() -> Self {
+        Self {
+            hdr: MailboxReqHeader::default(),
+            data_size: 0,
+            data: [0u8; InvokeDpeReq::DATA_MAX_SIZE],
+        }
+    }
+}
+
+impl MailboxReq for InvokeDpeReq {
+    const ID: CommandId = CommandId::InvokeDpe;
+    type Resp = InvokeDpeResp;
+}
+
+/// INVOKE_DPE_COMMAND response
+#[repr(C)]
+#[derive(Debug, Clone, Copy, AsBytes, FromBytes, PartialEq, Eq)]
+pub struct InvokeDpeResp {
+    pub hdr: MailboxRespHeader,
+
+    /// Number of valid bytes in `data`
+    pub data_size: u32,
+
+    /// DPE response header followed by the response, zero padded
+    pub data: [u8; InvokeDpeResp::DATA_MAX_SIZE],
+}
+
+impl InvokeDpeResp {
+    /// Maximum size of a DPE response
+    pub const DATA_MAX_SIZE: usize = size_of::<CertifyKeyResp>();
+
+    /// DPE response
+    pub fn resp(&self) -> Option<&[u8]> {
+        self.data.get(..self.data_size as usize)
+    }
+}
+
+impl Default for InvokeDpeResp {
+    fn default() -> Self {
+        Self {
+            hdr: MailboxRespHeader::default(),
+            data_size: 0,
+            data: [0u8; InvokeDpeResp::DATA_MAX_SIZE],
+        }
+    }
+}
+
+impl MailboxResp for InvokeDpeResp {}
+
+/// GET_IDEV_ID_CERT request
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, AsBytes, FromBytes, PartialEq, Eq)]
+pub struct GetIdevIdCertReq {
+    pub hdr: MailboxReqHeader,
+}
+
+impl MailboxReq for GetIdevIdCertReq {
+    const ID: CommandId = CommandId::GetIdevIdCert;
+    type Resp = GetCertResp;
+}
+
+/// GET_LDEV_ID_CERT request
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, AsBytes, FromBytes, PartialEq, Eq)]
+pub struct GetLdevIdCertReq {
+    pub hdr: MailboxReqHeader,
+}
+
+impl MailboxReq for GetLdevIdCertReq {
+    const ID: CommandId = CommandId::GetLdevIdCert;
+    type Resp = GetCertResp;
+}
+
+/// GET_FMC_ALIAS_CERT request
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, AsBytes, FromBytes, PartialEq, Eq)]
+pub struct GetFmcAliasCertReq {
+    pub hdr: MailboxReqHeader,
+}
+
+impl MailboxReq for GetFmcAliasCertReq {
+    const ID: CommandId = CommandId::GetFmcAliasCert;
+    type Resp = GetCertResp;
+}
+
+/// GET_RT_ALIAS_CERT request
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, AsBytes, FromBytes, PartialEq, Eq)]
+pub struct GetRtAliasCertReq {
+    pub hdr: MailboxReqHeader,
+}
+
+impl MailboxReq for GetRtAliasCertReq {
+    const ID: CommandId = CommandId::GetRtAliasCert;
+    type Resp = GetCertResp;
+}
+
+/// Response to the certificate retrieval commands
+#[repr(C)]
+#[derive(Debug, Clone, Copy, AsBytes, FromBytes, PartialEq, Eq)]
+pub struct GetCertResp {
+    pub hdr: MailboxRespHeader,
+
+    /// Number of valid bytes in `data`
+    pub data_size: u32,
+
+    /// DER-encoded certificate, zero padded
+    pub data: [u8; GetCertResp::DATA_MAX_SIZE],
+}
+
+impl GetCertResp {
+    /// Maximum size of a DER-encoded certificate
+    pub const DATA_MAX_SIZE: usize = 1024;
+
+    /// DER-encoded certificate
+    pub fn cert(&self) -> Option<&[u8]> {
+        self.data.get(..self.data_size as usize)
+    }
+}
+
+impl Default for GetCertResp {
+    fn default() -> Self {
+        Self {
+            hdr: MailboxRespHeader::default(),
+            data_size: 0,
+            data: [0u8; GetCertResp::DATA_MAX_SIZE],
+        }
+    }
+}
+
+impl MailboxResp for GetCertResp {}
+
+/// QUOTE_PCRS request
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, AsBytes, FromBytes, PartialEq, Eq)]
+pub struct QuotePcrsReq {
+    pub hdr: MailboxReqHeader,
+
+    /// Caller-supplied freshness nonce
+    pub nonce: [u8; 32],
+}
+
+impl MailboxReq for QuotePcrsReq {
+    const ID: CommandId = CommandId::QuotePcrs;
+    type Resp = QuotePcrsResp;
+}
+
+/// QUOTE_PCRS response
+#[repr(C)]
+#[derive(Debug, Clone, Copy, AsBytes, FromBytes, PartialEq, Eq)]
+pub struct QuotePcrsResp {
+    pub hdr: MailboxRespHeader,
+
+    /// PCR values, indexed by PCR number
+    pub pcrs: [[u8; 48]; QuotePcrsResp::NUM_PCRS],
+
+    /// Nonce from the request
+    pub nonce: [u8; 32],
+
+    /// R portion of the signature over SHA384(`pcrs` || `nonce`)
+    pub signature_r: [u8; 48],
+
+    /// S portion of the signature over SHA384(`pcrs` || `nonce`)
+    pub signature_s: [u8; 48],
+}
+
+impl QuotePcrsResp {
+    /// Number of PCRs in the PCR bank
+    pub const NUM_PCRS: usize = 32;
+
+    /// Data covered by the signature
+    pub fn signed_data(&self) -> &[u8] {
+        let start = size_of::<MailboxRespHeader>();
+        let len = size_of::<[[u8; 48]; QuotePcrsResp::NUM_PCRS]>() + size_of::<[u8; 32]>();
+        self.as_bytes().get(start..start + len).unwrap_or_default()
+    }
+}
+
+impl Default for QuotePcrsResp {
+    fn default() -> Self {
+        Self {
+            hdr: MailboxRespHeader::default(),
+            pcrs: [[0u8; 48]; QuotePcrsResp::NUM_PCRS],
+            nonce: [0u8; 32],
+            signature_r: [0u8; 48],
+            signature_s: [0u8; 48],
+        }
+    }
+}
+
+impl MailboxResp for QuotePcrsResp {}
+
+#[cfg(all(test, target_family = "unix"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_id() {
+        for id in [
+            CommandId::FirmwareLoad,
+            CommandId::FirmwareLoadChunk,
+            CommandId::GetIdevCsr,
+            CommandId::ProvisionIdevCert,
+            CommandId::DebugUnlockChallenge,
+            CommandId::DebugUnlockToken,
+            CommandId::Ecdsa384Verify,
+            CommandId::LmsVerify,
+            CommandId::StashMeasurement,
+            CommandId::InvokeDpe,
+            CommandId::GetIdevIdCert,
+            CommandId::GetLdevIdCert,
+            CommandId::GetFmcAliasCert,
+            CommandId::GetRtAliasCert,
+            CommandId::QuotePcrs,
+        ] {
+            assert_eq!(CommandId::try_from(u32::from(id)), Ok(id));
+        }
+        assert_eq!(CommandId::try_from(0), Err(()));
+    }
+
+    #[test]
+    fn test_req_chksum() {
+        let mut req = StashMeasurementReq {
+            hdr: MailboxReqHeader::default(),
+            metadata: [1, 2, 3, 4],
+            measurement: [0xAB; 48],
+            svn: 1,
+        };
+        req.populate_chksum();
+        assert!(req.verify_chksum());
+        assert_eq!(
+            req.hdr.chksum,
+            calc_checksum(CommandId::StashMeasurement.into(), &req.as_bytes()[4..])
+        );
+
+        req.measurement[0] ^= 1;
+        assert!(!req.verify_chksum());
+    }
+
+    #[test]
+    fn test_quote_signed_data() {
+        let mut resp = QuotePcrsResp::default();
+        resp.pcrs[0] = [0x11; 48];
+        resp.nonce = [0x22; 32];
+        resp.signature_r = [0x33; 48];
+
+        let data = resp.signed_data();
+        assert_eq!(data.len(), QuotePcrsResp::NUM_PCRS * 48 + 32);
+        assert_eq!(data[..48], [0x11; 48]);
+        assert_eq!(data[data.len() - 32..], [0x22; 32]);
+    }
+
+    #[test]
+    fn test_resp_chksum() {
+        let mut resp = StashMeasurementResp {
+            hdr: MailboxRespHeader::default(),
+            dpe_result: 0x1234_5678,
+        };
+        resp.populate_chksum();
+        assert!(resp.verify_chksum());
+        assert_eq!(resp.hdr.chksum, calc_checksum(0, &resp.as_bytes()[4..]));
+
+        resp.dpe_result = 0;
+        assert!(!resp.verify_chksum());
+    }
+}
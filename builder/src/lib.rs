@@ -7,14 +7,21 @@ use std::process::{Command, Stdio};
 
 use caliptra_image_elf::ElfExecutable;
 use caliptra_image_gen::{
-    ImageGenerator, ImageGeneratorConfig, ImageGeneratorOwnerConfig, ImageGeneratorVendorConfig,
+    ImageGenerator, ImageGeneratorAuxImage, ImageGeneratorConfig, ImageGeneratorOwnerConfig,
+    ImageGeneratorVendorConfig,
 };
-use caliptra_image_openssl::OsslCrypto;
+#[cfg(all(feature = "openssl", not(feature = "rustcrypto")))]
+use caliptra_image_openssl::OsslCrypto as Crypto;
+#[cfg(feature = "rustcrypto")]
+use caliptra_image_rustcrypto::RustCrypto as Crypto;
 use caliptra_image_types::{ImageBundle, ImageRevision};
 use elf::endian::LittleEndian;
 
 mod elf_symbols;
 
+#[cfg(not(any(feature = "openssl", feature = "rustcrypto")))]
+compile_error!("Either the openssl or the rustcrypto feature must be enabled");
+
 pub use elf_symbols::{elf_symbols, Symbol, SymbolBind, SymbolType, SymbolVisibility};
 
 pub const ROM: FwId = FwId {
@@ -29,6 +36,13 @@ pub const ROM_WITH_UART: FwId = FwId {
     features: &["emu"],
 };
 
+/// ROM seeding its CSRNG from a fixed constant, for tests only
+pub const ROM_WITH_UART_DETERMINISTIC_RNG: FwId = FwId {
+    crate_name: "caliptra-rom",
+    bin_name: "caliptra-rom",
+    features: &["emu", "deterministic-rng"],
+};
+
 pub const FMC_WITH_UART: FwId = FwId {
     crate_name: "caliptra-fmc",
     bin_name: "caliptra-fmc",
@@ -185,6 +199,7 @@ pub struct ImageOptions {
     pub app_svn: u32,
     pub vendor_config: ImageGeneratorVendorConfig,
     pub owner_config: Option<ImageGeneratorOwnerConfig>,
+    pub aux: Vec<ImageGeneratorAuxImage>,
 }
 impl Default for ImageOptions {
     fn default() -> Self {
@@ -195,6 +210,7 @@ impl Default for ImageOptions {
             app_svn: Default::default(),
             vendor_config: caliptra_image_fake_keys::VENDOR_CONFIG_KEY_0,
             owner_config: Some(caliptra_image_fake_keys::OWNER_CONFIG),
+            aux: Vec::new(),
         }
     }
 }
@@ -206,7 +222,7 @@ pub fn build_and_sign_image(
 ) -> anyhow::Result<ImageBundle> {
     let fmc_elf = build_firmware_elf(fmc)?;
     let app_elf = build_firmware_elf(app)?;
-    let gen = ImageGenerator::new(OsslCrypto::default());
+    let gen = ImageGenerator::new(Crypto::default());
     let image = gen.generate(&ImageGeneratorConfig {
         fmc: ElfExecutable::new(
             &fmc_elf,
@@ -222,6 +238,7 @@ pub fn build_and_sign_image(
         )?,
         vendor_config: opts.vendor_config,
         owner_config: opts.owner_config,
+        aux: opts.aux,
     })?;
     Ok(image)
 }
@@ -11,7 +11,12 @@ Abstract:
     Crypto helper routines
 
 --*/
-use caliptra_drivers::{Ecc384PubKey, KeyId};
+use caliptra_drivers::{
+    Array4x12, CaliptraResult, Ecc384, Ecc384PrivKeyIn, Ecc384PrivKeyOut, Ecc384PubKey, Ecc384Seed,
+    Ecc384Signature, Hmac384, Hmac384Data, Hmac384Key, Hmac384Tag, KeyId, KeyReadArgs, KeyUsage,
+    KeyWriteArgs, Sha384,
+};
+
 /// DICE  Layer Key Pair
 #[derive(Debug)]
 pub struct Ecc384KeyPair {
@@ -21,3 +26,122 @@ pub struct Ecc384KeyPair {
     /// Public Key
     pub pub_key: Ecc384PubKey,
 }
+
+/// Calculate HMAC-384
+///
+/// # Arguments
+///
+/// * `hmac` - HMAC384 Engine
+/// * `key`  - HMAC384 key
+/// * `data` - Input data to hash
+/// * `tag`  - Key slot to store the tag
+///
+/// # Returns
+///
+/// * `KeyId` - Key Id inputted
+pub fn hmac384_mac(
+    hmac: &Hmac384,
+    key: Hmac384Key,
+    data: Hmac384Data,
+    tag: KeyId,
+) -> CaliptraResult<KeyId> {
+    // Tag
+    let mut usage = KeyUsage::default();
+    usage.set_hmac_key(true);
+    usage.set_ecc_key_gen_seed(true);
+    let tag_args = Hmac384Tag::Key(KeyWriteArgs::new(tag, usage));
+
+    // Calculate the CDI
+    hmac.hmac(key, data, tag_args)?;
+
+    Ok(tag)
+}
+
+/// Generate ECC Key Pair
+///
+/// The nonce is fixed so that the DICE keys only depend on the CDI, the IV
+/// randomizes the side-channel countermeasures.
+///
+/// # Arguments
+///
+/// * `ecc`      - ECC384 Engine
+/// * `seed`     - Key slot to retrieve the seed from
+/// * `priv_key` - Key slot to store the private key
+/// * `iv`       - Initialization vector
+///
+/// # Returns
+///
+/// * `Ecc384KeyPair` - Private Key slot id and public key pairs
+pub fn ecc384_key_gen(
+    ecc: &Ecc384,
+    seed: KeyId,
+    priv_key: KeyId,
+    iv: &Array4x12,
+) -> CaliptraResult<Ecc384KeyPair> {
+    let seed = Ecc384Seed::Key(KeyReadArgs::new(seed));
+
+    let mut usage = KeyUsage::default();
+    usage.set_ecc_private_key(true);
+
+    let key_out = Ecc384PrivKeyOut::Key(KeyWriteArgs::new(priv_key, usage));
+
+    Ok(Ecc384KeyPair {
+        priv_key,
+        pub_key: ecc.key_pair(seed, &Array4x12::default(), iv, key_out)?,
+    })
+}
+
+/// Sign data using ECC Private Key
+///
+/// This routine calculates the digest of the `data` and signs the hash
+///
+/// # Arguments
+///
+/// * `sha384`   - SHA2-384 Engine
+/// * `ecc`      - ECC384 Engine
+/// * `priv_key` - Key slot to retrieve the private key
+/// * `data`     - Input data to hash
+/// * `iv`       - Initialization vector
+///
+/// # Returns
+///
+/// * `Ecc384Signature` - Signature
+pub fn ecdsa384_sign(
+    sha384: &Sha384,
+    ecc: &Ecc384,
+    priv_key: KeyId,
+    data: &[u8],
+    iv: &Array4x12,
+) -> CaliptraResult<Ecc384Signature> {
+    let mut digest = Array4x12::default();
+    sha384.digest(data, &mut digest)?;
+    let priv_key = Ecc384PrivKeyIn::Key(KeyReadArgs::new(priv_key));
+    ecc.sign(priv_key, &digest, iv)
+}
+
+/// Verify the ECC Signature
+///
+/// This routine calculates the digest and verifies the signature
+///
+/// # Arguments
+///
+/// * `sha384`  - SHA2-384 Engine
+/// * `ecc`     - ECC384 Engine
+/// * `pub_key` - Public key to verify the signature
+/// * `data`    - Input data to hash
+/// * `sig`     - Signature to verify
+///
+/// # Returns
+///
+/// * `bool` - True on success, false otherwise
+pub fn ecdsa384_verify(
+    sha384: &Sha384,
+    ecc: &Ecc384,
+    pub_key: &Ecc384PubKey,
+    data: &[u8],
+    sig: &Ecc384Signature,
+) -> CaliptraResult<bool> {
+    let mut digest = Array4x12::default();
+    sha384.digest(data, &mut digest)?;
+    ecc.verify(pub_key, &digest, sig)
+}
@@ -1,8 +1,7 @@
 // Licensed under the Apache-2.0 license.
 use bitfield::{bitfield_bitrange, bitfield_fields};
 use caliptra_drivers::{
-    report_fw_error_non_fatal, ColdResetEntry4, ColdResetEntry48, KeyId, WarmResetEntry4,
-    WarmResetEntry48,
+    handle_fatal_error, ColdResetEntry4, ColdResetEntry48, KeyId, WarmResetEntry4, WarmResetEntry48,
 };
 use zerocopy::{AsBytes, FromBytes};
 extern "C" {
@@ -261,8 +260,22 @@ pub struct FirmwareHandoffTable {
     /// Index of RT SVN value in the Data Vault
     pub rt_svn_dv_hdl: HandOffDataHandle,
 
+    /// Physical base address of the auxiliary TOC entries of the Manifest in
+    /// DCCM SRAM.
+    pub aux_toc_addr: u32,
+
+    /// Number of auxiliary TOC entries at `aux_toc_addr`.
+    pub aux_toc_len: u32,
+
+    /// Physical base address of the provisioned IDevID certificate in DCCM
+    /// SRAM.
+    pub idev_cert_addr: u32,
+
+    /// Index of the IDevID certificate size in the Data Vault.
+    pub idev_cert_size_dv_hdl: HandOffDataHandle,
+
     /// Reserved for future use.
-    pub reserved: [u8; 32],
+    pub reserved: [u8; 16],
 }
 
 impl Default for FirmwareHandoffTable {
@@ -291,7 +304,11 @@ impl Default for FirmwareHandoffTable {
             rt_cert_sig_r_dv_hdl: FHT_INVALID_HANDLE,
             rt_cert_sig_s_dv_hdl: FHT_INVALID_HANDLE,
             rt_svn_dv_hdl: FHT_INVALID_HANDLE,
-            reserved: [0; 32],
+            aux_toc_addr: FHT_INVALID_ADDRESS,
+            aux_toc_len: 0,
+            idev_cert_addr: FHT_INVALID_ADDRESS,
+            idev_cert_size_dv_hdl: FHT_INVALID_HANDLE,
+            reserved: [0; 16],
         }
     }
 }
@@ -362,6 +379,13 @@ pub fn print_fht(fht: &FirmwareHandoffTable) {
         fht.rt_cert_sig_s_dv_hdl.0
     );
     crate::cprintln!("RT SVN DV Handle: 0x{:08x}", fht.rt_svn_dv_hdl.0);
+    crate::cprintln!("Aux TOC Address: 0x{:08x}", fht.aux_toc_addr);
+    crate::cprintln!("Aux TOC Length: {}", fht.aux_toc_len);
+    crate::cprintln!("IDevID Certificate Address: 0x{:08x}", fht.idev_cert_addr);
+    crate::cprintln!(
+        "IDevID Certificate Size DV Handle: 0x{:08x}",
+        fht.idev_cert_size_dv_hdl.0
+    );
 }
 
 impl FirmwareHandoffTable {
@@ -401,13 +425,25 @@ impl FirmwareHandoffTable {
         }
         None
     }
+
+    /// Store the FHT at its fixed address so that updates made by the
+    /// current firmware layer are visible to the next one.
+    pub fn save(&self) {
+        let slice = unsafe {
+            let ptr = &mut FHT_ORG as *mut u32;
+            core::slice::from_raw_parts_mut(
+                ptr,
+                core::mem::size_of::<FirmwareHandoffTable>() / core::mem::size_of::<u32>(),
+            )
+        };
+
+        slice.as_bytes_mut().copy_from_slice(self.as_bytes());
+    }
 }
-/// Report a non fatal firmware error and halt.
-#[allow(clippy::empty_loop)]
+/// Report a fatal firmware error and halt.
 pub fn report_handoff_error_and_halt(msg: &str, code: u32) -> ! {
     crate::cprintln!("Handoff Error: {} 0x{:08X}", msg, code);
-    report_fw_error_non_fatal(code);
-    loop {}
+    handle_fatal_error(code, None)
 }
 
 #[cfg(all(test, target_family = "unix"))]
@@ -7,10 +7,11 @@ pub mod dice;
 pub mod hand_off;
 #[macro_use]
 pub mod printer;
+pub mod x509;
 ///merge imports
 pub use hand_off::{
     print_fht, report_handoff_error_and_halt, DataStore, DataVaultRegister, FirmwareHandoffTable,
-    HandOffDataHandle, Vault, FHT_INVALID_HANDLE, FHT_MARKER,
+    HandOffDataHandle, Vault, FHT_INVALID_ADDRESS, FHT_INVALID_HANDLE, FHT_MARKER,
 };
 
 pub use printer::MutablePrinter;
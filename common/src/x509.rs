@@ -0,0 +1,94 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+    x509.rs
+
+Abstract:
+
+    X509 Certificate related helper routines shared by ROM, FMC and Runtime
+
+--*/
+use caliptra_drivers::{Array4x8, CaliptraResult, Ecc384PubKey, Sha256};
+
+/// Get X509 Subject Serial Number
+///
+/// # Arguments
+///
+/// * `sha256`  - SHA2-256 Engine
+/// * `pub_key` - Public Key
+///
+/// # Returns
+///
+/// `[u8; 64]` - X509 Subject Identifier serial number
+pub fn subj_sn(sha256: &Sha256, pub_key: &Ecc384PubKey) -> CaliptraResult<[u8; 64]> {
+    let digest = pub_key_digest(sha256, pub_key)?;
+    Ok(hex(&digest))
+}
+
+/// Get Cert Subject Key Identifier
+///
+/// # Arguments
+///
+/// * `sha256`  - SHA2-256 Engine
+/// * `pub_key` - Public Key
+///
+/// # Returns
+///
+/// `[u8; 20]` - X509 Subject Key Identifier
+pub fn subj_key_id(sha256: &Sha256, pub_key: &Ecc384PubKey) -> CaliptraResult<[u8; 20]> {
+    let digest = pub_key_digest(sha256, pub_key)?;
+    Ok(digest[..20].try_into().unwrap())
+}
+
+/// Get Cert Serial Number
+///
+/// # Arguments
+///
+/// * `sha256`  - SHA2-256 Engine
+/// * `pub_key` - Public Key
+///
+/// # Returns
+///
+/// `[u8; 20]` - X509 Serial Number
+pub fn cert_sn(sha256: &Sha256, pub_key: &Ecc384PubKey) -> CaliptraResult<[u8; 20]> {
+    let mut digest = pub_key_digest(sha256, pub_key)?;
+    digest[0] &= !0x80;
+    Ok(digest[..20].try_into().unwrap())
+}
+
+/// Calculate the SHA2-256 digest of the DER encoded public key
+fn pub_key_digest(sha256: &Sha256, pub_key: &Ecc384PubKey) -> CaliptraResult<[u8; 32]> {
+    let mut digest = Array4x8::default();
+    sha256.digest(&pub_key.to_der(), &mut digest)?;
+    Ok(digest.into())
+}
+
+/// Return the hex representation of the input `buf`
+///
+/// # Arguments
+///
+/// `buf` - Buffer
+///
+/// # Returns
+///
+/// `[u8; 64]` - Hex representation of the buffer
+fn hex(buf: &[u8; 32]) -> [u8; 64] {
+    fn ch(byte: u8) -> u8 {
+        match byte & 0x0F {
+            b @ 0..=9 => b'0' + b,
+            b => b'A' + b - 10,
+        }
+    }
+
+    let mut hex = [0u8; 64];
+
+    for (dest, byte) in hex.chunks_exact_mut(2).zip(buf.iter()) {
+        dest[0] = ch(byte >> 4);
+        dest[1] = ch(byte & 0x0F);
+    }
+
+    hex
+}
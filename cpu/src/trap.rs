@@ -12,6 +12,8 @@ Abstract:
 
 --*/
 
+use caliptra_drivers::FatalErrorTrap;
+
 /// Exception Record
 #[repr(C)]
 pub struct TrapRecord {
@@ -39,6 +41,17 @@ pub struct TrapRecord {
     pub mtval: u32,
 }
 
+impl From<&TrapRecord> for FatalErrorTrap {
+    fn from(record: &TrapRecord) -> Self {
+        Self {
+            mcause: record.mcause,
+            mscause: record.mscause,
+            mepc: record.mepc,
+            mtval: record.mtval,
+        }
+    }
+}
+
 pub enum Trap {
     Synchronous(Exception),
     Asynchronous(Interrupt),
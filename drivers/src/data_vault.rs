@@ -73,6 +73,9 @@ pub enum ColdResetEntry4 {
     FmcLoadAddr = 1,
     FmcEntryPoint = 2,
     VendorPubKeyIndex = 3,
+    VendorLmsPubKeyIndex = 4,
+    DebugUnlocked = 5,
+    IdevCertSize = 6,
 }
 
 impl TryFrom<u8> for ColdResetEntry4 {
@@ -83,6 +86,9 @@ impl TryFrom<u8> for ColdResetEntry4 {
             1 => Ok(Self::FmcLoadAddr),
             2 => Ok(Self::FmcEntryPoint),
             3 => Ok(Self::VendorPubKeyIndex),
+            4 => Ok(Self::VendorLmsPubKeyIndex),
+            5 => Ok(Self::DebugUnlocked),
+            6 => Ok(Self::IdevCertSize),
             _ => Err(()),
         }
     }
@@ -109,6 +115,10 @@ impl From<ColdResetEntry4> for usize {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum WarmResetEntry48 {
     RtTci = 0,
+    RtDiceSigR = 1,
+    RtDiceSigS = 2,
+    RtPubKeyX = 3,
+    RtPubKeyY = 4,
 }
 
 impl From<WarmResetEntry48> for u8 {
@@ -173,6 +183,10 @@ impl TryFrom<u8> for WarmResetEntry48 {
     fn try_from(value: u8) -> Result<Self, Self::Error> {
         match value {
             0 => Ok(Self::RtTci),
+            1 => Ok(Self::RtDiceSigR),
+            2 => Ok(Self::RtDiceSigS),
+            3 => Ok(Self::RtPubKeyX),
+            4 => Ok(Self::RtPubKeyY),
             _ => Err(()),
         }
     }
@@ -384,6 +398,63 @@ impl DataVault {
         self.read_cold_reset_entry4(ColdResetEntry4::VendorPubKeyIndex)
     }
 
+    /// Set the vendor LMS public key index used for image verification
+    ///
+    /// # Arguments
+    ///
+    /// * `pk_index` - Vendor LMS public key index
+    ///
+    pub fn set_vendor_lms_pk_index(&mut self, pk_index: u32) {
+        self.write_lock_cold_reset_entry4(ColdResetEntry4::VendorLmsPubKeyIndex, pk_index);
+    }
+
+    /// Get the vendor LMS public key index used for image verification.
+    ///
+    /// # Returns
+    ///
+    /// * `u32` - Vendor LMS public key index
+    pub fn vendor_lms_pk_index(&self) -> u32 {
+        self.read_cold_reset_entry4(ColdResetEntry4::VendorLmsPubKeyIndex)
+    }
+
+    /// Set the flag indicating the SoC was granted debug access.
+    ///
+    /// # Arguments
+    ///
+    /// * `unlocked` - Debug unlock token was verified on cold reset
+    ///
+    pub fn set_debug_unlocked(&mut self, unlocked: bool) {
+        self.write_lock_cold_reset_entry4(ColdResetEntry4::DebugUnlocked, unlocked as u32);
+    }
+
+    /// Get the flag indicating the SoC was granted debug access.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Debug unlock token was verified on cold reset
+    pub fn debug_unlocked(&self) -> bool {
+        self.read_cold_reset_entry4(ColdResetEntry4::DebugUnlocked) != 0
+    }
+
+    /// Set the size of the provisioned IDevID certificate.
+    ///
+    /// # Arguments
+    ///
+    /// * `size` - Size of the DER IDevID certificate in DCCM, zero if none
+    ///
+    pub fn set_idev_cert_size(&mut self, size: u32) {
+        self.write_lock_cold_reset_entry4(ColdResetEntry4::IdevCertSize, size);
+    }
+
+    /// Get the size of the provisioned IDevID certificate.
+    ///
+    /// # Returns
+    ///
+    /// * `u32` - Size of the DER IDevID certificate in DCCM, zero if none
+    pub fn idev_cert_size(&self) -> u32 {
+        self.read_cold_reset_entry4(ColdResetEntry4::IdevCertSize)
+    }
+
     /// Set the rt tcb component identifier.
     ///
     /// # Arguments
@@ -402,6 +473,50 @@ impl DataVault {
         self.read_warm_reset_entry48(WarmResetEntry48::RtTci)
     }
 
+    /// Set the rt dice signature.
+    ///
+    /// # Arguments
+    /// * `sig` - rt dice signature
+    ///
+    pub fn set_rt_dice_signature(&mut self, sig: &Ecc384Signature) {
+        self.write_lock_warm_reset_entry48(WarmResetEntry48::RtDiceSigR, &sig.r);
+        self.write_lock_warm_reset_entry48(WarmResetEntry48::RtDiceSigS, &sig.s);
+    }
+
+    /// Get the rt dice signature.
+    ///
+    /// # Returns
+    /// * rt dice signature
+    ///
+    pub fn rt_dice_signature(&self) -> Ecc384Signature {
+        Ecc384Signature {
+            r: self.read_warm_reset_entry48(WarmResetEntry48::RtDiceSigR),
+            s: self.read_warm_reset_entry48(WarmResetEntry48::RtDiceSigS),
+        }
+    }
+
+    /// Set the rt public key.
+    ///
+    /// # Arguments
+    /// * `pub_key` - rt public key
+    ///
+    pub fn set_rt_pub_key(&mut self, pub_key: &Ecc384PubKey) {
+        self.write_lock_warm_reset_entry48(WarmResetEntry48::RtPubKeyX, &pub_key.x);
+        self.write_lock_warm_reset_entry48(WarmResetEntry48::RtPubKeyY, &pub_key.y);
+    }
+
+    /// Get the rt public key.
+    ///
+    /// # Returns
+    /// * rt public key
+    ///
+    pub fn rt_pub_key(&self) -> Ecc384PubKey {
+        Ecc384PubKey {
+            x: self.read_warm_reset_entry48(WarmResetEntry48::RtPubKeyX),
+            y: self.read_warm_reset_entry48(WarmResetEntry48::RtPubKeyY),
+        }
+    }
+
     /// Set the rt security version number.
     ///
     /// # Arguments
@@ -521,6 +636,22 @@ impl DataVault {
             .write(|w| w.lock_entry(true));
     }
 
+    /// Retrieve the lock status of the cold reset entry.
+    ///
+    /// # Arguments
+    /// * `entry` - cold reset entry
+    ///
+    /// # Returns
+    ///    `true` if the entry is locked
+    ///
+    pub fn cold_reset_entry48_lock(&self, entry: ColdResetEntry48) -> bool {
+        let dv = dv::RegisterBlock::dv_reg();
+        dv.sticky_data_vault_ctrl()
+            .at(entry.into())
+            .read()
+            .lock_entry()
+    }
+
     /// Read the warm reset entry.
     ///
     /// # Arguments
@@ -617,6 +748,22 @@ impl DataVault {
             .write(|w| w.lock_entry(true));
     }
 
+    /// Retrieve the lock status of the cold reset entry.
+    ///
+    /// # Arguments
+    /// * `entry` - cold reset entry
+    ///
+    /// # Returns
+    ///    `true` if the entry is locked
+    ///
+    pub fn cold_reset_entry4_lock(&self, entry: ColdResetEntry4) -> bool {
+        let dv = dv::RegisterBlock::dv_reg();
+        dv.sticky_lockable_scratch_reg_ctrl()
+            .at(entry.into())
+            .read()
+            .lock_entry()
+    }
+
     /// Read the warm reset entry.
     ///
     /// # Arguments
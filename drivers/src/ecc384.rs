@@ -160,6 +160,7 @@ impl Ecc384 {
     ///
     /// * `seed` - Seed for deterministic ECC Key Pair generation
     /// * `nonce` - Nonce for deterministic ECC Key Pair generation
+    /// * `iv` - Initialization vector randomizing the side-channel countermeasures
     /// * `priv_key` - Generate ECC-384 Private key
     ///
     /// # Returns
@@ -169,6 +170,7 @@ impl Ecc384 {
         &self,
         seed: Ecc384Seed,
         nonce: &Array4x12,
+        iv: &Array4x12,
         mut priv_key: Ecc384PrivKeyOut,
     ) -> CaliptraResult<Ecc384PubKey> {
         let ecc = ecc::RegisterBlock::ecc_reg();
@@ -198,6 +200,9 @@ impl Ecc384 {
         // Copy nonce to the hardware
         KvAccess::copy_from_arr(nonce, ecc.nonce())?;
 
+        // Copy IV to the hardware
+        KvAccess::copy_from_arr(iv, ecc.iv())?;
+
         // Program the command register for key generation
         ecc.ctrl().write(|w| w.ctrl(|w| w.keygen()));
 
@@ -225,6 +230,7 @@ impl Ecc384 {
     ///
     /// * `priv_key` - Private key
     /// * `digest` - Digest to sign
+    /// * `iv` - Initialization vector randomizing the signing nonce countermeasures
     ///
     /// # Returns
     ///
@@ -233,6 +239,7 @@ impl Ecc384 {
         &self,
         priv_key: Ecc384PrivKeyIn,
         data: &Ecc384Scalar,
+        iv: &Array4x12,
     ) -> CaliptraResult<Ecc384Signature> {
         let ecc = ecc::RegisterBlock::ecc_reg();
 
@@ -251,6 +258,9 @@ impl Ecc384 {
         // Copy digest
         KvAccess::copy_from_arr(data, ecc.msg())?;
 
+        // Copy IV to the hardware
+        KvAccess::copy_from_arr(iv, ecc.iv())?;
+
         // Program the command register
         ecc.ctrl().write(|w| w.ctrl(|w| w.signing()));
 
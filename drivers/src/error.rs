@@ -58,6 +58,9 @@ pub enum CaliptraComponent {
     /// TODO: Once https://github.com/chipsalliance/caliptra-sw/pull/220 is
     /// merged remove this and use RT error mechanism instead.
     Runtime = 14,
+
+    /// First Mutable Code
+    Fmc = 15,
 }
 
 #[macro_export]
@@ -81,6 +84,17 @@ macro_rules! caliptra_err_def {
                 core::num::NonZeroU32::from(val).into()
             }
         }
+        impl TryFrom<u32> for $enum_name {
+            type Error = ();
+
+            /// Find the error matching a full error code (component and value)
+            fn try_from(val: u32) -> Result<Self, Self::Error> {
+                match val {
+                    $(v if v == u32::from($enum_name::$field_name) => Ok($enum_name::$field_name),)*
+                    _ => Err(()),
+                }
+            }
+        }
 
         #[allow(unused_macros)]
         macro_rules! raise_err { ($comp_err: ident) => {
@@ -0,0 +1,87 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+    fatal_error.rs
+
+Abstract:
+
+    File contains the fatal error handler shared by ROM, FMC and Runtime
+    Firmware.
+
+--*/
+
+use crate::{
+    report_fw_error_fatal, report_fw_error_non_fatal, DeobfuscationEngine, KeyVault, Mailbox,
+};
+use caliptra_api::{CrashRecord, CRASH_RECORD_MARKER};
+use caliptra_registers::soc_ifc;
+
+/// Trap state recorded in the crash record
+#[derive(Default, Debug, Clone, Copy)]
+pub struct FatalErrorTrap {
+    /// Machine cause register
+    pub mcause: u32,
+
+    /// Machine secondary cause register
+    pub mscause: u32,
+
+    /// Machine exception program counter
+    pub mepc: u32,
+
+    /// Machine trap value register
+    pub mtval: u32,
+}
+
+/// Handle a fatal firmware error
+///
+/// Erases the key vault slots that are not write locked, clears the
+/// deobfuscation engine secrets, publishes a `CrashRecord` in
+/// CPTRA_FW_EXTENDED_ERROR_INFO and reports `code` in both the fatal and
+/// non-fatal firmware error registers. The firmware then stops, failing any
+/// mailbox transaction the SoC is waiting on.
+///
+/// # Arguments
+///
+/// * `code` - Firmware error code
+/// * `trap` - Trap state if the error was caused by an exception or NMI
+///
+/// # Returns
+///
+/// This function does not return
+#[allow(clippy::empty_loop)]
+pub fn handle_fatal_error(code: u32, trap: Option<&FatalErrorTrap>) -> ! {
+    // Zeroize the secrets before anything else so they do not outlive the
+    // failed boot.
+    KeyVault::default().erase_unlocked_keys();
+    let _ = DeobfuscationEngine::default().clear_secrets();
+
+    let soc_ifc = soc_ifc::RegisterBlock::soc_ifc_reg();
+    let trap = trap.copied().unwrap_or_default();
+    let record = CrashRecord {
+        marker: CRASH_RECORD_MARKER,
+        error_code: code,
+        mcause: trap.mcause,
+        mscause: trap.mscause,
+        mepc: trap.mepc,
+        mtval: trap.mtval,
+        boot_status: soc_ifc.cptra_boot_status().read(),
+        reset_reason: soc_ifc.cptra_reset_reason().read().into(),
+    };
+    soc_ifc
+        .cptra_fw_extended_error_info()
+        .write(&<[u32; 8]>::from(record));
+
+    // The non-fatal register is kept for SoC firmware that only polls it.
+    report_fw_error_non_fatal(code);
+    report_fw_error_fatal(code);
+
+    loop {
+        // SoC firmware might be stuck waiting for Caliptra to finish
+        // executing this pending mailbox transaction. Notify them that
+        // we've failed.
+        unsafe { Mailbox::abort_pending_soc_to_uc_transactions() };
+    }
+}
@@ -12,7 +12,7 @@ Abstract:
 
 --*/
 
-use crate::Array4x12;
+use crate::{Array4x12, Array4x4};
 use caliptra_registers::soc_ifc;
 
 #[derive(Default, Debug)]
@@ -176,6 +176,19 @@ impl FuseBank {
         )
     }
 
+    /// Get the vendor LMS public key revocation mask.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    ///     vendor LMS public key revocation mask
+    ///
+    pub fn vendor_lms_pub_key_revocation(&self) -> VendorPubKeyRevocation {
+        let soc_ifc_regs = soc_ifc::RegisterBlock::soc_ifc_reg();
+        VendorPubKeyRevocation::from_bits_truncate(soc_ifc_regs.fuse_lms_revocation().read())
+    }
+
     /// Get the owner public key hash.
     ///
     /// # Arguments
@@ -189,6 +202,21 @@ impl FuseBank {
         Array4x12::read_from_reg(soc_ifc_regs.fuse_owner_pk_hash())
     }
 
+    /// Get the identifier of the manufacturing HSM that signs the IDevID
+    /// certificate, the leading 128 bits of the SHA-384 digest of its public
+    /// key.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    ///     manufacturing HSM identifier
+    ///
+    pub fn idevid_manuf_hsm_id(&self) -> Array4x4 {
+        let soc_ifc_regs = soc_ifc::RegisterBlock::soc_ifc_reg();
+        Array4x4::read_from_reg(soc_ifc_regs.fuse_idevid_manuf_hsm_id())
+    }
+
     /// Get the rollback disability setting.
     ///
     /// # Arguments
@@ -153,6 +153,42 @@ caliptra_err_def! {
     }
 }
 
+/// All the keys in the key vault
+const KEY_IDS: [KeyId; 32] = [
+    KeyId::KeyId0,
+    KeyId::KeyId1,
+    KeyId::KeyId2,
+    KeyId::KeyId3,
+    KeyId::KeyId4,
+    KeyId::KeyId5,
+    KeyId::KeyId6,
+    KeyId::KeyId7,
+    KeyId::KeyId8,
+    KeyId::KeyId9,
+    KeyId::KeyId10,
+    KeyId::KeyId11,
+    KeyId::KeyId12,
+    KeyId::KeyId13,
+    KeyId::KeyId14,
+    KeyId::KeyId15,
+    KeyId::KeyId16,
+    KeyId::KeyId17,
+    KeyId::KeyId18,
+    KeyId::KeyId19,
+    KeyId::KeyId20,
+    KeyId::KeyId21,
+    KeyId::KeyId22,
+    KeyId::KeyId23,
+    KeyId::KeyId24,
+    KeyId::KeyId25,
+    KeyId::KeyId26,
+    KeyId::KeyId27,
+    KeyId::KeyId28,
+    KeyId::KeyId29,
+    KeyId::KeyId30,
+    KeyId::KeyId31,
+];
+
 /// Caliptra Key Vault
 #[derive(Default, Debug)]
 pub struct KeyVault {}
@@ -162,41 +198,6 @@ impl KeyVault {
     ///
     /// Note: The keys that have "use" or "write" lock set will not be erased
     pub fn erase_all_keys(&mut self) {
-        const KEY_IDS: [KeyId; 32] = [
-            KeyId::KeyId0,
-            KeyId::KeyId1,
-            KeyId::KeyId2,
-            KeyId::KeyId3,
-            KeyId::KeyId4,
-            KeyId::KeyId5,
-            KeyId::KeyId6,
-            KeyId::KeyId7,
-            KeyId::KeyId8,
-            KeyId::KeyId9,
-            KeyId::KeyId10,
-            KeyId::KeyId11,
-            KeyId::KeyId12,
-            KeyId::KeyId13,
-            KeyId::KeyId14,
-            KeyId::KeyId15,
-            KeyId::KeyId16,
-            KeyId::KeyId17,
-            KeyId::KeyId18,
-            KeyId::KeyId19,
-            KeyId::KeyId20,
-            KeyId::KeyId21,
-            KeyId::KeyId22,
-            KeyId::KeyId23,
-            KeyId::KeyId24,
-            KeyId::KeyId25,
-            KeyId::KeyId26,
-            KeyId::KeyId27,
-            KeyId::KeyId28,
-            KeyId::KeyId29,
-            KeyId::KeyId30,
-            KeyId::KeyId31,
-        ];
-
         let kv = kv::RegisterBlock::kv_reg();
 
         for id in KEY_IDS {
@@ -225,6 +226,20 @@ impl KeyVault {
         Ok(())
     }
 
+    /// Erase all the keys in the key vault that are not write locked
+    ///
+    /// Unlike `erase_all_keys`, this also erases the keys that have the
+    /// "use" lock set.
+    pub fn erase_unlocked_keys(&mut self) {
+        let kv = kv::RegisterBlock::kv_reg();
+
+        for id in KEY_IDS {
+            if !self.key_write_lock(id) {
+                kv.key_ctrl().at(id.into()).write(|w| w.clear(true));
+            }
+        }
+    }
+
     /// Retrieve the write lock status for a key
     ///
     /// # Arguments
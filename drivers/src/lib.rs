@@ -24,12 +24,14 @@ mod doe;
 mod ecc384;
 mod error_reporter;
 mod exit_ctrl;
+mod fatal_error;
 mod fuse_bank;
 mod hmac384;
 mod key_vault;
 mod kv_access;
 mod lms;
 mod mailbox;
+pub mod memory_layout;
 mod pcr_bank;
 mod reset;
 mod sha1;
@@ -62,6 +64,7 @@ pub use error_reporter::{
     report_hw_error_non_fatal,
 };
 pub use exit_ctrl::ExitCtrl;
+pub use fatal_error::{handle_fatal_error, FatalErrorTrap};
 pub use fuse_bank::{FuseBank, IdevidCertAttr, VendorPubKeyRevocation, X509KeyIdAlgo};
 pub use hmac384::{Hmac384, Hmac384Data, Hmac384Key, Hmac384Op, Hmac384Tag};
 pub use key_vault::{KeyId, KeyUsage, KeyVault};
@@ -32,6 +32,8 @@ caliptra_err_def! {
         InvalidHashWidth = 0x05,
         InvalidTreeHeight = 0x06,
         InvalidQValue = 0x07,
+        InvalidPathLength = 0x08,
+        InvalidCoefficientIndex = 0x09,
     }
 }
 #[derive(Default, Debug)]
@@ -278,10 +280,10 @@ pub fn get_lms_parameters(algo_type: &LmsAlgorithmType) -> CaliptraResult<(u8, u
 }
 
 // follows pseudo code at https://www.rfc-editor.org/rfc/rfc8554#section-3.1.3
-fn coefficient(s: &[u8], i: usize, w: usize) -> u8 {
+fn coefficient(s: &[u8], i: usize, w: usize) -> CaliptraResult<u8> {
     let bitmask: u16 = (1 << (w)) - 1;
     let index = i * w / 8;
-    let b = s[index];
+    let b = *s.get(index).ok_or(err_u32!(InvalidCoefficientIndex))?;
 
     // extra logic to avoid the divide by 0
     // which a good compiler would notice only happens when w is 0 and that portion of the
@@ -299,7 +301,7 @@ fn coefficient(s: &[u8], i: usize, w: usize) -> u8 {
         rs = b >> shift;
     }
     let small_bitmask = bitmask as u8;
-    small_bitmask & rs
+    Ok(small_bitmask & rs)
 }
 
 fn checksum(algo_type: &LmotsAlgorithmType, input_string: &[u8]) -> CaliptraResult<u16> {
@@ -308,7 +310,7 @@ fn checksum(algo_type: &LmotsAlgorithmType, input_string: &[u8]) -> CaliptraResu
     let upper_bound = params.n as u16 * (8 / params.w as u16);
     let bitmask = (1 << params.w) - 1;
     for i in 0..upper_bound as usize {
-        sum += bitmask - (coefficient(input_string, i, params.w as usize) as u16);
+        sum += bitmask - (coefficient(input_string, i, params.w as usize)? as u16);
     }
     let shifted = sum << params.ls;
     Ok(shifted)
@@ -367,9 +369,9 @@ pub fn candidate_ots_signature<const N: usize, const P: usize>(
     let mut hash_block = [0u8; 55];
     hash_block[0..16].clone_from_slice(lms_identifier);
     hash_block[16..20].clone_from_slice(q);
-    for i in 0..params.p {
-        let a = coefficient(&message_hash_with_checksum, i as usize, params.w as usize);
-        let mut tmp = signature.y[i as usize];
+    for (i, (y, z)) in (0..params.p).zip(signature.y.iter().zip(z.iter_mut())) {
+        let a = coefficient(&message_hash_with_checksum, i as usize, params.w as usize)?;
+        let mut tmp = *y;
         let t_upper: u16 = (1 << params.w) - 1; // subtract with overflow?
         let upper = t_upper as u8;
         hash_block[20..22].clone_from_slice(&i.to_be_bytes());
@@ -383,7 +385,7 @@ pub fn candidate_ots_signature<const N: usize, const P: usize>(
             hasher.finalize()?;
             tmp = HashValue::<N>::from(digest);
         }
-        z[i as usize] = tmp;
+        *z = tmp;
     }
     let mut digest = Array4x8::default();
     let sha = Sha256::default();
@@ -399,6 +401,13 @@ pub fn candidate_ots_signature<const N: usize, const P: usize>(
     Ok(result)
 }
 
+fn path_node<'a, const N: usize, const P: usize>(
+    lms_sig: &'a LmsSignature<N, P>,
+    i: usize,
+) -> CaliptraResult<&'a HashValue<N>> {
+    lms_sig.lms_path.get(i).ok_or(err_u32!(InvalidPathLength))
+}
+
 pub fn verify_lms_signature<const N: usize, const P: usize>(
     tree_height: u8,
     input_string: &[u8],
@@ -453,7 +462,7 @@ pub fn verify_lms_signature<const N: usize, const P: usize>(
             hasher.update(lms_identifier)?;
             hasher.update(&(node_num / 2).to_be_bytes())?;
             hasher.update(&D_INTR.to_be_bytes())?;
-            hasher.update(&lms_sig.lms_path[i].0)?;
+            hasher.update(&path_node(lms_sig, i)?.0)?;
             hasher.update(&temp.0)?;
             hasher.finalize()?;
             temp = HashValue::<N>::from(digest);
@@ -465,7 +474,7 @@ pub fn verify_lms_signature<const N: usize, const P: usize>(
             hasher.update(&(node_num / 2).to_be_bytes())?;
             hasher.update(&D_INTR.to_be_bytes())?;
             hasher.update(&temp.0)?;
-            hasher.update(&lms_sig.lms_path[i].0)?;
+            hasher.update(&path_node(lms_sig, i)?.0)?;
             hasher.finalize()?;
             temp = HashValue::<N>::from(digest);
         }
@@ -0,0 +1,26 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+    memory_layout.rs
+
+Abstract:
+
+    File contains the Caliptra memory layout shared by the firmware and the
+    host tools.
+
+--*/
+
+/// ICCM base address, must match `ICCM_ORG` in the ROM and FMC linker scripts
+pub const ICCM_ORG: u32 = 0x40000000;
+
+/// ICCM size in bytes, must match `ICCM_SIZE` in the linker scripts
+pub const ICCM_SIZE: u32 = 128 << 10;
+
+/// Mailbox SRAM base address, must match `MBOX_ORG` in the linker scripts
+pub const MBOX_ORG: u32 = 0x30000000;
+
+/// Mailbox SRAM size in bytes, must match `MBOX_SIZE` in the linker scripts
+pub const MBOX_SIZE: u32 = 128 << 10;
@@ -64,6 +64,18 @@ impl DeviceState {
         soc_ifc_regs.cptra_security_state().read().debug_locked()
     }
 
+    /// Retrieve the trusted time supplied by the SoC
+    ///
+    /// Generic input wires 0 and 1 are reserved for the trusted time: the SoC
+    /// drives the number of seconds since the Unix epoch, low word on wire 0
+    /// and high word on wire 1. Zero indicates the SoC does not supply a time.
+    pub fn trusted_time(&self) -> Option<u64> {
+        let soc_ifc_regs = soc_ifc::RegisterBlock::soc_ifc_reg();
+        let wires = soc_ifc_regs.cptra_generic_input_wires();
+        let time = u64::from(wires.at(0).read()) | u64::from(wires.at(1).read()) << 32;
+        (time != 0).then_some(time)
+    }
+
     /// Locks or unlocks the ICCM.
     ///
     /// # Arguments
@@ -80,6 +92,12 @@ bitflags::bitflags! {
     pub struct MfgFlags : u32 {
         /// Generate Initial Device Id Certificate Signing Request
        const GENERATE_IDEVID_CSR = 0x01;
+
+        /// Request debug unlock with a token signed by the owner key
+       const DEBUG_UNLOCK = 0x02;
+
+        /// Provision the HSM-signed Initial Device Id Certificate
+       const PROVISION_IDEVID_CERT = 0x04;
     }
 }
 
@@ -102,4 +120,19 @@ impl MfgState {
         let flags: MfgFlags = soc_ifc_regs.cptra_dbg_manuf_service_reg().read().into();
         flags.contains(MfgFlags::GENERATE_IDEVID_CSR)
     }
+
+    /// Returns the flag indicating whether the SoC requests debug unlock
+    pub fn debug_unlock_req(&self) -> bool {
+        let soc_ifc_regs = caliptra_registers::soc_ifc::RegisterBlock::soc_ifc_reg();
+        let flags: MfgFlags = soc_ifc_regs.cptra_dbg_manuf_service_reg().read().into();
+        flags.contains(MfgFlags::DEBUG_UNLOCK)
+    }
+
+    /// Returns the flag indicating whether the SoC will provision the Initial
+    /// Device ID Certificate signed by the manufacturing HSM
+    pub fn provision_idev_id_cert(&self) -> bool {
+        let soc_ifc_regs = caliptra_registers::soc_ifc::RegisterBlock::soc_ifc_reg();
+        let flags: MfgFlags = soc_ifc_regs.cptra_dbg_manuf_service_reg().read().into();
+        flags.contains(MfgFlags::PROVISION_IDEVID_CERT)
+    }
 }
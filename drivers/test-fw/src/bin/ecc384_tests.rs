@@ -60,6 +60,7 @@ fn test_gen_key_pair() {
     let result = Ecc384::default().key_pair(
         Ecc384Seed::from(&Ecc384Scalar::from(seed)),
         &nonce,
+        &Array4x12::default(),
         Ecc384PrivKeyOut::from(&mut priv_key),
     );
     assert!(result.is_ok());
@@ -77,7 +78,11 @@ fn test_gen_key_pair() {
 
 fn test_sign() {
     let digest: Array4xN<12, 48> = Array4xN([0u32; 12]);
-    let result = Ecc384::default().sign(Ecc384PrivKeyIn::from(&Array4x12::from(PRIV_KEY)), &digest);
+    let result = Ecc384::default().sign(
+        Ecc384PrivKeyIn::from(&Array4x12::from(PRIV_KEY)),
+        &digest,
+        &Array4x12::default(),
+    );
     assert!(result.is_ok());
     let signature = result.unwrap();
     assert_eq!(signature.r, Ecc384Scalar::from(SIGNATURE_R));
@@ -87,7 +92,11 @@ fn test_sign() {
 fn test_verify() {
     let digest: Array4xN<12, 48> = Array4xN([0u32; 12]);
     let ecc = Ecc384::default();
-    let result = ecc.sign(Ecc384PrivKeyIn::from(&Array4x12::from(PRIV_KEY)), &digest);
+    let result = ecc.sign(
+        Ecc384PrivKeyIn::from(&Array4x12::from(PRIV_KEY)),
+        &digest,
+        &Array4x12::default(),
+    );
     assert!(result.is_ok());
     let signature = result.unwrap();
     let pub_key = Ecc384PubKey {
@@ -102,7 +111,11 @@ fn test_verify() {
 fn test_verify_failure() {
     let digest: Array4xN<12, 48> = Array4xN([0u32; 12]);
     let ecc = Ecc384::default();
-    let result = ecc.sign(Ecc384PrivKeyIn::from(&Array4x12::from(PRIV_KEY)), &digest);
+    let result = ecc.sign(
+        Ecc384PrivKeyIn::from(&Array4x12::from(PRIV_KEY)),
+        &digest,
+        &Array4x12::default(),
+    );
     assert!(result.is_ok());
     let signature = result.unwrap();
     let pub_key = Ecc384PubKey {
@@ -130,6 +143,7 @@ fn test_kv_seed_from_input_msg_from_input() {
     let result = Ecc384::default().key_pair(
         Ecc384Seed::from(&Ecc384Scalar::from(seed)),
         &nonce,
+        &Array4x12::default(),
         Ecc384PrivKeyOut::from(key_out_1),
     );
     assert!(result.is_ok());
@@ -143,7 +157,7 @@ fn test_kv_seed_from_input_msg_from_input() {
     let digest: Array4xN<12, 48> = Array4xN([0u32; 12]);
     let key_in_1 = KeyReadArgs::new(KeyId::KeyId2);
 
-    let result = Ecc384::default().sign(key_in_1.into(), &digest);
+    let result = Ecc384::default().sign(key_in_1.into(), &digest, &Array4x12::default());
     assert!(result.is_ok());
     let signature = result.unwrap();
     assert_eq!(signature.r, Ecc384Scalar::from(SIGNATURE_R));
@@ -184,6 +198,7 @@ fn test_kv_seed_from_kv_msg_from_input() {
     let result = Ecc384::default().key_pair(
         Ecc384Seed::from(&Ecc384Scalar::from(seed)),
         &nonce,
+        &Array4x12::default(),
         Ecc384PrivKeyOut::from(key_out_1),
     );
     assert!(result.is_ok());
@@ -223,6 +238,7 @@ fn test_kv_seed_from_kv_msg_from_input() {
     let result = Ecc384::default().key_pair(
         Ecc384Seed::from(key_in_seed),
         &nonce,
+        &Array4x12::default(),
         Ecc384PrivKeyOut::from(key_out_priv_key),
     );
     assert!(result.is_ok());
@@ -257,7 +273,11 @@ fn test_kv_seed_from_kv_msg_from_input() {
         0xe8, 0x8c, 0x10,
     ];
     let key_in_priv_key = KeyReadArgs::new(KeyId::KeyId1);
-    let result = Ecc384::default().sign(key_in_priv_key.into(), &Array4x12::from(msg));
+    let result = Ecc384::default().sign(
+        key_in_priv_key.into(),
+        &Array4x12::from(msg),
+        &Array4x12::default(),
+    );
     assert!(result.is_ok());
     let signature = result.unwrap();
     assert_eq!(signature.r, Ecc384Scalar::from(sig_r));
@@ -97,6 +97,7 @@ fn test_hmac3() {
     let result = Ecc384::default().key_pair(
         Ecc384Seed::from(&Ecc384Scalar::from(seed)),
         &nonce,
+        &Array4x12::default(),
         Ecc384PrivKeyOut::from(key_out_1),
     );
     assert!(result.is_ok());
@@ -139,6 +140,7 @@ fn test_hmac4() {
     let result = Ecc384::default().key_pair(
         Ecc384Seed::from(&Ecc384Scalar::from(seed)),
         &nonce,
+        &Array4x12::default(),
         Ecc384PrivKeyOut::from(key_out_1),
     );
     assert!(result.is_ok());
@@ -186,6 +188,7 @@ fn test_hmac5() {
     let result = Ecc384::default().key_pair(
         Ecc384Seed::from(&Ecc384Scalar::from(seed)),
         &nonce,
+        &Array4x12::default(),
         Ecc384PrivKeyOut::from(key_out_1),
     );
     assert!(result.is_ok());
@@ -251,6 +254,7 @@ fn test_hmac6() {
     let result = Ecc384::default().key_pair(
         Ecc384Seed::from(&Ecc384Scalar::from(seed)),
         &nonce,
+        &Array4x12::default(),
         Ecc384PrivKeyOut::from(key_out_1),
     );
     assert!(result.is_ok());
@@ -0,0 +1,143 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+    crypto.rs
+
+Abstract:
+
+    Crypto helper routines
+
+--*/
+use crate::fmc_env::FmcEnv;
+use caliptra_common::crypto::{self, Ecc384KeyPair};
+use caliptra_drivers::{
+    Array4x12, CaliptraResult, Ecc384PubKey, Ecc384Signature, Hmac384Data, Hmac384Key, KeyId,
+};
+use core::num::NonZeroUsize;
+
+/// Number of words in a CSRNG request
+const CSRNG_NUM_WORDS: NonZeroUsize = match NonZeroUsize::new(12) {
+    Some(num_words) => num_words,
+    None => unreachable!(),
+};
+
+pub enum Crypto {}
+
+impl Crypto {
+    /// Generate random words with the CSRNG
+    ///
+    /// # Arguments
+    ///
+    /// * `env` - FMC Environment
+    ///
+    /// # Returns
+    ///
+    /// * `Array4x12` - Random words
+    pub fn csrng_generate(env: &FmcEnv) -> CaliptraResult<Array4x12> {
+        let mut words = [0u32; 12];
+        env.csrng().map(|c| -> CaliptraResult<()> {
+            for (dst, src) in words.iter_mut().zip(c.generate(CSRNG_NUM_WORDS)?) {
+                *dst = src;
+            }
+            Ok(())
+        })?;
+        Ok(Array4x12::from(words))
+    }
+
+    /// Calculate HMAC-384
+    ///
+    /// # Arguments
+    ///
+    /// * `env`  - FMC Environment
+    /// * `key`  - HMAC384 key
+    /// * `data` - Input data to hash
+    /// * `tag`  - Key slot to store the tag
+    ///
+    /// # Returns
+    ///
+    /// * `KeyId` - Key Id inputted
+    pub fn hmac384_mac(
+        env: &FmcEnv,
+        key: Hmac384Key,
+        data: Hmac384Data,
+        tag: KeyId,
+    ) -> CaliptraResult<KeyId> {
+        env.hmac384()
+            .map(|h| crypto::hmac384_mac(h, key, data, tag))
+    }
+
+    /// Generate ECC Key Pair
+    ///
+    /// # Arguments
+    ///
+    /// * `env`      - FMC Environment
+    /// * `seed`     - Key slot to retrieve the seed from
+    /// * `priv_key` - Key slot to store the private key
+    ///
+    /// # Returns
+    ///
+    /// * `Ecc384KeyPair` - Private Key slot id and public key pairs
+    pub fn ecc384_key_gen(
+        env: &FmcEnv,
+        seed: KeyId,
+        priv_key: KeyId,
+    ) -> CaliptraResult<Ecc384KeyPair> {
+        let iv = Self::csrng_generate(env)?;
+        env.ecc384()
+            .map(|e| crypto::ecc384_key_gen(e, seed, priv_key, &iv))
+    }
+
+    /// Sign data using ECC Private Key
+    ///
+    /// This routine calculates the digest of the `data` and signs the hash
+    ///
+    /// # Arguments
+    ///
+    /// * `env`      - FMC Environment
+    /// * `priv_key` - Key slot to retrieve the private key
+    /// * `data`     - Input data to hash
+    ///
+    /// # Returns
+    ///
+    /// * `Ecc384Signature` - Signature
+    pub fn ecdsa384_sign(
+        env: &FmcEnv,
+        priv_key: KeyId,
+        data: &[u8],
+    ) -> CaliptraResult<Ecc384Signature> {
+        let iv = Self::csrng_generate(env)?;
+        env.sha384().map(|sha| {
+            env.ecc384()
+                .map(|ecc| crypto::ecdsa384_sign(sha, ecc, priv_key, data, &iv))
+        })
+    }
+
+    /// Verify the ECC Signature
+    ///
+    /// This routine calculates the digest and verifies the signature
+    ///
+    /// # Arguments
+    ///
+    /// * `env`     - FMC Environment
+    /// * `pub_key` - Public key to verify the signature
+    /// * `data`    - Input data to hash
+    /// * `sig`     - Signature to verify
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - True on success, false otherwise
+    pub fn ecdsa384_verify(
+        env: &FmcEnv,
+        pub_key: &Ecc384PubKey,
+        data: &[u8],
+        sig: &Ecc384Signature,
+    ) -> CaliptraResult<bool> {
+        env.sha384().map(|sha| {
+            env.ecc384()
+                .map(|ecc| crypto::ecdsa384_verify(sha, ecc, pub_key, data, sig))
+        })
+    }
+}
@@ -42,9 +42,6 @@ pub struct DiceInput {
     /// Layer Private Key will be generated in the Key Vault slot specified
     /// by this slot
     pub subj_priv_key: KeyId,
-
-    /// Temporary KeyId used during DICE derivations
-    pub uds_key: KeyId,
 }
 
 /// DICE Layer Output
@@ -17,9 +17,11 @@ mod unknown_reset;
 mod update_reset;
 mod warm_reset;
 
+mod crypto;
 mod dice;
 mod pcr;
 mod rt_alias;
+mod x509;
 
 use crate::flow::rt_alias::RtAliasLayer;
 
@@ -34,7 +36,8 @@ use caliptra_drivers::ResetReason;
 ///
 /// * `env` - FMC Environment
 pub fn run(env: &FmcEnv, hand_off: &mut HandOff) -> CaliptraResult<()> {
-    let _ = RtAliasLayer::run(env, hand_off);
+    RtAliasLayer::run(env, hand_off)?;
+
     // Retrieve reset reason.
     let reset_reason = env.reset().map(|r| r.reset_reason());
     match reset_reason {
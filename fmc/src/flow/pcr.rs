@@ -19,6 +19,10 @@ Note:
     PCR2 - Journey PCR unlocked and cleared on cold reset
     PCR3 - Current PCR unlocked and cleared on any reset
 
+    Both PCRs are locked once extended so that Runtime Firmware cannot clear
+    them. Resets other than cold reset release the locks, the journey PCR
+    keeps the measurements of every Runtime Firmware run since cold reset.
+
 --*/
 
 use crate::fmc_env::FmcEnv;
@@ -34,6 +38,9 @@ const JOURNEY_PCR: PcrId = PcrId::PcrId2;
 ///
 /// * `env` - FMC Environment
 pub fn extend_current_pcr(env: &FmcEnv, hand_off: &HandOff) -> CaliptraResult<()> {
+    // Clear the measurements of the previous Runtime Firmware
+    env.pcr_bank().map(|p| p.erase_pcr(CURRENT_PCR))?;
+
     extend_pcr_common(env, hand_off, CURRENT_PCR)
 }
 
@@ -66,5 +73,8 @@ fn extend_pcr_common(env: &FmcEnv, hand_off: &HandOff, pcr_id: PcrId) -> Caliptr
     let bytes = &data.to_le_bytes();
     sha.map(|s| pcr_bank.map(|p| p.extend_pcr(pcr_id, s, bytes)))?;
 
+    // Lock the PCR from clear
+    pcr_bank.map(|p| p.set_pcr_lock(pcr_id));
+
     Ok(())
 }
@@ -11,29 +11,92 @@ Abstract:
     Alias RT DICE Layer & PCR extension
 
 --*/
+use crate::flow::crypto::Crypto;
 use crate::flow::dice::{DiceInput, DiceLayer, DiceOutput};
 use crate::flow::pcr::{extend_current_pcr, extend_journey_pcr};
+use crate::flow::x509::X509;
 use crate::fmc_env::FmcEnv;
 use crate::HandOff;
 use caliptra_common::cprintln;
-use caliptra_drivers::{CaliptraError, CaliptraResult};
+use caliptra_common::crypto::Ecc384KeyPair;
+use caliptra_drivers::{
+    caliptra_err_def, Array4x12, CaliptraResult, Ecc384Signature, Hmac384Data, Hmac384Key, KeyId,
+    KeyReadArgs,
+};
+use caliptra_x509::{NotAfter, NotBefore, RtAliasCertTbs, RtAliasCertTbsParams};
+
+caliptra_err_def! {
+    Fmc,
+    RtAliasErr
+    {
+        CertVerify = 0x1,
+    }
+}
+
+/// Key Vault slot holding the RT CDI
+const KEY_ID_RT_CDI: KeyId = KeyId::KeyId4;
+
+/// Key Vault slot holding the RT Alias Private Key
+const KEY_ID_RT_PRIV_KEY: KeyId = KeyId::KeyId5;
 
 #[derive(Default)]
 pub struct RtAliasLayer {}
 
 impl DiceLayer for RtAliasLayer {
     /// Perform derivations for the DICE layer
-    fn derive(_env: &FmcEnv, _input: &DiceInput) -> CaliptraResult<DiceOutput> {
-        // TODO : implement derivation.
-        Err(CaliptraError::new(0xdead).unwrap())
+    fn derive(env: &FmcEnv, input: &DiceInput) -> CaliptraResult<DiceOutput> {
+        cprintln!("[art] CDI.KEYID = {}", KEY_ID_RT_CDI as u8);
+        cprintln!("[art] SUBJECT.KEYID = {}", input.subj_priv_key as u8);
+        cprintln!(
+            "[art] AUTHORITY.KEYID = {}",
+            input.auth_key_pair.priv_key as u8
+        );
+
+        // Derive the RT CDI from the FMC CDI and the RT measurement
+        let measurement = env.data_vault().map(|d| d.rt_tci());
+        Self::derive_cdi(env, input.cdi, measurement, KEY_ID_RT_CDI)?;
+
+        // Derive DICE Key Pair from CDI
+        let key_pair = Self::derive_key_pair(env, KEY_ID_RT_CDI, input.subj_priv_key)?;
+
+        // Generate the Subject Serial Number and Subject Key Identifier.
+        //
+        // This information will be used by next DICE Layer while generating
+        // certificates
+        let subj_sn = X509::subj_sn(env, &key_pair.pub_key)?;
+        let subj_key_id = X509::subj_key_id(env, &key_pair.pub_key)?;
+
+        Ok(DiceOutput {
+            cdi: KEY_ID_RT_CDI,
+            subj_key_pair: key_pair,
+            subj_sn,
+            subj_key_id,
+        })
     }
 }
 
 impl RtAliasLayer {
     #[inline(never)]
-    pub fn run(env: &FmcEnv, hand_off: &HandOff) -> CaliptraResult<()> {
+    pub fn run(env: &FmcEnv, hand_off: &mut HandOff) -> CaliptraResult<()> {
         cprintln!("[art] Extend PCRs");
         Self::extend_pcrs(env, hand_off)?;
+
+        cprintln!("[art] ++");
+
+        // The FMC Alias Key is the authority for the RT Alias Certificate
+        let input = Self::dice_input_from_hand_off(env, hand_off)?;
+        let output = Self::derive(env, &input)?;
+
+        hand_off.set_rt_cdi(output.cdi);
+        hand_off.set_rt_priv_key(output.subj_key_pair.priv_key);
+        hand_off.set_rt_pub_key(env, &output.subj_key_pair.pub_key);
+
+        // Generate the RT Alias Certificate
+        let sig = Self::generate_cert_sig(env, &input, &output)?;
+        hand_off.set_rt_cert_sig(env, &sig);
+
+        cprintln!("[art] --");
+
         Ok(())
     }
 
@@ -47,4 +110,115 @@ impl RtAliasLayer {
         extend_journey_pcr(env, hand_off)?;
         Ok(())
     }
+
+    /// Build the DICE input from the FMC layer values handed off by ROM
+    ///
+    /// # Arguments
+    ///
+    /// * `env`      - FMC Environment
+    /// * `hand_off` - Firmware Handoff Table
+    fn dice_input_from_hand_off(env: &FmcEnv, hand_off: &HandOff) -> CaliptraResult<DiceInput> {
+        let auth_pub_key = hand_off.fmc_pub_key(env);
+        let auth_sn = X509::subj_sn(env, &auth_pub_key)?;
+        let auth_key_id = X509::subj_key_id(env, &auth_pub_key)?;
+
+        Ok(DiceInput {
+            cdi: hand_off.fmc_cdi(),
+            auth_key_pair: Ecc384KeyPair {
+                priv_key: hand_off.fmc_priv_key(),
+                pub_key: auth_pub_key,
+            },
+            auth_sn,
+            auth_key_id,
+            subj_priv_key: KEY_ID_RT_PRIV_KEY,
+        })
+    }
+
+    /// Derive Composite Device Identity (CDI) from RT measurements
+    ///
+    /// # Arguments
+    ///
+    /// * `env`         - FMC Environment
+    /// * `fmc_cdi`     - Key Slot holding the FMC CDI
+    /// * `measurement` - RT TCI
+    /// * `cdi`         - Key Slot to store the generated CDI
+    fn derive_cdi(
+        env: &FmcEnv,
+        fmc_cdi: KeyId,
+        measurement: Array4x12,
+        cdi: KeyId,
+    ) -> CaliptraResult<()> {
+        let key = Hmac384Key::Key(KeyReadArgs::new(fmc_cdi));
+        let data: [u8; 48] = measurement.into();
+        let data = Hmac384Data::Slice(&data);
+        Crypto::hmac384_mac(env, key, data, cdi)?;
+        Ok(())
+    }
+
+    /// Derive Dice Layer Key Pair
+    ///
+    /// # Arguments
+    ///
+    /// * `env`      - FMC Environment
+    /// * `cdi`      - Composite Device Identity
+    /// * `priv_key` - Key slot to store the private key into
+    ///
+    /// # Returns
+    ///
+    /// * `Ecc384KeyPair` - Derive DICE Layer Key Pair
+    fn derive_key_pair(env: &FmcEnv, cdi: KeyId, priv_key: KeyId) -> CaliptraResult<Ecc384KeyPair> {
+        Crypto::ecc384_key_gen(env, cdi, priv_key)
+    }
+
+    /// Generate RT Alias Certificate Signature
+    ///
+    /// # Arguments
+    ///
+    /// * `env`    - FMC Environment
+    /// * `input`  - DICE Input
+    /// * `output` - DICE Output
+    ///
+    /// # Returns
+    ///
+    /// * `Ecc384Signature` - Signature over the `To Be Signed` portion
+    fn generate_cert_sig(
+        env: &FmcEnv,
+        input: &DiceInput,
+        output: &DiceOutput,
+    ) -> CaliptraResult<Ecc384Signature> {
+        let auth_priv_key = input.auth_key_pair.priv_key;
+        let auth_pub_key = &input.auth_key_pair.pub_key;
+        let pub_key = &output.subj_key_pair.pub_key;
+
+        // Certificate `To Be Signed` Parameters
+        let params = RtAliasCertTbsParams {
+            ueid: &X509::ueid(env)?,
+            subject_sn: &output.subj_sn,
+            subject_key_id: &output.subj_key_id,
+            issuer_sn: &input.auth_sn,
+            authority_key_id: &input.auth_key_id,
+            serial_number: &X509::cert_sn(env, pub_key)?,
+            public_key: &pub_key.to_der(),
+            tcb_info_rt_tci: &env.data_vault().map(|d| d.rt_tci()).into(),
+            not_before: &NotBefore::default().not_before,
+            not_after: &NotAfter::default().not_after,
+        };
+
+        // Generate the `To Be Signed` portion of the certificate
+        let tbs = RtAliasCertTbs::new(&params);
+
+        // Sign the `To Be Signed` portion
+        cprintln!(
+            "[art] Signing Cert with AUTHORITY.KEYID = {}",
+            auth_priv_key as u8
+        );
+        let sig = Crypto::ecdsa384_sign(env, auth_priv_key, tbs.tbs())?;
+
+        // Verify the signature of the `To Be Signed` portion
+        if !Crypto::ecdsa384_verify(env, auth_pub_key, tbs.tbs(), &sig)? {
+            raise_err!(CertVerify);
+        }
+
+        Ok(sig)
+    }
 }
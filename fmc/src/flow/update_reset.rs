@@ -8,7 +8,7 @@ File Name:
 
 Abstract:
 
-    File contains the implementation of unknown/spurious reset flow.
+    File contains the implementation of update reset flow.
 
 --*/
 use crate::fmc_env::FmcEnv;
@@ -22,6 +22,12 @@ pub struct UpdateResetFlow {}
 impl UpdateResetFlow {
     /// Execute update reset flow
     ///
+    /// ROM has verified and loaded the new Runtime Firmware and stored its
+    /// measurements in the data vault. By the time this flow runs, the RT
+    /// Alias layer has been derived from them, the current PCR holds the new
+    /// measurements only and the journey PCR has been extended with them.
+    /// Nothing else needs to be done before handing off to Runtime Firmware.
+    ///
     /// # Arguments
     ///
     /// * `env` - FMC Environment
@@ -29,8 +35,6 @@ impl UpdateResetFlow {
     pub fn run(_: &FmcEnv, _: &mut HandOff) -> CaliptraResult<()> {
         cprintln!("[update-reset] ++");
 
-        // TODO: Implement
-
         cprintln!("[update-reset] --");
 
         Ok(())
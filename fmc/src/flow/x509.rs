@@ -0,0 +1,77 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+    x509.rs
+
+Abstract:
+
+    File contains X509 Certificate related utility functions
+
+--*/
+use crate::fmc_env::FmcEnv;
+use caliptra_common::x509;
+use caliptra_drivers::{CaliptraResult, Ecc384PubKey};
+
+/// X509 API
+pub enum X509 {}
+
+impl X509 {
+    /// Get device serial number
+    ///
+    /// # Arguments
+    ///
+    /// * `env` - FMC Environment
+    ///
+    /// # Returns
+    ///
+    /// `[u8; 8]` - 64-bit Unique Endpoint Identifier
+    pub fn ueid(env: &FmcEnv) -> CaliptraResult<[u8; 8]> {
+        let ueid = env.fuse_bank().map(|f| f.ueid());
+        Ok(ueid)
+    }
+
+    /// Get X509 Subject Serial Number
+    ///
+    /// # Arguments
+    ///
+    /// * `env`     - FMC Environment
+    /// * `pub_key` - Public Key
+    ///
+    /// # Returns
+    ///
+    /// `[u8; 64]` - X509 Subject Identifier serial number
+    pub fn subj_sn(env: &FmcEnv, pub_key: &Ecc384PubKey) -> CaliptraResult<[u8; 64]> {
+        env.sha256().map(|sha| x509::subj_sn(sha, pub_key))
+    }
+
+    /// Get Cert Subject Key Identifier
+    ///
+    /// # Arguments
+    ///
+    /// * `env`     - FMC Environment
+    /// * `pub_key` - Public Key
+    ///
+    /// # Returns
+    ///
+    /// `[u8; 20]` - X509 Subject Key Identifier
+    pub fn subj_key_id(env: &FmcEnv, pub_key: &Ecc384PubKey) -> CaliptraResult<[u8; 20]> {
+        env.sha256().map(|sha| x509::subj_key_id(sha, pub_key))
+    }
+
+    /// Get Cert Serial Number
+    ///
+    /// # Arguments
+    ///
+    /// * `env`     - FMC Environment
+    /// * `pub_key` - Public Key
+    ///
+    /// # Returns
+    ///
+    /// `[u8; 20]` - X509 Serial Number
+    pub fn cert_sn(env: &FmcEnv, pub_key: &Ecc384PubKey) -> CaliptraResult<[u8; 20]> {
+        env.sha256().map(|sha| x509::cert_sn(sha, pub_key))
+    }
+}
@@ -17,8 +17,8 @@ Abstract:
 use crate::fmc_env_cell::FmcEnvCell;
 
 use caliptra_drivers::{
-    DataVault, DeviceState, Ecc384, FlowStatus, FuseBank, Hmac384, KeyVault, Mailbox, MfgState,
-    PcrBank, ResetService, Sha1, Sha256, Sha384, Sha384Acc,
+    CaliptraResult, Csrng, DataVault, DeviceState, Ecc384, FlowStatus, FuseBank, Hmac384, KeyVault,
+    Mailbox, MfgState, PcrBank, ResetService, Sha1, Sha256, Sha384, Sha384Acc,
 };
 
 /// Hardware Context
@@ -67,11 +67,18 @@ pub struct FmcEnv {
 
     /// PCR Bank
     pcr_bank: FmcEnvCell<PcrBank>,
+
+    /// Cryptographically Secure Random Number Generator
+    csrng: FmcEnvCell<Csrng>,
 }
 
-impl Default for FmcEnv {
-    fn default() -> Self {
-        Self {
+impl FmcEnv {
+    /// Create the FMC Environment
+    ///
+    /// The CSRNG is instantiated from the entropy source, whose health was
+    /// checked by ROM.
+    pub fn new() -> CaliptraResult<Self> {
+        Ok(Self {
             reset: FmcEnvCell::new(ResetService::default()),
             sha1: FmcEnvCell::new(Sha1::default()),
             sha256: FmcEnvCell::new(Sha256::default()),
@@ -87,11 +94,11 @@ impl Default for FmcEnv {
             flow_status: FmcEnvCell::new(FlowStatus::default()),
             fuse_bank: FmcEnvCell::new(FuseBank::default()),
             pcr_bank: FmcEnvCell::new(PcrBank::default()),
-        }
+            // This is the only CSRNG handle in FMC
+            csrng: FmcEnvCell::new(unsafe { Csrng::new()? }),
+        })
     }
-}
 
-impl FmcEnv {
     /// Get reset service reference
     pub fn reset(&self) -> &FmcEnvCell<ResetService> {
         &self.reset
@@ -166,4 +173,9 @@ impl FmcEnv {
     pub fn ecc384(&self) -> &FmcEnvCell<Ecc384> {
         &self.ecc384
     }
+
+    /// Get CSRNG
+    pub fn csrng(&self) -> &FmcEnvCell<Csrng> {
+        &self.csrng
+    }
 }
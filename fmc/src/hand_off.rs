@@ -13,8 +13,10 @@ File Name:
 
 use crate::fmc_env::FmcEnv;
 use caliptra_common::DataStore::*;
-use caliptra_common::{DataStore, FirmwareHandoffTable};
-use caliptra_drivers::Array4x12;
+use caliptra_common::{DataStore, FirmwareHandoffTable, HandOffDataHandle};
+use caliptra_drivers::{
+    memory_layout, Array4x12, Ecc384PubKey, Ecc384Signature, KeyId, WarmResetEntry48,
+};
 #[cfg(feature = "riscv")]
 core::arch::global_asm!(include_str!("transfer_control.S"));
 
@@ -31,11 +33,9 @@ impl MemoryRegion {
 }
 
 impl IccmAddress {
-    const ICCM_ORG: u32 = 0x40000000;
-    const ICCM_SIZE: u32 = 128 << 10;
     const ICCM: MemoryRegion = MemoryRegion {
-        start: Self::ICCM_ORG,
-        size: Self::ICCM_SIZE,
+        start: memory_layout::ICCM_ORG,
+        size: memory_layout::ICCM_SIZE,
     };
 
     /// Validate that the address is within the ICCM region.
@@ -72,10 +72,63 @@ impl HandOff {
         if !rt_entry.is_valid() {
             crate::report_error(0xdead);
         }
+        // Publish the handles populated by FMC to the runtime firmware
+        self.fht.save();
+
         // Exit FMC and jump to speicified entry point
         unsafe { transfer_control(rt_entry.0) }
     }
 
+    /// Retrieve FMC CDI
+    pub fn fmc_cdi(&self) -> KeyId {
+        Self::key_id(self.fht.fmc_cdi_kv_hdl, "Invalid FMC CDI KV handle")
+    }
+
+    /// Retrieve FMC Alias Private Key
+    pub fn fmc_priv_key(&self) -> KeyId {
+        Self::key_id(
+            self.fht.fmc_priv_key_kv_hdl,
+            "Invalid FMC private key KV handle",
+        )
+    }
+
+    /// Retrieve FMC Alias Public Key
+    pub fn fmc_pub_key(&self, env: &FmcEnv) -> Ecc384PubKey {
+        Ecc384PubKey {
+            x: Self::read_dv48(env, self.fht.fmc_pub_key_x_dv_hdl),
+            y: Self::read_dv48(env, self.fht.fmc_pub_key_y_dv_hdl),
+        }
+    }
+
+    /// Record the Key Vault slot holding the RT CDI.
+    pub fn set_rt_cdi(&mut self, key_id: KeyId) {
+        self.fht.rt_cdi_kv_hdl = HandOffDataHandle::from(KeyVaultSlot(key_id));
+    }
+
+    /// Record the Key Vault slot holding the RT Alias Private Key.
+    pub fn set_rt_priv_key(&mut self, key_id: KeyId) {
+        self.fht.rt_priv_key_kv_hdl = HandOffDataHandle::from(KeyVaultSlot(key_id));
+    }
+
+    /// Store the RT Alias Public Key in the Data Vault and record its location.
+    pub fn set_rt_pub_key(&mut self, env: &FmcEnv, pub_key: &Ecc384PubKey) {
+        self.fht.rt_pub_key_x_dv_hdl =
+            HandOffDataHandle::from(DataVaultNonSticky48(WarmResetEntry48::RtPubKeyX));
+        self.fht.rt_pub_key_y_dv_hdl =
+            HandOffDataHandle::from(DataVaultNonSticky48(WarmResetEntry48::RtPubKeyY));
+        env.data_vault().map(|d| d.set_rt_pub_key(pub_key));
+    }
+
+    /// Store the RT Alias Certificate Signature in the Data Vault and record
+    /// its location.
+    pub fn set_rt_cert_sig(&mut self, env: &FmcEnv, sig: &Ecc384Signature) {
+        self.fht.rt_cert_sig_r_dv_hdl =
+            HandOffDataHandle::from(DataVaultNonSticky48(WarmResetEntry48::RtDiceSigR));
+        self.fht.rt_cert_sig_s_dv_hdl =
+            HandOffDataHandle::from(DataVaultNonSticky48(WarmResetEntry48::RtDiceSigS));
+        env.data_vault().map(|d| d.set_rt_dice_signature(sig));
+    }
+
     /// Retrieve runtime TCI (digest)
     pub fn rt_tci(&self, env: &FmcEnv) -> Array4x12 {
         let ds: DataStore = self.fht.rt_tci_dv_hdl.try_into().unwrap_or_else(|_| {
@@ -124,6 +177,40 @@ impl HandOff {
         }
     }
 
+    /// Retrieve the Key Vault slot referenced by `hdl`.
+    fn key_id(hdl: HandOffDataHandle, msg: &str) -> KeyId {
+        let ds: DataStore = hdl
+            .try_into()
+            .unwrap_or_else(|_| caliptra_common::report_handoff_error_and_halt(msg, 0xbabedead));
+
+        match ds {
+            KeyVaultSlot(key_id) => key_id,
+            _ => {
+                crate::report_error(0xbabedead);
+            }
+        }
+    }
+
+    /// Read the 384-bit Data Vault entry referenced by `hdl`.
+    fn read_dv48(env: &FmcEnv, hdl: HandOffDataHandle) -> Array4x12 {
+        let ds: DataStore = hdl.try_into().unwrap_or_else(|_| {
+            caliptra_common::report_handoff_error_and_halt("Invalid DV handle", 0xbabedead)
+        });
+
+        // The data store is either a warm reset entry or a cold reset entry.
+        match ds {
+            DataVaultNonSticky48(dv_entry) => env
+                .data_vault()
+                .map(|d| d.read_warm_reset_entry48(dv_entry)),
+            DataVaultSticky48(dv_entry) => env
+                .data_vault()
+                .map(|d| d.read_cold_reset_entry48(dv_entry)),
+            _ => {
+                crate::report_error(0xbabedead);
+            }
+        }
+    }
+
     /// Retrieve the entry point of the runtime firmware.
     fn rt_entry_point(&self, env: &FmcEnv) -> u32 {
         let ds: DataStore = self
@@ -16,7 +16,7 @@ Abstract:
 use core::hint::black_box;
 
 use caliptra_common::cprintln;
-use caliptra_drivers::{report_fw_error_non_fatal, Mailbox};
+use caliptra_drivers::{handle_fatal_error, FatalErrorTrap};
 mod flow;
 pub mod fmc_env;
 pub mod fmc_env_cell;
@@ -37,9 +37,10 @@ pub extern "C" fn entry_point() -> ! {
     cprintln!("{}", BANNER);
 
     if let Some(mut hand_off) = HandOff::from_previous() {
-        let env = fmc_env::FmcEnv::default();
-        if flow::run(&env, &mut hand_off).is_ok() {
-            hand_off.to_rt(&env)
+        if let Ok(env) = fmc_env::FmcEnv::new() {
+            if flow::run(&env, &mut hand_off).is_ok() {
+                hand_off.to_rt(&env)
+            }
         }
     }
     caliptra_drivers::ExitCtrl::exit(0xff)
@@ -55,7 +56,7 @@ extern "C" fn exception_handler(trap_record: &TrapRecord) {
         trap_record.mscause,
         trap_record.mepc
     );
-    report_error(0xdead);
+    report_trap_error(0xdead, trap_record);
 }
 
 #[no_mangle]
@@ -69,10 +70,7 @@ extern "C" fn nmi_handler(trap_record: &TrapRecord) {
         trap_record.mepc
     );
 
-    // TODO: Signal error to SOC
-    // - Signal Fatal error for ICCM/DCCM double bit faults
-    // - Signal Non=-Fatal error for all other errors
-    report_error(0xdead);
+    report_trap_error(0xdead, trap_record);
 }
 #[panic_handler]
 #[inline(never)]
@@ -82,21 +80,17 @@ fn fmc_panic(_: &core::panic::PanicInfo) -> ! {
     cprintln!("FMC Panic!!");
     panic_is_possible();
 
-    // TODO: Signal non-fatal error to SOC
     report_error(0xdead);
 }
 
-#[allow(clippy::empty_loop)]
 fn report_error(code: u32) -> ! {
     cprintln!("FMC Error: 0x{:08X}", code);
-    report_fw_error_non_fatal(code);
+    handle_fatal_error(code, None)
+}
 
-    loop {
-        // SoC firmware might be stuck waiting for Caliptra to finish
-        // executing this pending mailbox transaction. Notify them that
-        // we've failed.
-        unsafe { Mailbox::abort_pending_soc_to_uc_transactions() };
-    }
+fn report_trap_error(code: u32, trap_record: &TrapRecord) -> ! {
+    cprintln!("FMC Error: 0x{:08X}", code);
+    handle_fatal_error(code, Some(&FatalErrorTrap::from(trap_record)))
 }
 
 #[no_mangle]
@@ -5,7 +5,7 @@ META_ORG  = ROM_SIZE;
 ICCM_ORG  = 0x40002000;
 DCCM_ORG  = 0x50000000;
 FHT_ORG   = 0x50002000;
-DATA_ORG  = 0x50003000;
+DATA_ORG  = 0x50005000;
 STACK_ORG = 0x5001C000;
 ESTACK_ORG  = 0x5001F800;
 NSTACK_ORG  = 0x5001FC00;
@@ -18,7 +18,7 @@ DCCM_SIZE   = 128K;
 MAN1_SIZE   = 4K;
 MAN2_SIZE   = 4K;
 FHT_SIZE    = 4K;
-DATA_SIZE   = 100K;
+DATA_SIZE   = 92K;
 STACK_SIZE  = 14K;
 ESTACK_SIZE = 1K;
 NSTACK_SIZE = 1K;
@@ -16,8 +16,9 @@ Abstract:
 
 use caliptra_common::cprintln;
 use caliptra_cpu::TrapRecord;
-use caliptra_drivers::{report_fw_error_non_fatal, Mailbox};
+use caliptra_drivers::{report_fw_error_non_fatal, DataVault, Ecc384PubKey, FuseBank, Mailbox};
 use core::hint::black_box;
+use ufmt::{uDisplay, uWrite};
 
 #[cfg(feature = "std")]
 pub fn main() {}
@@ -36,12 +37,49 @@ pub extern "C" fn entry_point() -> ! {
     cprintln!("{}", BANNER);
 
     if let Some(_fht) = caliptra_common::FirmwareHandoffTable::try_load() {
+        print_rt_alias();
         caliptra_drivers::ExitCtrl::exit(0)
     } else {
         caliptra_drivers::ExitCtrl::exit(0xff)
     }
 }
 
+struct HexBytes<'a>(&'a [u8]);
+
+impl uDisplay for HexBytes<'_> {
+    fn fmt<W>(&self, f: &mut ufmt::Formatter<'_, W>) -> Result<(), W::Error>
+    where
+        W: uWrite + ?Sized,
+    {
+        for byte in self.0.iter() {
+            ufmt::uwrite!(f, "{:02X}", *byte)?;
+        }
+        Ok(())
+    }
+}
+
+/// Print the RT Alias layer values handed off by FMC, so that tests can
+/// rebuild and verify the RT Alias certificate
+fn print_rt_alias() {
+    fn print_pub_key(name: &str, pub_key: &Ecc384PubKey) {
+        let x: [u8; 48] = pub_key.x.into();
+        let y: [u8; 48] = pub_key.y.into();
+        cprintln!("{} = {}{}", name, HexBytes(&x), HexBytes(&y));
+    }
+
+    let dv = DataVault::default();
+    let sig = dv.rt_dice_signature();
+    let r: [u8; 48] = sig.r.into();
+    let s: [u8; 48] = sig.s.into();
+    let tci: [u8; 48] = dv.rt_tci().into();
+
+    cprintln!("UEID = {}", HexBytes(&FuseBank::default().ueid()));
+    print_pub_key("FMC_PUB_KEY", &dv.fmc_pub_key());
+    print_pub_key("RT_PUB_KEY", &dv.rt_pub_key());
+    cprintln!("RT_TCI = {}", HexBytes(&tci));
+    cprintln!("RT_CERT_SIG = {}{}", HexBytes(&r), HexBytes(&s));
+}
+
 #[no_mangle]
 #[inline(never)]
 #[allow(clippy::empty_loop)]
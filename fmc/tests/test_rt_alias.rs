@@ -0,0 +1,117 @@
+// Licensed under the Apache-2.0 license
+use caliptra_builder::{FwId, ImageOptions, FMC_WITH_UART, ROM_WITH_UART};
+use caliptra_hw_model::{BootParams, HwModel, InitParams};
+use caliptra_x509::{NotAfter, NotBefore, RtAliasCertTbs, RtAliasCertTbsParams};
+use openssl::bn::{BigNum, BigNumContext};
+use openssl::ec::{EcGroup, EcKey, EcPoint};
+use openssl::ecdsa::EcdsaSig;
+use openssl::nid::Nid;
+use openssl::sha::{sha256, sha384};
+
+const MOCK_RT_WITH_UART: FwId = FwId {
+    crate_name: "caliptra-fmc-mock-rt",
+    bin_name: "caliptra-fmc-mock-rt",
+    features: &["emu"],
+};
+
+/// Boot ROM -> FMC -> mock RT and return the mock RT output
+fn boot(rom: &[u8], image: &[u8]) -> String {
+    let mut hw = caliptra_hw_model::new(BootParams {
+        init_params: InitParams {
+            rom,
+            ..Default::default()
+        },
+        fw_image: Some(image),
+        ..Default::default()
+    })
+    .unwrap();
+
+    let mut output = vec![];
+    hw.copy_output_until_exit_success(&mut output).unwrap();
+    String::from_utf8_lossy(&output).into_owned()
+}
+
+/// Get the value of a `NAME = HEX` line printed by the mock RT
+fn value(output: &str, name: &str) -> Vec<u8> {
+    let prefix = format!("{name} = ");
+    let line = output
+        .lines()
+        .find_map(|l| l.strip_prefix(&prefix))
+        .unwrap_or_else(|| panic!("{name} not found in output"));
+    hex::decode(line.trim()).unwrap()
+}
+
+/// Subject serial number, key identifier and certificate serial number of
+/// a DER encoded public key
+fn key_ids(pub_key: &[u8]) -> ([u8; 64], [u8; 20], [u8; 20]) {
+    let digest = sha256(pub_key);
+    let sn: [u8; 64] = hex::encode_upper(digest).into_bytes().try_into().unwrap();
+    let key_id: [u8; 20] = digest[..20].try_into().unwrap();
+    let mut cert_sn = key_id;
+    cert_sn[0] &= !0x80;
+    (sn, key_id, cert_sn)
+}
+
+fn der(raw_pub_key: &[u8]) -> [u8; 97] {
+    let mut der = [0x04; 97];
+    der[1..].copy_from_slice(raw_pub_key);
+    der
+}
+
+#[test]
+fn test_rt_alias() {
+    let rom = caliptra_builder::build_firmware_rom(&ROM_WITH_UART).unwrap();
+    let image = caliptra_builder::build_and_sign_image(
+        &FMC_WITH_UART,
+        &MOCK_RT_WITH_UART,
+        ImageOptions::default(),
+    )
+    .unwrap();
+    let image_bytes = image.to_bytes().unwrap();
+
+    let output = boot(&rom, &image_bytes);
+
+    let ueid = value(&output, "UEID");
+    let fmc_pub_key = der(&value(&output, "FMC_PUB_KEY"));
+    let rt_pub_key = der(&value(&output, "RT_PUB_KEY"));
+    let rt_tci = value(&output, "RT_TCI");
+    let sig = value(&output, "RT_CERT_SIG");
+
+    // The RT Alias key is a new key, derived from the runtime measurement
+    assert_ne!(rt_pub_key, fmc_pub_key);
+    assert_eq!(rt_tci, sha384(&image.runtime));
+
+    // Rebuild the RT Alias certificate and check FMC signed it with the FMC
+    // Alias key
+    let (subject_sn, subject_key_id, serial_number) = key_ids(&rt_pub_key);
+    let (issuer_sn, authority_key_id, _) = key_ids(&fmc_pub_key);
+    let params = RtAliasCertTbsParams {
+        ueid: &ueid.try_into().unwrap(),
+        subject_sn: &subject_sn,
+        subject_key_id: &subject_key_id,
+        issuer_sn: &issuer_sn,
+        authority_key_id: &authority_key_id,
+        serial_number: &serial_number,
+        public_key: &rt_pub_key,
+        tcb_info_rt_tci: &rt_tci.try_into().unwrap(),
+        not_before: &NotBefore::default().not_before,
+        not_after: &NotAfter::default().not_after,
+    };
+    let tbs = RtAliasCertTbs::new(&params);
+
+    let group = EcGroup::from_curve_name(Nid::SECP384R1).unwrap();
+    let mut ctx = BigNumContext::new().unwrap();
+    let point = EcPoint::from_bytes(&group, &fmc_pub_key, &mut ctx).unwrap();
+    let fmc_key = EcKey::from_public_key(&group, &point).unwrap();
+    let sig = EcdsaSig::from_private_components(
+        BigNum::from_slice(&sig[..48]).unwrap(),
+        BigNum::from_slice(&sig[48..]).unwrap(),
+    )
+    .unwrap();
+    assert!(sig.verify(&sha384(tbs.tbs()), &fmc_key).unwrap());
+
+    // The derivation is deterministic: the same FMC CDI and runtime yield the
+    // same RT Alias key
+    let output = boot(&rom, &image_bytes);
+    assert_eq!(der(&value(&output, "RT_PUB_KEY")), rt_pub_key);
+}
@@ -7,7 +7,9 @@ use std::{
     io::{stdout, ErrorKind, Write},
 };
 
+use caliptra_api::{CommandId, MailboxReq, MailboxResp};
 use caliptra_emu_bus::Bus;
+use caliptra_image_types::{IMAGE_CHUNK_BYTE_SIZE, IMAGE_MANIFEST_BYTE_SIZE};
 
 use caliptra_registers::mbox;
 use caliptra_registers::mbox::enums::{MboxFsmE, MboxStatusE};
@@ -31,6 +33,7 @@ pub use model_emulated::ModelEmulated;
 #[cfg(feature = "verilator")]
 pub use model_verilated::ModelVerilated;
 use ureg::Mmio;
+use zerocopy::{AsBytes, FromBytes};
 
 /// Ideally, general-purpose functions would return `impl HwModel` instead of
 /// `DefaultHwModel` to prevent users from calling functions that aren't
@@ -65,6 +68,26 @@ pub fn new(params: BootParams) -> Result<DefaultHwModel, Box<dyn Error>> {
     DefaultHwModel::new(params)
 }
 
+/// Split a firmware image into the mailbox transactions that load it
+///
+/// Images of up to `chunk_size` bytes are sent in a single FIRMWARE_LOAD
+/// transaction. Larger images are sent as a FIRMWARE_LOAD transaction
+/// carrying only the manifest, followed by the rest of the image in order in
+/// FIRMWARE_LOAD_CHUNK transactions of up to `chunk_size` bytes.
+pub fn firmware_load_txns(firmware: &[u8], chunk_size: usize) -> Vec<(CommandId, &[u8])> {
+    if firmware.len() <= chunk_size || firmware.len() <= IMAGE_MANIFEST_BYTE_SIZE {
+        return vec![(CommandId::FirmwareLoad, firmware)];
+    }
+    let (manifest, sections) = firmware.split_at(IMAGE_MANIFEST_BYTE_SIZE);
+    std::iter::once((CommandId::FirmwareLoad, manifest))
+        .chain(
+            sections
+                .chunks(chunk_size)
+                .map(|chunk| (CommandId::FirmwareLoadChunk, chunk)),
+        )
+        .collect()
+}
+
 struct RandomNibbles<R: RngCore>(pub R);
 
 impl<R: RngCore> Iterator for RandomNibbles<R> {
@@ -131,6 +154,9 @@ pub enum ModelError {
     ProvidedIccmTooLarge,
     ProvidedDccmTooLarge,
     UnexpectedMailboxFsmStatus { expected: u32, actual: u32 },
+    MailboxNoResponseData,
+    MailboxRespInvalidLength,
+    MailboxRespInvalidChecksum,
 }
 impl Error for ModelError {}
 impl Display for ModelError {
@@ -157,6 +183,15 @@ impl Display for ModelError {
                 f,
                 "Expected mailbox FSM status to be {expected}, was {actual}"
             ),
+            ModelError::MailboxNoResponseData => {
+                write!(f, "Expected response data but none was provided")
+            }
+            ModelError::MailboxRespInvalidLength => {
+                write!(f, "Mailbox response has an unexpected length")
+            }
+            ModelError::MailboxRespInvalidChecksum => {
+                write!(f, "Mailbox response has an invalid checksum")
+            }
         }
     }
 }
@@ -253,9 +288,6 @@ fn mbox_write_fifo(mbox: &mbox::RegisterBlock<impl Mmio>, buf: &[u8]) -> Result<
     Ok(())
 }
 
-/// Firmware Load Command Opcode
-const FW_LOAD_CMD_OPCODE: u32 = 0x4657_4C44;
-
 // Represents a emulator or simulation of the caliptra hardware, to be called
 // from tests. Typically, test cases should use [`crate::new()`] to create a model
 // based on the cargo features (and any model-specific environment variables).
@@ -370,6 +402,9 @@ pub trait HwModel {
         self.soc_ifc()
             .fuse_life_cycle()
             .write(|w| w.life_cycle(fuses.life_cycle.into()));
+        self.soc_ifc()
+            .fuse_lms_revocation()
+            .write(|_| fuses.lms_revocation.into());
 
         self.soc_ifc().cptra_fuse_wr_done().write(|w| w.done(true));
         assert!(self.soc_ifc().cptra_fuse_wr_done().read().done());
@@ -507,11 +542,40 @@ pub trait HwModel {
         Ok(Some(result))
     }
 
-    /// Upload firmware to the mailbox.
+    /// Executes a checksummed request `req` after populating its checksum,
+    /// and returns the parsed response after verifying its checksum.
+    fn mailbox_execute_req<R: MailboxReq>(&mut self, mut req: R) -> Result<R::Resp, ModelError> {
+        req.populate_chksum();
+        let Some(data) = self.mailbox_execute(R::ID.into(), req.as_bytes())? else {
+            return Err(ModelError::MailboxNoResponseData);
+        };
+        let Some(resp) = R::Resp::read_from(data.as_slice()) else {
+            return Err(ModelError::MailboxRespInvalidLength);
+        };
+        if !resp.verify_chksum() {
+            return Err(ModelError::MailboxRespInvalidChecksum);
+        }
+        Ok(resp)
+    }
+
+    /// Upload firmware to the mailbox, in chunks if it is larger than the
+    /// mailbox.
     fn upload_firmware(&mut self, firmware: &[u8]) -> Result<(), ModelError> {
-        let response = self.mailbox_execute(FW_LOAD_CMD_OPCODE, firmware)?;
-        if response.is_some() {
-            return Err(ModelError::UploadFirmwareUnexpectedResponse);
+        self.upload_firmware_chunked(firmware, IMAGE_CHUNK_BYTE_SIZE)
+    }
+
+    /// Upload firmware to the mailbox, sending the sections of images larger
+    /// than `chunk_size` bytes in chunks of up to `chunk_size` bytes.
+    fn upload_firmware_chunked(
+        &mut self,
+        firmware: &[u8],
+        chunk_size: usize,
+    ) -> Result<(), ModelError> {
+        for (cmd, data) in firmware_load_txns(firmware, chunk_size) {
+            let response = self.mailbox_execute(cmd.into(), data)?;
+            if response.is_some() {
+                return Err(ModelError::UploadFirmwareUnexpectedResponse);
+            }
         }
         Ok(())
     }
@@ -553,10 +617,14 @@ pub trait HwModel {
 
 #[cfg(test)]
 mod tests {
-    use crate::{mmio::Rv32GenMmio, BootParams, HwModel, InitParams, ModelError};
+    use crate::{
+        firmware_load_txns, mmio::Rv32GenMmio, BootParams, HwModel, InitParams, ModelError,
+    };
+    use caliptra_api::CommandId;
     use caliptra_builder::FwId;
     use caliptra_emu_bus::Bus;
     use caliptra_emu_types::RvSize;
+    use caliptra_image_types::IMAGE_MANIFEST_BYTE_SIZE;
     use caliptra_registers::{mbox::enums::MboxStatusE, soc_ifc};
 
     use crate as caliptra_hw_model;
@@ -765,4 +833,27 @@ mod tests {
         // TODO: Add test for txn.respond_with_data (this doesn't work yet due
         // to https://github.com/chipsalliance/caliptra-rtl/issues/78)
     }
+
+    #[test]
+    fn test_firmware_load_txns() {
+        let firmware: Vec<u8> = (0..IMAGE_MANIFEST_BYTE_SIZE + 10)
+            .map(|i| i as u8)
+            .collect();
+
+        assert_eq!(
+            firmware_load_txns(&firmware, firmware.len()),
+            [(CommandId::FirmwareLoad, &firmware[..])]
+        );
+
+        let (manifest, sections) = firmware.split_at(IMAGE_MANIFEST_BYTE_SIZE);
+        assert_eq!(
+            firmware_load_txns(&firmware, 4),
+            [
+                (CommandId::FirmwareLoad, manifest),
+                (CommandId::FirmwareLoadChunk, &sections[..4]),
+                (CommandId::FirmwareLoadChunk, &sections[4..8]),
+                (CommandId::FirmwareLoadChunk, &sections[8..]),
+            ]
+        );
+    }
 }
@@ -146,6 +146,7 @@ pub struct Fuses {
     pub idevid_cert_attr: [u32; 24],
     pub idevid_manuf_hsm_id: [u32; 4],
     pub life_cycle: DeviceLifecycle,
+    pub lms_revocation: U4,
 }
 
 #[cfg(test)]
@@ -0,0 +1,246 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+   mod.rs
+
+Abstract:
+
+    File contains implementation Caliptra Image assembly command.
+
+--*/
+
+use anyhow::Context;
+use caliptra_image_gen::*;
+use caliptra_image_serde::{ImageBundleReader, ImageBundleWriter};
+use caliptra_image_types::*;
+use clap::ArgMatches;
+use std::path::PathBuf;
+
+/// Run the command
+pub(crate) fn run_cmd(args: &ArgMatches) -> anyhow::Result<()> {
+    let image_path: &PathBuf = args
+        .get_one::<PathBuf>("image")
+        .with_context(|| "image arg not specified")?;
+
+    let vendor_ecc_sig_path: &PathBuf = args
+        .get_one::<PathBuf>("vendor-ecc-sig")
+        .with_context(|| "vendor-ecc-sig arg not specified")?;
+
+    let vendor_lms_sig_path: &PathBuf = args
+        .get_one::<PathBuf>("vendor-lms-sig")
+        .with_context(|| "vendor-lms-sig arg not specified")?;
+
+    let out_path: &PathBuf = args
+        .get_one::<PathBuf>("out")
+        .with_context(|| "out arg not specified")?;
+
+    let image_file = std::fs::File::open(image_path)
+        .with_context(|| format!("Failed to open image {}", image_path.display()))?;
+    let image = ImageBundleReader::new(image_file)
+        .read()
+        .with_context(|| format!("Failed to parse image {}", image_path.display()))?;
+
+    let vendor_sigs = signatures(vendor_ecc_sig_path, vendor_lms_sig_path)?;
+
+    let owner_sigs = match (
+        args.get_one::<PathBuf>("owner-ecc-sig"),
+        args.get_one::<PathBuf>("owner-lms-sig"),
+    ) {
+        (Some(ecc_sig_path), Some(lms_sig_path)) => Some(signatures(ecc_sig_path, lms_sig_path)?),
+        _ => None,
+    };
+
+    let gen = ImageGenerator::new(crate::Crypto::default());
+    let image = gen.assemble(image, vendor_sigs, owner_sigs)?;
+
+    let out_file = std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(true)
+        .write(true)
+        .open(out_path)
+        .with_context(|| format!("Failed to create file {}", out_path.display()))?;
+
+    let mut writer = ImageBundleWriter::new(out_file);
+    writer.write(&image)?;
+
+    Ok(())
+}
+
+/// Read the ECC and LMS signatures from files
+fn signatures(ecc_sig_path: &PathBuf, lms_sig_path: &PathBuf) -> anyhow::Result<ImageSignatures> {
+    let ecc_sig = std::fs::read(ecc_sig_path)
+        .with_context(|| format!("Failed to read ECC signature {}", ecc_sig_path.display()))?;
+    let lms_sig = std::fs::read(lms_sig_path)
+        .with_context(|| format!("Failed to read LMS signature {}", lms_sig_path.display()))?;
+
+    Ok(ImageSignatures {
+        ecc_sig: ecc_sig_from_bytes(&ecc_sig)
+            .with_context(|| format!("Invalid ECC signature {}", ecc_sig_path.display()))?,
+        lms_sig: lms_sig_from_bytes(&lms_sig)
+            .with_context(|| format!("Invalid LMS signature {}", lms_sig_path.display()))?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{self, TempDir};
+    use caliptra_image_fake_keys::*;
+
+    /// Encode a DER INTEGER from a scalar in hardware format
+    fn der_integer(scalar: &ImageScalar) -> Vec<u8> {
+        let bytes: Vec<u8> = scalar.iter().flat_map(|w| w.to_be_bytes()).collect();
+        let start = bytes
+            .iter()
+            .position(|&b| b != 0)
+            .unwrap_or(bytes.len() - 1);
+        let mut value = bytes[start..].to_vec();
+        if value[0] & 0x80 != 0 {
+            value.insert(0, 0);
+        }
+        let mut der = vec![0x02, value.len() as u8];
+        der.extend(value);
+        der
+    }
+
+    /// Encode an `Ecdsa-Sig-Value` as produced by an HSM
+    fn ecc_sig_to_der(sig: &ImageEccSignature) -> Vec<u8> {
+        let mut seq = der_integer(&sig.r);
+        seq.extend(der_integer(&sig.s));
+        let mut der = if seq.len() < 0x80 {
+            vec![0x30, seq.len() as u8]
+        } else {
+            vec![0x30, 0x81, seq.len() as u8]
+        };
+        der.extend(seq);
+        der
+    }
+
+    /// Encode a single level HSS signature
+    fn lms_sig_to_hss(sig: &ImageLmsSignature) -> Vec<u8> {
+        let mut hss = 0u32.to_be_bytes().to_vec();
+        hss.extend(sig.q.to_be_bytes());
+        hss.extend(sig.ots.otstype.to_be_bytes());
+        hss.extend(sig.ots.nonce);
+        sig.ots.y.iter().for_each(|y| hss.extend(y));
+        hss.extend(sig.tree_type.to_be_bytes());
+        sig.tree_path.iter().for_each(|node| hss.extend(node));
+        hss
+    }
+
+    /// Sign `digest` outside of the generator and write the signature files
+    fn sign_detached(
+        dir: &TempDir,
+        name: &str,
+        digest: &[u8],
+        ecc_priv_key: &ImageEccPrivKey,
+        ecc_pub_key: &ImageEccPubKey,
+        lms_priv_key: &ImageLmsPrivKey,
+    ) -> (PathBuf, PathBuf) {
+        let crypto = crate::Crypto::default();
+        let mut digest_words = ImageDigest::default();
+        for (word, bytes) in digest_words.iter_mut().zip(digest.chunks_exact(4)) {
+            *word = u32::from_be_bytes(bytes.try_into().unwrap());
+        }
+
+        let ecc_sig = crypto
+            .ecdsa384_sign(&digest_words, ecc_priv_key, ecc_pub_key)
+            .unwrap();
+        let lms_sig = crypto.lms_sign(&digest_words, lms_priv_key).unwrap();
+
+        let ecc_sig_path = dir.path(&format!("{name}-ecc.sig"));
+        let lms_sig_path = dir.path(&format!("{name}-lms.sig"));
+        std::fs::write(&ecc_sig_path, ecc_sig_to_der(&ecc_sig)).unwrap();
+        std::fs::write(&lms_sig_path, lms_sig_to_hss(&lms_sig)).unwrap();
+        (ecc_sig_path, lms_sig_path)
+    }
+
+    /// Sign `digest` with the fake vendor and owner keys and assemble the
+    /// unsigned image
+    fn assemble_detached(
+        dir: &TempDir,
+        image: ImageBundle,
+        vendor_digest: &[u8],
+        owner_digest: &[u8],
+    ) -> anyhow::Result<ImageBundle> {
+        let vendor = sign_detached(
+            dir,
+            "vendor",
+            vendor_digest,
+            &VENDOR_KEY_0_PRIVATE,
+            &VENDOR_KEY_0_PUBLIC,
+            &VENDOR_LMS_KEY_0_PRIVATE,
+        );
+        let owner = sign_detached(
+            dir,
+            "owner",
+            owner_digest,
+            &OWNER_KEY_PRIVATE,
+            &OWNER_KEY_PUBLIC,
+            &OWNER_LMS_KEY_PRIVATE,
+        );
+
+        let vendor_sigs = signatures(&vendor.0, &vendor.1)?;
+        let owner_sigs = signatures(&owner.0, &owner.1)?;
+        ImageGenerator::new(crate::Crypto::default()).assemble(image, vendor_sigs, Some(owner_sigs))
+    }
+
+    /// Generate an unsigned image and the header digest given to the signers
+    fn unsigned_image() -> (ImageBundle, Vec<u8>) {
+        let gen = ImageGenerator::new(crate::Crypto::default());
+        let image = gen.generate_unsigned(&test_util::image_config()).unwrap();
+        let digest = gen.header_digest(&image.manifest.header).unwrap();
+        let digest = digest.iter().flat_map(|w| w.to_be_bytes()).collect();
+        (image, digest)
+    }
+
+    #[test]
+    fn test_assemble_detached() {
+        let dir = TempDir::new("assemble-detached");
+        let (image, digest) = unsigned_image();
+
+        let image = assemble_detached(&dir, image, &digest, &digest).unwrap();
+
+        // The assembled image is equivalent to one signed by the generator
+        let signed = test_util::image_bundle();
+        assert_eq!(image.to_bytes().unwrap(), signed.to_bytes().unwrap());
+
+        let fuses = test_util::fuse_config(&image);
+        crate::verify::verify_image(&image.to_bytes().unwrap(), &fuses, None).unwrap();
+    }
+
+    #[test]
+    fn test_assemble_wrong_vendor_digest() {
+        let dir = TempDir::new("assemble-wrong-vendor-digest");
+        let (image, digest) = unsigned_image();
+
+        let mut wrong_digest = digest.clone();
+        wrong_digest[0] ^= 1;
+        let err = assemble_detached(&dir, image, &wrong_digest, &digest).unwrap_err();
+        assert_eq!(err.to_string(), "Invalid vendor ECC signature");
+    }
+
+    #[test]
+    fn test_assemble_wrong_owner_digest() {
+        let dir = TempDir::new("assemble-wrong-owner-digest");
+        let (image, digest) = unsigned_image();
+
+        let mut wrong_digest = digest.clone();
+        wrong_digest[47] ^= 1;
+        let err = assemble_detached(&dir, image, &digest, &wrong_digest).unwrap_err();
+        assert_eq!(err.to_string(), "Invalid owner ECC signature");
+    }
+
+    #[test]
+    fn test_assemble_missing_owner_sigs() {
+        let (image, _) = unsigned_image();
+        let gen = ImageGenerator::new(crate::Crypto::default());
+        let err = gen
+            .assemble(image, ImageSignatures::default(), None)
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Owner signatures missing");
+    }
+}
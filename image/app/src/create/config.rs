@@ -13,7 +13,7 @@ Abstract:
 --*/
 
 use anyhow::Context;
-use caliptra_image_types::VENDOR_ECC_KEY_COUNT;
+use caliptra_image_types::{VENDOR_ECC_KEY_COUNT, VENDOR_LMS_KEY_COUNT};
 use serde_derive::{Deserialize, Serialize};
 use std::path::PathBuf;
 
@@ -23,6 +23,11 @@ pub(crate) struct VendorKeyConfig {
     pub ecc_pub_keys: [String; VENDOR_ECC_KEY_COUNT as usize],
 
     pub ecc_priv_keys: Option<[String; VENDOR_ECC_KEY_COUNT as usize]>,
+
+    /// Derived from the private keys when not specified
+    pub lms_pub_keys: Option<[String; VENDOR_LMS_KEY_COUNT as usize]>,
+
+    pub lms_priv_keys: Option<[String; VENDOR_LMS_KEY_COUNT as usize]>,
 }
 
 /// Owner Key Configuration
@@ -31,6 +36,11 @@ pub(crate) struct OwnerKeyConfig {
     pub ecc_pub_key: String,
 
     pub ecc_priv_key: Option<String>,
+
+    /// Derived from the private key when not specified
+    pub lms_pub_key: Option<String>,
+
+    pub lms_priv_key: Option<String>,
 }
 
 //Key Configuration
@@ -12,16 +12,21 @@ Abstract:
 
 --*/
 
-mod config;
+pub(crate) mod config;
 
+use crate::crypto::ecc_priv_key_from_pem;
+use crate::crypto::ecc_pub_key_from_pem;
+use crate::crypto::lms_priv_key_from_file;
+use crate::crypto::lms_pub_key_from_file;
+use crate::crypto::lms_pub_key_from_priv_key;
 use anyhow::anyhow;
 use anyhow::Context;
 use caliptra_image_gen::*;
-use caliptra_image_openssl::ecc_priv_key_from_pem;
-use caliptra_image_openssl::ecc_pub_key_from_pem;
 use caliptra_image_serde::ImageBundleWriter;
 use caliptra_image_types::*;
 use clap::ArgMatches;
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
 use std::path::Path;
 use std::path::PathBuf;
 
@@ -29,6 +34,7 @@ use caliptra_image_elf::ElfExecutable;
 use config::{OwnerKeyConfig, VendorKeyConfig};
 
 use chrono::NaiveDate;
+use zerocopy::AsBytes;
 
 ///
 /// This function takes the string as the input
@@ -68,6 +74,29 @@ fn check_date(from_date: &str, to_date: &str) -> anyhow::Result<bool> {
     Ok(true)
 }
 
+///
+/// This function parses an auxiliary image argument of the form
+/// `<ID>:<FILE>` and reads the image content
+///
+fn aux_image(arg: &str) -> anyhow::Result<ImageGeneratorAuxImage> {
+    let (id, path) = arg
+        .split_once(':')
+        .with_context(|| format!("Invalid auxiliary image {arg}"))?;
+
+    let id = id
+        .parse::<u32>()
+        .with_context(|| format!("Invalid auxiliary image id {id}"))?;
+
+    let content =
+        std::fs::read(path).with_context(|| format!("Failed to read auxiliary image {path}"))?;
+
+    Ok(ImageGeneratorAuxImage {
+        id,
+        content,
+        ..Default::default()
+    })
+}
+
 /// Run the command
 pub(crate) fn run_cmd(args: &ArgMatches) -> anyhow::Result<()> {
     let config_path: &PathBuf = args
@@ -110,10 +139,18 @@ pub(crate) fn run_cmd(args: &ArgMatches) -> anyhow::Result<()> {
         .get_one::<u32>("ecc-pk-idx")
         .with_context(|| "ecc-pk-idx arg not specified")?;
 
+    let lms_key_idx: &u32 = args
+        .get_one::<u32>("lms-pk-idx")
+        .with_context(|| "lms-pk-idx arg not specified")?;
+
     let out_path: &PathBuf = args
         .get_one::<PathBuf>("out")
         .with_context(|| "out arg not specified")?;
 
+    let unsigned = args.get_flag("unsigned");
+    let vendor_digest_path = args.get_one::<PathBuf>("vendor-digest-out");
+    let owner_digest_path = args.get_one::<PathBuf>("owner-digest-out");
+
     //YYYYMMDDHHMMSS - Zulu Time
     let mut own_from_date: [u8; 15] = [0u8; 15];
     let mut own_to_date: [u8; 15] = [0u8; 15];
@@ -140,6 +177,12 @@ pub(crate) fn run_cmd(args: &ArgMatches) -> anyhow::Result<()> {
         }
     }
 
+    let aux = args
+        .get_many::<String>("aux")
+        .unwrap_or_default()
+        .map(|arg| aux_image(arg))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
     let config = config::load_key_config(config_path)?;
 
     let fmc_rev = hex::decode(fmc_rev)?;
@@ -167,16 +210,32 @@ pub(crate) fn run_cmd(args: &ArgMatches) -> anyhow::Result<()> {
             config_dir,
             &config.vendor,
             *ecc_key_idx,
+            *lms_key_idx,
             mfg_from_date,
             mfg_to_date,
         )?,
         owner_config: owner_config(config_dir, &config.owner, own_from_date, own_to_date)?,
         fmc,
         runtime,
+        aux,
     };
 
-    let gen = ImageGenerator::new(caliptra_image_openssl::OsslCrypto::default());
-    let image = gen.generate(&gen_config).unwrap();
+    let gen = ImageGenerator::new(crate::Crypto::default());
+    let image = if unsigned {
+        gen.generate_unsigned(&gen_config)?
+    } else {
+        // LMS keys are stateful, the leaves used to sign the image are
+        // reserved before signing so that they are never used again, even if
+        // the image is not written
+        if let Some(lms_priv_keys) = &config.vendor.lms_priv_keys {
+            reserve_lms_leaf(&config_dir.join(&lms_priv_keys[*lms_key_idx as usize]))?;
+        }
+        if let Some(lms_priv_key) = config.owner.as_ref().and_then(|o| o.lms_priv_key.as_ref()) {
+            reserve_lms_leaf(&config_dir.join(lms_priv_key))?;
+        }
+
+        gen.generate(&gen_config)?
+    };
 
     let out_file = std::fs::OpenOptions::new()
         .create(true)
@@ -188,18 +247,86 @@ pub(crate) fn run_cmd(args: &ArgMatches) -> anyhow::Result<()> {
     let mut writer = ImageBundleWriter::new(out_file);
     writer.write(&image)?;
 
+    if unsigned {
+        // Vendor and owner both sign the header digest
+        let digest = gen.header_digest(&image.manifest.header)?;
+        let digest_bytes: Vec<u8> = digest.iter().flat_map(|w| w.to_be_bytes()).collect();
+
+        println!("Vendor header digest: {}", hex::encode(&digest_bytes));
+        if let Some(path) = vendor_digest_path {
+            write_digest(path, &digest_bytes)?;
+        }
+
+        if gen_config.owner_config.is_some() {
+            println!("Owner header digest: {}", hex::encode(&digest_bytes));
+            if let Some(path) = owner_digest_path {
+                write_digest(path, &digest_bytes)?;
+            }
+        } else if owner_digest_path.is_some() {
+            return Err(anyhow!("Owner keys not specified in the key configuration"));
+        }
+    }
+
+    Ok(())
+}
+
+/// Write a header digest to be signed outside of the tool
+fn write_digest(path: &PathBuf, digest: &[u8]) -> anyhow::Result<()> {
+    std::fs::write(path, digest)
+        .with_context(|| format!("Failed to write digest file {}", path.display()))
+}
+
+/// Reserve the next leaf of the LMS private key stored in a file
+///
+/// The key moved to the following leaf is written to a temporary file which
+/// is synced and renamed over the key file, so that the key file always holds
+/// either the old or the new key.
+fn reserve_lms_leaf(path: &PathBuf) -> anyhow::Result<()> {
+    let mut priv_key = lms_priv_key_from_file(path)?;
+    priv_key.q = priv_key
+        .q
+        .checked_add(1)
+        .with_context(|| format!("LMS private key {} is exhausted", path.display()))?;
+
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_path);
+
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(&tmp_path)
+        .with_context(|| format!("Failed to create file {}", tmp_path.display()))?;
+    file.write_all(priv_key.as_bytes())
+        .and_then(|_| file.sync_all())
+        .with_context(|| format!("Failed to write file {}", tmp_path.display()))?;
+
+    std::fs::rename(&tmp_path, path)
+        .with_context(|| format!("Failed to update LMS private key file {}", path.display()))?;
+
+    // Persist the rename
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::File::open(dir)
+            .and_then(|dir| dir.sync_all())
+            .with_context(|| format!("Failed to sync directory {}", dir.display()))?;
+    }
+
     Ok(())
 }
 
 /// Generate Vendor Config
-fn vendor_config(
+pub(crate) fn vendor_config(
     path: &Path,
     config: &VendorKeyConfig,
     ecc_key_idx: u32,
+    lms_key_idx: u32,
     from_date: [u8; 15],
     to_date: [u8; 15],
 ) -> anyhow::Result<ImageGeneratorVendorConfig> {
     let mut gen_config = ImageGeneratorVendorConfig::default();
+    let mut priv_keys = ImageVendorPrivKeys::default();
     let ecc_pub_keys = &config.ecc_pub_keys;
 
     for (i, pem_file) in ecc_pub_keys
@@ -212,7 +339,6 @@ fn vendor_config(
     }
 
     if let Some(ecc_priv_keys) = &config.ecc_priv_keys {
-        let mut priv_keys = ImageVendorPrivKeys::default();
         for (i, pem_file) in ecc_priv_keys
             .iter()
             .enumerate()
@@ -221,10 +347,44 @@ fn vendor_config(
             let priv_key_path = path.join(pem_file);
             priv_keys.ecc_priv_keys[i] = ecc_priv_key_from_pem(&priv_key_path)?;
         }
+    }
+
+    if let Some(lms_priv_keys) = &config.lms_priv_keys {
+        for (i, key_file) in lms_priv_keys
+            .iter()
+            .enumerate()
+            .take(VENDOR_LMS_KEY_COUNT as usize)
+        {
+            let priv_key_path = path.join(key_file);
+            priv_keys.lms_priv_keys[i] = lms_priv_key_from_file(&priv_key_path)?;
+        }
+    }
+
+    match (&config.lms_pub_keys, &config.lms_priv_keys) {
+        (Some(lms_pub_keys), _) => {
+            for (i, key_file) in lms_pub_keys
+                .iter()
+                .enumerate()
+                .take(VENDOR_LMS_KEY_COUNT as usize)
+            {
+                let pub_key_path = path.join(key_file);
+                gen_config.pub_keys.lms_pub_keys[i] = lms_pub_key_from_file(&pub_key_path)?;
+            }
+        }
+        (None, Some(_)) => {
+            for (i, priv_key) in priv_keys.lms_priv_keys.iter().enumerate() {
+                gen_config.pub_keys.lms_pub_keys[i] = lms_pub_key_from_priv_key(priv_key)?;
+            }
+        }
+        (None, None) => return Err(anyhow!("Vendor LMS keys not specified")),
+    }
+
+    if config.ecc_priv_keys.is_some() {
         gen_config.priv_keys = Some(priv_keys);
     }
 
     gen_config.ecc_key_idx = ecc_key_idx;
+    gen_config.lms_key_idx = lms_key_idx;
     gen_config.not_before = from_date;
     gen_config.not_after = to_date;
 
@@ -232,7 +392,7 @@ fn vendor_config(
 }
 
 /// Generate owner config
-fn owner_config(
+pub(crate) fn owner_config(
     path: &Path,
     config: &Option<OwnerKeyConfig>,
     from_date: [u8; 15],
@@ -245,10 +405,24 @@ fn owner_config(
         let pub_key_path = path.join(pem_file);
         gen_config.pub_keys.ecc_pub_key = ecc_pub_key_from_pem(&pub_key_path)?;
 
+        let mut priv_keys = ImageOwnerPrivKeys::default();
         if let Some(pem_file) = &config.ecc_priv_key {
-            let mut priv_keys = ImageOwnerPrivKeys::default();
             let pub_key_path = path.join(pem_file);
             priv_keys.ecc_priv_key = ecc_priv_key_from_pem(&pub_key_path)?;
+        }
+
+        if let Some(key_file) = &config.lms_priv_key {
+            let priv_key_path = path.join(key_file);
+            priv_keys.lms_priv_key = lms_priv_key_from_file(&priv_key_path)?;
+        }
+
+        gen_config.pub_keys.lms_pub_key = match (&config.lms_pub_key, &config.lms_priv_key) {
+            (Some(key_file), _) => lms_pub_key_from_file(&path.join(key_file))?,
+            (None, Some(_)) => lms_pub_key_from_priv_key(&priv_keys.lms_priv_key)?,
+            (None, None) => return Err(anyhow!("Owner LMS key not specified")),
+        };
+
+        if config.ecc_priv_key.is_some() {
             gen_config.priv_keys = Some(priv_keys);
         }
         gen_config.not_before = from_date;
@@ -259,3 +433,40 @@ fn owner_config(
         Ok(None)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+    use caliptra_image_fake_keys::VENDOR_LMS_KEY_0_PRIVATE;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn test_reserve_lms_leaf() {
+        let dir = TempDir::new("reserve-lms-leaf");
+        let path = dir.path("vnd-lms-priv-key-0.bin");
+        std::fs::write(&path, VENDOR_LMS_KEY_0_PRIVATE.as_bytes()).unwrap();
+
+        reserve_lms_leaf(&path).unwrap();
+        reserve_lms_leaf(&path).unwrap();
+
+        let priv_key = lms_priv_key_from_file(&path).unwrap();
+        assert_eq!(priv_key.q, VENDOR_LMS_KEY_0_PRIVATE.q + 2);
+        assert_eq!(priv_key.seed, VENDOR_LMS_KEY_0_PRIVATE.seed);
+        assert_eq!(
+            std::fs::metadata(&path).unwrap().permissions().mode() & 0o777,
+            0o600
+        );
+        assert!(!dir.path("vnd-lms-priv-key-0.bin.tmp").exists());
+    }
+
+    #[test]
+    fn test_reserve_lms_leaf_missing_key() {
+        let dir = TempDir::new("reserve-lms-leaf-missing-key");
+        let path = dir.path("vnd-lms-priv-key-0.bin");
+
+        assert!(reserve_lms_leaf(&path).is_err());
+        assert!(!path.exists());
+        assert!(!dir.path("vnd-lms-priv-key-0.bin.tmp").exists());
+    }
+}
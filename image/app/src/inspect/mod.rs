@@ -0,0 +1,518 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+   mod.rs
+
+Abstract:
+
+    File contains implementation Caliptra Image inspection command.
+
+--*/
+
+use anyhow::Context;
+use caliptra_image_gen::ImageGeneratorCrypto;
+use caliptra_image_serde::ImageBundleReader;
+use caliptra_image_types::*;
+use clap::ArgMatches;
+use serde_derive::Serialize;
+use serde_json::Value;
+use std::ops::Range;
+use std::path::PathBuf;
+use zerocopy::AsBytes;
+
+/// ECC Public Key
+#[derive(Serialize)]
+struct EccPubKeyInfo {
+    x: String,
+    y: String,
+}
+
+impl From<&ImageEccPubKey> for EccPubKeyInfo {
+    fn from(key: &ImageEccPubKey) -> Self {
+        Self {
+            x: words_to_hex(&key.x),
+            y: words_to_hex(&key.y),
+        }
+    }
+}
+
+/// LMS Public Key
+#[derive(Serialize)]
+struct LmsPubKeyInfo {
+    tree_type: u32,
+    otstype: u32,
+    id: String,
+    digest: String,
+}
+
+impl From<&ImageLmsPubKey> for LmsPubKeyInfo {
+    fn from(key: &ImageLmsPubKey) -> Self {
+        Self {
+            tree_type: key.tree_type,
+            otstype: key.otstype,
+            id: hex::encode(key.id),
+            digest: hex::encode(key.digest),
+        }
+    }
+}
+
+/// ECC Signature
+#[derive(Serialize)]
+struct EccSignatureInfo {
+    r: String,
+    s: String,
+}
+
+/// LMS Signature
+#[derive(Serialize)]
+struct LmsSignatureInfo {
+    q: u32,
+    otstype: u32,
+    nonce: String,
+    y: Vec<String>,
+    tree_type: u32,
+    tree_path: Vec<String>,
+}
+
+/// Signatures
+#[derive(Serialize)]
+struct SignaturesInfo {
+    ecc_sig: EccSignatureInfo,
+    lms_sig: LmsSignatureInfo,
+}
+
+impl From<&ImageSignatures> for SignaturesInfo {
+    fn from(sigs: &ImageSignatures) -> Self {
+        let lms_sig = &sigs.lms_sig;
+        Self {
+            ecc_sig: EccSignatureInfo {
+                r: words_to_hex(&sigs.ecc_sig.r),
+                s: words_to_hex(&sigs.ecc_sig.s),
+            },
+            lms_sig: LmsSignatureInfo {
+                q: lms_sig.q,
+                otstype: lms_sig.ots.otstype,
+                nonce: hex::encode(lms_sig.ots.nonce),
+                y: lms_sig.ots.y.iter().map(hex::encode).collect(),
+                tree_type: lms_sig.tree_type,
+                tree_path: lms_sig.tree_path.iter().map(hex::encode).collect(),
+            },
+        }
+    }
+}
+
+/// Owner Public Keys
+#[derive(Serialize)]
+struct OwnerPubKeysInfo {
+    ecc_pub_key: EccPubKeyInfo,
+    lms_pub_key: LmsPubKeyInfo,
+}
+
+/// Preamble
+#[derive(Serialize)]
+struct PreambleInfo {
+    vendor_ecc_pub_keys: Vec<EccPubKeyInfo>,
+    vendor_lms_pub_keys: Vec<LmsPubKeyInfo>,
+    vendor_ecc_pub_key_idx: u32,
+    vendor_lms_pub_key_idx: u32,
+    vendor_sigs: SignaturesInfo,
+    owner_pub_keys: OwnerPubKeysInfo,
+    owner_sigs: SignaturesInfo,
+}
+
+/// Header
+#[derive(Serialize)]
+struct HeaderInfo {
+    revision: String,
+    vendor_ecc_pub_key_idx: u32,
+    vendor_lms_pub_key_idx: u32,
+    flags: String,
+    toc_len: u32,
+    toc_digest: String,
+    vendor_not_before: String,
+    vendor_not_after: String,
+    owner_not_before: String,
+    owner_not_after: String,
+}
+
+/// Stored digest checked against the recomputed one
+#[derive(Serialize)]
+struct DigestCheckInfo {
+    stored: String,
+    computed: String,
+    matches: bool,
+}
+
+impl DigestCheckInfo {
+    fn new(stored: &ImageDigest, computed: &ImageDigest) -> Self {
+        Self {
+            stored: words_to_hex(stored),
+            computed: words_to_hex(computed),
+            matches: stored == computed,
+        }
+    }
+}
+
+/// Table of Contents Entry
+#[derive(Serialize)]
+struct TocEntryInfo {
+    id: u32,
+    r#type: u32,
+    revision: String,
+    svn: u32,
+    min_svn: u32,
+    load_addr: String,
+    entry_point: String,
+    offset: String,
+    size: u32,
+    digest: DigestCheckInfo,
+}
+
+/// Digests recomputed from the image
+#[derive(Serialize)]
+struct DigestsInfo {
+    /// Value of the `key_manifest_pk_hash` fuses
+    vendor_pub_keys: String,
+
+    /// Value of the `owner_pk_hash` fuses
+    owner_pub_keys: String,
+
+    /// Digest signed by the vendor and owner
+    header: String,
+
+    toc: DigestCheckInfo,
+}
+
+/// Manifest
+#[derive(Serialize)]
+struct ManifestInfo {
+    marker: String,
+    size: u32,
+    preamble: PreambleInfo,
+    header: HeaderInfo,
+    toc: Vec<TocEntryInfo>,
+    digests: DigestsInfo,
+}
+
+/// Run the command
+pub(crate) fn run_cmd(args: &ArgMatches) -> anyhow::Result<()> {
+    let image_path: &PathBuf = args
+        .get_one::<PathBuf>("image")
+        .with_context(|| "image arg not specified")?;
+
+    let json = args.get_flag("json");
+
+    let image_file = std::fs::File::open(image_path)
+        .with_context(|| format!("Failed to open image {}", image_path.display()))?;
+    let image = ImageBundleReader::new(image_file)
+        .read()
+        .with_context(|| format!("Failed to parse image {}", image_path.display()))?;
+
+    let info = manifest_info(&crate::Crypto::default(), &image)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&info)?);
+    } else {
+        print_value("manifest", &serde_json::to_value(&info)?, 0);
+    }
+
+    Ok(())
+}
+
+/// Decode the manifest of an image bundle and recompute its digests
+fn manifest_info(
+    crypto: &impl ImageGeneratorCrypto,
+    image: &ImageBundle,
+) -> anyhow::Result<ManifestInfo> {
+    let manifest = &image.manifest;
+    let preamble = &manifest.preamble;
+    let header = &manifest.header;
+
+    let manifest_digest = |range: Range<u32>| -> anyhow::Result<ImageDigest> {
+        let data = manifest
+            .as_bytes()
+            .get(range.start as usize..range.end as usize)
+            .with_context(|| "Invalid manifest range")?;
+        crypto.sha384_digest(data)
+    };
+
+    let toc_digest = if ImageManifest::toc_range(header.toc_len).is_empty() {
+        // Invalid TOC length, the ROM rejects the image before hashing the TOC
+        ImageDigest::default()
+    } else {
+        manifest_digest(ImageManifest::toc_range(header.toc_len))?
+    };
+
+    let sections = [&image.fmc, &image.runtime]
+        .into_iter()
+        .chain(image.aux.iter());
+    let toc = [&manifest.fmc, &manifest.runtime]
+        .into_iter()
+        .chain(manifest.aux_toc_entries())
+        .zip(sections)
+        .map(|(toc, section)| {
+            Ok(TocEntryInfo {
+                id: toc.id,
+                r#type: toc.r#type,
+                revision: hex::encode(toc.revision),
+                svn: toc.svn,
+                min_svn: toc.min_svn,
+                load_addr: format!("0x{:08x}", toc.load_addr),
+                entry_point: format!("0x{:08x}", toc.entry_point),
+                offset: format!("0x{:08x}", toc.offset),
+                size: toc.size,
+                digest: DigestCheckInfo::new(&toc.digest, &crypto.sha384_digest(section)?),
+            })
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    Ok(ManifestInfo {
+        marker: format!("0x{:08x}", manifest.marker),
+        size: manifest.size,
+        preamble: PreambleInfo {
+            vendor_ecc_pub_keys: preamble
+                .vendor_pub_keys
+                .ecc_pub_keys
+                .iter()
+                .map(EccPubKeyInfo::from)
+                .collect(),
+            vendor_lms_pub_keys: preamble
+                .vendor_pub_keys
+                .lms_pub_keys
+                .iter()
+                .map(LmsPubKeyInfo::from)
+                .collect(),
+            vendor_ecc_pub_key_idx: preamble.vendor_ecc_pub_key_idx,
+            vendor_lms_pub_key_idx: preamble.vendor_lms_pub_key_idx,
+            vendor_sigs: SignaturesInfo::from(&preamble.vendor_sigs),
+            owner_pub_keys: OwnerPubKeysInfo {
+                ecc_pub_key: EccPubKeyInfo::from(&preamble.owner_pub_keys.ecc_pub_key),
+                lms_pub_key: LmsPubKeyInfo::from(&preamble.owner_pub_keys.lms_pub_key),
+            },
+            owner_sigs: SignaturesInfo::from(&preamble.owner_sigs),
+        },
+        header: HeaderInfo {
+            revision: words_to_hex(&header.revision),
+            vendor_ecc_pub_key_idx: header.vendor_ecc_pub_key_idx,
+            vendor_lms_pub_key_idx: header.vendor_lms_pub_key_idx,
+            flags: format!("0x{:08x}", header.flags),
+            toc_len: header.toc_len,
+            toc_digest: words_to_hex(&header.toc_digest),
+            vendor_not_before: date_to_string(&header.vendor_not_before),
+            vendor_not_after: date_to_string(&header.vendor_not_after),
+            owner_not_before: date_to_string(&header.owner_data.owner_not_before),
+            owner_not_after: date_to_string(&header.owner_data.owner_not_after),
+        },
+        toc,
+        digests: DigestsInfo {
+            vendor_pub_keys: words_to_hex(
+                &manifest_digest(ImageManifest::vendor_pub_keys_range())?,
+            ),
+            owner_pub_keys: words_to_hex(&manifest_digest(ImageManifest::owner_pub_key_range())?),
+            header: words_to_hex(&manifest_digest(ImageManifest::header_range())?),
+            toc: DigestCheckInfo::new(&header.toc_digest, &toc_digest),
+        },
+    })
+}
+
+/// Print a value as indented text
+fn print_value(name: &str, value: &Value, indent: usize) {
+    match value {
+        Value::Object(fields) => {
+            println!("{:indent$}{name}:", "");
+            for (field, value) in fields {
+                print_value(field, value, indent + 2);
+            }
+        }
+        Value::Array(items) => {
+            println!("{:indent$}{name}:", "");
+            for (i, value) in items.iter().enumerate() {
+                print_value(&format!("[{i}]"), value, indent + 2);
+            }
+        }
+        Value::String(s) => println!("{:indent$}{name}: {s}", ""),
+        value => println!("{:indent$}{name}: {value}", ""),
+    }
+}
+
+/// Convert words in hardware format to hex
+fn words_to_hex(words: &[u32]) -> String {
+    words.iter().map(|w| format!("{w:08x}")).collect()
+}
+
+/// Convert an ASN1 time to a string, empty if not set
+fn date_to_string(date: &[u8; 15]) -> String {
+    String::from_utf8_lossy(date)
+        .trim_end_matches('\0')
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use caliptra_image_gen::{ImageGenerator, ImageGeneratorAuxImage, ImageGeneratorConfig};
+    use zerocopy::FromBytes;
+
+    /// Image bundle with two auxiliary images
+    fn aux_image_bundle() -> ImageBundle {
+        let aux = |id, len| ImageGeneratorAuxImage {
+            id,
+            content: vec![id as u8; len],
+            ..Default::default()
+        };
+        let config = ImageGeneratorConfig {
+            aux: vec![aux(3, 256), aux(4, 512)],
+            ..test_util::image_config()
+        };
+        ImageGenerator::new(crate::Crypto::default())
+            .generate(&config)
+            .unwrap()
+    }
+
+    /// Parse `bytes`, returning the error message on failure
+    fn from_bytes_err(bytes: &[u8]) -> String {
+        ImageBundle::from_bytes(bytes).unwrap_err().to_string()
+    }
+
+    /// Replace the manifest at the start of `bytes`
+    fn write_manifest(bytes: &mut [u8], manifest: &ImageManifest) {
+        bytes[..IMAGE_MANIFEST_BYTE_SIZE].copy_from_slice(manifest.as_bytes());
+    }
+
+    #[test]
+    fn test_from_bytes_round_trip() {
+        for bundle in [test_util::image_bundle(), aux_image_bundle()] {
+            let bytes = bundle.to_bytes().unwrap();
+            let parsed = ImageBundle::from_bytes(&bytes).unwrap();
+
+            assert_eq!(parsed.manifest.as_bytes(), bundle.manifest.as_bytes());
+            assert_eq!(parsed.fmc, bundle.fmc);
+            assert_eq!(parsed.runtime, bundle.runtime);
+            assert_eq!(parsed.aux, bundle.aux);
+            assert_eq!(parsed.to_bytes().unwrap(), bytes);
+        }
+    }
+
+    #[test]
+    fn test_from_bytes_truncated() {
+        let bundle = aux_image_bundle();
+        let bytes = bundle.to_bytes().unwrap();
+
+        assert_eq!(
+            from_bytes_err(&bytes[..IMAGE_MANIFEST_BYTE_SIZE - 1]),
+            "image is smaller than the manifest"
+        );
+        assert_eq!(
+            from_bytes_err(&bytes[..bundle.manifest.fmc.offset as usize + 1]),
+            "fmc size does not match manifest"
+        );
+        assert_eq!(
+            from_bytes_err(&bytes[..bundle.manifest.runtime.offset as usize + 1]),
+            "runtime size does not match manifest"
+        );
+        assert_eq!(
+            from_bytes_err(&bytes[..bytes.len() - 1]),
+            "aux size does not match manifest"
+        );
+    }
+
+    #[test]
+    fn test_from_bytes_trailing_data() {
+        let mut bytes = test_util::image_bundle().to_bytes().unwrap();
+        bytes.push(0);
+        assert_eq!(from_bytes_err(&bytes), "image is larger than the manifest");
+    }
+
+    #[test]
+    fn test_from_bytes_toc_out_of_range() {
+        let bundle = aux_image_bundle();
+        let bytes = bundle.to_bytes().unwrap();
+
+        let mut manifest = ImageManifest::read_from_prefix(bytes.as_slice()).unwrap();
+        manifest.fmc.offset = u32::MAX;
+        let mut image = bytes.clone();
+        write_manifest(&mut image, &manifest);
+        assert_eq!(from_bytes_err(&image), "fmc offset does not match manifest");
+
+        let mut manifest = ImageManifest::read_from_prefix(bytes.as_slice()).unwrap();
+        manifest.runtime.size = u32::MAX;
+        let mut image = bytes.clone();
+        write_manifest(&mut image, &manifest);
+        assert_eq!(
+            from_bytes_err(&image),
+            "runtime size does not match manifest"
+        );
+
+        // Overlapping sections
+        let mut manifest = ImageManifest::read_from_prefix(bytes.as_slice()).unwrap();
+        manifest.aux[1].offset = manifest.aux[0].offset;
+        let mut image = bytes.clone();
+        write_manifest(&mut image, &manifest);
+        assert_eq!(from_bytes_err(&image), "aux offset does not match manifest");
+
+        // TOC length beyond the maximum is clamped to the aux TOC entries
+        let mut manifest = ImageManifest::read_from_prefix(bytes.as_slice()).unwrap();
+        manifest.header.toc_len = u32::MAX;
+        let mut image = bytes;
+        write_manifest(&mut image, &manifest);
+        assert_eq!(from_bytes_err(&image), "aux offset does not match manifest");
+    }
+
+    #[test]
+    fn test_inspect() {
+        let bundle = aux_image_bundle();
+        let info = manifest_info(&crate::Crypto::default(), &bundle).unwrap();
+
+        assert_eq!(info.marker, format!("0x{:08x}", MANIFEST_MARKER));
+        assert_eq!(info.header.toc_len, 4);
+        assert_eq!(info.toc.len(), 4);
+        assert!(info.toc.iter().all(|toc| toc.digest.matches));
+        assert!(info.digests.toc.matches);
+        assert_eq!(
+            info.toc[3].offset,
+            format!("0x{:08x}", bundle.manifest.aux[1].offset)
+        );
+
+        let gen = ImageGenerator::new(crate::Crypto::default());
+        let preamble = &bundle.manifest.preamble;
+        assert_eq!(
+            info.digests.vendor_pub_keys,
+            words_to_hex(&gen.vendor_pubkey_digest(preamble).unwrap())
+        );
+        assert_eq!(
+            info.digests.owner_pub_keys,
+            words_to_hex(&gen.owner_pubkey_digest(preamble).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_inspect_tampered() {
+        let mut bundle = aux_image_bundle();
+        bundle.runtime[0] ^= 1;
+        bundle.manifest.aux[0].id ^= 1;
+
+        let info = manifest_info(&crate::Crypto::default(), &bundle).unwrap();
+        let matches: Vec<_> = info.toc.iter().map(|toc| toc.digest.matches).collect();
+        assert_eq!(matches, [true, false, true, true]);
+        assert!(!info.digests.toc.matches);
+    }
+
+    #[test]
+    fn test_inspect_invalid_toc_len() {
+        let mut bundle = test_util::image_bundle();
+        bundle.manifest.header.toc_len = 0;
+
+        // The TOC digest is not computed but the sections are still listed
+        let info = manifest_info(&crate::Crypto::default(), &bundle).unwrap();
+        assert_eq!(info.toc.len(), 2);
+        assert_eq!(
+            info.digests.toc.computed,
+            words_to_hex(&ImageDigest::default())
+        );
+        assert!(!info.digests.toc.matches);
+    }
+}
@@ -0,0 +1,319 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+   mod.rs
+
+Abstract:
+
+    File contains implementation Caliptra Image key generation command.
+
+--*/
+
+use anyhow::Context;
+use caliptra_image_gen::ImageGenerator;
+use caliptra_image_types::*;
+use clap::ArgMatches;
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use zerocopy::AsBytes;
+
+use crate::create::config::{self, KeyConfig, OwnerKeyConfig, VendorKeyConfig};
+use crate::create::{owner_config, vendor_config};
+use crate::crypto::{ecc_key_pair_gen_pem, lms_priv_key_gen, lms_pub_key_from_priv_key};
+use crate::verify::digest_to_hex;
+
+/// Name of the generated key configuration file
+const KEY_CONFIG_FILE: &str = "keys.toml";
+
+/// Mode of the private key files, only accessible to their owner
+const PRIV_KEY_FILE_MODE: u32 = 0o600;
+
+/// Mode of the public files, the `OpenOptions` default
+const PUB_FILE_MODE: u32 = 0o666;
+
+/// Fuse values matching the generated keys
+struct FuseHashes {
+    key_manifest_pk_hash: String,
+    owner_pk_hash: Option<String>,
+}
+
+/// Run the command
+pub(crate) fn run_cmd(args: &ArgMatches) -> anyhow::Result<()> {
+    let out_dir: &PathBuf = args
+        .get_one::<PathBuf>("out-dir")
+        .with_context(|| "out-dir arg not specified")?;
+
+    let no_owner = args.get_flag("no-owner");
+
+    let fuses = gen_keys(out_dir, no_owner)?;
+
+    println!(
+        "Key configuration: {}",
+        out_dir.join(KEY_CONFIG_FILE).display()
+    );
+    println!("key_manifest_pk_hash = \"{}\"", fuses.key_manifest_pk_hash);
+    if let Some(owner_pk_hash) = fuses.owner_pk_hash {
+        println!("owner_pk_hash = \"{owner_pk_hash}\"");
+    }
+
+    Ok(())
+}
+
+/// Generate the keys and key configuration in `out_dir`
+///
+/// # Arguments
+///
+/// * `out_dir`  - Output directory
+/// * `no_owner` - Generate the vendor keys only
+fn gen_keys(out_dir: &Path, no_owner: bool) -> anyhow::Result<FuseHashes> {
+    std::fs::create_dir_all(out_dir)
+        .with_context(|| format!("Failed to create directory {}", out_dir.display()))?;
+
+    let mut vendor = VendorKeyConfig::default();
+    let mut lms_pub_keys: [String; VENDOR_LMS_KEY_COUNT as usize] = Default::default();
+    let mut lms_priv_keys: [String; VENDOR_LMS_KEY_COUNT as usize] = Default::default();
+    let mut ecc_priv_keys: [String; VENDOR_ECC_KEY_COUNT as usize] = Default::default();
+
+    for (i, (pub_key, priv_key)) in vendor
+        .ecc_pub_keys
+        .iter_mut()
+        .zip(ecc_priv_keys.iter_mut())
+        .enumerate()
+    {
+        (*pub_key, *priv_key) = gen_ecc_key_pair(
+            out_dir,
+            &format!("vnd-pub-key-{i}.pem"),
+            &format!("vnd-priv-key-{i}.pem"),
+        )?;
+    }
+
+    for (i, (pub_key, priv_key)) in lms_pub_keys
+        .iter_mut()
+        .zip(lms_priv_keys.iter_mut())
+        .enumerate()
+    {
+        (*pub_key, *priv_key) = gen_lms_key_pair(
+            out_dir,
+            &format!("vnd-lms-pub-key-{i}.bin"),
+            &format!("vnd-lms-priv-key-{i}.bin"),
+        )?;
+    }
+
+    vendor.ecc_priv_keys = Some(ecc_priv_keys);
+    vendor.lms_pub_keys = Some(lms_pub_keys);
+    vendor.lms_priv_keys = Some(lms_priv_keys);
+
+    let owner = if no_owner {
+        None
+    } else {
+        let (ecc_pub_key, ecc_priv_key) =
+            gen_ecc_key_pair(out_dir, "own-pub-key.pem", "own-priv-key.pem")?;
+        let (lms_pub_key, lms_priv_key) =
+            gen_lms_key_pair(out_dir, "own-lms-pub-key.bin", "own-lms-priv-key.bin")?;
+        Some(OwnerKeyConfig {
+            ecc_pub_key,
+            ecc_priv_key: Some(ecc_priv_key),
+            lms_pub_key: Some(lms_pub_key),
+            lms_priv_key: Some(lms_priv_key),
+        })
+    };
+
+    let config = KeyConfig { vendor, owner };
+    let config_str = toml::to_string(&config).with_context(|| "Failed to encode key config")?;
+    write_new_file(
+        out_dir,
+        KEY_CONFIG_FILE,
+        config_str.as_bytes(),
+        PUB_FILE_MODE,
+    )?;
+
+    // Compute the fuse values from the written files, the same way the keys
+    // are loaded when creating an image
+    let config_path = out_dir.join(KEY_CONFIG_FILE);
+    let config = config::load_key_config(&config_path)?;
+    let vendor = vendor_config(out_dir, &config.vendor, 0, 0, [0u8; 15], [0u8; 15])?;
+    let owner = owner_config(out_dir, &config.owner, [0u8; 15], [0u8; 15])?;
+
+    let mut preamble = ImagePreamble {
+        vendor_pub_keys: vendor.pub_keys,
+        ..Default::default()
+    };
+    if let Some(owner) = &owner {
+        preamble.owner_pub_keys = owner.pub_keys;
+    }
+
+    let gen = ImageGenerator::new(crate::Crypto::default());
+    let owner_pk_hash = match owner {
+        Some(_) => Some(digest_to_hex(&gen.owner_pubkey_digest(&preamble)?)),
+        None => None,
+    };
+
+    Ok(FuseHashes {
+        key_manifest_pk_hash: digest_to_hex(&gen.vendor_pubkey_digest(&preamble)?),
+        owner_pk_hash,
+    })
+}
+
+/// Generate an ECC key pair and write it to PEM files
+///
+/// # Returns
+///
+/// * `(String, String)` - Public and private key file names
+fn gen_ecc_key_pair(
+    dir: &Path,
+    pub_key_file: &str,
+    priv_key_file: &str,
+) -> anyhow::Result<(String, String)> {
+    let (priv_pem, pub_pem) = ecc_key_pair_gen_pem()?;
+    write_new_file(dir, priv_key_file, &priv_pem, PRIV_KEY_FILE_MODE)?;
+    write_new_file(dir, pub_key_file, &pub_pem, PUB_FILE_MODE)?;
+    Ok((pub_key_file.into(), priv_key_file.into()))
+}
+
+/// Generate an LMS key pair and write it to files
+///
+/// # Returns
+///
+/// * `(String, String)` - Public and private key file names
+fn gen_lms_key_pair(
+    dir: &Path,
+    pub_key_file: &str,
+    priv_key_file: &str,
+) -> anyhow::Result<(String, String)> {
+    let priv_key = lms_priv_key_gen()?;
+    let pub_key = lms_pub_key_from_priv_key(&priv_key)?;
+    write_new_file(dir, priv_key_file, priv_key.as_bytes(), PRIV_KEY_FILE_MODE)?;
+    write_new_file(dir, pub_key_file, pub_key.as_bytes(), PUB_FILE_MODE)?;
+    Ok((pub_key_file.into(), priv_key_file.into()))
+}
+
+/// Write a file, existing keys are never overwritten
+///
+/// The file is created with `mode`, so that private keys are never readable
+/// by other users, even while they are written.
+fn write_new_file(dir: &Path, name: &str, contents: &[u8], mode: u32) -> anyhow::Result<()> {
+    let path = dir.join(name);
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(mode)
+        .open(&path)
+        .with_context(|| format!("Failed to create file {}", path.display()))?;
+    file.write_all(contents)
+        .with_context(|| format!("Failed to write file {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{self, TempDir};
+    use crate::verify::{verify_image, FuseConfig};
+    use caliptra_image_gen::ImageGeneratorConfig;
+
+    /// Create an image signed with the generated keys, as the create command
+    fn create_image(dir: &Path, ecc_key_idx: u32, lms_key_idx: u32) -> ImageBundle {
+        let config = config::load_key_config(&dir.join(KEY_CONFIG_FILE)).unwrap();
+        let gen_config = ImageGeneratorConfig {
+            vendor_config: vendor_config(
+                dir,
+                &config.vendor,
+                ecc_key_idx,
+                lms_key_idx,
+                [0u8; 15],
+                [0u8; 15],
+            )
+            .unwrap(),
+            owner_config: owner_config(dir, &config.owner, [0u8; 15], [0u8; 15]).unwrap(),
+            ..test_util::image_config()
+        };
+        ImageGenerator::new(crate::Crypto::default())
+            .generate(&gen_config)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_keygen_create_verify() {
+        let dir = TempDir::new("keygen-create-verify");
+        let fuses = gen_keys(dir.dir(), false).unwrap();
+
+        let image = create_image(dir.dir(), 2, 1);
+
+        // The emitted fuses match the keys in the manifest
+        let gen = ImageGenerator::new(crate::Crypto::default());
+        let preamble = &image.manifest.preamble;
+        assert_eq!(
+            fuses.key_manifest_pk_hash,
+            digest_to_hex(&gen.vendor_pubkey_digest(preamble).unwrap())
+        );
+        assert_eq!(
+            fuses.owner_pk_hash.as_deref(),
+            Some(digest_to_hex(&gen.owner_pubkey_digest(preamble).unwrap()).as_str())
+        );
+
+        let fuse_config = FuseConfig {
+            key_manifest_pk_hash: fuses.key_manifest_pk_hash,
+            owner_pk_hash: fuses.owner_pk_hash.unwrap(),
+            ..Default::default()
+        };
+        let info = verify_image(&image.to_bytes().unwrap(), &fuse_config, None).unwrap();
+        assert_eq!(info.vendor_ecc_pub_key_idx, 2);
+        assert_eq!(info.vendor_lms_pub_key_idx, 1);
+    }
+
+    #[test]
+    fn test_keygen_no_owner() {
+        let dir = TempDir::new("keygen-no-owner");
+        let fuses = gen_keys(dir.dir(), true).unwrap();
+        assert!(fuses.owner_pk_hash.is_none());
+        assert!(!dir.path("own-pub-key.pem").exists());
+
+        let image = create_image(dir.dir(), 0, 0);
+        let gen = ImageGenerator::new(crate::Crypto::default());
+        assert_eq!(
+            fuses.key_manifest_pk_hash,
+            digest_to_hex(&gen.vendor_pubkey_digest(&image.manifest.preamble).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_keygen_private_key_mode() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new("keygen-private-key-mode");
+        gen_keys(dir.dir(), false).unwrap();
+
+        let config = config::load_key_config(&dir.path(KEY_CONFIG_FILE)).unwrap();
+        let owner = config.owner.unwrap();
+        let priv_keys = config
+            .vendor
+            .ecc_priv_keys
+            .unwrap()
+            .into_iter()
+            .chain(config.vendor.lms_priv_keys.unwrap())
+            .chain(owner.ecc_priv_key)
+            .chain(owner.lms_priv_key);
+        for file in priv_keys {
+            let mode = std::fs::metadata(dir.path(&file))
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, PRIV_KEY_FILE_MODE, "{file}");
+        }
+    }
+
+    #[test]
+    fn test_keygen_keeps_existing_keys() {
+        let dir = TempDir::new("keygen-keeps-existing-keys");
+        gen_keys(dir.dir(), false).unwrap();
+        let config = std::fs::read(dir.path(KEY_CONFIG_FILE)).unwrap();
+        let key = std::fs::read(dir.path("vnd-priv-key-0.pem")).unwrap();
+
+        assert!(gen_keys(dir.dir(), false).is_err());
+        assert_eq!(std::fs::read(dir.path(KEY_CONFIG_FILE)).unwrap(), config);
+        assert_eq!(std::fs::read(dir.path("vnd-priv-key-0.pem")).unwrap(), key);
+    }
+}
@@ -13,9 +13,23 @@ Abstract:
 --*/
 use std::path::PathBuf;
 
-use clap::{arg, value_parser, Command};
+use clap::{arg, value_parser, ArgAction, Command};
 
+mod assemble;
 mod create;
+mod inspect;
+mod keygen;
+#[cfg(test)]
+mod test_util;
+mod verify;
+
+#[cfg(not(any(feature = "openssl", feature = "rustcrypto")))]
+compile_error!("Either the openssl or the rustcrypto feature must be enabled");
+
+#[cfg(all(feature = "openssl", not(feature = "rustcrypto")))]
+use caliptra_image_openssl::{self as crypto, OsslCrypto as Crypto};
+#[cfg(feature = "rustcrypto")]
+use caliptra_image_rustcrypto::{self as crypto, RustCrypto as Crypto};
 
 /// Entry point
 fn main() {
@@ -31,6 +45,11 @@ fn main() {
                 .required(true)
                 .value_parser(value_parser!(u32)),
         )
+        .arg(
+            arg!(--"lms-pk-idx" <U32> "Vendor LMS Public Key Index")
+                .required(true)
+                .value_parser(value_parser!(u32)),
+        )
         .arg(
             arg!(--"fmc" <FILE> "FMC ELF binary")
                 .required(true)
@@ -71,6 +90,12 @@ fn main() {
                 .required(true)
                 .value_parser(value_parser!(u32)),
         )
+        .arg(
+            arg!(--"aux" <ID_FILE> "Auxiliary image as <ID>:<FILE>, may be repeated")
+                .required(false)
+                .action(ArgAction::Append)
+                .value_parser(value_parser!(String)),
+        )
         .arg(
             arg!(--"out" <FILE> "Output file")
                 .required(true)
@@ -79,22 +104,118 @@ fn main() {
         .arg(
             arg!(--"own-from-date" <String> "Certificate Validity Start Date By Owner [YYYYMMDDHHMMSS - Zulu Time]")
                 .required(false)
+                .requires("own-to-date")
                 .value_parser(value_parser!(String)),
         )
         .arg(
             arg!(--"own-to-date" <String> "Certificate Validity End Date By Owner [YYYYMMDDHHMMSS - Zulu Time]")
                 .required(false)
+                .requires("own-from-date")
                 .value_parser(value_parser!(String)),
         )
         .arg(
             arg!(--"mfg-from-date" <String> "Certificate Validity Start Date By Manufacturer [YYYYMMDDHHMMSS - Zulu Time]")
                 .required(false)
+                .requires("mfg-to-date")
                 .value_parser(value_parser!(String)),
         )
         .arg(
             arg!(--"mfg-to-date" <String> "Certificate Validity End Date By Manufacturer [YYYYMMDDHHMMSS - Zulu Time]")
                 .required(false)
+                .requires("mfg-from-date")
                 .value_parser(value_parser!(String)),
+        )
+        .arg(
+            arg!(--"unsigned" "Create an unsigned image, to be signed externally and completed with the assemble command")
+                .required(false)
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            arg!(--"vendor-digest-out" <FILE> "Output file for the header digest to be signed by the vendor")
+                .required(false)
+                .requires("unsigned")
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            arg!(--"owner-digest-out" <FILE> "Output file for the header digest to be signed by the owner")
+                .required(false)
+                .requires("unsigned")
+                .value_parser(value_parser!(PathBuf)),
+        ),
+        Command::new("assemble")
+        .about("Add externally produced signatures to an unsigned firmware image bundle")
+        .arg(
+            arg!(--"image" <FILE> "Unsigned firmware image bundle")
+                .required(true)
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            arg!(--"vendor-ecc-sig" <FILE> "Vendor ECDSA signature, DER or raw r||s")
+                .required(true)
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            arg!(--"vendor-lms-sig" <FILE> "Vendor LMS signature")
+                .required(true)
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            arg!(--"owner-ecc-sig" <FILE> "Owner ECDSA signature, DER or raw r||s")
+                .required(false)
+                .requires("owner-lms-sig")
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            arg!(--"owner-lms-sig" <FILE> "Owner LMS signature")
+                .required(false)
+                .requires("owner-ecc-sig")
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            arg!(--"out" <FILE> "Output file")
+                .required(true)
+                .value_parser(value_parser!(PathBuf)),
+        ),
+        Command::new("keygen")
+        .about("Generate vendor and owner keys, and the key configuration file")
+        .arg(
+            arg!(--"out-dir" <DIR> "Output directory, existing keys are never overwritten")
+                .required(true)
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            arg!(--"no-owner" "Only generate the vendor keys")
+                .required(false)
+                .action(ArgAction::SetTrue),
+        ),
+        Command::new("verify")
+        .about("Verify a firmware image bundle against fuses, as ROM does on cold reset")
+        .arg(
+            arg!(--"image" <FILE> "Firmware image bundle")
+                .required(true)
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            arg!(--"fuses" <FILE> "Fuse Configuration file")
+                .required(true)
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            arg!(--"trusted-time" <U64> "Trusted time in seconds since the Unix epoch, enforces the image validity windows")
+                .required(false)
+                .value_parser(value_parser!(u64)),
+        ),
+        Command::new("inspect")
+        .about("Print the manifest of a firmware image bundle")
+        .arg(
+            arg!(--"image" <FILE> "Firmware image bundle")
+                .required(true)
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            arg!(--"json" "Print the manifest as JSON")
+                .required(false)
+                .action(ArgAction::SetTrue),
         )];
 
     let cmd = Command::new("caliptra-image-app")
@@ -105,11 +226,15 @@ fn main() {
 
     let result = match cmd.subcommand().unwrap() {
         ("create", args) => create::run_cmd(args),
+        ("assemble", args) => assemble::run_cmd(args),
+        ("verify", args) => verify::run_cmd(args),
+        ("inspect", args) => inspect::run_cmd(args),
+        ("keygen", args) => keygen::run_cmd(args),
         (_, _) => unreachable!(),
     };
 
-    result.unwrap();
-
-    // let exit_code = if result.is_ok() { 0 } else { -1 };
-    // std::process::exit(exit_code);
+    if let Err(err) = result {
+        eprintln!("Error: {err:#}");
+        std::process::exit(1);
+    }
 }
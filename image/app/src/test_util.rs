@@ -0,0 +1,125 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+   test_util.rs
+
+Abstract:
+
+    File contains helpers shared by the command tests.
+
+--*/
+
+use caliptra_drivers::memory_layout::ICCM_ORG;
+use caliptra_image_fake_keys::{OWNER_CONFIG, VENDOR_CONFIG_KEY_0};
+use caliptra_image_gen::{ImageGenerator, ImageGeneratorConfig, ImageGenratorExecutable};
+use caliptra_image_types::*;
+use std::path::{Path, PathBuf};
+
+use crate::verify::{digest_to_hex, FuseConfig};
+
+/// Executable with raw content, standing in for an ELF file
+pub(crate) struct TestExecutable {
+    rev: ImageRevision,
+    load_addr: u32,
+    content: Vec<u8>,
+}
+
+impl TestExecutable {
+    pub fn new(load_addr: u32, content: Vec<u8>) -> Self {
+        Self {
+            rev: ImageRevision::default(),
+            load_addr,
+            content,
+        }
+    }
+}
+
+impl ImageGenratorExecutable for TestExecutable {
+    fn svn(&self) -> u32 {
+        0
+    }
+
+    fn min_svn(&self) -> u32 {
+        0
+    }
+
+    fn rev(&self) -> &ImageRevision {
+        &self.rev
+    }
+
+    fn load_addr(&self) -> u32 {
+        self.load_addr
+    }
+
+    fn entry_point(&self) -> u32 {
+        self.load_addr
+    }
+
+    fn content(&self) -> &Vec<u8> {
+        &self.content
+    }
+
+    fn size(&self) -> u32 {
+        self.content.len() as u32
+    }
+}
+
+/// Image configuration signed with the fake vendor and owner keys
+pub(crate) fn image_config() -> ImageGeneratorConfig<TestExecutable> {
+    ImageGeneratorConfig {
+        vendor_config: VENDOR_CONFIG_KEY_0,
+        owner_config: Some(OWNER_CONFIG),
+        fmc: TestExecutable::new(ICCM_ORG, vec![0xf0; 1024]),
+        runtime: TestExecutable::new(ICCM_ORG + 0x2000, vec![0x0f; 2048]),
+        aux: vec![],
+    }
+}
+
+/// Generate an image bundle signed with the fake vendor and owner keys
+pub(crate) fn image_bundle() -> ImageBundle {
+    ImageGenerator::new(crate::Crypto::default())
+        .generate(&image_config())
+        .unwrap()
+}
+
+/// Fuses matching the keys that signed `bundle`
+pub(crate) fn fuse_config(bundle: &ImageBundle) -> FuseConfig {
+    let gen = ImageGenerator::new(crate::Crypto::default());
+    let preamble = &bundle.manifest.preamble;
+    FuseConfig {
+        key_manifest_pk_hash: digest_to_hex(&gen.vendor_pubkey_digest(preamble).unwrap()),
+        owner_pk_hash: digest_to_hex(&gen.owner_pubkey_digest(preamble).unwrap()),
+        ..Default::default()
+    }
+}
+
+/// Directory for the files of a test, removed when dropped
+pub(crate) struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn new(name: &str) -> Self {
+        let path =
+            std::env::temp_dir().join(format!("caliptra-image-app-{name}-{}", std::process::id()));
+        // Leftover from an aborted run
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.0
+    }
+
+    pub fn path(&self, file: &str) -> PathBuf {
+        self.0.join(file)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
@@ -0,0 +1,226 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+   env.rs
+
+Abstract:
+
+    File contains the host image verification environment.
+
+--*/
+
+use std::cell::RefCell;
+use std::ops::Range;
+
+use caliptra_drivers::*;
+use caliptra_image_gen::ImageGeneratorCrypto;
+use caliptra_image_types::*;
+use caliptra_image_verify::ImageVerificationEnv;
+
+use super::fuses::FuseConfig;
+
+/// Error returned for any crypto failure, the cause is kept by the environment
+const CRYPTO_ERR: CaliptraError =
+    match CaliptraError::new((error::CaliptraComponent::ImageVerifier as u32) << 24) {
+        Some(err) => err,
+        None => unreachable!(),
+    };
+
+/// Host Verification Environment
+///
+/// Verifies an image bundle in memory against a fuse description, the way ROM
+/// does on cold reset.
+pub(crate) struct HostImageVerificationEnv<'a, Crypto: ImageGeneratorCrypto> {
+    crypto: Crypto,
+
+    image: &'a [u8],
+
+    fuses: &'a FuseConfig,
+
+    vendor_pub_key_digest: ImageDigest,
+
+    owner_pub_key_digest: ImageDigest,
+
+    trusted_time: Option<u64>,
+
+    /// Last crypto failure, the verifier only reports which check failed
+    crypto_err: RefCell<Option<anyhow::Error>>,
+}
+
+impl<'a, Crypto: ImageGeneratorCrypto> HostImageVerificationEnv<'a, Crypto> {
+    /// Create an instance `HostImageVerificationEnv`
+    ///
+    /// # Arguments
+    ///
+    /// * `crypto`       - Crypto backend
+    /// * `image`        - Image bundle
+    /// * `fuses`        - Fuse configuration
+    /// * `trusted_time` - Trusted time enforcing the image validity windows
+    pub fn new(
+        crypto: Crypto,
+        image: &'a [u8],
+        fuses: &'a FuseConfig,
+        trusted_time: Option<u64>,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            crypto,
+            image,
+            fuses,
+            vendor_pub_key_digest: fuses.vendor_pub_key_digest()?,
+            owner_pub_key_digest: fuses.owner_pub_key_digest()?,
+            trusted_time,
+            crypto_err: RefCell::new(None),
+        })
+    }
+
+    /// Take the last crypto failure
+    pub fn take_crypto_err(&self) -> Option<anyhow::Error> {
+        self.crypto_err.take()
+    }
+
+    /// Record a crypto failure
+    ///
+    /// The verifier replaces the returned error with the error of the check
+    /// that requested the operation.
+    ///
+    /// # Arguments
+    ///
+    /// * `err` - Crypto failure
+    fn crypto_err(&self, err: anyhow::Error) -> CaliptraError {
+        *self.crypto_err.borrow_mut() = Some(err);
+        CRYPTO_ERR
+    }
+}
+
+impl<'a, Crypto: ImageGeneratorCrypto> ImageVerificationEnv
+    for &HostImageVerificationEnv<'a, Crypto>
+{
+    type Image = ();
+
+    /// Calculate Digest of the image range
+    fn sha384_digest(
+        &self,
+        _image: Self::Image,
+        offset: u32,
+        len: u32,
+    ) -> CaliptraResult<ImageDigest> {
+        let data = offset
+            .checked_add(len)
+            .and_then(|end| self.image.get(offset as usize..end as usize))
+            .ok_or_else(|| {
+                self.crypto_err(anyhow::anyhow!(
+                    "Range 0x{offset:x}+0x{len:x} is outside of the image"
+                ))
+            })?;
+
+        self.crypto
+            .sha384_digest(data)
+            .map_err(|err| self.crypto_err(err))
+    }
+
+    /// Calculate Digest of data outside the image
+    fn sha384_digest_data(&self, data: &[u8]) -> CaliptraResult<ImageDigest> {
+        self.crypto
+            .sha384_digest(data)
+            .map_err(|err| self.crypto_err(err))
+    }
+
+    /// ECC-384 Verification routine
+    fn ecc384_verify(
+        &self,
+        _image: Self::Image,
+        digest: &ImageDigest,
+        pub_key: &ImageEccPubKey,
+        sig: &ImageEccSignature,
+    ) -> CaliptraResult<bool> {
+        self.crypto
+            .ecdsa384_verify(digest, pub_key, sig)
+            .map_err(|err| self.crypto_err(err))
+    }
+
+    /// LMS Verification routine
+    fn lms_verify(
+        &self,
+        _image: Self::Image,
+        digest: &ImageDigest,
+        pub_key: &ImageLmsPubKey,
+        sig: &ImageLmsSignature,
+    ) -> CaliptraResult<bool> {
+        self.crypto
+            .lms_verify(digest, pub_key, sig)
+            .map_err(|err| self.crypto_err(err))
+    }
+
+    /// Retrieve Vendor Public Key Digest
+    fn vendor_pub_key_digest(&self, _image: Self::Image) -> ImageDigest {
+        self.vendor_pub_key_digest
+    }
+
+    /// Retrieve Vendor Public Key Revocation Bitmask
+    fn vendor_pub_key_revocation(&self, _image: Self::Image) -> VendorPubKeyRevocation {
+        VendorPubKeyRevocation::from_bits_truncate(self.fuses.key_manifest_pk_hash_mask)
+    }
+
+    /// Retrieve Vendor LMS Public Key Revocation Bitmask
+    fn vendor_lms_pub_key_revocation(&self, _image: Self::Image) -> VendorPubKeyRevocation {
+        VendorPubKeyRevocation::from_bits_truncate(self.fuses.lms_revocation)
+    }
+
+    /// Retrieve Owner Public Key Digest from fuses
+    fn owner_pub_key_digest_fuses(&self) -> ImageDigest {
+        self.owner_pub_key_digest
+    }
+
+    /// Retrieve Anti-Rollback disable fuse value
+    fn anti_rollback_disable(&self, _image: Self::Image) -> bool {
+        self.fuses.anti_rollback_disable
+    }
+
+    /// Retrieve Device Lifecycle state
+    fn dev_lifecycle(&self, _image: Self::Image) -> Lifecycle {
+        self.fuses.life_cycle.into()
+    }
+
+    // Data vault values are only used on update reset
+
+    fn vendor_pub_key_idx_dv(&self) -> u32 {
+        0
+    }
+
+    fn vendor_lms_pub_key_idx_dv(&self) -> u32 {
+        0
+    }
+
+    fn owner_pub_key_digest_dv(&self) -> ImageDigest {
+        ImageDigest::default()
+    }
+
+    fn get_fmc_digest_dv(&self) -> ImageDigest {
+        ImageDigest::default()
+    }
+
+    // Get Fuse FMC Key Manifest SVN
+    fn fmc_svn(&self) -> u32 {
+        self.fuses.fmc_svn()
+    }
+
+    // Get Runtime fuse SVN
+    fn runtime_svn(&self) -> u32 {
+        self.fuses.runtime_svn()
+    }
+
+    fn iccm_range(&self) -> Range<u32> {
+        Range {
+            start: memory_layout::ICCM_ORG,
+            end: memory_layout::ICCM_ORG + memory_layout::ICCM_SIZE,
+        }
+    }
+
+    // Get the trusted time supplied on the command line
+    fn trusted_time(&self) -> Option<u64> {
+        self.trusted_time
+    }
+}
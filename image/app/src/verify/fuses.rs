@@ -0,0 +1,117 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+   fuses.rs
+
+Abstract:
+
+    File contains utilities for parsing fuse description files
+
+--*/
+
+use anyhow::{anyhow, Context};
+use caliptra_drivers::Lifecycle;
+use caliptra_image_types::{ImageDigest, SHA384_DIGEST_BYTE_SIZE};
+use serde_derive::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Device Lifecycle
+#[derive(Default, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum FuseLifecycle {
+    #[default]
+    Unprovisioned,
+    Manufacturing,
+    Production,
+}
+
+impl From<FuseLifecycle> for Lifecycle {
+    fn from(value: FuseLifecycle) -> Self {
+        match value {
+            FuseLifecycle::Unprovisioned => Lifecycle::Unprovisioned,
+            FuseLifecycle::Manufacturing => Lifecycle::Manufacturing,
+            FuseLifecycle::Production => Lifecycle::Production,
+        }
+    }
+}
+
+/// Fuse Configuration
+///
+/// Digests are the hex encoded SHA-384 digests, as programmed in the fuse
+/// registers. The other values are the raw fuse register values.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct FuseConfig {
+    pub key_manifest_pk_hash: String,
+
+    pub key_manifest_pk_hash_mask: u32,
+
+    pub owner_pk_hash: String,
+
+    pub fmc_key_manifest_svn: u32,
+
+    /// Only the first 64 bits are used by ROM
+    pub runtime_svn: [u32; 4],
+
+    pub anti_rollback_disable: bool,
+
+    pub lms_revocation: u32,
+
+    pub life_cycle: FuseLifecycle,
+}
+
+impl FuseConfig {
+    /// Vendor public key digest
+    pub fn vendor_pub_key_digest(&self) -> anyhow::Result<ImageDigest> {
+        digest_from_hex(&self.key_manifest_pk_hash)
+            .with_context(|| "Invalid key_manifest_pk_hash fuse")
+    }
+
+    /// Owner public key digest
+    pub fn owner_pub_key_digest(&self) -> anyhow::Result<ImageDigest> {
+        digest_from_hex(&self.owner_pk_hash).with_context(|| "Invalid owner_pk_hash fuse")
+    }
+
+    /// FMC security version number, computed as ROM does
+    pub fn fmc_svn(&self) -> u32 {
+        32 - self.fmc_key_manifest_svn.leading_zeros()
+    }
+
+    /// Runtime security version number, computed as ROM does
+    pub fn runtime_svn(&self) -> u32 {
+        64 - ((self.runtime_svn[1] as u64) << 32 | self.runtime_svn[0] as u64).leading_zeros()
+    }
+}
+
+/// Convert a hex encoded digest to fuse register words, an empty string is an
+/// unprogrammed fuse
+fn digest_from_hex(value: &str) -> anyhow::Result<ImageDigest> {
+    let mut digest = ImageDigest::default();
+    if value.is_empty() {
+        return Ok(digest);
+    }
+
+    let bytes = hex::decode(value)?;
+    if bytes.len() != SHA384_DIGEST_BYTE_SIZE {
+        return Err(anyhow!("Expected {SHA384_DIGEST_BYTE_SIZE} bytes"));
+    }
+
+    for (word, bytes) in digest.iter_mut().zip(bytes.chunks_exact(4)) {
+        *word = u32::from_be_bytes(bytes.try_into()?);
+    }
+    Ok(digest)
+}
+
+/// Load Fuse Configuration from file
+pub(crate) fn load_fuse_config(path: &PathBuf) -> anyhow::Result<FuseConfig> {
+    let config_str = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read the fuse file {}", path.display()))?;
+
+    let config: FuseConfig = toml::from_str(&config_str)
+        .with_context(|| format!("Failed to parse fuse file {}", path.display()))?;
+
+    Ok(config)
+}
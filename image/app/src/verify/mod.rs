@@ -0,0 +1,193 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+   mod.rs
+
+Abstract:
+
+    File contains implementation Caliptra Image verification command.
+
+--*/
+
+mod env;
+mod fuses;
+
+use anyhow::{anyhow, Context};
+use caliptra_drivers::ResetReason;
+use caliptra_image_types::*;
+use caliptra_image_verify::{
+    ImageVerificationExeInfo, ImageVerificationInfo, ImageVerifier, ImageVerifierErr,
+};
+use clap::ArgMatches;
+use std::path::PathBuf;
+use zerocopy::FromBytes;
+
+use env::HostImageVerificationEnv;
+pub(crate) use fuses::FuseConfig;
+
+/// Run the command
+pub(crate) fn run_cmd(args: &ArgMatches) -> anyhow::Result<()> {
+    let image_path: &PathBuf = args
+        .get_one::<PathBuf>("image")
+        .with_context(|| "image arg not specified")?;
+
+    let fuses_path: &PathBuf = args
+        .get_one::<PathBuf>("fuses")
+        .with_context(|| "fuses arg not specified")?;
+
+    let trusted_time = args.get_one::<u64>("trusted-time").copied();
+
+    let image = std::fs::read(image_path)
+        .with_context(|| format!("Failed to read image {}", image_path.display()))?;
+
+    let fuses = fuses::load_fuse_config(fuses_path)?;
+
+    let info = verify_image(&image, &fuses, trusted_time)?;
+
+    println!("Image verification succeeded");
+    println!("Vendor ECC key index: {}", info.vendor_ecc_pub_key_idx);
+    println!("Vendor LMS key index: {}", info.vendor_lms_pub_key_idx);
+    println!(
+        "Vendor public keys digest: {}",
+        digest_to_hex(&info.vendor_pub_keys_digest)
+    );
+    println!(
+        "Owner public keys digest: {}",
+        digest_to_hex(&info.owner_pub_keys_digest)
+    );
+    print_exe_info("FMC", &info.fmc);
+    print_exe_info("Runtime", &info.runtime);
+
+    Ok(())
+}
+
+/// Verify an image bundle against a fuse configuration
+///
+/// # Arguments
+///
+/// * `image`        - Image bundle
+/// * `fuses`        - Fuse configuration
+/// * `trusted_time` - Trusted time enforcing the image validity windows
+pub(crate) fn verify_image(
+    image: &[u8],
+    fuses: &FuseConfig,
+    trusted_time: Option<u64>,
+) -> anyhow::Result<ImageVerificationInfo> {
+    let manifest = ImageManifest::read_from_prefix(image).with_context(|| "Image is too small")?;
+
+    let env = HostImageVerificationEnv::new(crate::Crypto::default(), image, fuses, trusted_time)?;
+
+    let verifier = ImageVerifier::new(&env);
+    verifier
+        .verify(&manifest, (), ResetReason::ColdReset)
+        .map_err(|err| {
+            let code = u32::from(err);
+            let check = ImageVerifierErr::try_from(code)
+                .map(|e| format!("{e:?}"))
+                .unwrap_or_else(|_| "Unknown".into());
+            let err = anyhow!("Image verification failed: {check} (0x{code:08x})");
+            match env.take_crypto_err() {
+                Some(cause) => cause.context(err),
+                None => err,
+            }
+        })
+}
+
+/// Print the verified information of an executable
+fn print_exe_info(name: &str, info: &ImageVerificationExeInfo) {
+    println!(
+        "{name}: load address 0x{:08x}, entry point 0x{:08x}, size {}, SVN {}, digest {}",
+        info.load_addr,
+        info.entry_point,
+        info.size,
+        info.svn,
+        digest_to_hex(&info.digest)
+    );
+}
+
+/// Convert a digest in hardware format to hex
+pub(crate) fn digest_to_hex(digest: &ImageDigest) -> String {
+    digest.iter().map(|w| format!("{w:08x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+
+    /// Verify `image`, returning the error message on failure
+    fn verify_err(image: &[u8], fuses: &FuseConfig) -> String {
+        format!("{:#}", verify_image(image, fuses, None).unwrap_err())
+    }
+
+    #[test]
+    fn test_verify_generated_bundle() {
+        let bundle = test_util::image_bundle();
+        let fuses = test_util::fuse_config(&bundle);
+
+        let info = verify_image(&bundle.to_bytes().unwrap(), &fuses, None).unwrap();
+        assert_eq!(info.fmc.load_addr, bundle.manifest.fmc.load_addr);
+        assert_eq!(info.fmc.digest, bundle.manifest.fmc.digest);
+        assert_eq!(info.runtime.load_addr, bundle.manifest.runtime.load_addr);
+        assert_eq!(info.runtime.digest, bundle.manifest.runtime.digest);
+    }
+
+    #[test]
+    fn test_verify_tampered_fmc() {
+        let bundle = test_util::image_bundle();
+        let fuses = test_util::fuse_config(&bundle);
+
+        let mut image = bundle.to_bytes().unwrap();
+        image[bundle.manifest.fmc.offset as usize] ^= 1;
+        assert!(verify_err(&image, &fuses).contains("FmcDigestMismatch (0x0b000014)"));
+    }
+
+    #[test]
+    fn test_verify_tampered_runtime() {
+        let bundle = test_util::image_bundle();
+        let fuses = test_util::fuse_config(&bundle);
+
+        let mut image = bundle.to_bytes().unwrap();
+        let end = bundle.manifest.runtime.image_range().end as usize;
+        image[end - 1] ^= 1;
+        assert!(verify_err(&image, &fuses).contains("RuntimeDigestMismatch (0x0b000016)"));
+    }
+
+    #[test]
+    fn test_verify_tampered_header() {
+        let bundle = test_util::image_bundle();
+        let fuses = test_util::fuse_config(&bundle);
+
+        let mut image = bundle.to_bytes().unwrap();
+        image[ImageManifest::header_range().start as usize] ^= 1;
+        assert!(verify_err(&image, &fuses).contains("VendorEccSignatureInvalid (0x0b00000c)"));
+    }
+
+    #[test]
+    fn test_verify_truncated_runtime() {
+        let bundle = test_util::image_bundle();
+        let fuses = test_util::fuse_config(&bundle);
+
+        // The digest fails because the runtime is outside of the image
+        let mut image = bundle.to_bytes().unwrap();
+        image.truncate(bundle.manifest.runtime.offset as usize);
+        let err = verify_err(&image, &fuses);
+        assert!(err.contains("RuntimeDigestFailure (0x0b000015)"));
+        assert!(err.contains("outside of the image"));
+    }
+
+    #[test]
+    fn test_verify_wrong_owner_fuses() {
+        let bundle = test_util::image_bundle();
+        let fuses = FuseConfig {
+            owner_pk_hash: "11".repeat(SHA384_DIGEST_BYTE_SIZE),
+            ..test_util::fuse_config(&bundle)
+        };
+
+        let image = bundle.to_bytes().unwrap();
+        assert!(verify_err(&image, &fuses).contains("OwnerPubKeyDigestMismatch (0x0b000007)"));
+    }
+}
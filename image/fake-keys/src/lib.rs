@@ -2,8 +2,9 @@
 
 use caliptra_image_gen::{ImageGeneratorOwnerConfig, ImageGeneratorVendorConfig};
 use caliptra_image_types::{
-    ImageEccPrivKey, ImageEccPubKey, ImageOwnerPrivKeys, ImageOwnerPubKeys, ImageVendorPrivKeys,
-    ImageVendorPubKeys,
+    ImageEccPrivKey, ImageEccPubKey, ImageLmsPrivKey, ImageLmsPubKey, ImageOwnerPrivKeys,
+    ImageOwnerPubKeys, ImageVendorPrivKeys, ImageVendorPubKeys, IMAGE_LMS_OTS_TYPE,
+    IMAGE_LMS_TREE_TYPE,
 };
 
 /// Generated with
@@ -103,6 +104,142 @@ pub const OWNER_KEY_PRIVATE: ImageEccPrivKey = [
     0xb75f88fd, 0xfa4bc6a4, 0x6b88340f, 0x05dd8890,
 ];
 
+/// Test-only LMS keys. Each image built with these keys is signed with leaf
+/// 0, which is fine for testing but must never be done with production keys.
+/// The public keys were generated with
+///
+/// ```no_run
+/// use caliptra_image_fake_keys::*;
+///
+/// let key = caliptra_image_openssl::lms_pub_key_from_priv_key(&VENDOR_LMS_KEY_0_PRIVATE).unwrap();
+/// println!("pub const VENDOR_LMS_KEY_0_PUBLIC: ImageLmsPubKey = {key:#04x?};");
+/// ```
+pub const VENDOR_LMS_KEY_0_PUBLIC: ImageLmsPubKey = ImageLmsPubKey {
+    tree_type: IMAGE_LMS_TREE_TYPE,
+    otstype: IMAGE_LMS_OTS_TYPE,
+    id: [
+        0x47, 0x4e, 0xd8, 0x0d, 0xc3, 0xb4, 0x02, 0xfb, 0x9c, 0x33, 0xf7, 0xcb, 0x12, 0x80, 0xcc,
+        0xef,
+    ],
+    digest: [
+        0x08, 0xe6, 0xd4, 0xc2, 0x38, 0x69, 0x34, 0x0b, 0xf0, 0x7e, 0x0c, 0x81, 0xf8, 0xd2, 0xa2,
+        0x8b, 0x43, 0x05, 0xc3, 0xcf, 0x6b, 0xe1, 0x25, 0x4e,
+    ],
+};
+pub const VENDOR_LMS_KEY_0_PRIVATE: ImageLmsPrivKey = ImageLmsPrivKey {
+    tree_type: IMAGE_LMS_TREE_TYPE,
+    otstype: IMAGE_LMS_OTS_TYPE,
+    id: [
+        0x47, 0x4e, 0xd8, 0x0d, 0xc3, 0xb4, 0x02, 0xfb, 0x9c, 0x33, 0xf7, 0xcb, 0x12, 0x80, 0xcc,
+        0xef,
+    ],
+    seed: [
+        0x9b, 0xb9, 0x2e, 0xa8, 0xbb, 0x98, 0x4c, 0x9b, 0xc5, 0x82, 0xdb, 0x7f, 0x41, 0x14, 0x75,
+        0x08, 0xc2, 0x45, 0xe3, 0x68, 0x35, 0x3d, 0xc7, 0xc4,
+    ],
+    q: 0,
+};
+pub const VENDOR_LMS_KEY_1_PUBLIC: ImageLmsPubKey = ImageLmsPubKey {
+    tree_type: IMAGE_LMS_TREE_TYPE,
+    otstype: IMAGE_LMS_OTS_TYPE,
+    id: [
+        0x9f, 0x1d, 0x1f, 0x33, 0xce, 0xbb, 0xba, 0xcd, 0xdb, 0xed, 0xae, 0xdf, 0x92, 0x95, 0x22,
+        0xd9,
+    ],
+    digest: [
+        0x9b, 0xd5, 0x3a, 0x08, 0x42, 0x3c, 0x4a, 0xe2, 0x4d, 0x66, 0x32, 0x14, 0x31, 0xed, 0xf2,
+        0xb9, 0xa4, 0xf3, 0xb2, 0xf5, 0xdb, 0xd4, 0x65, 0xe1,
+    ],
+};
+pub const VENDOR_LMS_KEY_1_PRIVATE: ImageLmsPrivKey = ImageLmsPrivKey {
+    tree_type: IMAGE_LMS_TREE_TYPE,
+    otstype: IMAGE_LMS_OTS_TYPE,
+    id: [
+        0x9f, 0x1d, 0x1f, 0x33, 0xce, 0xbb, 0xba, 0xcd, 0xdb, 0xed, 0xae, 0xdf, 0x92, 0x95, 0x22,
+        0xd9,
+    ],
+    seed: [
+        0x57, 0xa3, 0x1b, 0x55, 0x20, 0x3d, 0xa6, 0xa9, 0xbb, 0x68, 0x8d, 0xe0, 0x7a, 0x87, 0x32,
+        0x13, 0x12, 0x0f, 0x1b, 0xf8, 0xf4, 0x10, 0x61, 0xe7,
+    ],
+    q: 0,
+};
+pub const VENDOR_LMS_KEY_2_PUBLIC: ImageLmsPubKey = ImageLmsPubKey {
+    tree_type: IMAGE_LMS_TREE_TYPE,
+    otstype: IMAGE_LMS_OTS_TYPE,
+    id: [
+        0x69, 0x33, 0xe0, 0x1e, 0xb6, 0xf0, 0xa3, 0x90, 0x44, 0x04, 0x56, 0x0c, 0x9f, 0xae, 0x17,
+        0xb8,
+    ],
+    digest: [
+        0x3e, 0x0c, 0xd6, 0x25, 0x15, 0xbe, 0xb7, 0x94, 0xa4, 0x80, 0x10, 0xcb, 0x6d, 0xa0, 0x27,
+        0xdb, 0x76, 0xbb, 0xf4, 0x8c, 0xe4, 0x91, 0xd5, 0xd1,
+    ],
+};
+pub const VENDOR_LMS_KEY_2_PRIVATE: ImageLmsPrivKey = ImageLmsPrivKey {
+    tree_type: IMAGE_LMS_TREE_TYPE,
+    otstype: IMAGE_LMS_OTS_TYPE,
+    id: [
+        0x69, 0x33, 0xe0, 0x1e, 0xb6, 0xf0, 0xa3, 0x90, 0x44, 0x04, 0x56, 0x0c, 0x9f, 0xae, 0x17,
+        0xb8,
+    ],
+    seed: [
+        0x17, 0xfa, 0x09, 0x90, 0x0b, 0x83, 0x32, 0x26, 0x8a, 0xd4, 0xfb, 0xeb, 0x12, 0xef, 0x20,
+        0x23, 0xd2, 0xc8, 0x34, 0xf3, 0x30, 0xba, 0x7d, 0x01,
+    ],
+    q: 0,
+};
+pub const VENDOR_LMS_KEY_3_PUBLIC: ImageLmsPubKey = ImageLmsPubKey {
+    tree_type: IMAGE_LMS_TREE_TYPE,
+    otstype: IMAGE_LMS_OTS_TYPE,
+    id: [
+        0x9c, 0x63, 0x2c, 0xdc, 0x6e, 0xee, 0xa1, 0x39, 0x33, 0x0e, 0xce, 0x86, 0x5d, 0xaa, 0x44,
+        0x2d,
+    ],
+    digest: [
+        0x39, 0x0e, 0xa1, 0x82, 0x90, 0x13, 0x36, 0x45, 0x3d, 0x26, 0xa1, 0x20, 0x18, 0xe8, 0x61,
+        0x2e, 0x72, 0xf0, 0x57, 0xf1, 0x73, 0x75, 0x08, 0xc6,
+    ],
+};
+pub const VENDOR_LMS_KEY_3_PRIVATE: ImageLmsPrivKey = ImageLmsPrivKey {
+    tree_type: IMAGE_LMS_TREE_TYPE,
+    otstype: IMAGE_LMS_OTS_TYPE,
+    id: [
+        0x9c, 0x63, 0x2c, 0xdc, 0x6e, 0xee, 0xa1, 0x39, 0x33, 0x0e, 0xce, 0x86, 0x5d, 0xaa, 0x44,
+        0x2d,
+    ],
+    seed: [
+        0x2c, 0x87, 0x80, 0x12, 0x04, 0xbf, 0x6d, 0x79, 0x1d, 0x04, 0x1d, 0x57, 0x18, 0x4c, 0x1d,
+        0x44, 0x80, 0x7d, 0xb9, 0xc9, 0xce, 0xda, 0x4a, 0x03,
+    ],
+    q: 0,
+};
+pub const OWNER_LMS_KEY_PUBLIC: ImageLmsPubKey = ImageLmsPubKey {
+    tree_type: IMAGE_LMS_TREE_TYPE,
+    otstype: IMAGE_LMS_OTS_TYPE,
+    id: [
+        0xd3, 0x91, 0xe3, 0x0f, 0x77, 0x53, 0xe2, 0xc3, 0xca, 0xc2, 0x80, 0xf8, 0xe1, 0x31, 0x34,
+        0xed,
+    ],
+    digest: [
+        0xba, 0xd5, 0xd4, 0x97, 0xbb, 0xe9, 0x24, 0x39, 0xfe, 0x07, 0x6a, 0xb8, 0x13, 0x87, 0x29,
+        0x9c, 0x3a, 0x81, 0x4d, 0x90, 0x5c, 0x95, 0xca, 0x40,
+    ],
+};
+pub const OWNER_LMS_KEY_PRIVATE: ImageLmsPrivKey = ImageLmsPrivKey {
+    tree_type: IMAGE_LMS_TREE_TYPE,
+    otstype: IMAGE_LMS_OTS_TYPE,
+    id: [
+        0xd3, 0x91, 0xe3, 0x0f, 0x77, 0x53, 0xe2, 0xc3, 0xca, 0xc2, 0x80, 0xf8, 0xe1, 0x31, 0x34,
+        0xed,
+    ],
+    seed: [
+        0x04, 0x08, 0x02, 0x22, 0x54, 0x3b, 0x7c, 0x84, 0x7d, 0xf1, 0x4a, 0xbb, 0x6c, 0xd3, 0x80,
+        0x46, 0xe3, 0x37, 0x91, 0x2e, 0xd5, 0xea, 0xcf, 0x30,
+    ],
+    q: 0,
+};
+
 pub const VENDOR_PUBLIC_KEYS: ImageVendorPubKeys = ImageVendorPubKeys {
     ecc_pub_keys: [
         VENDOR_KEY_0_PUBLIC,
@@ -110,6 +247,12 @@ pub const VENDOR_PUBLIC_KEYS: ImageVendorPubKeys = ImageVendorPubKeys {
         VENDOR_KEY_2_PUBLIC,
         VENDOR_KEY_3_PUBLIC,
     ],
+    lms_pub_keys: [
+        VENDOR_LMS_KEY_0_PUBLIC,
+        VENDOR_LMS_KEY_1_PUBLIC,
+        VENDOR_LMS_KEY_2_PUBLIC,
+        VENDOR_LMS_KEY_3_PUBLIC,
+    ],
 };
 
 pub const VENDOR_PRIVATE_KEYS: ImageVendorPrivKeys = ImageVendorPrivKeys {
@@ -119,11 +262,18 @@ pub const VENDOR_PRIVATE_KEYS: ImageVendorPrivKeys = ImageVendorPrivKeys {
         VENDOR_KEY_2_PRIVATE,
         VENDOR_KEY_3_PRIVATE,
     ],
+    lms_priv_keys: [
+        VENDOR_LMS_KEY_0_PRIVATE,
+        VENDOR_LMS_KEY_1_PRIVATE,
+        VENDOR_LMS_KEY_2_PRIVATE,
+        VENDOR_LMS_KEY_3_PRIVATE,
+    ],
 };
 
 pub const VENDOR_CONFIG_KEY_0: ImageGeneratorVendorConfig = ImageGeneratorVendorConfig {
     pub_keys: VENDOR_PUBLIC_KEYS,
     ecc_key_idx: 0,
+    lms_key_idx: 0,
     priv_keys: Some(VENDOR_PRIVATE_KEYS),
     not_before: [0u8; 15],
     not_after: [0u8; 15],
@@ -131,25 +281,30 @@ pub const VENDOR_CONFIG_KEY_0: ImageGeneratorVendorConfig = ImageGeneratorVendor
 
 pub const VENDOR_CONFIG_KEY_1: ImageGeneratorVendorConfig = ImageGeneratorVendorConfig {
     ecc_key_idx: 1,
+    lms_key_idx: 1,
     ..VENDOR_CONFIG_KEY_0
 };
 
 pub const VENDOR_CONFIG_KEY_2: ImageGeneratorVendorConfig = ImageGeneratorVendorConfig {
     ecc_key_idx: 2,
+    lms_key_idx: 2,
     ..VENDOR_CONFIG_KEY_0
 };
 
 pub const VENDOR_CONFIG_KEY_3: ImageGeneratorVendorConfig = ImageGeneratorVendorConfig {
     ecc_key_idx: 3,
+    lms_key_idx: 3,
     ..VENDOR_CONFIG_KEY_0
 };
 
 pub const OWNER_CONFIG: ImageGeneratorOwnerConfig = ImageGeneratorOwnerConfig {
     pub_keys: ImageOwnerPubKeys {
         ecc_pub_key: OWNER_KEY_PUBLIC,
+        lms_pub_key: OWNER_LMS_KEY_PUBLIC,
     },
     priv_keys: Some(ImageOwnerPrivKeys {
         ecc_priv_key: OWNER_KEY_PRIVATE,
+        lms_priv_key: OWNER_LMS_KEY_PRIVATE,
     }),
     not_before: [0u8; 15],
     not_after: [0u8; 15],
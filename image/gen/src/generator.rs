@@ -11,7 +11,7 @@ Abstract:
     Caliptra Image generator
 
 --*/
-use anyhow::bail;
+use anyhow::{bail, Context};
 use caliptra_image_types::*;
 use zerocopy::AsBytes;
 
@@ -43,12 +43,59 @@ impl<Crypto: ImageGeneratorCrypto> ImageGenerator<Crypto> {
     where
         E: ImageGenratorExecutable,
     {
-        if IMAGE_MANIFEST_BYTE_SIZE as u32 + config.fmc.size() + config.runtime.size()
-            > IMAGE_BYTE_SIZE as u32
+        let mut image = self.generate_unsigned(config)?;
+
+        let ecc_key_idx = config.vendor_config.ecc_key_idx;
+        let lms_key_idx = config.vendor_config.lms_key_idx;
+        let header_digest = self.header_digest(&image.manifest.header)?;
+        image.manifest.preamble =
+            self.gen_preamble(config, ecc_key_idx, lms_key_idx, &header_digest)?;
+
+        Ok(image)
+    }
+
+    /// Generate image without signatures
+    ///
+    /// The vendor and owner sign the header digest of the image outside of
+    /// the generator; the signatures are added with `assemble()`.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - Image generator configuration, private keys are ignored
+    ///
+    /// # Returns
+    ///
+    /// * `ImageBundle` - Unsigned Caliptra Image Bundle
+    pub fn generate_unsigned<E>(
+        &self,
+        config: &ImageGeneratorConfig<E>,
+    ) -> anyhow::Result<ImageBundle>
+    where
+        E: ImageGenratorExecutable,
+    {
+        if config.aux.len() > MAX_AUX_TOC_ENTRY_COUNT as usize {
+            bail!("More than {MAX_AUX_TOC_ENTRY_COUNT} auxiliary images");
+        }
+
+        let aux_size: usize = config.aux.iter().map(|aux| aux.content.len()).sum();
+        if IMAGE_MANIFEST_BYTE_SIZE
+            + config.fmc.size() as usize
+            + config.runtime.size() as usize
+            + aux_size
+            > IMAGE_BYTE_SIZE
         {
             bail!("Image larger than {IMAGE_BYTE_SIZE} bytes");
         }
 
+        for (i, aux) in config.aux.iter().enumerate() {
+            if aux.id == u32::from(ImageTocEntryId::Fmc)
+                || aux.id == u32::from(ImageTocEntryId::Runtime)
+                || config.aux[..i].iter().any(|prev| prev.id == aux.id)
+            {
+                bail!("Invalid or duplicate auxiliary image id {}", aux.id);
+            }
+        }
+
         // Create FMC TOC & Content
         let id = ImageTocEntryId::Fmc;
         let offset = IMAGE_MANIFEST_BYTE_SIZE as u32;
@@ -59,31 +106,52 @@ impl<Crypto: ImageGeneratorCrypto> ImageGenerator<Crypto> {
         let offset = offset + fmc_toc.size;
         let (runtime_toc, runtime) = self.gen_image(&config.runtime, id, offset)?;
 
+        // Create Auxiliary TOCs & Content
+        let mut offset = offset + runtime_toc.size;
+        let mut aux_toc = Vec::new();
+        let mut aux = Vec::new();
+        for aux_image in &config.aux {
+            let (toc, content) = self.gen_aux_image(aux_image, offset)?;
+            offset += toc.size;
+            aux_toc.push(toc);
+            aux.push(content);
+        }
+
         let ecc_key_idx = config.vendor_config.ecc_key_idx;
+        let lms_key_idx = config.vendor_config.lms_key_idx;
 
         // Create Header
-        let toc_digest = self.toc_digest(&fmc_toc, &runtime_toc)?;
-        let header = self.gen_header(config, ecc_key_idx, Self::DEFAULT_FLAGS, toc_digest)?;
+        let toc_digest = self.toc_digest(&fmc_toc, &runtime_toc, &aux_toc)?;
+        let header = self.gen_header(
+            config,
+            ecc_key_idx,
+            lms_key_idx,
+            Self::DEFAULT_FLAGS,
+            MIN_TOC_ENTRY_COUNT + aux_toc.len() as u32,
+            toc_digest,
+        )?;
 
         // Create Preamable
-        let header_digest = self.header_digest(&header)?;
-        let preamble = self.gen_preamble(config, ecc_key_idx, &header_digest)?;
+        let preamble = self.gen_unsigned_preamble(config, ecc_key_idx, lms_key_idx);
 
         // Create Manifest
-        let manifest = ImageManifest {
+        let mut manifest = ImageManifest {
             marker: MANIFEST_MARKER,
             size: core::mem::size_of::<ImageManifest>() as u32,
             preamble,
             header,
             fmc: fmc_toc,
             runtime: runtime_toc,
+            ..Default::default()
         };
+        manifest.aux[..aux_toc.len()].copy_from_slice(&aux_toc);
 
         // Create Image Bundle
         let image = ImageBundle {
             manifest,
             fmc,
             runtime,
+            aux,
         };
 
         Ok(image)
@@ -94,6 +162,7 @@ impl<Crypto: ImageGeneratorCrypto> ImageGenerator<Crypto> {
         &self,
         config: &ImageGeneratorConfig<E>,
         ecc_key_idx: u32,
+        lms_key_idx: u32,
         digest: &ImageDigest,
     ) -> anyhow::Result<ImagePreamble>
     where
@@ -109,6 +178,11 @@ impl<Crypto: ImageGeneratorCrypto> ImageGenerator<Crypto> {
                 &config.vendor_config.pub_keys.ecc_pub_keys[ecc_key_idx as usize],
             )?;
             vendor_sigs.ecc_sig = sig;
+
+            let sig = self
+                .crypto
+                .lms_sign(digest, &priv_keys.lms_priv_keys[lms_key_idx as usize])?;
+            vendor_sigs.lms_sig = sig;
         }
 
         if let Some(owner_config) = &config.owner_config {
@@ -119,14 +193,31 @@ impl<Crypto: ImageGeneratorCrypto> ImageGenerator<Crypto> {
                     &owner_config.pub_keys.ecc_pub_key,
                 )?;
                 owner_sigs.ecc_sig = sig;
+                owner_sigs.lms_sig = self.crypto.lms_sign(digest, &priv_keys.lms_priv_key)?;
             }
         }
 
+        let mut preamble = self.gen_unsigned_preamble(config, ecc_key_idx, lms_key_idx);
+        preamble.vendor_sigs = vendor_sigs;
+        preamble.owner_sigs = owner_sigs;
+
+        Ok(preamble)
+    }
+
+    /// Create preamble without signatures
+    fn gen_unsigned_preamble<E>(
+        &self,
+        config: &ImageGeneratorConfig<E>,
+        ecc_key_idx: u32,
+        lms_key_idx: u32,
+    ) -> ImagePreamble
+    where
+        E: ImageGenratorExecutable,
+    {
         let mut preamble = ImagePreamble {
             vendor_pub_keys: config.vendor_config.pub_keys,
             vendor_ecc_pub_key_idx: ecc_key_idx,
-            vendor_sigs,
-            owner_sigs,
+            vendor_lms_pub_key_idx: lms_key_idx,
             ..Default::default()
         };
 
@@ -134,7 +225,95 @@ impl<Crypto: ImageGeneratorCrypto> ImageGenerator<Crypto> {
             preamble.owner_pub_keys = owner_config.pub_keys;
         }
 
-        Ok(preamble)
+        preamble
+    }
+
+    /// Add externally produced signatures to an unsigned image
+    ///
+    /// The signatures are checked against the public keys of the image
+    /// before the signed image is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `image`       - Unsigned image, as created by `generate_unsigned()`
+    /// * `vendor_sigs` - Vendor signatures of the header digest
+    /// * `owner_sigs`  - Owner signatures, if the image has owner public keys
+    ///
+    /// # Returns
+    ///
+    /// * `ImageBundle` - Signed Caliptra Image Bundle
+    pub fn assemble(
+        &self,
+        mut image: ImageBundle,
+        vendor_sigs: ImageSignatures,
+        owner_sigs: Option<ImageSignatures>,
+    ) -> anyhow::Result<ImageBundle> {
+        let preamble = &mut image.manifest.preamble;
+        let has_owner = preamble.owner_pub_keys.as_bytes().iter().any(|&b| b != 0);
+        preamble.vendor_sigs = vendor_sigs;
+        preamble.owner_sigs = match owner_sigs {
+            Some(sigs) if has_owner => sigs,
+            Some(_) => bail!("Owner signatures given for an image without owner keys"),
+            None if has_owner => bail!("Owner signatures missing"),
+            None => ImageSignatures::default(),
+        };
+
+        self.verify_sigs(&image.manifest)?;
+
+        Ok(image)
+    }
+
+    /// Verify the vendor and owner signatures of the manifest header
+    ///
+    /// # Arguments
+    ///
+    /// * `manifest` - Image manifest
+    pub fn verify_sigs(&self, manifest: &ImageManifest) -> anyhow::Result<()> {
+        let preamble = &manifest.preamble;
+        let digest = self.header_digest(&manifest.header)?;
+
+        let pub_keys = &preamble.vendor_pub_keys;
+        let ecc_pub_key = pub_keys
+            .ecc_pub_keys
+            .get(preamble.vendor_ecc_pub_key_idx as usize)
+            .with_context(|| "Invalid vendor ECC public key index")?;
+        let lms_pub_key = pub_keys
+            .lms_pub_keys
+            .get(preamble.vendor_lms_pub_key_idx as usize)
+            .with_context(|| "Invalid vendor LMS public key index")?;
+
+        let sigs = &preamble.vendor_sigs;
+        if !self
+            .crypto
+            .ecdsa384_verify(&digest, ecc_pub_key, &sigs.ecc_sig)?
+        {
+            bail!("Invalid vendor ECC signature");
+        }
+        if !self
+            .crypto
+            .lms_verify(&digest, lms_pub_key, &sigs.lms_sig)?
+        {
+            bail!("Invalid vendor LMS signature");
+        }
+
+        let pub_keys = &preamble.owner_pub_keys;
+        if pub_keys.as_bytes().iter().any(|&b| b != 0) {
+            let sigs = &preamble.owner_sigs;
+            if !self
+                .crypto
+                .ecdsa384_verify(&digest, &pub_keys.ecc_pub_key, &sigs.ecc_sig)?
+            {
+                bail!("Invalid owner ECC signature");
+            }
+            if !self
+                .crypto
+                .lms_verify(&digest, &pub_keys.lms_pub_key, &sigs.lms_sig)?
+            {
+                bail!("Invalid owner LMS signature");
+            }
+        }
+
+        Ok(())
     }
 
     /// Generate header
@@ -142,7 +321,9 @@ impl<Crypto: ImageGeneratorCrypto> ImageGenerator<Crypto> {
         &self,
         config: &ImageGeneratorConfig<E>,
         ecc_key_idx: u32,
+        lms_key_idx: u32,
         flags: u32,
+        toc_len: u32,
         digest: ImageDigest,
     ) -> anyhow::Result<ImageHeader>
     where
@@ -150,8 +331,9 @@ impl<Crypto: ImageGeneratorCrypto> ImageGenerator<Crypto> {
     {
         let mut header = ImageHeader {
             vendor_ecc_pub_key_idx: ecc_key_idx,
+            vendor_lms_pub_key_idx: lms_key_idx,
             flags,
-            toc_len: MAX_TOC_ENTRY_COUNT,
+            toc_len,
             toc_digest: digest,
             ..Default::default()
         };
@@ -213,15 +395,41 @@ impl<Crypto: ImageGeneratorCrypto> ImageGenerator<Crypto> {
         Ok((entry, image.content().clone()))
     }
 
+    /// Generate auxiliary image
+    fn gen_aux_image(
+        &self,
+        image: &ImageGeneratorAuxImage,
+        offset: u32,
+    ) -> anyhow::Result<(ImageTocEntry, Vec<u8>)> {
+        let r#type = ImageTocEntryType::Data;
+        let digest = self.crypto.sha384_digest(&image.content)?;
+
+        let entry = ImageTocEntry {
+            id: image.id,
+            r#type: r#type.into(),
+            revision: image.rev,
+            offset,
+            size: image.content.len() as u32,
+            digest,
+            ..Default::default()
+        };
+
+        Ok((entry, image.content.clone()))
+    }
+
     /// Calculate TOC digest
     pub fn toc_digest(
         &self,
         fmc_toc: &ImageTocEntry,
         rt_toc: &ImageTocEntry,
+        aux_toc: &[ImageTocEntry],
     ) -> anyhow::Result<ImageDigest> {
         let mut toc_content: Vec<u8> = Vec::new();
         toc_content.extend_from_slice(fmc_toc.as_bytes());
         toc_content.extend_from_slice(rt_toc.as_bytes());
+        for toc in aux_toc {
+            toc_content.extend_from_slice(toc.as_bytes());
+        }
         self.crypto.sha384_digest(&toc_content)
     }
 }
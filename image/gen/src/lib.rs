@@ -13,8 +13,11 @@ Abstract:
 --*/
 
 mod generator;
+pub mod lms;
+mod signature;
 
 pub use generator::ImageGenerator;
+pub use signature::{ecc_sig_from_bytes, lms_sig_from_bytes};
 
 use caliptra_image_types::*;
 
@@ -54,6 +57,32 @@ pub trait ImageGeneratorCrypto {
         priv_key: &ImageEccPrivKey,
         pub_key: &ImageEccPubKey,
     ) -> anyhow::Result<ImageEccSignature>;
+
+    /// Calculate LMS Signature
+    ///
+    /// The signature is made with the one-time key `priv_key.q`; the caller
+    /// is responsible for never signing twice with the same leaf.
+    fn lms_sign(
+        &self,
+        digest: &ImageDigest,
+        priv_key: &ImageLmsPrivKey,
+    ) -> anyhow::Result<ImageLmsSignature>;
+
+    /// Verify ECDSA Signature
+    fn ecdsa384_verify(
+        &self,
+        digest: &ImageDigest,
+        pub_key: &ImageEccPubKey,
+        sig: &ImageEccSignature,
+    ) -> anyhow::Result<bool>;
+
+    /// Verify LMS Signature
+    fn lms_verify(
+        &self,
+        digest: &ImageDigest,
+        pub_key: &ImageLmsPubKey,
+        sig: &ImageLmsSignature,
+    ) -> anyhow::Result<bool>;
 }
 
 /// Image Generator Vendor Configuration
@@ -63,6 +92,8 @@ pub struct ImageGeneratorVendorConfig {
 
     pub ecc_key_idx: u32,
 
+    pub lms_key_idx: u32,
+
     pub priv_keys: Option<ImageVendorPrivKeys>,
 
     pub not_before: [u8; 15],
@@ -82,6 +113,22 @@ pub struct ImageGeneratorOwnerConfig {
     pub not_after: [u8; 15],
 }
 
+/// Image Generator Auxiliary Image
+///
+/// Auxiliary images are signed and measured along with the firmware but are
+/// never loaded or executed by Caliptra.
+#[derive(Default, Clone)]
+pub struct ImageGeneratorAuxImage {
+    /// Identifier, must not collide with the FMC or Runtime identifiers
+    pub id: u32,
+
+    /// Revision
+    pub rev: ImageRevision,
+
+    /// Content
+    pub content: Vec<u8>,
+}
+
 /// Image Generator Configuration
 #[derive(Default)]
 pub struct ImageGeneratorConfig<T>
@@ -95,4 +142,6 @@ where
     pub fmc: T,
 
     pub runtime: T,
+
+    pub aux: Vec<ImageGeneratorAuxImage>,
 }
@@ -0,0 +1,293 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+   lms.rs
+
+Abstract:
+
+    File contains LMS (RFC 8554) key and signature generation for the
+    parameter set used by the firmware manifest, shared by the crypto
+    backends of the image generator.
+
+--*/
+
+use anyhow::bail;
+use caliptra_image_types::*;
+
+const D_PBLC: u16 = 0x8080;
+const D_MESG: u16 = 0x8181;
+const D_LEAF: u16 = 0x8282;
+const D_INTR: u16 = 0x8383;
+
+/// Winternitz parameter of LMOTS_SHA256_N24_W8
+const LMOTS_W: u32 = 8;
+
+/// Number of leaves in the tree
+const LMS_LEAF_COUNT: u32 = 1 << LMS_TREE_HEIGHT;
+
+/// Index of the one-time private key elements used to derive the
+/// signature nonce. Outside of `0..LMOTS_P`, it never collides with a key
+/// element.
+const NONCE_INDEX: u16 = 0xfffe;
+
+/// SHA-256 implementation of a crypto backend
+pub trait LmsHasher {
+    /// Calculate the SHA-256 digest of the concatenated inputs
+    fn sha256(&self, data: &[&[u8]]) -> [u8; 32];
+}
+
+/// Calculate the truncated SHA-256 digest of the concatenated inputs
+fn hash(hasher: &impl LmsHasher, data: &[&[u8]]) -> ImageLmsDigest {
+    let mut digest = ImageLmsDigest::default();
+    digest.copy_from_slice(&hasher.sha256(data)[..LMS_DIGEST_BYTE_SIZE]);
+    digest
+}
+
+/// Derive the i-th element of the one-time private key of leaf `q`
+/// (RFC 8554, Appendix A)
+fn ots_priv_element(
+    hasher: &impl LmsHasher,
+    priv_key: &ImageLmsPrivKey,
+    q: u32,
+    i: u16,
+) -> ImageLmsDigest {
+    hash(
+        hasher,
+        &[
+            &priv_key.id,
+            &q.to_be_bytes(),
+            &i.to_be_bytes(),
+            &[0xff],
+            &priv_key.seed,
+        ],
+    )
+}
+
+/// Apply the chaining function to `value` for the steps `start..end`
+fn chain(
+    hasher: &impl LmsHasher,
+    id: &[u8; 16],
+    q: u32,
+    i: u16,
+    start: u8,
+    end: u8,
+    value: ImageLmsDigest,
+) -> ImageLmsDigest {
+    (start..end).fold(value, |tmp, j| {
+        hash(
+            hasher,
+            &[id, &q.to_be_bytes(), &i.to_be_bytes(), &[j], &tmp],
+        )
+    })
+}
+
+/// Calculate the one-time public key of leaf `q`
+fn ots_pub_key(hasher: &impl LmsHasher, priv_key: &ImageLmsPrivKey, q: u32) -> ImageLmsDigest {
+    let max = ((1u32 << LMOTS_W) - 1) as u8;
+    let z: Vec<ImageLmsDigest> = (0..LMOTS_P as u16)
+        .map(|i| {
+            let x = ots_priv_element(hasher, priv_key, q, i);
+            chain(hasher, &priv_key.id, q, i, 0, max, x)
+        })
+        .collect();
+    ots_pub_key_from_chains(hasher, &priv_key.id, q, &z)
+}
+
+/// Calculate the one-time public key of leaf `q` from the ends of its chains
+fn ots_pub_key_from_chains(
+    hasher: &impl LmsHasher,
+    id: &[u8; 16],
+    q: u32,
+    z: &[ImageLmsDigest],
+) -> ImageLmsDigest {
+    let q = q.to_be_bytes();
+    let d_pblc = D_PBLC.to_be_bytes();
+    let mut data: Vec<&[u8]> = vec![id, &q, &d_pblc];
+    data.extend(z.iter().map(|z| z.as_slice()));
+    hash(hasher, &data)
+}
+
+/// Calculate all nodes of the Merkle tree. Node `r` is at index `r`; the
+/// root is node 1.
+fn tree(hasher: &impl LmsHasher, priv_key: &ImageLmsPrivKey) -> Vec<ImageLmsDigest> {
+    let mut nodes = vec![ImageLmsDigest::default(); 2 * LMS_LEAF_COUNT as usize];
+    for q in 0..LMS_LEAF_COUNT {
+        let r = LMS_LEAF_COUNT + q;
+        nodes[r as usize] = hash(
+            hasher,
+            &[
+                &priv_key.id,
+                &r.to_be_bytes(),
+                &D_LEAF.to_be_bytes(),
+                &ots_pub_key(hasher, priv_key, q),
+            ],
+        );
+    }
+    for r in (1..LMS_LEAF_COUNT).rev() {
+        nodes[r as usize] = hash(
+            hasher,
+            &[
+                &priv_key.id,
+                &r.to_be_bytes(),
+                &D_INTR.to_be_bytes(),
+                &nodes[2 * r as usize],
+                &nodes[2 * r as usize + 1],
+            ],
+        );
+    }
+    nodes
+}
+
+/// Check the private key uses the parameter set of the manifest
+fn check_priv_key(priv_key: &ImageLmsPrivKey) -> anyhow::Result<()> {
+    if priv_key.tree_type != IMAGE_LMS_TREE_TYPE || priv_key.otstype != IMAGE_LMS_OTS_TYPE {
+        bail!(
+            "Unsupported LMS key type {}/{}",
+            priv_key.tree_type,
+            priv_key.otstype
+        );
+    }
+    Ok(())
+}
+
+/// Calculate the Winternitz coefficients of `message` signed with leaf `q`:
+/// the message digest followed by its checksum. With w = 8 every byte is a
+/// coefficient.
+fn coefficients(
+    hasher: &impl LmsHasher,
+    id: &[u8; 16],
+    q: u32,
+    nonce: &ImageLmsDigest,
+    message: &[u8],
+) -> [u8; LMS_DIGEST_BYTE_SIZE + 2] {
+    let mut coeffs = [0u8; LMS_DIGEST_BYTE_SIZE + 2];
+    coeffs[..LMS_DIGEST_BYTE_SIZE].copy_from_slice(&hash(
+        hasher,
+        &[id, &q.to_be_bytes(), &D_MESG.to_be_bytes(), nonce, message],
+    ));
+    let checksum: u16 = coeffs[..LMS_DIGEST_BYTE_SIZE]
+        .iter()
+        .map(|&c| u16::from(u8::MAX - c))
+        .sum();
+    coeffs[LMS_DIGEST_BYTE_SIZE..].copy_from_slice(&checksum.to_be_bytes());
+    coeffs
+}
+
+/// Calculate the LMS public key of a private key
+pub fn pub_key(
+    hasher: &impl LmsHasher,
+    priv_key: &ImageLmsPrivKey,
+) -> anyhow::Result<ImageLmsPubKey> {
+    check_priv_key(priv_key)?;
+    Ok(ImageLmsPubKey {
+        tree_type: priv_key.tree_type,
+        otstype: priv_key.otstype,
+        id: priv_key.id,
+        digest: tree(hasher, priv_key)[1],
+    })
+}
+
+/// Sign `message` with the one-time key `priv_key.q`
+///
+/// The signature is deterministic: the nonce is derived from the private
+/// key seed like the one-time key elements, so signing the same message
+/// with the same leaf on any host produces the same signature.
+pub fn sign(
+    hasher: &impl LmsHasher,
+    message: &[u8],
+    priv_key: &ImageLmsPrivKey,
+) -> anyhow::Result<ImageLmsSignature> {
+    check_priv_key(priv_key)?;
+
+    let q = priv_key.q;
+    if q >= LMS_LEAF_COUNT {
+        bail!("LMS private key is exhausted");
+    }
+
+    let nonce = ots_priv_element(hasher, priv_key, q, NONCE_INDEX);
+
+    let coeffs = coefficients(hasher, &priv_key.id, q, &nonce, message);
+
+    let mut y = [ImageLmsDigest::default(); LMOTS_P];
+    for (i, (y, &a)) in y.iter_mut().zip(coeffs.iter()).enumerate() {
+        let i = i as u16;
+        let x = ots_priv_element(hasher, priv_key, q, i);
+        *y = chain(hasher, &priv_key.id, q, i, 0, a, x);
+    }
+
+    let nodes = tree(hasher, priv_key);
+    let mut tree_path = [ImageLmsDigest::default(); LMS_TREE_HEIGHT];
+    let mut r = LMS_LEAF_COUNT + q;
+    for node in tree_path.iter_mut() {
+        *node = nodes[(r ^ 1) as usize];
+        r /= 2;
+    }
+
+    Ok(ImageLmsSignature {
+        q,
+        ots: ImageLmOtsSignature {
+            otstype: priv_key.otstype,
+            nonce,
+            y,
+        },
+        tree_type: priv_key.tree_type,
+        tree_path,
+    })
+}
+
+/// Verify the signature of `message` (RFC 8554, Algorithm 6a)
+pub fn verify(
+    hasher: &impl LmsHasher,
+    message: &[u8],
+    pub_key: &ImageLmsPubKey,
+    sig: &ImageLmsSignature,
+) -> bool {
+    if pub_key.tree_type != IMAGE_LMS_TREE_TYPE
+        || pub_key.otstype != IMAGE_LMS_OTS_TYPE
+        || sig.tree_type != pub_key.tree_type
+        || sig.ots.otstype != pub_key.otstype
+        || sig.q >= LMS_LEAF_COUNT
+    {
+        return false;
+    }
+
+    let id = &pub_key.id;
+    let q = sig.q;
+    let max = ((1u32 << LMOTS_W) - 1) as u8;
+
+    // Candidate one-time public key
+    let coeffs = coefficients(hasher, id, q, &sig.ots.nonce, message);
+    let z: Vec<ImageLmsDigest> = sig
+        .ots
+        .y
+        .iter()
+        .zip(coeffs.iter())
+        .enumerate()
+        .map(|(i, (y, &a))| chain(hasher, id, q, i as u16, a, max, *y))
+        .collect();
+    let ots_pub_key = ots_pub_key_from_chains(hasher, id, q, &z);
+
+    // Candidate root of the tree
+    let mut r = LMS_LEAF_COUNT + q;
+    let mut node = hash(
+        hasher,
+        &[id, &r.to_be_bytes(), &D_LEAF.to_be_bytes(), &ots_pub_key],
+    );
+    for sibling in sig.tree_path.iter() {
+        let (left, right) = if r % 2 == 1 {
+            (sibling, &node)
+        } else {
+            (&node, sibling)
+        };
+        r /= 2;
+        node = hash(
+            hasher,
+            &[id, &r.to_be_bytes(), &D_INTR.to_be_bytes(), left, right],
+        );
+    }
+
+    node == pub_key.digest
+}
@@ -0,0 +1,150 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+   signature.rs
+
+Abstract:
+
+    File contains parsers for signatures produced outside of the generator.
+
+--*/
+
+use anyhow::{bail, Context};
+use caliptra_image_types::*;
+
+/// Size of an LMS signature in its RFC 8554 encoding
+const LMS_SIG_BYTE_SIZE: usize =
+    4 + 4 + LMS_DIGEST_BYTE_SIZE * (1 + LMOTS_P) + 4 + LMS_DIGEST_BYTE_SIZE * LMS_TREE_HEIGHT;
+
+/// Parse an ECDSA-384 signature
+///
+/// # Arguments
+///
+/// * `bytes` - DER encoded `Ecdsa-Sig-Value` or raw big-endian `r || s`
+///
+/// # Returns
+///
+/// * `ImageEccSignature` - Signature
+pub fn ecc_sig_from_bytes(bytes: &[u8]) -> anyhow::Result<ImageEccSignature> {
+    if bytes.len() == 2 * ECC384_SCALAR_BYTE_SIZE {
+        let (r, s) = bytes.split_at(ECC384_SCALAR_BYTE_SIZE);
+        return Ok(ImageEccSignature {
+            r: scalar_from_be_bytes(r)?,
+            s: scalar_from_be_bytes(s)?,
+        });
+    }
+
+    let (seq, rest) = der_read(bytes, 0x30).with_context(|| "Invalid ECDSA signature")?;
+    if !rest.is_empty() {
+        bail!("Invalid ECDSA signature: trailing data");
+    }
+    let (r, seq) = der_read(seq, 0x02).with_context(|| "Invalid ECDSA signature r")?;
+    let (s, seq) = der_read(seq, 0x02).with_context(|| "Invalid ECDSA signature s")?;
+    if !seq.is_empty() {
+        bail!("Invalid ECDSA signature: trailing data");
+    }
+
+    Ok(ImageEccSignature {
+        r: scalar_from_be_bytes(r)?,
+        s: scalar_from_be_bytes(s)?,
+    })
+}
+
+/// Parse an LMS signature
+///
+/// # Arguments
+///
+/// * `bytes` - RFC 8554 encoded LMS signature or single level HSS signature
+///
+/// # Returns
+///
+/// * `ImageLmsSignature` - Signature
+pub fn lms_sig_from_bytes(bytes: &[u8]) -> anyhow::Result<ImageLmsSignature> {
+    // HSS signature with a single level: Nspk = 0 followed by the signature
+    let bytes = match bytes.len() {
+        LMS_SIG_BYTE_SIZE => bytes,
+        len if len == 4 + LMS_SIG_BYTE_SIZE && bytes[..4] == [0; 4] => &bytes[4..],
+        len => bail!("Invalid LMS signature size {len}"),
+    };
+
+    // The size was checked above
+    let mut bytes = bytes;
+    let mut sig = ImageLmsSignature {
+        q: take_u32(&mut bytes),
+        ..Default::default()
+    };
+    sig.ots.otstype = take_u32(&mut bytes);
+    sig.ots
+        .nonce
+        .copy_from_slice(take(&mut bytes, LMS_DIGEST_BYTE_SIZE));
+    for y in sig.ots.y.iter_mut() {
+        y.copy_from_slice(take(&mut bytes, LMS_DIGEST_BYTE_SIZE));
+    }
+    sig.tree_type = take_u32(&mut bytes);
+    for node in sig.tree_path.iter_mut() {
+        node.copy_from_slice(take(&mut bytes, LMS_DIGEST_BYTE_SIZE));
+    }
+
+    if sig.ots.otstype != IMAGE_LMS_OTS_TYPE || sig.tree_type != IMAGE_LMS_TREE_TYPE {
+        bail!(
+            "Unsupported LMS signature type {}/{}",
+            sig.tree_type,
+            sig.ots.otstype
+        );
+    }
+
+    Ok(sig)
+}
+
+/// Take the next `len` bytes
+fn take<'a>(bytes: &mut &'a [u8], len: usize) -> &'a [u8] {
+    let (data, rest) = bytes.split_at(len);
+    *bytes = rest;
+    data
+}
+
+/// Take the next big-endian 32-bit integer
+fn take_u32(bytes: &mut &[u8]) -> u32 {
+    let mut value = [0u8; 4];
+    value.copy_from_slice(take(bytes, 4));
+    u32::from_be_bytes(value)
+}
+
+/// Read a DER element with a short or one byte long form length
+///
+/// # Returns
+///
+/// * Contents of the element and the data following it
+fn der_read(bytes: &[u8], tag: u8) -> anyhow::Result<(&[u8], &[u8])> {
+    let (len, rest) = match bytes {
+        [t, 0x81, len, rest @ ..] if *t == tag && *len >= 0x80 => (*len as usize, rest),
+        [t, len, rest @ ..] if *t == tag && *len < 0x80 => (*len as usize, rest),
+        _ => bail!("Expected DER tag 0x{tag:02x}"),
+    };
+    if rest.len() < len {
+        bail!("Truncated DER element");
+    }
+    Ok(rest.split_at(len))
+}
+
+/// Convert a big-endian unsigned integer to hardware format
+fn scalar_from_be_bytes(bytes: &[u8]) -> anyhow::Result<ImageScalar> {
+    // DER integers may have a leading zero byte to stay positive
+    let start = bytes.iter().position(|&b| b != 0).unwrap_or(bytes.len());
+    let bytes = &bytes[start..];
+    if bytes.len() > ECC384_SCALAR_BYTE_SIZE {
+        bail!("Integer larger than {ECC384_SCALAR_BYTE_SIZE} bytes");
+    }
+
+    let mut padded = [0u8; ECC384_SCALAR_BYTE_SIZE];
+    padded[ECC384_SCALAR_BYTE_SIZE - bytes.len()..].copy_from_slice(bytes);
+
+    let mut scalar = ImageScalar::default();
+    for (word, bytes) in scalar.iter_mut().zip(padded.chunks_exact(4)) {
+        *word = u32::from_be_bytes(bytes.try_into()?);
+    }
+    Ok(scalar)
+}
@@ -12,16 +12,21 @@ Abstract:
 
 --*/
 
+mod rfc6979;
+
 use std::path::PathBuf;
 
 use anyhow::Context;
+use caliptra_image_gen::lms::{self, LmsHasher};
 use caliptra_image_gen::ImageGeneratorCrypto;
 use caliptra_image_types::*;
 use openssl::bn::{BigNum, BigNumContext};
-use openssl::ec::{EcGroup, EcKey, EcPoint};
+use openssl::ec::{EcGroup, EcKey};
 use openssl::ecdsa::EcdsaSig;
 use openssl::nid::Nid;
-use openssl::sha::Sha384;
+use openssl::rand::rand_bytes;
+use openssl::sha::{Sha256, Sha384};
+use zerocopy::FromBytes;
 
 #[derive(Default)]
 pub struct OsslCrypto {}
@@ -35,38 +40,89 @@ impl ImageGeneratorCrypto for OsslCrypto {
     }
 
     /// Calculate ECDSA-384 Signature
+    ///
+    /// The signature nonce is derived as specified in RFC 6979, the
+    /// signature of a digest is the same on every host and matches the
+    /// signature of the RustCrypto backend.
     fn ecdsa384_sign(
         &self,
         digest: &ImageDigest,
         priv_key: &ImageEccPrivKey,
-        pub_key: &ImageEccPubKey,
+        _pub_key: &ImageEccPubKey,
     ) -> anyhow::Result<ImageEccSignature> {
         let priv_key: [u8; ECC384_SCALAR_BYTE_SIZE] = from_hw_format(priv_key);
+        let digest: [u8; SHA384_DIGEST_BYTE_SIZE] = from_hw_format(digest);
+
+        let group = EcGroup::from_curve_name(Nid::SECP384R1)?;
+        let mut ctx = BigNumContext::new()?;
+
+        let (r, s) = rfc6979::sign(&group, &priv_key, &digest, &mut ctx)?;
+        let r = r.to_vec_padded(ECC384_SCALAR_BYTE_SIZE as i32)?;
+        let s = s.to_vec_padded(ECC384_SCALAR_BYTE_SIZE as i32)?;
+
+        let image_sig = ImageEccSignature {
+            r: to_hw_format(&r),
+            s: to_hw_format(&s),
+        };
+        Ok(image_sig)
+    }
+
+    /// Calculate LMS Signature
+    fn lms_sign(
+        &self,
+        digest: &ImageDigest,
+        priv_key: &ImageLmsPrivKey,
+    ) -> anyhow::Result<ImageLmsSignature> {
+        let digest: [u8; SHA384_DIGEST_BYTE_SIZE] = from_hw_format(digest);
+        lms::sign(self, &digest, priv_key)
+    }
+
+    /// Verify ECDSA-384 Signature
+    fn ecdsa384_verify(
+        &self,
+        digest: &ImageDigest,
+        pub_key: &ImageEccPubKey,
+        sig: &ImageEccSignature,
+    ) -> anyhow::Result<bool> {
         let pub_key_x: [u8; ECC384_SCALAR_BYTE_SIZE] = from_hw_format(&pub_key.x);
         let pub_key_y: [u8; ECC384_SCALAR_BYTE_SIZE] = from_hw_format(&pub_key.y);
+        let r: [u8; ECC384_SCALAR_BYTE_SIZE] = from_hw_format(&sig.r);
+        let s: [u8; ECC384_SCALAR_BYTE_SIZE] = from_hw_format(&sig.s);
         let digest: [u8; SHA384_DIGEST_BYTE_SIZE] = from_hw_format(digest);
 
         let group = EcGroup::from_curve_name(Nid::SECP384R1)?;
-        let mut ctx = BigNumContext::new()?;
 
-        let priv_key = BigNum::from_slice(&priv_key)?;
         let pub_key_x = BigNum::from_slice(&pub_key_x)?;
         let pub_key_y = BigNum::from_slice(&pub_key_y)?;
+        let pub_key = EcKey::from_public_key_affine_coordinates(&group, &pub_key_x, &pub_key_y)?;
 
-        let mut pub_key = EcPoint::new(&group)?;
-        pub_key.set_affine_coordinates_gfp(&group, &pub_key_x, &pub_key_y, &mut ctx)?;
+        let r = BigNum::from_slice(&r)?;
+        let s = BigNum::from_slice(&s)?;
+        let sig = EcdsaSig::from_private_components(r, s)?;
 
-        let ec_key = EcKey::from_private_components(&group, &priv_key, &pub_key)?;
-        let sig = EcdsaSig::sign(&digest, &ec_key)?;
+        Ok(sig.verify(&digest, &pub_key)?)
+    }
 
-        let r = sig.r().to_vec_padded(ECC384_SCALAR_BYTE_SIZE as i32)?;
-        let s = sig.s().to_vec_padded(ECC384_SCALAR_BYTE_SIZE as i32)?;
+    /// Verify LMS Signature
+    fn lms_verify(
+        &self,
+        digest: &ImageDigest,
+        pub_key: &ImageLmsPubKey,
+        sig: &ImageLmsSignature,
+    ) -> anyhow::Result<bool> {
+        let digest: [u8; SHA384_DIGEST_BYTE_SIZE] = from_hw_format(digest);
+        Ok(lms::verify(self, &digest, pub_key, sig))
+    }
+}
 
-        let image_sig = ImageEccSignature {
-            r: to_hw_format(&r),
-            s: to_hw_format(&s),
-        };
-        Ok(image_sig)
+impl LmsHasher for OsslCrypto {
+    /// Calculate SHA-256 Digest
+    fn sha256(&self, data: &[&[u8]]) -> [u8; 32] {
+        let mut engine = Sha256::new();
+        for d in data {
+            engine.update(d);
+        }
+        engine.finish()
     }
 }
 
@@ -107,6 +163,50 @@ pub fn ecc_priv_key_from_pem(path: &PathBuf) -> anyhow::Result<ImageEccPrivKey>
     Ok(to_hw_format(&priv_key))
 }
 
+/// Read LMS Public Key from file
+pub fn lms_pub_key_from_file(path: &PathBuf) -> anyhow::Result<ImageLmsPubKey> {
+    let key_bytes = std::fs::read(path)
+        .with_context(|| format!("Failed to read LMS public key file {}", path.display()))?;
+    ImageLmsPubKey::read_from(key_bytes.as_slice())
+        .with_context(|| format!("Invalid LMS public key file {}", path.display()))
+}
+
+/// Read LMS Private Key from file
+pub fn lms_priv_key_from_file(path: &PathBuf) -> anyhow::Result<ImageLmsPrivKey> {
+    let key_bytes = std::fs::read(path)
+        .with_context(|| format!("Failed to read LMS private key file {}", path.display()))?;
+    ImageLmsPrivKey::read_from(key_bytes.as_slice())
+        .with_context(|| format!("Invalid LMS private key file {}", path.display()))
+}
+
+/// Calculate the LMS Public Key of a Private Key
+pub fn lms_pub_key_from_priv_key(priv_key: &ImageLmsPrivKey) -> anyhow::Result<ImageLmsPubKey> {
+    lms::pub_key(&OsslCrypto::default(), priv_key)
+}
+
+/// Generate an ECC-384 Key Pair
+///
+/// # Returns
+///
+/// * `(Vec<u8>, Vec<u8>)` - Private key (SEC1) and public key PEM files
+pub fn ecc_key_pair_gen_pem() -> anyhow::Result<(Vec<u8>, Vec<u8>)> {
+    let group = EcGroup::from_curve_name(Nid::SECP384R1)?;
+    let key = EcKey::generate(&group)?;
+    Ok((key.private_key_to_pem()?, key.public_key_to_pem()?))
+}
+
+/// Generate an LMS Private Key with a random identifier and seed
+pub fn lms_priv_key_gen() -> anyhow::Result<ImageLmsPrivKey> {
+    let mut priv_key = ImageLmsPrivKey {
+        tree_type: IMAGE_LMS_TREE_TYPE,
+        otstype: IMAGE_LMS_OTS_TYPE,
+        ..Default::default()
+    };
+    rand_bytes(&mut priv_key.id)?;
+    rand_bytes(&mut priv_key.seed)?;
+    Ok(priv_key)
+}
+
 /// Convert the slice to hardware format
 fn to_hw_format(value: &[u8]) -> [u32; ECC384_SCALAR_WORD_SIZE] {
     let arr = TryInto::<[u8; ECC384_SCALAR_BYTE_SIZE]>::try_into(value).unwrap();
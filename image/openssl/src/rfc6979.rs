@@ -0,0 +1,122 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+   rfc6979.rs
+
+Abstract:
+
+    File contains deterministic ECDSA-384 signing (RFC 6979) on top of the
+    OpenSSL big number and elliptic curve primitives.
+
+--*/
+
+use anyhow::bail;
+use caliptra_image_types::{ECC384_SCALAR_BYTE_SIZE, SHA384_DIGEST_BYTE_SIZE};
+use openssl::bn::{BigNum, BigNumContext};
+use openssl::ec::{EcGroup, EcPoint};
+use openssl::hash::MessageDigest;
+use openssl::pkey::PKey;
+use openssl::sign::Signer;
+
+/// HMAC_DRBG instantiated with HMAC-SHA-384 (RFC 6979, Section 3.2)
+struct HmacDrbg {
+    k: [u8; SHA384_DIGEST_BYTE_SIZE],
+    v: [u8; SHA384_DIGEST_BYTE_SIZE],
+}
+
+impl HmacDrbg {
+    /// Seed the generator with the private key and the message digest
+    fn new(priv_key: &[u8], digest: &[u8]) -> anyhow::Result<Self> {
+        let mut drbg = Self {
+            k: [0x00; SHA384_DIGEST_BYTE_SIZE],
+            v: [0x01; SHA384_DIGEST_BYTE_SIZE],
+        };
+        for sep in [0x00, 0x01] {
+            drbg.k = drbg.hmac(&[&drbg.v, &[sep], priv_key, digest])?;
+            drbg.v = drbg.hmac(&[&drbg.v])?;
+        }
+        Ok(drbg)
+    }
+
+    /// Generate the next candidate nonce
+    fn next(&mut self) -> anyhow::Result<[u8; ECC384_SCALAR_BYTE_SIZE]> {
+        self.v = self.hmac(&[&self.v])?;
+        let t = self.v;
+        self.k = self.hmac(&[&self.v, &[0x00]])?;
+        self.v = self.hmac(&[&self.v])?;
+        Ok(t)
+    }
+
+    /// Calculate HMAC-SHA-384 of the concatenated inputs with the current key
+    fn hmac(&self, data: &[&[u8]]) -> anyhow::Result<[u8; SHA384_DIGEST_BYTE_SIZE]> {
+        let key = PKey::hmac(&self.k)?;
+        let mut signer = Signer::new(MessageDigest::sha384(), &key)?;
+        for d in data {
+            signer.update(d)?;
+        }
+        let mut mac = [0u8; SHA384_DIGEST_BYTE_SIZE];
+        signer.sign(&mut mac)?;
+        Ok(mac)
+    }
+}
+
+/// Sign the digest with a nonce derived from the private key and the digest
+///
+/// # Arguments
+///
+/// * `group`    - SECP384R1 curve
+/// * `priv_key` - Big endian private key
+/// * `digest`   - Big endian SHA-384 digest
+/// * `ctx`      - Big number context
+///
+/// # Returns
+///
+/// * `(BigNum, BigNum)` - Signature `r` and `s` components
+pub(crate) fn sign(
+    group: &EcGroup,
+    priv_key: &[u8; ECC384_SCALAR_BYTE_SIZE],
+    digest: &[u8; SHA384_DIGEST_BYTE_SIZE],
+    ctx: &mut BigNumContext,
+) -> anyhow::Result<(BigNum, BigNum)> {
+    let mut n = BigNum::new()?;
+    group.order(&mut n, ctx)?;
+
+    let d = BigNum::from_slice(priv_key)?;
+    let z = BigNum::from_slice(digest)?;
+
+    let mut drbg = HmacDrbg::new(priv_key, digest)?;
+    let k = loop {
+        let k = BigNum::from_slice(&drbg.next()?)?;
+        if k.num_bits() > 0 && k < n {
+            break k;
+        }
+    };
+
+    // r = x(k * G) mod n
+    let mut point = EcPoint::new(group)?;
+    point.mul_generator(group, &k, ctx)?;
+    let mut x = BigNum::new()?;
+    let mut y = BigNum::new()?;
+    point.affine_coordinates_gfp(group, &mut x, &mut y, ctx)?;
+    let mut r = BigNum::new()?;
+    r.nnmod(&x, &n, ctx)?;
+
+    // s = k^-1 * (z + r * d) mod n
+    let mut rd = BigNum::new()?;
+    rd.mod_mul(&r, &d, &n, ctx)?;
+    let mut sum = BigNum::new()?;
+    sum.mod_add(&z, &rd, &n, ctx)?;
+    let mut k_inv = BigNum::new()?;
+    k_inv.mod_inverse(&k, &n, ctx)?;
+    let mut s = BigNum::new()?;
+    s.mod_mul(&k_inv, &sum, &n, ctx)?;
+
+    if r.num_bits() == 0 || s.num_bits() == 0 {
+        bail!("Invalid ECDSA signature");
+    }
+
+    Ok((r, s))
+}
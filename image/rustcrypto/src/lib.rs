@@ -0,0 +1,375 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+   lib.rs
+
+Abstract:
+
+    File contains crypto utilities needed to generate images, implemented
+    with the RustCrypto crates. The output is identical to the OpenSSL
+    implementation.
+
+--*/
+
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Context};
+use caliptra_image_gen::lms::{self, LmsHasher};
+use caliptra_image_gen::ImageGeneratorCrypto;
+use caliptra_image_types::*;
+use p384::ecdsa::signature::hazmat::{PrehashSigner, PrehashVerifier};
+use p384::ecdsa::{Signature, SigningKey, VerifyingKey};
+use p384::elliptic_curve::sec1::ToEncodedPoint;
+use p384::pkcs8::{DecodePrivateKey, DecodePublicKey, EncodePublicKey, LineEnding};
+use p384::{EncodedPoint, PublicKey, SecretKey};
+use rand_core::{OsRng, RngCore};
+use sha2::{Digest, Sha256, Sha384};
+use zerocopy::FromBytes;
+
+#[derive(Default)]
+pub struct RustCrypto {}
+
+impl ImageGeneratorCrypto for RustCrypto {
+    /// Calculate SHA-384 Digest
+    fn sha384_digest(&self, data: &[u8]) -> anyhow::Result<ImageDigest> {
+        Ok(to_hw_format(&Sha384::digest(data)))
+    }
+
+    /// Calculate ECDSA-384 Signature
+    ///
+    /// The signature nonce is derived as specified in RFC 6979.
+    fn ecdsa384_sign(
+        &self,
+        digest: &ImageDigest,
+        priv_key: &ImageEccPrivKey,
+        _pub_key: &ImageEccPubKey,
+    ) -> anyhow::Result<ImageEccSignature> {
+        let priv_key: [u8; ECC384_SCALAR_BYTE_SIZE] = from_hw_format(priv_key);
+        let digest: [u8; SHA384_DIGEST_BYTE_SIZE] = from_hw_format(digest);
+
+        let signing_key =
+            SigningKey::from_bytes(&priv_key).map_err(|_| anyhow!("Invalid ECC private key"))?;
+        let sig: Signature = signing_key
+            .sign_prehash(&digest)
+            .map_err(|_| anyhow!("Failed to calculate ECDSA signature"))?;
+
+        let (r, s) = sig.split_bytes();
+        let image_sig = ImageEccSignature {
+            r: to_hw_format(&r),
+            s: to_hw_format(&s),
+        };
+        Ok(image_sig)
+    }
+
+    /// Calculate LMS Signature
+    fn lms_sign(
+        &self,
+        digest: &ImageDigest,
+        priv_key: &ImageLmsPrivKey,
+    ) -> anyhow::Result<ImageLmsSignature> {
+        let digest: [u8; SHA384_DIGEST_BYTE_SIZE] = from_hw_format(digest);
+        lms::sign(self, &digest, priv_key)
+    }
+
+    /// Verify ECDSA-384 Signature
+    fn ecdsa384_verify(
+        &self,
+        digest: &ImageDigest,
+        pub_key: &ImageEccPubKey,
+        sig: &ImageEccSignature,
+    ) -> anyhow::Result<bool> {
+        let pub_key_x: [u8; ECC384_SCALAR_BYTE_SIZE] = from_hw_format(&pub_key.x);
+        let pub_key_y: [u8; ECC384_SCALAR_BYTE_SIZE] = from_hw_format(&pub_key.y);
+        let r: [u8; ECC384_SCALAR_BYTE_SIZE] = from_hw_format(&sig.r);
+        let s: [u8; ECC384_SCALAR_BYTE_SIZE] = from_hw_format(&sig.s);
+        let digest: [u8; SHA384_DIGEST_BYTE_SIZE] = from_hw_format(digest);
+
+        let point =
+            EncodedPoint::from_affine_coordinates(&pub_key_x.into(), &pub_key_y.into(), false);
+        let pub_key = VerifyingKey::from_encoded_point(&point)
+            .map_err(|_| anyhow!("Invalid ECC public key"))?;
+
+        // Out of range components can never make a valid signature
+        let Ok(sig) = Signature::from_scalars(r, s) else {
+            return Ok(false);
+        };
+
+        Ok(pub_key.verify_prehash(&digest, &sig).is_ok())
+    }
+
+    /// Verify LMS Signature
+    fn lms_verify(
+        &self,
+        digest: &ImageDigest,
+        pub_key: &ImageLmsPubKey,
+        sig: &ImageLmsSignature,
+    ) -> anyhow::Result<bool> {
+        let digest: [u8; SHA384_DIGEST_BYTE_SIZE] = from_hw_format(digest);
+        Ok(lms::verify(self, &digest, pub_key, sig))
+    }
+}
+
+impl LmsHasher for RustCrypto {
+    /// Calculate SHA-256 Digest
+    fn sha256(&self, data: &[&[u8]]) -> [u8; 32] {
+        let mut engine = Sha256::new();
+        for d in data {
+            engine.update(d);
+        }
+        engine.finalize().into()
+    }
+}
+
+/// Read ECC-384 Public Key from PEM file
+pub fn ecc_pub_key_from_pem(path: &PathBuf) -> anyhow::Result<ImageEccPubKey> {
+    let key_str = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read public key PEM file {}", path.display()))?;
+    let key = PublicKey::from_public_key_pem(&key_str)
+        .map_err(|err| anyhow!("Invalid public key PEM file {}: {err}", path.display()))?;
+
+    let point = key.to_encoded_point(false);
+    let (x, y) = point
+        .x()
+        .zip(point.y())
+        .with_context(|| format!("Invalid public key PEM file {}", path.display()))?;
+
+    let image_key = ImageEccPubKey {
+        x: to_hw_format(x),
+        y: to_hw_format(y),
+    };
+    Ok(image_key)
+}
+
+/// Read ECC-384 Private Key from PEM file
+///
+/// Both SEC1 (`EC PRIVATE KEY`) and PKCS#8 (`PRIVATE KEY`) encodings are
+/// accepted.
+pub fn ecc_priv_key_from_pem(path: &PathBuf) -> anyhow::Result<ImageEccPrivKey> {
+    let key_str = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read private key PEM file {}", path.display()))?;
+
+    let key = SecretKey::from_sec1_pem(&key_str)
+        .or_else(|_| SecretKey::from_pkcs8_pem(&key_str))
+        .map_err(|_| anyhow!("Invalid private key PEM file {}", path.display()))?;
+
+    Ok(to_hw_format(&key.to_be_bytes()))
+}
+
+/// Read LMS Public Key from file
+pub fn lms_pub_key_from_file(path: &PathBuf) -> anyhow::Result<ImageLmsPubKey> {
+    let key_bytes = std::fs::read(path)
+        .with_context(|| format!("Failed to read LMS public key file {}", path.display()))?;
+    ImageLmsPubKey::read_from(key_bytes.as_slice())
+        .with_context(|| format!("Invalid LMS public key file {}", path.display()))
+}
+
+/// Read LMS Private Key from file
+pub fn lms_priv_key_from_file(path: &PathBuf) -> anyhow::Result<ImageLmsPrivKey> {
+    let key_bytes = std::fs::read(path)
+        .with_context(|| format!("Failed to read LMS private key file {}", path.display()))?;
+    ImageLmsPrivKey::read_from(key_bytes.as_slice())
+        .with_context(|| format!("Invalid LMS private key file {}", path.display()))
+}
+
+/// Calculate the LMS Public Key of a Private Key
+pub fn lms_pub_key_from_priv_key(priv_key: &ImageLmsPrivKey) -> anyhow::Result<ImageLmsPubKey> {
+    lms::pub_key(&RustCrypto::default(), priv_key)
+}
+
+/// Generate an ECC-384 Key Pair
+///
+/// # Returns
+///
+/// * `(Vec<u8>, Vec<u8>)` - Private key (SEC1) and public key PEM files
+pub fn ecc_key_pair_gen_pem() -> anyhow::Result<(Vec<u8>, Vec<u8>)> {
+    let key = SecretKey::random(&mut OsRng);
+    let priv_pem = key
+        .to_pem(LineEnding::LF)
+        .map_err(|_| anyhow!("Failed to encode private key"))?;
+    let pub_pem = key
+        .public_key()
+        .to_public_key_pem(LineEnding::LF)
+        .map_err(|_| anyhow!("Failed to encode public key"))?;
+    Ok((priv_pem.as_bytes().to_vec(), pub_pem.into_bytes()))
+}
+
+/// Generate an LMS Private Key with a random identifier and seed
+pub fn lms_priv_key_gen() -> anyhow::Result<ImageLmsPrivKey> {
+    let mut priv_key = ImageLmsPrivKey {
+        tree_type: IMAGE_LMS_TREE_TYPE,
+        otstype: IMAGE_LMS_OTS_TYPE,
+        ..Default::default()
+    };
+    OsRng.try_fill_bytes(&mut priv_key.id)?;
+    OsRng.try_fill_bytes(&mut priv_key.seed)?;
+    Ok(priv_key)
+}
+
+/// Convert the slice to hardware format
+fn to_hw_format(value: &[u8]) -> [u32; ECC384_SCALAR_WORD_SIZE] {
+    let arr = TryInto::<[u8; ECC384_SCALAR_BYTE_SIZE]>::try_into(value).unwrap();
+    let mut result = [0u32; ECC384_SCALAR_WORD_SIZE];
+    for i in 0..result.len() {
+        result[i] = u32::from_be_bytes(arr[i * 4..][..4].try_into().unwrap())
+    }
+    result
+}
+
+/// Convert the hardware format to byte array
+fn from_hw_format(value: &[u32; ECC384_SCALAR_WORD_SIZE]) -> [u8; ECC384_SCALAR_BYTE_SIZE] {
+    let mut result = [0u8; ECC384_SCALAR_BYTE_SIZE];
+    for i in 0..value.len() {
+        *<&mut [u8; 4]>::try_from(&mut result[i * 4..][..4]).unwrap() = value[i].to_be_bytes();
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use caliptra_image_fake_keys::{OWNER_CONFIG, VENDOR_CONFIG_KEY_1};
+    use caliptra_image_gen::{
+        ImageGenerator, ImageGeneratorAuxImage, ImageGeneratorConfig, ImageGenratorExecutable,
+    };
+    use caliptra_image_openssl::OsslCrypto;
+    use zerocopy::AsBytes;
+
+    /// RFC 6979 A.2.6, ECDSA P-384 with SHA-384
+    const PRIV_KEY: &str = "6b9d3dad2e1b8c1c05b19875b6659f4de23c3b667bf297ba9aa47740787137d896d5724e4c70a825f872c9ea60d2edf5";
+    const PUB_KEY_X: &str = "ec3a4e415b4e19a4568618029f427fa5da9a8bc4ae92e02e06aae5286b300c64def8f0ea9055866064a254515480bc13";
+    const PUB_KEY_Y: &str = "8015d9b72d7d57244ea8ef9ac0c621896708a59367f9dfb9f54ca84b3f1c9db1288b231c3ae0d4fe7344fd2533264720";
+    const SIGS: [(&str, &str, &str); 2] = [
+        (
+            "sample",
+            "94edbb92a5ecb8aad4736e56c691916b3f88140666ce9fa73d64c4ea95ad133c81a648152e44acf96e36dd1e80fabe46",
+            "99ef4aeb15f178cea1fe40db2603138f130e740a19624526203b6351d0a3a94fa329c145786e679e7b82c71a38628ac8",
+        ),
+        (
+            "test",
+            "8203b63d3c853e8d77227fb377bcf7b7b772e97892a80f36ab775d509d7a5feb0542a7f0812998da8f1dd3ca3cf023db",
+            "ddd0760448d42d8a43af45af836fce4de8be06b485e9b61b827c2f13173923e06a739f040649a667bf3b828246baa5a5",
+        ),
+    ];
+
+    fn scalar(hex_str: &str) -> ImageScalar {
+        to_hw_format(&hex::decode(hex_str).unwrap())
+    }
+
+    /// Check the RFC 6979 test vectors
+    fn check_rfc6979(crypto: &impl ImageGeneratorCrypto) {
+        let priv_key = scalar(PRIV_KEY);
+        let pub_key = ImageEccPubKey {
+            x: scalar(PUB_KEY_X),
+            y: scalar(PUB_KEY_Y),
+        };
+
+        for (msg, r, s) in SIGS {
+            let digest = crypto.sha384_digest(msg.as_bytes()).unwrap();
+            let sig = crypto.ecdsa384_sign(&digest, &priv_key, &pub_key).unwrap();
+            assert_eq!(sig.r, scalar(r), "r of {msg:?}");
+            assert_eq!(sig.s, scalar(s), "s of {msg:?}");
+            assert!(crypto.ecdsa384_verify(&digest, &pub_key, &sig).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_rfc6979_rustcrypto() {
+        check_rfc6979(&RustCrypto::default());
+    }
+
+    #[test]
+    fn test_rfc6979_openssl() {
+        check_rfc6979(&OsslCrypto::default());
+    }
+
+    /// Executable with raw content
+    struct TestExecutable {
+        rev: ImageRevision,
+        load_addr: u32,
+        content: Vec<u8>,
+    }
+
+    impl ImageGenratorExecutable for TestExecutable {
+        fn svn(&self) -> u32 {
+            1
+        }
+
+        fn min_svn(&self) -> u32 {
+            0
+        }
+
+        fn rev(&self) -> &ImageRevision {
+            &self.rev
+        }
+
+        fn load_addr(&self) -> u32 {
+            self.load_addr
+        }
+
+        fn entry_point(&self) -> u32 {
+            self.load_addr
+        }
+
+        fn content(&self) -> &Vec<u8> {
+            &self.content
+        }
+
+        fn size(&self) -> u32 {
+            self.content.len() as u32
+        }
+    }
+
+    fn generate_image(crypto: impl ImageGeneratorCrypto) -> Vec<u8> {
+        let config = ImageGeneratorConfig {
+            vendor_config: VENDOR_CONFIG_KEY_1,
+            owner_config: Some(OWNER_CONFIG),
+            fmc: TestExecutable {
+                rev: [0x11; IMAGE_REVISION_BYTE_SIZE],
+                load_addr: 0x4000_0000,
+                content: (0..4096).map(|i| i as u8).collect(),
+            },
+            runtime: TestExecutable {
+                rev: [0x22; IMAGE_REVISION_BYTE_SIZE],
+                load_addr: 0x4000_2000,
+                content: (0..8192).map(|i| (i * 7) as u8).collect(),
+            },
+            aux: vec![ImageGeneratorAuxImage {
+                id: 3,
+                content: vec![0xa5; 512],
+                ..Default::default()
+            }],
+        };
+        ImageGenerator::new(crypto)
+            .generate(&config)
+            .unwrap()
+            .to_bytes()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_image_matches_openssl() {
+        assert_eq!(
+            generate_image(RustCrypto::default()),
+            generate_image(OsslCrypto::default())
+        );
+    }
+
+    #[test]
+    fn test_lms_pub_key_matches_openssl() {
+        let priv_key = OWNER_CONFIG.priv_keys.unwrap().lms_priv_key;
+        let pub_key = lms_pub_key_from_priv_key(&priv_key).unwrap();
+        assert_eq!(
+            pub_key.as_bytes(),
+            caliptra_image_openssl::lms_pub_key_from_priv_key(&priv_key)
+                .unwrap()
+                .as_bytes()
+        );
+        assert_eq!(
+            pub_key.as_bytes(),
+            OWNER_CONFIG.pub_keys.lms_pub_key.as_bytes()
+        );
+    }
+}
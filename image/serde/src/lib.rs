@@ -12,7 +12,7 @@ Abstract:
 
 --*/
 use caliptra_image_types::*;
-use std::io::Write;
+use std::io::{Read, Write};
 use zerocopy::AsBytes;
 
 /// Image Bundle Writer
@@ -31,6 +31,28 @@ impl<W: Write> ImageBundleWriter<W> {
         self.writer.write_all(image.manifest.as_bytes())?;
         self.writer.write_all(&image.fmc)?;
         self.writer.write_all(&image.runtime)?;
+        for aux in &image.aux {
+            self.writer.write_all(aux)?;
+        }
         Ok(())
     }
 }
+
+/// Image Bundle Reader
+pub struct ImageBundleReader<R: Read> {
+    reader: R,
+}
+
+impl<R: Read> ImageBundleReader<R> {
+    /// Create an instance of `ImageBundleReader`
+    pub fn new(reader: R) -> Self {
+        Self { reader }
+    }
+
+    /// Read Image Bundle
+    pub fn read(&mut self) -> anyhow::Result<ImageBundle> {
+        let mut bytes = vec![];
+        self.reader.read_to_end(&mut bytes)?;
+        Ok(ImageBundle::from_bytes(&bytes)?)
+    }
+}
@@ -21,19 +21,32 @@ use zerocopy::{AsBytes, FromBytes};
 
 pub const MANIFEST_MARKER: u32 = 0x4E414D43;
 pub const VENDOR_ECC_KEY_COUNT: u32 = 4;
-pub const MAX_TOC_ENTRY_COUNT: u32 = 2;
+pub const VENDOR_LMS_KEY_COUNT: u32 = 4;
+pub const MIN_TOC_ENTRY_COUNT: u32 = 2;
+pub const MAX_AUX_TOC_ENTRY_COUNT: u32 = 8;
+pub const MAX_TOC_ENTRY_COUNT: u32 = MIN_TOC_ENTRY_COUNT + MAX_AUX_TOC_ENTRY_COUNT;
 pub const IMAGE_REVISION_BYTE_SIZE: usize = 20;
 pub const ECC384_SCALAR_WORD_SIZE: usize = 12;
 pub const ECC384_SCALAR_BYTE_SIZE: usize = 48;
 pub const SHA384_DIGEST_WORD_SIZE: usize = 12;
 pub const SHA384_DIGEST_BYTE_SIZE: usize = 48;
-pub const IMAGE_BYTE_SIZE: usize = 128 * 1024;
+pub const LMS_DIGEST_BYTE_SIZE: usize = 24;
+pub const LMS_TREE_HEIGHT: usize = 5;
+pub const LMOTS_P: usize = 26;
+/// LMS_SHA256_N24_H5
+pub const IMAGE_LMS_TREE_TYPE: u32 = 10;
+/// LMOTS_SHA256_N24_W8
+pub const IMAGE_LMS_OTS_TYPE: u32 = 8;
+pub const IMAGE_BYTE_SIZE: usize = 256 * 1024;
+/// Maximum size of a single firmware load mailbox transaction
+pub const IMAGE_CHUNK_BYTE_SIZE: usize = 128 * 1024;
 pub const IMAGE_MANIFEST_BYTE_SIZE: usize = core::mem::size_of::<ImageManifest>();
 
 pub type ImageScalar = [u32; ECC384_SCALAR_WORD_SIZE];
 pub type ImageDigest = [u32; SHA384_DIGEST_WORD_SIZE];
 pub type ImageRevision = [u8; IMAGE_REVISION_BYTE_SIZE];
 pub type ImageEccPrivKey = ImageScalar;
+pub type ImageLmsDigest = [u8; LMS_DIGEST_BYTE_SIZE];
 
 #[repr(C)]
 #[derive(AsBytes, FromBytes, Default, Debug, Copy, Clone, Eq, PartialEq)]
@@ -55,6 +68,70 @@ pub struct ImageEccSignature {
     pub s: ImageScalar,
 }
 
+#[repr(C)]
+#[derive(AsBytes, FromBytes, Default, Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ImageLmsPubKey {
+    /// LMS Algorithm Type
+    pub tree_type: u32,
+
+    /// LM-OTS Algorithm Type
+    pub otstype: u32,
+
+    /// LMS Key Identifier
+    pub id: [u8; 16],
+
+    /// Root of the Merkle Tree
+    pub digest: ImageLmsDigest,
+}
+
+#[repr(C)]
+#[derive(AsBytes, FromBytes, Default, Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ImageLmsPrivKey {
+    /// LMS Algorithm Type
+    pub tree_type: u32,
+
+    /// LM-OTS Algorithm Type
+    pub otstype: u32,
+
+    /// LMS Key Identifier
+    pub id: [u8; 16],
+
+    /// Seed the one-time private keys are derived from
+    pub seed: ImageLmsDigest,
+
+    /// Leaf of the next unused one-time key
+    pub q: u32,
+}
+
+#[repr(C)]
+#[derive(AsBytes, FromBytes, Default, Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ImageLmOtsSignature {
+    /// LM-OTS Algorithm Type
+    pub otstype: u32,
+
+    /// Randomizer
+    pub nonce: ImageLmsDigest,
+
+    /// One-time signature
+    pub y: [ImageLmsDigest; LMOTS_P],
+}
+
+#[repr(C)]
+#[derive(AsBytes, FromBytes, Default, Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ImageLmsSignature {
+    /// Leaf of the one-time key
+    pub q: u32,
+
+    /// One-time signature
+    pub ots: ImageLmOtsSignature,
+
+    /// LMS Algorithm Type
+    pub tree_type: u32,
+
+    /// Authentication path from the leaf to the root
+    pub tree_path: [ImageLmsDigest; LMS_TREE_HEIGHT],
+}
+
 /// Caliptra Image Bundle
 #[cfg(feature = "std")]
 #[derive(Debug, Default)]
@@ -67,6 +144,9 @@ pub struct ImageBundle {
 
     /// Runtime
     pub runtime: Vec<u8>,
+
+    /// Auxiliary images, in TOC order
+    pub aux: Vec<Vec<u8>>,
 }
 
 #[cfg(feature = "std")]
@@ -101,8 +181,81 @@ impl ImageBundle {
             ));
         }
         result.extend_from_slice(&self.runtime);
+        let aux_toc = self.manifest.aux_toc_entries();
+        if aux_toc.len() != self.aux.len() {
+            return Err(std::io::Error::new(
+                ErrorKind::Other,
+                "actual aux image count does not match manifest",
+            ));
+        }
+        for (toc, aux) in aux_toc.iter().zip(self.aux.iter()) {
+            if toc.offset as usize != result.len() {
+                return Err(std::io::Error::new(
+                    ErrorKind::Other,
+                    "actual aux offset does not match manifest",
+                ));
+            }
+            if toc.size as usize != aux.len() {
+                return Err(std::io::Error::new(
+                    ErrorKind::Other,
+                    "actual aux size does not match manifest",
+                ));
+            }
+            result.extend_from_slice(aux);
+        }
         Ok(result)
     }
+
+    /// Parse an image bundle serialized by `to_bytes`
+    pub fn from_bytes(bytes: &[u8]) -> std::io::Result<Self> {
+        use std::io::ErrorKind;
+        let manifest = ImageManifest::read_from_prefix(bytes).ok_or_else(|| {
+            std::io::Error::new(ErrorKind::Other, "image is smaller than the manifest")
+        })?;
+
+        let mut offset = IMAGE_MANIFEST_BYTE_SIZE;
+        let mut read_section = |toc: &ImageTocEntry, name: &str| {
+            if toc.offset as usize != offset {
+                return Err(std::io::Error::new(
+                    ErrorKind::Other,
+                    format!("{name} offset does not match manifest"),
+                ));
+            }
+            let section = offset
+                .checked_add(toc.size as usize)
+                .and_then(|end| bytes.get(offset..end))
+                .ok_or_else(|| {
+                    std::io::Error::new(
+                        ErrorKind::Other,
+                        format!("{name} size does not match manifest"),
+                    )
+                })?;
+            offset += section.len();
+            Ok(section.to_vec())
+        };
+
+        let fmc = read_section(&manifest.fmc, "fmc")?;
+        let runtime = read_section(&manifest.runtime, "runtime")?;
+        let aux = manifest
+            .aux_toc_entries()
+            .iter()
+            .map(|toc| read_section(toc, "aux"))
+            .collect::<std::io::Result<Vec<_>>>()?;
+
+        if offset != bytes.len() {
+            return Err(std::io::Error::new(
+                ErrorKind::Other,
+                "image is larger than the manifest",
+            ));
+        }
+
+        Ok(Self {
+            manifest,
+            fmc,
+            runtime,
+            aux,
+        })
+    }
 }
 
 /// Calipatra Image Manifest
@@ -126,6 +279,9 @@ pub struct ImageManifest {
 
     /// Runtime TOC Entry
     pub runtime: ImageTocEntry,
+
+    /// Auxiliary TOC Entries, only the first `header.toc_len - 2` are used
+    pub aux: [ImageTocEntry; MAX_AUX_TOC_ENTRY_COUNT as usize],
 }
 
 impl ImageManifest {
@@ -136,26 +292,34 @@ impl ImageManifest {
         span.start as u32 + offset..span.end as u32 + offset
     }
 
-    /// Returns the `Range<u32>` containing the specified vendor public key,
+    /// Returns the `Range<u32>` containing the specified vendor ECC public key,
     /// or an empty range if the index is invalid.
-    pub fn vendor_pub_key_range(vendor_ecc_pub_key_idx: u32) -> Range<u32> {
-        if vendor_ecc_pub_key_idx > VENDOR_ECC_KEY_COUNT {
+    pub fn vendor_ecc_pub_key_range(vendor_ecc_pub_key_idx: u32) -> Range<u32> {
+        if vendor_ecc_pub_key_idx >= VENDOR_ECC_KEY_COUNT {
             return 0..0;
         }
 
-        let pub_key_size = (2 * 4 * ECC384_SCALAR_WORD_SIZE) as u32;
-        let range = Self::vendor_pub_keys_range();
+        let pub_key_size = core::mem::size_of::<ImageEccPubKey>() as u32;
+        let offset = Self::vendor_pub_keys_range().start
+            + offset_of!(ImageVendorPubKeys, ecc_pub_keys) as u32
+            + vendor_ecc_pub_key_idx * pub_key_size;
 
-        // Sanity check
-        // TODO: can remove this when LMS keys are added
-        if range.len() as u32 != VENDOR_ECC_KEY_COUNT * pub_key_size {
+        offset..offset + pub_key_size
+    }
+
+    /// Returns the `Range<u32>` containing the specified vendor LMS public key,
+    /// or an empty range if the index is invalid.
+    pub fn vendor_lms_pub_key_range(vendor_lms_pub_key_idx: u32) -> Range<u32> {
+        if vendor_lms_pub_key_idx >= VENDOR_LMS_KEY_COUNT {
             return 0..0;
         }
 
-        let offset = (offset_of!(ImageVendorPubKeys, ecc_pub_keys) as u32)
-            + vendor_ecc_pub_key_idx * pub_key_size;
+        let pub_key_size = core::mem::size_of::<ImageLmsPubKey>() as u32;
+        let offset = Self::vendor_pub_keys_range().start
+            + offset_of!(ImageVendorPubKeys, lms_pub_keys) as u32
+            + vendor_lms_pub_key_idx * pub_key_size;
 
-        range.start + offset..range.len() as u32 + offset
+        offset..offset + pub_key_size
     }
 
     /// Returns `Range<u32>` containing the owner public key
@@ -171,10 +335,22 @@ impl ImageManifest {
         span.start as u32..span.end as u32
     }
 
-    /// Returns `Range<u32>` containing the table of contents
-    pub fn toc_range() -> Range<u32> {
-        let span = span_of!(ImageManifest, fmc..=runtime);
-        span.start as u32..span.end as u32
+    /// Returns `Range<u32>` containing the first `toc_len` entries of the
+    /// table of contents, or an empty range if the length is invalid.
+    pub fn toc_range(toc_len: u32) -> Range<u32> {
+        if !(MIN_TOC_ENTRY_COUNT..=MAX_TOC_ENTRY_COUNT).contains(&toc_len) {
+            return 0..0;
+        }
+
+        let start = offset_of!(ImageManifest, fmc) as u32;
+        start..start + toc_len * core::mem::size_of::<ImageTocEntry>() as u32
+    }
+
+    /// Returns the auxiliary TOC entries in use
+    pub fn aux_toc_entries(&self) -> &[ImageTocEntry] {
+        let len = self.header.toc_len.saturating_sub(MIN_TOC_ENTRY_COUNT);
+        let len = core::cmp::min(len, MAX_AUX_TOC_ENTRY_COUNT);
+        &self.aux[..len as usize]
     }
 }
 
@@ -182,36 +358,35 @@ impl ImageManifest {
 #[derive(AsBytes, FromBytes, Default, Debug, Clone, Copy)]
 pub struct ImageVendorPubKeys {
     pub ecc_pub_keys: [ImageEccPubKey; VENDOR_ECC_KEY_COUNT as usize],
-    // TODO: Add LMS Public Keys here
-    // TODO: Update vendor_pub_key_range to pick up the new LMS keys
+    pub lms_pub_keys: [ImageLmsPubKey; VENDOR_LMS_KEY_COUNT as usize],
 }
 
 #[repr(C)]
 #[derive(AsBytes, FromBytes, Default, Debug, Clone, Copy)]
 pub struct ImageVendorPrivKeys {
     pub ecc_priv_keys: [ImageEccPrivKey; VENDOR_ECC_KEY_COUNT as usize],
-    // TODO: Add LMS Private Keys here
+    pub lms_priv_keys: [ImageLmsPrivKey; VENDOR_LMS_KEY_COUNT as usize],
 }
 
 #[repr(C)]
 #[derive(AsBytes, FromBytes, Default, Debug, Clone, Copy)]
 pub struct ImageOwnerPubKeys {
     pub ecc_pub_key: ImageEccPubKey,
-    // TODO: Add LMS Public Keys here
+    pub lms_pub_key: ImageLmsPubKey,
 }
 
 #[repr(C)]
 #[derive(AsBytes, FromBytes, Default, Debug)]
 pub struct ImageOwnerPrivKeys {
     pub ecc_priv_key: ImageEccPrivKey,
-    // TODO: Add LMS Private Keys here
+    pub lms_priv_key: ImageLmsPrivKey,
 }
 
 #[repr(C)]
 #[derive(AsBytes, FromBytes, Default, Debug)]
 pub struct ImageSignatures {
     pub ecc_sig: ImageEccSignature,
-    // TODO: Add LMS Signature here
+    pub lms_sig: ImageLmsSignature,
 }
 
 /// Calipatra Image Bundle Preamble
@@ -224,6 +399,9 @@ pub struct ImagePreamble {
     /// Vendor ECC Public Key Index
     pub vendor_ecc_pub_key_idx: u32,
 
+    /// Vendor LMS Public Key Index
+    pub vendor_lms_pub_key_idx: u32,
+
     /// Vendor Signatures
     pub vendor_sigs: ImageSignatures,
 
@@ -256,6 +434,9 @@ pub struct ImageHeader {
     /// Vendor ECC Public Key Index
     pub vendor_ecc_pub_key_idx: u32,
 
+    /// Vendor LMS Public Key Index
+    pub vendor_lms_pub_key_idx: u32,
+
     /// Flags
     pub flags: u32,
 
@@ -279,6 +460,9 @@ pub struct ImageHeader {
 pub enum ImageTocEntryType {
     /// First mutable code
     Executable = 1,
+
+    /// Content that is verified and measured but never executed
+    Data = 2,
 }
 
 impl From<ImageTocEntryType> for u32 {
@@ -306,7 +490,7 @@ impl From<ImageTocEntryId> for u32 {
 
 /// Caliptra Table of contents entry
 #[repr(C)]
-#[derive(AsBytes, FromBytes, Default, Debug)]
+#[derive(AsBytes, FromBytes, Default, Debug, Copy, Clone)]
 pub struct ImageTocEntry {
     /// ID
     pub id: u32,
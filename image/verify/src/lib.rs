@@ -19,7 +19,7 @@ use caliptra_drivers::*;
 use caliptra_image_types::*;
 use core::ops::Range;
 
-pub use verifier::ImageVerifier;
+pub use verifier::{ImageVerifier, ImageVerifierErr};
 
 /// Image Verifification Executable Info
 #[derive(Default, Debug)]
@@ -46,12 +46,22 @@ pub struct ImageVerificationInfo {
     /// Vendor ECC public key index
     pub vendor_ecc_pub_key_idx: u32,
 
-    /// Digest of vendor public keys that verified the image
+    /// Vendor LMS public key index
+    pub vendor_lms_pub_key_idx: u32,
+
+    /// Digest of the vendor ECC public key followed by the vendor LMS public
+    /// key that verified the image
     pub vendor_pub_keys_digest: ImageDigest,
 
     /// Digest of owner public keys that verified the image
     pub owner_pub_keys_digest: ImageDigest,
 
+    /// Start of the validity window of the image, all zeros if none
+    pub not_before: [u8; 15],
+
+    /// End of the validity window of the image, all zeros if none
+    pub not_after: [u8; 15],
+
     /// First mutable code
     pub fmc: ImageVerificationExeInfo,
 
@@ -71,6 +81,9 @@ pub trait ImageVerificationEnv {
         len: u32,
     ) -> CaliptraResult<ImageDigest>;
 
+    /// Calculate SHA-384 Digest of data outside the image
+    fn sha384_digest_data(&self, data: &[u8]) -> CaliptraResult<ImageDigest>;
+
     /// Perform ECC-348 Verification
     fn ecc384_verify(
         &self,
@@ -80,12 +93,24 @@ pub trait ImageVerificationEnv {
         sig: &ImageEccSignature,
     ) -> CaliptraResult<bool>;
 
+    /// Perform LMS Verification
+    fn lms_verify(
+        &self,
+        image: Self::Image,
+        digest: &ImageDigest,
+        pub_key: &ImageLmsPubKey,
+        sig: &ImageLmsSignature,
+    ) -> CaliptraResult<bool>;
+
     /// Get Vendor Public Key Digest
     fn vendor_pub_key_digest(&self, image: Self::Image) -> ImageDigest;
 
     /// Get Vendor Public Key Revocation list
     fn vendor_pub_key_revocation(&self, image: Self::Image) -> VendorPubKeyRevocation;
 
+    /// Get Vendor LMS Public Key Revocation list
+    fn vendor_lms_pub_key_revocation(&self, image: Self::Image) -> VendorPubKeyRevocation;
+
     /// Get Owner Public Key Digest from fuses
     fn owner_pub_key_digest_fuses(&self) -> ImageDigest;
 
@@ -98,6 +123,9 @@ pub trait ImageVerificationEnv {
     // Get the vendor key index saved on cold boot in data vault
     fn vendor_pub_key_idx_dv(&self) -> u32;
 
+    // Get the vendor LMS key index saved on cold boot in data vault
+    fn vendor_lms_pub_key_idx_dv(&self) -> u32;
+
     // Get the owner key digest saved on cold boot in data vault
     fn owner_pub_key_digest_dv(&self) -> ImageDigest;
 
@@ -112,4 +140,9 @@ pub trait ImageVerificationEnv {
 
     // ICCM Range
     fn iccm_range(&self) -> Range<u32>;
+
+    // Get the trusted time in seconds since the Unix epoch, or `None` if none
+    // is supplied. Images with a validity window are rejected without a
+    // trusted time in the production lifecycle.
+    fn trusted_time(&self) -> Option<u64>;
 }
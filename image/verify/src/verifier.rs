@@ -17,9 +17,12 @@ use core::num::NonZeroU32;
 use crate::*;
 use caliptra_drivers::*;
 use caliptra_image_types::*;
+use zerocopy::AsBytes;
 
 const ZERO_DIGEST: ImageDigest = [0u32; SHA384_DIGEST_WORD_SIZE];
 
+const NULL_TIME: [u8; 15] = [0u8; 15];
+
 caliptra_err_def! {
     ImageVerifier,
     ImageVerifierErr {
@@ -69,14 +72,39 @@ caliptra_err_def! {
         RuntimeSvnGreaterThanMaxSupported = 44,
         RuntimeSvnLessThanMinSupported = 45,
         RuntimeSvnLessThanFuse = 46,
+        VendorLmsPubKeyIndexOutOfBounds = 47,
+        VendorLmsPubKeyRevoked = 48,
+        VendorLmsPubKeyIndexMismatch = 49,
+        VendorLmsVerifyFailure = 50,
+        VendorLmsSignatureInvalid = 51,
+        OwnerLmsVerifyFailure = 52,
+        OwnerLmsSignatureInvalid = 53,
+        UpdateResetVenLmsPubKeyIdxMismatch = 54,
+        VendorValidityDateInvalid = 55,
+        VendorImageNotYetValid = 56,
+        VendorImageExpired = 57,
+        OwnerValidityDateInvalid = 58,
+        OwnerImageNotYetValid = 59,
+        OwnerImageExpired = 60,
+        AuxTocEntryInvalid = 61,
+        AuxImageIncorrectOrder = 62,
+        AuxDigestFailure = 63,
+        AuxDigestMismatch = 64,
+        TrustedTimeUnavailable = 65,
+        FmcLoadRangeInvalid = 66,
+        RuntimeLoadRangeInvalid = 67,
+        FmcRuntimeLoadRangeOverlap = 68,
     }
 }
 
 /// Header Info
 struct HeaderInfo<'a> {
     vendor_ecc_pub_key_idx: u32,
+    vendor_lms_pub_key_idx: u32,
     vendor_info: (&'a ImageEccPubKey, &'a ImageEccSignature),
+    vendor_lms_info: (&'a ImageLmsPubKey, &'a ImageLmsSignature),
     owner_info: Option<(&'a ImageEccPubKey, &'a ImageEccSignature)>,
+    owner_lms_info: Option<(&'a ImageLmsPubKey, &'a ImageLmsSignature)>,
     owner_pub_keys_digest: ImageDigest,
 }
 
@@ -90,6 +118,7 @@ struct TocInfo<'a> {
 struct ImageInfo<'a> {
     fmc: &'a ImageTocEntry,
     runtime: &'a ImageTocEntry,
+    aux: &'a [ImageTocEntry],
 }
 
 /// Image Verifier
@@ -124,6 +153,41 @@ impl<Env: ImageVerificationEnv> ImageVerifier<Env> {
         manifest: &ImageManifest,
         image: Env::Image,
         reason: ResetReason,
+    ) -> CaliptraResult<ImageVerificationInfo> {
+        self.verify_image(manifest, image, reason, true)
+    }
+
+    /// Verify the manifest of a Caliptra image received without its sections
+    ///
+    /// The digests of the FMC, runtime and auxiliary images are not
+    /// calculated; the caller must check every section it receives against
+    /// its verified TOC entry before using it.
+    ///
+    /// # Arguments
+    ///
+    /// * `manifest` - Image Manifest
+    /// * `image`    - Image containing the manifest
+    /// * `reason`   - Reset Reason
+    ///
+    /// # Returns
+    ///
+    /// * `ImageVerificationInfo` - Image verifiaction information success
+    pub fn verify_manifest(
+        &self,
+        manifest: &ImageManifest,
+        image: Env::Image,
+        reason: ResetReason,
+    ) -> CaliptraResult<ImageVerificationInfo> {
+        self.verify_image(manifest, image, reason, false)
+    }
+
+    /// Verify Caliptra image, optionally without the digests of its sections
+    fn verify_image(
+        &self,
+        manifest: &ImageManifest,
+        image: Env::Image,
+        reason: ResetReason,
+        verify_sections: bool,
     ) -> CaliptraResult<ImageVerificationInfo> {
         // Check if manifest has required marker
         if manifest.marker != MANIFEST_MARKER {
@@ -143,19 +207,31 @@ impl<Env: ImageVerificationEnv> ImageVerifier<Env> {
         let header = &manifest.header;
         let toc_info = self.verify_header(image, header, &header_info)?;
 
+        // Verify the validity windows
+        let (not_before, not_after) = self.verify_validity(image, header)?;
+
         // Verify TOC
         let image_info = self.verify_toc(image, manifest, &toc_info)?;
 
         // Verify FMC
-        let fmc_info = self.verify_fmc(image, image_info.fmc, reason)?;
+        let fmc_info = self.verify_fmc(image, image_info.fmc, reason, verify_sections)?;
 
         // Verify Runtime
-        let runtime_info = self.verify_runtime(image, image_info.runtime)?;
+        let runtime_info = self.verify_runtime(image, image_info.runtime, verify_sections)?;
+
+        // Verify the FMC and Runtime load ranges
+        Self::verify_load_ranges(&fmc_info, &runtime_info)?;
+
+        // Verify Auxiliary Images
+        self.verify_aux(image, image_info.runtime, image_info.aux, verify_sections)?;
 
         let info = ImageVerificationInfo {
             vendor_ecc_pub_key_idx: header_info.vendor_ecc_pub_key_idx,
-            vendor_pub_keys_digest: self.make_vendor_key_digest(image, &header_info)?,
+            vendor_lms_pub_key_idx: header_info.vendor_lms_pub_key_idx,
+            vendor_pub_keys_digest: self.make_vendor_key_digest(&header_info)?,
             owner_pub_keys_digest: header_info.owner_pub_keys_digest,
+            not_before,
+            not_after,
             fmc: fmc_info,
             runtime: runtime_info,
         };
@@ -183,30 +259,45 @@ impl<Env: ImageVerificationEnv> ImageVerifier<Env> {
             raise_err!(UpdateResetVenPubKeyIdxOutOfBounds)
         }
 
+        // Verify Vendor LMS Key Index
+        let vendor_lms_pub_key_idx = self.verify_vendor_lms_pk_idx(preamble, image, reason)?;
+
         // Vendor Information
         let vendor_info = (
             &preamble.vendor_pub_keys.ecc_pub_keys[vendor_ecc_pub_key_idx as usize],
             &preamble.vendor_sigs.ecc_sig,
         );
+        let vendor_lms_info = (
+            &preamble.vendor_pub_keys.lms_pub_keys[vendor_lms_pub_key_idx as usize],
+            &preamble.vendor_sigs.lms_sig,
+        );
 
         // Owner Information
-        let (owner_pub_keys_digest, owner_info) = if let Some(digest) = owner_pk_digest {
-            (
-                digest,
-                Some((
-                    &preamble.owner_pub_keys.ecc_pub_key,
-                    &preamble.owner_sigs.ecc_sig,
-                )),
-            )
-        } else {
-            (ZERO_DIGEST, None)
-        };
+        let (owner_pub_keys_digest, owner_info, owner_lms_info) =
+            if let Some(digest) = owner_pk_digest {
+                (
+                    digest,
+                    Some((
+                        &preamble.owner_pub_keys.ecc_pub_key,
+                        &preamble.owner_sigs.ecc_sig,
+                    )),
+                    Some((
+                        &preamble.owner_pub_keys.lms_pub_key,
+                        &preamble.owner_sigs.lms_sig,
+                    )),
+                )
+            } else {
+                (ZERO_DIGEST, None, None)
+            };
 
         let info = HeaderInfo {
             vendor_ecc_pub_key_idx,
+            vendor_lms_pub_key_idx,
             vendor_info,
+            vendor_lms_info,
             owner_pub_keys_digest,
             owner_info,
+            owner_lms_info,
         };
 
         Ok(info)
@@ -248,6 +339,42 @@ impl<Env: ImageVerificationEnv> ImageVerifier<Env> {
         Ok(key_idx)
     }
 
+    /// Verify Vendor LMS Public Key Index
+    fn verify_vendor_lms_pk_idx(
+        &self,
+        preamble: &ImagePreamble,
+        image: Env::Image,
+        reason: ResetReason,
+    ) -> CaliptraResult<u32> {
+        const SECOND_LAST_KEY_IDX: u32 = VENDOR_LMS_KEY_COUNT - 2;
+        const LAST_KEY_IDX: u32 = VENDOR_LMS_KEY_COUNT - 1;
+
+        let key_idx = preamble.vendor_lms_pub_key_idx;
+        let revocation = self.env.vendor_lms_pub_key_revocation(image);
+
+        match key_idx {
+            0..=SECOND_LAST_KEY_IDX => {
+                let key = VendorPubKeyRevocation::from_bits_truncate(0x01u32 << key_idx);
+                if revocation.contains(key) {
+                    raise_err!(VendorLmsPubKeyRevoked)
+                }
+            }
+            LAST_KEY_IDX => {
+                // The last key is never revoked
+            }
+            _ => raise_err!(VendorLmsPubKeyIndexOutOfBounds),
+        }
+
+        if reason == ResetReason::UpdateReset {
+            let expected = self.env.vendor_lms_pub_key_idx_dv();
+            if expected != key_idx {
+                raise_err!(UpdateResetVenLmsPubKeyIdxMismatch)
+            }
+        }
+
+        Ok(key_idx)
+    }
+
     /// Verify vendor public key digest
     fn verify_vendor_pk_digest(
         &self,
@@ -323,21 +450,35 @@ impl<Env: ImageVerificationEnv> ImageVerifier<Env> {
             .sha384_digest(image, range.start, range.len() as u32)
             .map_err(|_| err_u32!(HeaderDigestFailure))?;
 
-        // Verify vendor signature
+        // Verify vendor signatures. Both the ECC and the LMS signature must
+        // be valid.
         let (pub_key, sig) = info.vendor_info;
         self.verify_vendor_sig(image, &digest, pub_key, sig)?;
 
+        let (pub_key, sig) = info.vendor_lms_info;
+        self.verify_vendor_lms_sig(image, &digest, pub_key, sig)?;
+
         // Verify the ECC public key index used verify header signature is encoded
         // in the header
         if header.vendor_ecc_pub_key_idx != info.vendor_ecc_pub_key_idx {
             raise_err!(VendorEccPubKeyIndexMismatch)
         }
 
-        // Verify owner signature
+        // Verify the LMS public key index used verify header signature is encoded
+        // in the header
+        if header.vendor_lms_pub_key_idx != info.vendor_lms_pub_key_idx {
+            raise_err!(VendorLmsPubKeyIndexMismatch)
+        }
+
+        // Verify owner signatures
         if let Some((pub_key, sig)) = info.owner_info {
             self.verify_owner_sig(image, &digest, pub_key, sig)?;
         }
 
+        if let Some((pub_key, sig)) = info.owner_lms_info {
+            self.verify_owner_lms_sig(image, &digest, pub_key, sig)?;
+        }
+
         let verif_info = TocInfo {
             len: header.toc_len,
             digest: &header.toc_digest,
@@ -373,6 +514,26 @@ impl<Env: ImageVerificationEnv> ImageVerifier<Env> {
         Ok(())
     }
 
+    /// Verify Owner LMS Signature
+    fn verify_owner_lms_sig(
+        &self,
+        image: Env::Image,
+        digest: &ImageDigest,
+        pub_key: &ImageLmsPubKey,
+        sig: &ImageLmsSignature,
+    ) -> CaliptraResult<()> {
+        let result = self
+            .env
+            .lms_verify(image, digest, pub_key, sig)
+            .map_err(|_| err_u32!(OwnerLmsVerifyFailure))?;
+
+        if !result {
+            raise_err!(OwnerLmsSignatureInvalid)
+        }
+
+        Ok(())
+    }
+
     /// Verify Vendor Signature
     fn verify_vendor_sig(
         &self,
@@ -400,6 +561,92 @@ impl<Env: ImageVerificationEnv> ImageVerifier<Env> {
         Ok(())
     }
 
+    /// Verify Vendor LMS Signature
+    fn verify_vendor_lms_sig(
+        &self,
+        image: Env::Image,
+        digest: &ImageDigest,
+        pub_key: &ImageLmsPubKey,
+        sig: &ImageLmsSignature,
+    ) -> CaliptraResult<()> {
+        let result = self
+            .env
+            .lms_verify(image, digest, pub_key, sig)
+            .map_err(|_| err_u32!(VendorLmsVerifyFailure))?;
+
+        if !result {
+            raise_err!(VendorLmsSignatureInvalid)
+        }
+
+        Ok(())
+    }
+
+    /// Verify the vendor and owner validity windows against the trusted time
+    ///
+    /// A window is present when both of its dates are set and absent when
+    /// neither is. In the production lifecycle, the windows present are always
+    /// enforced and a trusted time is required. In other lifecycles, they are
+    /// only enforced when the environment supplies a trusted time.
+    ///
+    /// # Returns
+    ///
+    /// * `(not_before, not_after)` - Owner window if present, vendor window otherwise
+    fn verify_validity(
+        &self,
+        image: Env::Image,
+        header: &ImageHeader,
+    ) -> CaliptraResult<([u8; 15], [u8; 15])> {
+        let vendor = (header.vendor_not_before, header.vendor_not_after);
+        let owner = (
+            header.owner_data.owner_not_before,
+            header.owner_data.owner_not_after,
+        );
+        let vendor_present =
+            is_window_present(vendor).ok_or(err_u32!(VendorValidityDateInvalid))?;
+        let owner_present = is_window_present(owner).ok_or(err_u32!(OwnerValidityDateInvalid))?;
+
+        let now = self.env.trusted_time();
+        if now.is_none()
+            && (vendor_present || owner_present)
+            && self.env.dev_lifecycle(image) == Lifecycle::Production
+        {
+            raise_err!(TrustedTimeUnavailable)
+        }
+
+        if let Some(now) = now {
+            if vendor_present {
+                let not_before = unix_time(&vendor.0).ok_or(err_u32!(VendorValidityDateInvalid))?;
+                let not_after = unix_time(&vendor.1).ok_or(err_u32!(VendorValidityDateInvalid))?;
+                if now < not_before {
+                    raise_err!(VendorImageNotYetValid)
+                }
+                if now > not_after {
+                    raise_err!(VendorImageExpired)
+                }
+            }
+
+            if owner_present {
+                let not_before = unix_time(&owner.0).ok_or(err_u32!(OwnerValidityDateInvalid))?;
+                let not_after = unix_time(&owner.1).ok_or(err_u32!(OwnerValidityDateInvalid))?;
+                if now < not_before {
+                    raise_err!(OwnerImageNotYetValid)
+                }
+                if now > not_after {
+                    raise_err!(OwnerImageExpired)
+                }
+            }
+        }
+
+        // The owner window takes preference
+        if owner_present {
+            Ok(owner)
+        } else if vendor_present {
+            Ok(vendor)
+        } else {
+            Ok((NULL_TIME, NULL_TIME))
+        }
+    }
+
     /// Verify Table of Contents
     fn verify_toc<'a>(
         &self,
@@ -407,11 +654,11 @@ impl<Env: ImageVerificationEnv> ImageVerifier<Env> {
         manifest: &'a ImageManifest,
         verify_info: &TocInfo,
     ) -> CaliptraResult<ImageInfo<'a>> {
-        if verify_info.len != MAX_TOC_ENTRY_COUNT {
+        if !(MIN_TOC_ENTRY_COUNT..=MAX_TOC_ENTRY_COUNT).contains(&verify_info.len) {
             raise_err!(TocEntryCountInvalid)
         }
 
-        let range = ImageManifest::toc_range();
+        let range = ImageManifest::toc_range(verify_info.len);
 
         let actual = self
             .env
@@ -444,6 +691,7 @@ impl<Env: ImageVerificationEnv> ImageVerifier<Env> {
         let info = ImageInfo {
             fmc: &manifest.fmc,
             runtime: &manifest.runtime,
+            aux: manifest.aux_toc_entries(),
         };
 
         Ok(info)
@@ -462,16 +710,19 @@ impl<Env: ImageVerificationEnv> ImageVerifier<Env> {
         image: Env::Image,
         verify_info: &ImageTocEntry,
         reason: ResetReason,
+        verify_digest: bool,
     ) -> CaliptraResult<ImageVerificationExeInfo> {
-        let range = verify_info.image_range();
+        if verify_digest {
+            let range = verify_info.image_range();
 
-        let actual = self
-            .env
-            .sha384_digest(image, range.start, range.len() as u32)
-            .map_err(|_| err_u32!(FmcDigestFailure))?;
+            let actual = self
+                .env
+                .sha384_digest(image, range.start, range.len() as u32)
+                .map_err(|_| err_u32!(FmcDigestFailure))?;
 
-        if verify_info.digest != actual {
-            raise_err!(FmcDigestMismatch)
+            if verify_info.digest != actual {
+                raise_err!(FmcDigestMismatch)
+            }
         }
 
         // TODO: Perform following Address check
@@ -483,6 +734,10 @@ impl<Env: ImageVerificationEnv> ImageVerifier<Env> {
             raise_err!(FmcLoadAddrUnaligned)
         }
 
+        if !self.is_in_iccm(verify_info) {
+            raise_err!(FmcLoadRangeInvalid)
+        }
+
         if !self.env.iccm_range().contains(&verify_info.entry_point) {
             raise_err!(FmcEntryPointInvalid)
         }
@@ -504,7 +759,8 @@ impl<Env: ImageVerificationEnv> ImageVerifier<Env> {
             }
         }
 
-        if reason == ResetReason::UpdateReset && actual != self.env.get_fmc_digest_dv() {
+        if reason == ResetReason::UpdateReset && verify_info.digest != self.env.get_fmc_digest_dv()
+        {
             raise_err!(UpdateResetFmcDigestMismatch)
         }
 
@@ -524,16 +780,19 @@ impl<Env: ImageVerificationEnv> ImageVerifier<Env> {
         &self,
         image: Env::Image,
         verify_info: &ImageTocEntry,
+        verify_digest: bool,
     ) -> CaliptraResult<ImageVerificationExeInfo> {
-        let range = verify_info.image_range();
+        if verify_digest {
+            let range = verify_info.image_range();
 
-        let actual = self
-            .env
-            .sha384_digest(image, range.start, range.len() as u32)
-            .map_err(|_| err_u32!(RuntimeDigestFailure))?;
+            let actual = self
+                .env
+                .sha384_digest(image, range.start, range.len() as u32)
+                .map_err(|_| err_u32!(RuntimeDigestFailure))?;
 
-        if verify_info.digest != actual {
-            raise_err!(RuntimeDigestMismatch)
+            if verify_info.digest != actual {
+                raise_err!(RuntimeDigestMismatch)
+            }
         }
 
         // TODO: Perform following Address checks
@@ -545,6 +804,9 @@ impl<Env: ImageVerificationEnv> ImageVerifier<Env> {
         if verify_info.load_addr % 4 != 0 {
             raise_err!(RuntimeLoadAddrUnaligned)
         }
+        if !self.is_in_iccm(verify_info) {
+            raise_err!(RuntimeLoadRangeInvalid)
+        }
         if !self.env.iccm_range().contains(&verify_info.entry_point) {
             raise_err!(RuntimeEntryPointInvalid)
         }
@@ -577,26 +839,152 @@ impl<Env: ImageVerificationEnv> ImageVerifier<Env> {
         Ok(info)
     }
 
-    /// Calculates a digest of the vendor key that signed the image.
+    /// Check that a section is loaded entirely within ICCM
+    fn is_in_iccm(&self, verify_info: &ImageTocEntry) -> bool {
+        let iccm = self.env.iccm_range();
+        match verify_info.load_addr.checked_add(verify_info.size) {
+            Some(end) => iccm.start <= verify_info.load_addr && end <= iccm.end,
+            None => false,
+        }
+    }
+
+    /// Verify that the FMC and Runtime are not loaded over each other
+    fn verify_load_ranges(
+        fmc_info: &ImageVerificationExeInfo,
+        runtime_info: &ImageVerificationExeInfo,
+    ) -> CaliptraResult<()> {
+        // Both ranges were checked to be within ICCM
+        let fmc_end = fmc_info.load_addr + fmc_info.size;
+        let runtime_end = runtime_info.load_addr + runtime_info.size;
+        if fmc_info.load_addr < runtime_end && runtime_info.load_addr < fmc_end {
+            raise_err!(FmcRuntimeLoadRangeOverlap)
+        }
+
+        Ok(())
+    }
+
+    /// Verify Auxiliary Images
     ///
-    /// TODO: include the LMS key in the digest.
-    fn make_vendor_key_digest(
+    /// Auxiliary images are never executed, hence only their placement and
+    /// digests are verified.
+    fn verify_aux(
         &self,
         image: Env::Image,
-        info: &HeaderInfo,
-    ) -> CaliptraResult<ImageDigest> {
-        let range = ImageManifest::vendor_pub_key_range(info.vendor_ecc_pub_key_idx);
+        runtime: &ImageTocEntry,
+        aux: &[ImageTocEntry],
+        verify_digest: bool,
+    ) -> CaliptraResult<()> {
+        let mut prev_end = runtime.image_range().end;
+
+        for (i, verify_info) in aux.iter().enumerate() {
+            if verify_info.r#type != u32::from(ImageTocEntryType::Data)
+                || verify_info.id == u32::from(ImageTocEntryId::Fmc)
+                || verify_info.id == u32::from(ImageTocEntryId::Runtime)
+                || aux[..i].iter().any(|prev| prev.id == verify_info.id)
+            {
+                raise_err!(AuxTocEntryInvalid)
+            }
 
-        if range.is_empty() {
-            raise_err!(VendorEccPubKeyIndexOutOfBounds)
+            // Ensure the auxiliary images follow the runtime section in order
+            if verify_info.offset < prev_end {
+                raise_err!(AuxImageIncorrectOrder)
+            }
+            prev_end = verify_info
+                .offset
+                .checked_add(verify_info.size)
+                .ok_or(err_u32!(AuxTocEntryInvalid))?;
+
+            if verify_digest {
+                let actual = self
+                    .env
+                    .sha384_digest(image, verify_info.offset, verify_info.size)
+                    .map_err(|_| err_u32!(AuxDigestFailure))?;
+
+                if verify_info.digest != actual {
+                    raise_err!(AuxDigestMismatch)
+                }
+            }
         }
 
+        Ok(())
+    }
+
+    /// Calculates a digest of the vendor ECC and LMS public keys that
+    /// signed the image.
+    fn make_vendor_key_digest(&self, info: &HeaderInfo) -> CaliptraResult<ImageDigest> {
+        const ECC_KEY_SIZE: usize = core::mem::size_of::<ImageEccPubKey>();
+        const LMS_KEY_SIZE: usize = core::mem::size_of::<ImageLmsPubKey>();
+
+        let mut data = [0u8; ECC_KEY_SIZE + LMS_KEY_SIZE];
+        data[..ECC_KEY_SIZE].copy_from_slice(info.vendor_info.0.as_bytes());
+        data[ECC_KEY_SIZE..].copy_from_slice(info.vendor_lms_info.0.as_bytes());
+
         self.env
-            .sha384_digest(image, range.start, range.len() as u32)
+            .sha384_digest_data(&data)
             .map_err(|_| err_u32!(VendorPubKeyDigestFailure))
     }
 }
 
+/// Check whether a `(not_before, not_after)` validity window is present
+///
+/// # Returns
+///
+/// * `None` if only one of the dates is set
+fn is_window_present((not_before, not_after): ([u8; 15], [u8; 15])) -> Option<bool> {
+    match (not_before != NULL_TIME, not_after != NULL_TIME) {
+        (true, true) => Some(true),
+        (false, false) => Some(false),
+        _ => None,
+    }
+}
+
+/// Convert a `YYYYMMDDHHMMSSZ` time to seconds since the Unix epoch
+///
+/// # Returns
+///
+/// * `None` if the time is malformed or before the epoch
+fn unix_time(time: &[u8; 15]) -> Option<u64> {
+    if time[14] != b'Z' {
+        return None;
+    }
+
+    // Year, month, day, hour, minute and second
+    let mut fields = [0u32; 6];
+    let mut digits = time[..14].iter();
+    for (field, width) in fields.iter_mut().zip([4, 2, 2, 2, 2, 2]) {
+        for _ in 0..width {
+            let digit = digits.next().filter(|d| d.is_ascii_digit())?;
+            *field = *field * 10 + u32::from(digit - b'0');
+        }
+    }
+
+    let [year, month, day, hour, minute, second] = fields;
+    if year < 1970
+        || !(1..=12).contains(&month)
+        || !(1..=31).contains(&day)
+        || hour > 23
+        || minute > 59
+        || second > 59
+    {
+        return None;
+    }
+
+    // Days since the epoch, counting years from March so the leap day is
+    // the last day of the year
+    let (year, month) = if month <= 2 {
+        (year - 1, month + 9)
+    } else {
+        (year, month - 3)
+    };
+    let era = year / 400;
+    let year_of_era = year % 400;
+    let day_of_year = (153 * month + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = u64::from(era) * 146097 + u64::from(day_of_era) - 719468;
+
+    Some(days * 86400 + u64::from(hour * 3600 + minute * 60 + second))
+}
+
 #[cfg(all(test, target_family = "unix"))]
 mod tests {
     use super::*;
@@ -621,6 +1009,24 @@ mod tests {
         r: DUMMY_DATA,
         s: DUMMY_DATA,
     };
+    const VENDOR_LMS_PUBKEY: ImageLmsPubKey = ImageLmsPubKey {
+        tree_type: IMAGE_LMS_TREE_TYPE,
+        otstype: IMAGE_LMS_OTS_TYPE,
+        id: [0xaa; 16],
+        digest: [0xbb; LMS_DIGEST_BYTE_SIZE],
+    };
+    const VENDOR_LMS_SIG: ImageLmsSignature = ImageLmsSignature {
+        q: 0,
+        ots: ImageLmOtsSignature {
+            otstype: IMAGE_LMS_OTS_TYPE,
+            nonce: [0xcc; LMS_DIGEST_BYTE_SIZE],
+            y: [[0xdd; LMS_DIGEST_BYTE_SIZE]; LMOTS_P],
+        },
+        tree_type: IMAGE_LMS_TREE_TYPE,
+        tree_path: [[0xee; LMS_DIGEST_BYTE_SIZE]; LMS_TREE_HEIGHT],
+    };
+    const OWNER_LMS_PUBKEY: ImageLmsPubKey = VENDOR_LMS_PUBKEY;
+    const OWNER_LMS_SIG: ImageLmsSignature = VENDOR_LMS_SIG;
 
     #[test]
     fn test_vendor_ecc_pk_idx_update_rst() {
@@ -645,6 +1051,7 @@ mod tests {
 
         let preamble = ImagePreamble {
             vendor_ecc_pub_key_idx: 2,
+            vendor_lms_pub_key_idx: 0,
             ..Default::default()
         };
 
@@ -655,6 +1062,52 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_vendor_lms_pk_idx_mismatch_update_rst() {
+        let test_env = TestEnv {
+            vendor_lms_pub_key_idx: 1,
+            ..Default::default()
+        };
+        let verifier = ImageVerifier::new(test_env);
+
+        let preamble = ImagePreamble {
+            vendor_lms_pub_key_idx: 2,
+            ..Default::default()
+        };
+
+        let result = verifier.verify_vendor_lms_pk_idx(&preamble, (), ResetReason::UpdateReset);
+        assert_eq!(
+            result.err(),
+            Some(err_u32!(UpdateResetVenLmsPubKeyIdxMismatch))
+        );
+    }
+
+    #[test]
+    fn test_vendor_lms_pk_idx_revoked() {
+        let test_env = TestEnv {
+            vendor_lms_pub_key_revocation: VendorPubKeyRevocation::KEY1,
+            ..Default::default()
+        };
+        let verifier = ImageVerifier::new(test_env);
+
+        let preamble = ImagePreamble {
+            vendor_lms_pub_key_idx: 1,
+            ..Default::default()
+        };
+        let result = verifier.verify_vendor_lms_pk_idx(&preamble, (), ResetReason::ColdReset);
+        assert_eq!(result.err(), Some(err_u32!(VendorLmsPubKeyRevoked)));
+
+        let preamble = ImagePreamble {
+            vendor_lms_pub_key_idx: VENDOR_LMS_KEY_COUNT,
+            ..Default::default()
+        };
+        let result = verifier.verify_vendor_lms_pk_idx(&preamble, (), ResetReason::ColdReset);
+        assert_eq!(
+            result.err(),
+            Some(err_u32!(VendorLmsPubKeyIndexOutOfBounds))
+        );
+    }
+
     #[test]
     fn test_owner_pk_digest_update_rst() {
         let test_env = TestEnv {
@@ -692,7 +1145,7 @@ mod tests {
             ..Default::default()
         };
 
-        let result = verifier.verify_fmc((), &verify_info, ResetReason::UpdateReset);
+        let result = verifier.verify_fmc((), &verify_info, ResetReason::UpdateReset, true);
         assert!(result.is_ok());
     }
 
@@ -714,7 +1167,7 @@ mod tests {
             ..Default::default()
         };
 
-        let result = verifier.verify_fmc((), &verify_info, ResetReason::UpdateReset);
+        let result = verifier.verify_fmc((), &verify_info, ResetReason::UpdateReset, true);
         assert_eq!(result.err(), Some(err_u32!(UpdateResetFmcDigestMismatch)));
     }
 
@@ -809,8 +1262,11 @@ mod tests {
         let ecc_sig = ImageEccSignature::default();
         let header_info: HeaderInfo = HeaderInfo {
             vendor_ecc_pub_key_idx: 0,
+            vendor_lms_pub_key_idx: 0,
             vendor_info: (&ImageEccPubKey::default(), &ImageEccSignature::default()),
+            vendor_lms_info: (&VENDOR_LMS_PUBKEY, &VENDOR_LMS_SIG),
             owner_info: Some((&ecc_pubkey, &ecc_sig)),
+            owner_lms_info: Some((&OWNER_LMS_PUBKEY, &OWNER_LMS_SIG)),
             owner_pub_keys_digest: ImageDigest::default(),
         };
         let result = verifier.verify_header((), &header, &header_info);
@@ -826,8 +1282,11 @@ mod tests {
         let owner_ecc_sig = ImageEccSignature::default();
         let header_info: HeaderInfo = HeaderInfo {
             vendor_ecc_pub_key_idx: 0,
+            vendor_lms_pub_key_idx: 0,
             vendor_info: (&VENDOR_ECC_PUBKEY, &ImageEccSignature::default()),
+            vendor_lms_info: (&VENDOR_LMS_PUBKEY, &VENDOR_LMS_SIG),
             owner_info: Some((&owner_ecc_pubkey, &owner_ecc_sig)),
+            owner_lms_info: Some((&OWNER_LMS_PUBKEY, &OWNER_LMS_SIG)),
             owner_pub_keys_digest: ImageDigest::default(),
         };
         let result = verifier.verify_header((), &header, &header_info);
@@ -848,8 +1307,11 @@ mod tests {
         let owner_ecc_sig = ImageEccSignature::default();
         let header_info: HeaderInfo = HeaderInfo {
             vendor_ecc_pub_key_idx: 0,
+            vendor_lms_pub_key_idx: 0,
             vendor_info: (&VENDOR_ECC_PUBKEY, &VENDOR_ECC_SIG),
+            vendor_lms_info: (&VENDOR_LMS_PUBKEY, &VENDOR_LMS_SIG),
             owner_info: Some((&owner_ecc_pubkey, &owner_ecc_sig)),
+            owner_lms_info: Some((&OWNER_LMS_PUBKEY, &OWNER_LMS_SIG)),
             owner_pub_keys_digest: ImageDigest::default(),
         };
         let result = verifier.verify_header((), &header, &header_info);
@@ -860,6 +1322,7 @@ mod tests {
     fn test_header_incorrect_pubkey_index() {
         let test_env = TestEnv {
             verify_result: true,
+            lms_verify_result: true,
             ..Default::default()
         };
         let verifier = ImageVerifier::new(test_env);
@@ -868,18 +1331,65 @@ mod tests {
         let owner_ecc_sig = ImageEccSignature::default();
         let header_info: HeaderInfo = HeaderInfo {
             vendor_ecc_pub_key_idx: 1,
+            vendor_lms_pub_key_idx: 0,
             vendor_info: (&VENDOR_ECC_PUBKEY, &VENDOR_ECC_SIG),
+            vendor_lms_info: (&VENDOR_LMS_PUBKEY, &VENDOR_LMS_SIG),
             owner_info: Some((&owner_ecc_pubkey, &owner_ecc_sig)),
+            owner_lms_info: Some((&OWNER_LMS_PUBKEY, &OWNER_LMS_SIG)),
             owner_pub_keys_digest: ImageDigest::default(),
         };
         let result = verifier.verify_header((), &header, &header_info);
         assert_eq!(result.err(), Some(err_u32!(VendorEccPubKeyIndexMismatch)));
     }
 
+    #[test]
+    fn test_header_vendor_lms_signature_invalid() {
+        let test_env = TestEnv {
+            verify_result: true,
+            ..Default::default()
+        };
+        let verifier = ImageVerifier::new(test_env);
+        let header = ImageHeader::default();
+        let header_info: HeaderInfo = HeaderInfo {
+            vendor_ecc_pub_key_idx: 0,
+            vendor_lms_pub_key_idx: 0,
+            vendor_info: (&VENDOR_ECC_PUBKEY, &VENDOR_ECC_SIG),
+            vendor_lms_info: (&VENDOR_LMS_PUBKEY, &VENDOR_LMS_SIG),
+            owner_info: Some((&OWNER_ECC_PUBKEY, &OWNER_ECC_SIG)),
+            owner_lms_info: Some((&OWNER_LMS_PUBKEY, &OWNER_LMS_SIG)),
+            owner_pub_keys_digest: ImageDigest::default(),
+        };
+        let result = verifier.verify_header((), &header, &header_info);
+        assert_eq!(result.err(), Some(err_u32!(VendorLmsSignatureInvalid)));
+    }
+
+    #[test]
+    fn test_header_incorrect_lms_pubkey_index() {
+        let test_env = TestEnv {
+            verify_result: true,
+            lms_verify_result: true,
+            ..Default::default()
+        };
+        let verifier = ImageVerifier::new(test_env);
+        let header = ImageHeader::default();
+        let header_info: HeaderInfo = HeaderInfo {
+            vendor_ecc_pub_key_idx: 0,
+            vendor_lms_pub_key_idx: 1,
+            vendor_info: (&VENDOR_ECC_PUBKEY, &VENDOR_ECC_SIG),
+            vendor_lms_info: (&VENDOR_LMS_PUBKEY, &VENDOR_LMS_SIG),
+            owner_info: Some((&OWNER_ECC_PUBKEY, &OWNER_ECC_SIG)),
+            owner_lms_info: Some((&OWNER_LMS_PUBKEY, &OWNER_LMS_SIG)),
+            owner_pub_keys_digest: ImageDigest::default(),
+        };
+        let result = verifier.verify_header((), &header, &header_info);
+        assert_eq!(result.err(), Some(err_u32!(VendorLmsPubKeyIndexMismatch)));
+    }
+
     #[test]
     fn test_header_owner_pubkey_invalid_arg() {
         let test_env = TestEnv {
             verify_result: true,
+            lms_verify_result: true,
             ..Default::default()
         };
         let verifier = ImageVerifier::new(test_env);
@@ -888,8 +1398,11 @@ mod tests {
         let owner_ecc_sig = ImageEccSignature::default();
         let header_info: HeaderInfo = HeaderInfo {
             vendor_ecc_pub_key_idx: 0,
+            vendor_lms_pub_key_idx: 0,
             vendor_info: (&VENDOR_ECC_PUBKEY, &VENDOR_ECC_SIG),
+            vendor_lms_info: (&VENDOR_LMS_PUBKEY, &VENDOR_LMS_SIG),
             owner_info: Some((&owner_ecc_pubkey, &owner_ecc_sig)),
+            owner_lms_info: Some((&OWNER_LMS_PUBKEY, &OWNER_LMS_SIG)),
             owner_pub_keys_digest: ImageDigest::default(),
         };
         let result = verifier.verify_header((), &header, &header_info);
@@ -900,6 +1413,7 @@ mod tests {
     fn test_header_owner_signature_invalid_arg() {
         let test_env = TestEnv {
             verify_result: true,
+            lms_verify_result: true,
             ..Default::default()
         };
         let verifier = ImageVerifier::new(test_env);
@@ -907,8 +1421,11 @@ mod tests {
         let owner_ecc_sig = ImageEccSignature::default();
         let header_info: HeaderInfo = HeaderInfo {
             vendor_ecc_pub_key_idx: 0,
+            vendor_lms_pub_key_idx: 0,
             vendor_info: (&VENDOR_ECC_PUBKEY, &VENDOR_ECC_SIG),
+            vendor_lms_info: (&VENDOR_LMS_PUBKEY, &VENDOR_LMS_SIG),
             owner_info: Some((&OWNER_ECC_PUBKEY, &owner_ecc_sig)),
+            owner_lms_info: Some((&OWNER_LMS_PUBKEY, &OWNER_LMS_SIG)),
             owner_pub_keys_digest: ImageDigest::default(),
         };
         let result = verifier.verify_header((), &header, &header_info);
@@ -919,6 +1436,7 @@ mod tests {
     fn test_header_success() {
         let test_env = TestEnv {
             verify_result: true,
+            lms_verify_result: true,
             ..Default::default()
         };
         let verifier = ImageVerifier::new(test_env);
@@ -929,8 +1447,11 @@ mod tests {
         };
         let header_info: HeaderInfo = HeaderInfo {
             vendor_ecc_pub_key_idx: 0,
+            vendor_lms_pub_key_idx: 0,
             vendor_info: (&VENDOR_ECC_PUBKEY, &VENDOR_ECC_SIG),
+            vendor_lms_info: (&VENDOR_LMS_PUBKEY, &VENDOR_LMS_SIG),
             owner_info: Some((&OWNER_ECC_PUBKEY, &OWNER_ECC_SIG)),
+            owner_lms_info: Some((&OWNER_LMS_PUBKEY, &OWNER_LMS_SIG)),
             owner_pub_keys_digest: ImageDigest::default(),
         };
         let toc_info = verifier.verify_header((), &header, &header_info).unwrap();
@@ -944,7 +1465,14 @@ mod tests {
         let test_env = TestEnv::default();
         let verifier = ImageVerifier::new(test_env);
         let toc_info = TocInfo {
-            len: MAX_TOC_ENTRY_COUNT / 2,
+            len: MIN_TOC_ENTRY_COUNT - 1,
+            digest: &ImageDigest::default(),
+        };
+        let result = verifier.verify_toc((), &manifest, &toc_info);
+        assert_eq!(result.err(), Some(err_u32!(TocEntryCountInvalid)));
+
+        let toc_info = TocInfo {
+            len: MAX_TOC_ENTRY_COUNT + 1,
             digest: &ImageDigest::default(),
         };
         let result = verifier.verify_toc((), &manifest, &toc_info);
@@ -1073,7 +1601,7 @@ mod tests {
             digest: DUMMY_DATA,
             ..Default::default()
         };
-        let result = verifier.verify_fmc((), &verify_info, ResetReason::ColdReset);
+        let result = verifier.verify_fmc((), &verify_info, ResetReason::ColdReset, true);
         assert_eq!(result.err(), Some(err_u32!(FmcDigestMismatch)));
     }
 
@@ -1089,7 +1617,7 @@ mod tests {
             ..Default::default()
         };
 
-        let result = verifier.verify_fmc((), &verify_info, ResetReason::ColdReset);
+        let result = verifier.verify_fmc((), &verify_info, ResetReason::ColdReset, true);
         assert!(result.is_ok());
         let info = result.unwrap();
         assert_eq!(info.load_addr, 0x40000000);
@@ -1106,7 +1634,7 @@ mod tests {
             digest: DUMMY_DATA,
             ..Default::default()
         };
-        let result = verifier.verify_runtime((), &verify_info);
+        let result = verifier.verify_runtime((), &verify_info, true);
         assert_eq!(result.err(), Some(err_u32!(RuntimeDigestMismatch)));
     }
 
@@ -1121,7 +1649,7 @@ mod tests {
             size: 100,
             ..Default::default()
         };
-        let result = verifier.verify_runtime((), &verify_info);
+        let result = verifier.verify_runtime((), &verify_info, true);
         assert!(result.is_ok());
         let info = result.unwrap();
         assert_eq!(info.load_addr, 0x40000000);
@@ -1130,14 +1658,376 @@ mod tests {
         assert_eq!(info.size, 100);
     }
 
+    #[test]
+    fn test_load_range_outside_iccm() {
+        let test_env = TestEnv::default();
+        let verifier = ImageVerifier::new(test_env);
+
+        // The section ends past ICCM
+        let mut verify_info = ImageTocEntry {
+            load_addr: 0x40000000 + (128 * 1024) - 96,
+            entry_point: 0x40000000,
+            size: 100,
+            ..Default::default()
+        };
+        let result = verifier.verify_fmc((), &verify_info, ResetReason::ColdReset, false);
+        assert_eq!(result.err(), Some(err_u32!(FmcLoadRangeInvalid)));
+        let result = verifier.verify_runtime((), &verify_info, false);
+        assert_eq!(result.err(), Some(err_u32!(RuntimeLoadRangeInvalid)));
+
+        // The section wraps around the address space
+        verify_info.load_addr = 0x40000000;
+        verify_info.size = u32::MAX;
+        let result = verifier.verify_fmc((), &verify_info, ResetReason::ColdReset, false);
+        assert_eq!(result.err(), Some(err_u32!(FmcLoadRangeInvalid)));
+        let result = verifier.verify_runtime((), &verify_info, false);
+        assert_eq!(result.err(), Some(err_u32!(RuntimeLoadRangeInvalid)));
+
+        // The section ends at the end of ICCM
+        verify_info.load_addr = 0x40000000 + (128 * 1024) - 100;
+        verify_info.size = 100;
+        let result = verifier.verify_fmc((), &verify_info, ResetReason::ColdReset, false);
+        assert!(result.is_ok());
+        let result = verifier.verify_runtime((), &verify_info, false);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_load_ranges_overlap() {
+        let fmc_info = ImageVerificationExeInfo {
+            load_addr: 0x40000000,
+            size: 100,
+            ..Default::default()
+        };
+
+        // The runtime starts in the FMC
+        let mut runtime_info = ImageVerificationExeInfo {
+            load_addr: 0x40000000 + 99,
+            size: 100,
+            ..Default::default()
+        };
+        let result = ImageVerifier::<TestEnv>::verify_load_ranges(&fmc_info, &runtime_info);
+        assert_eq!(result.err(), Some(err_u32!(FmcRuntimeLoadRangeOverlap)));
+
+        // The runtime contains the FMC
+        runtime_info.load_addr = 0x40000000 - 4;
+        runtime_info.size = 200;
+        let result = ImageVerifier::<TestEnv>::verify_load_ranges(&fmc_info, &runtime_info);
+        assert_eq!(result.err(), Some(err_u32!(FmcRuntimeLoadRangeOverlap)));
+
+        // The runtime follows the FMC
+        runtime_info.load_addr = 0x40000000 + 100;
+        runtime_info.size = 100;
+        let result = ImageVerifier::<TestEnv>::verify_load_ranges(&fmc_info, &runtime_info);
+        assert!(result.is_ok());
+
+        // The runtime precedes the FMC
+        let fmc_info = ImageVerificationExeInfo {
+            load_addr: 0x40000000 + 200,
+            ..fmc_info
+        };
+        let result = ImageVerifier::<TestEnv>::verify_load_ranges(&fmc_info, &runtime_info);
+        assert!(result.is_ok());
+    }
+
+    fn aux_toc_entry(id: u32, offset: u32, size: u32) -> ImageTocEntry {
+        ImageTocEntry {
+            id,
+            r#type: ImageTocEntryType::Data.into(),
+            offset,
+            size,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_aux_success() {
+        let test_env = TestEnv::default();
+        let verifier = ImageVerifier::new(test_env);
+        let runtime = ImageTocEntry {
+            offset: 100,
+            size: 100,
+            ..Default::default()
+        };
+        let aux = [aux_toc_entry(3, 200, 50), aux_toc_entry(4, 250, 0)];
+        let result = verifier.verify_aux((), &runtime, &aux, true);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_aux_entry_invalid() {
+        let test_env = TestEnv::default();
+        let verifier = ImageVerifier::new(test_env);
+        let runtime = ImageTocEntry::default();
+
+        let mut aux = [aux_toc_entry(3, 0, 10)];
+        aux[0].r#type = ImageTocEntryType::Executable.into();
+        let result = verifier.verify_aux((), &runtime, &aux, true);
+        assert_eq!(result.err(), Some(err_u32!(AuxTocEntryInvalid)));
+
+        let aux = [aux_toc_entry(ImageTocEntryId::Runtime.into(), 0, 10)];
+        let result = verifier.verify_aux((), &runtime, &aux, true);
+        assert_eq!(result.err(), Some(err_u32!(AuxTocEntryInvalid)));
+
+        let aux = [aux_toc_entry(3, 0, 10), aux_toc_entry(3, 10, 10)];
+        let result = verifier.verify_aux((), &runtime, &aux, true);
+        assert_eq!(result.err(), Some(err_u32!(AuxTocEntryInvalid)));
+
+        let aux = [aux_toc_entry(3, u32::MAX, 10)];
+        let result = verifier.verify_aux((), &runtime, &aux, true);
+        assert_eq!(result.err(), Some(err_u32!(AuxTocEntryInvalid)));
+    }
+
+    #[test]
+    fn test_aux_incorrect_order() {
+        let test_env = TestEnv::default();
+        let verifier = ImageVerifier::new(test_env);
+        let runtime = ImageTocEntry {
+            offset: 100,
+            size: 100,
+            ..Default::default()
+        };
+
+        let aux = [aux_toc_entry(3, 150, 100)];
+        let result = verifier.verify_aux((), &runtime, &aux, true);
+        assert_eq!(result.err(), Some(err_u32!(AuxImageIncorrectOrder)));
+
+        let aux = [aux_toc_entry(3, 250, 100), aux_toc_entry(4, 300, 100)];
+        let result = verifier.verify_aux((), &runtime, &aux, true);
+        assert_eq!(result.err(), Some(err_u32!(AuxImageIncorrectOrder)));
+    }
+
+    #[test]
+    fn test_aux_digest_mismatch() {
+        let test_env = TestEnv::default();
+        let verifier = ImageVerifier::new(test_env);
+        let runtime = ImageTocEntry::default();
+        let mut aux = [aux_toc_entry(3, 0, 10)];
+        aux[0].digest = DUMMY_DATA;
+        let result = verifier.verify_aux((), &runtime, &aux, true);
+        assert_eq!(result.err(), Some(err_u32!(AuxDigestMismatch)));
+    }
+
+    #[test]
+    fn test_deferred_section_digests() {
+        let test_env = TestEnv::default();
+        let verifier = ImageVerifier::new(test_env);
+        let verify_info = ImageTocEntry {
+            digest: DUMMY_DATA,
+            load_addr: 0x40000000,
+            entry_point: 0x40000000,
+            ..Default::default()
+        };
+        let result = verifier.verify_fmc((), &verify_info, ResetReason::ColdReset, false);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap().digest, DUMMY_DATA);
+
+        let result = verifier.verify_runtime((), &verify_info, false);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap().digest, DUMMY_DATA);
+
+        let runtime = ImageTocEntry::default();
+        let mut aux = [aux_toc_entry(3, 0, 10)];
+        aux[0].digest = DUMMY_DATA;
+        let result = verifier.verify_aux((), &runtime, &aux, false);
+        assert!(result.is_ok());
+
+        // The digest saved on cold boot is still checked against the TOC
+        let result = verifier.verify_fmc((), &verify_info, ResetReason::UpdateReset, false);
+        assert_eq!(result.err(), Some(err_u32!(UpdateResetFmcDigestMismatch)));
+    }
+
+    /// 2023-01-01T00:00:00Z
+    const TIME_2023: u64 = 1672531200;
+
+    fn validity_header() -> ImageHeader {
+        ImageHeader {
+            vendor_not_before: *b"20220101000000Z",
+            vendor_not_after: *b"20241231235959Z",
+            owner_data: OwnerSignedData {
+                owner_not_before: *b"20221001000000Z",
+                owner_not_after: *b"20230630235959Z",
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_unix_time() {
+        assert_eq!(unix_time(b"19700101000000Z"), Some(0));
+        assert_eq!(unix_time(b"20230101000000Z"), Some(TIME_2023));
+        assert_eq!(unix_time(b"20000229120000Z"), Some(951825600));
+        assert_eq!(unix_time(b"99991231235959Z"), Some(253402300799));
+        assert_eq!(unix_time(b"20230101000000X"), None);
+        assert_eq!(unix_time(b"2023010100000AZ"), None);
+        assert_eq!(unix_time(b"20231301000000Z"), None);
+        assert_eq!(unix_time(b"19691231235959Z"), None);
+    }
+
+    #[test]
+    fn test_validity_not_enforced() {
+        let test_env = TestEnv::default();
+        let verifier = ImageVerifier::new(test_env);
+        let header = ImageHeader {
+            vendor_not_before: *b"20300101000000Z",
+            vendor_not_after: *b"20200101000000Z",
+            ..Default::default()
+        };
+        let result = verifier.verify_validity((), &header);
+        assert_eq!(
+            result.ok(),
+            Some((*b"20300101000000Z", *b"20200101000000Z"))
+        );
+    }
+
+    #[test]
+    fn test_validity_success() {
+        let test_env = TestEnv {
+            trusted_time: Some(TIME_2023),
+            ..Default::default()
+        };
+        let verifier = ImageVerifier::new(test_env);
+        let result = verifier.verify_validity((), &validity_header());
+        assert_eq!(
+            result.ok(),
+            Some((*b"20221001000000Z", *b"20230630235959Z"))
+        );
+
+        let header = ImageHeader {
+            owner_data: OwnerSignedData::default(),
+            ..validity_header()
+        };
+        let result = verifier.verify_validity((), &header);
+        assert_eq!(
+            result.ok(),
+            Some((*b"20220101000000Z", *b"20241231235959Z"))
+        );
+    }
+
+    #[test]
+    fn test_validity_vendor_window() {
+        let test_env = TestEnv {
+            trusted_time: Some(TIME_2023 - 366 * 86400),
+            ..Default::default()
+        };
+        let verifier = ImageVerifier::new(test_env);
+        let result = verifier.verify_validity((), &validity_header());
+        assert_eq!(result.err(), Some(err_u32!(VendorImageNotYetValid)));
+
+        let test_env = TestEnv {
+            trusted_time: Some(TIME_2023 + 2 * 366 * 86400),
+            ..Default::default()
+        };
+        let verifier = ImageVerifier::new(test_env);
+        let result = verifier.verify_validity((), &validity_header());
+        assert_eq!(result.err(), Some(err_u32!(VendorImageExpired)));
+    }
+
+    #[test]
+    fn test_validity_owner_window() {
+        let test_env = TestEnv {
+            trusted_time: Some(TIME_2023 - 100 * 86400),
+            ..Default::default()
+        };
+        let verifier = ImageVerifier::new(test_env);
+        let result = verifier.verify_validity((), &validity_header());
+        assert_eq!(result.err(), Some(err_u32!(OwnerImageNotYetValid)));
+
+        let test_env = TestEnv {
+            trusted_time: Some(TIME_2023 + 200 * 86400),
+            ..Default::default()
+        };
+        let verifier = ImageVerifier::new(test_env);
+        let result = verifier.verify_validity((), &validity_header());
+        assert_eq!(result.err(), Some(err_u32!(OwnerImageExpired)));
+    }
+
+    #[test]
+    fn test_validity_date_invalid() {
+        let test_env = TestEnv {
+            trusted_time: Some(TIME_2023),
+            ..Default::default()
+        };
+        let verifier = ImageVerifier::new(test_env);
+        let header = ImageHeader {
+            vendor_not_after: *b"2024123123595Z9",
+            ..validity_header()
+        };
+        let result = verifier.verify_validity((), &header);
+        assert_eq!(result.err(), Some(err_u32!(VendorValidityDateInvalid)));
+
+        let header = ImageHeader {
+            owner_data: OwnerSignedData {
+                owner_not_before: *b"20221301000000Z",
+                ..validity_header().owner_data
+            },
+            ..validity_header()
+        };
+        let result = verifier.verify_validity((), &header);
+        assert_eq!(result.err(), Some(err_u32!(OwnerValidityDateInvalid)));
+    }
+
+    #[test]
+    fn test_validity_half_set_window() {
+        let test_env = TestEnv::default();
+        let verifier = ImageVerifier::new(test_env);
+        let header = ImageHeader {
+            vendor_not_before: *b"20220101000000Z",
+            ..Default::default()
+        };
+        let result = verifier.verify_validity((), &header);
+        assert_eq!(result.err(), Some(err_u32!(VendorValidityDateInvalid)));
+
+        let header = ImageHeader {
+            owner_data: OwnerSignedData {
+                owner_not_after: *b"20230630235959Z",
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let result = verifier.verify_validity((), &header);
+        assert_eq!(result.err(), Some(err_u32!(OwnerValidityDateInvalid)));
+    }
+
+    #[test]
+    fn test_validity_production_requires_time() {
+        let test_env = TestEnv {
+            lifecycle: Lifecycle::Production,
+            ..Default::default()
+        };
+        let verifier = ImageVerifier::new(test_env);
+        let result = verifier.verify_validity((), &validity_header());
+        assert_eq!(result.err(), Some(err_u32!(TrustedTimeUnavailable)));
+
+        // Nothing to enforce without a window
+        let result = verifier.verify_validity((), &ImageHeader::default());
+        assert_eq!(result.ok(), Some((NULL_TIME, NULL_TIME)));
+
+        let test_env = TestEnv {
+            lifecycle: Lifecycle::Production,
+            trusted_time: Some(TIME_2023),
+            ..Default::default()
+        };
+        let verifier = ImageVerifier::new(test_env);
+        let result = verifier.verify_validity((), &validity_header());
+        assert_eq!(
+            result.ok(),
+            Some((*b"20221001000000Z", *b"20230630235959Z"))
+        );
+    }
+
     struct TestEnv {
         digest: ImageDigest,
         fmc_digest: ImageDigest,
         verify_result: bool,
+        lms_verify_result: bool,
         vendor_pub_key_digest: ImageDigest,
         vendor_pub_key_revocation: VendorPubKeyRevocation,
+        vendor_lms_pub_key_revocation: VendorPubKeyRevocation,
+        vendor_lms_pub_key_idx: u32,
         owner_pub_key_digest: ImageDigest,
         lifecycle: Lifecycle,
+        trusted_time: Option<u64>,
     }
 
     impl Default for TestEnv {
@@ -1146,10 +2036,14 @@ mod tests {
                 digest: ImageDigest::default(),
                 fmc_digest: ImageDigest::default(),
                 verify_result: false,
+                lms_verify_result: false,
                 vendor_pub_key_digest: ImageDigest::default(),
                 vendor_pub_key_revocation: VendorPubKeyRevocation::default(),
+                vendor_lms_pub_key_revocation: VendorPubKeyRevocation::default(),
+                vendor_lms_pub_key_idx: 0,
                 owner_pub_key_digest: ImageDigest::default(),
                 lifecycle: Lifecycle::Unprovisioned,
+                trusted_time: None,
             }
         }
     }
@@ -1166,6 +2060,10 @@ mod tests {
             Ok(self.digest)
         }
 
+        fn sha384_digest_data(&self, _data: &[u8]) -> CaliptraResult<ImageDigest> {
+            Ok(self.digest)
+        }
+
         fn ecc384_verify(
             &self,
             _image: Self::Image,
@@ -1176,6 +2074,16 @@ mod tests {
             Ok(self.verify_result)
         }
 
+        fn lms_verify(
+            &self,
+            _image: Self::Image,
+            _digest: &ImageDigest,
+            _pub_key: &ImageLmsPubKey,
+            _sig: &ImageLmsSignature,
+        ) -> CaliptraResult<bool> {
+            Ok(self.lms_verify_result)
+        }
+
         fn vendor_pub_key_digest(&self, _image: Self::Image) -> ImageDigest {
             self.vendor_pub_key_digest
         }
@@ -1184,6 +2092,10 @@ mod tests {
             self.vendor_pub_key_revocation
         }
 
+        fn vendor_lms_pub_key_revocation(&self, _image: Self::Image) -> VendorPubKeyRevocation {
+            self.vendor_lms_pub_key_revocation
+        }
+
         fn owner_pub_key_digest_fuses(&self) -> ImageDigest {
             self.owner_pub_key_digest
         }
@@ -1200,6 +2112,10 @@ mod tests {
             0
         }
 
+        fn vendor_lms_pub_key_idx_dv(&self) -> u32 {
+            self.vendor_lms_pub_key_idx
+        }
+
         fn owner_pub_key_digest_dv(&self) -> ImageDigest {
             self.owner_pub_key_digest
         }
@@ -1222,5 +2138,9 @@ mod tests {
                 end: 0x40000000 + (128 * 1024),
             }
         }
+
+        fn trusted_time(&self) -> Option<u64> {
+            self.trusted_time
+        }
     }
 }
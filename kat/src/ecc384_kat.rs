@@ -78,7 +78,11 @@ impl Ecc384Kat {
     fn kat_signature_generate(&self, ecc: &Ecc384) -> CaliptraResult<()> {
         let digest: Array4xN<12, 48> = Array4xN([0u32; 12]);
         let signature = ecc
-            .sign(Ecc384PrivKeyIn::from(&PRIV_KEY), &digest)
+            .sign(
+                Ecc384PrivKeyIn::from(&PRIV_KEY),
+                &digest,
+                &Array4xN::default(),
+            )
             .map_err(|_| err_u32!(SignatureGenerateFailure))?;
 
         if signature != SIGNATURE {
@@ -710,6 +710,21 @@ impl<TMmio: ureg::Mmio> RegisterBlock<TMmio> {
             )
         }
     }
+    /// LMS Key Manifest Revocation Fuse.
+    /// [br]Caliptra Access: RO
+    /// [br]SOC Access:      RWL-S
+    ///
+    /// Read value: [`u32`]; Write value: [`u32`]
+    pub fn fuse_lms_revocation(
+        &self,
+    ) -> ureg::RegRef<crate::soc_ifc::meta::FuseLmsRevocation, &TMmio> {
+        unsafe {
+            ureg::RegRef::new_with_mmio(
+                self.ptr.wrapping_add(0x340 / core::mem::size_of::<u32>()),
+                core::borrow::Borrow::borrow(&self.mmio),
+            )
+        }
+    }
     /// Stored De-Obfuscation key, not accessible by software.
     /// [br]Caliptra Access: -
     /// [br]SOC Access:      -
@@ -1797,6 +1812,7 @@ pub mod meta {
         crate::soc_ifc::regs::FuseLifeCycleReadVal,
         crate::soc_ifc::regs::FuseLifeCycleWriteVal,
     >;
+    pub type FuseLmsRevocation = ureg::ReadWriteReg32<0, u32, u32>;
     pub type InternalObfKey = ureg::WriteOnlyReg32<0, u32>;
     pub type InternalIccmLock = ureg::ReadWriteReg32<
         0,
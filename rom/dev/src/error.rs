@@ -28,6 +28,18 @@ pub enum RomComponent {
 
     /// Global Error
     Global = 0x104,
+
+    /// Warm Reset Errors
+    WarmReset = 0x105,
+
+    /// Image Verification Environment Errors
+    Verifier = 0x106,
+
+    /// Debug Unlock Errors
+    DebugUnlock = 0x107,
+
+    /// ROM Environment Errors
+    RomEnv = 0x108,
 }
 
 #[macro_export]
@@ -12,6 +12,8 @@ Abstract:
 
 --*/
 
+use caliptra_drivers::FatalErrorTrap;
+
 /// Exception Record
 #[repr(C)]
 pub(crate) struct ExceptionRecord {
@@ -38,3 +40,14 @@ pub(crate) struct ExceptionRecord {
     pub mstatus: u32,
     pub mtval: u32,
 }
+
+impl From<&ExceptionRecord> for FatalErrorTrap {
+    fn from(record: &ExceptionRecord) -> Self {
+        Self {
+            mcause: record.mcause,
+            mscause: record.mscause,
+            mepc: record.mepc,
+            mtval: record.mtval,
+        }
+    }
+}
@@ -13,11 +13,14 @@ Abstract:
 --*/
 
 use caliptra_common::{
-    DataVaultRegister, FirmwareHandoffTable, HandOffDataHandle, Vault, FHT_INVALID_HANDLE,
-    FHT_MARKER,
+    DataVaultRegister, FirmwareHandoffTable, HandOffDataHandle, Vault, FHT_INVALID_ADDRESS,
+    FHT_INVALID_HANDLE, FHT_MARKER,
 };
 use caliptra_drivers::{ColdResetEntry4, ColdResetEntry48, WarmResetEntry4, WarmResetEntry48};
-use zerocopy::AsBytes;
+use caliptra_image_types::{
+    ImageHeader, ImageManifest, MAX_AUX_TOC_ENTRY_COUNT, MIN_TOC_ENTRY_COUNT,
+};
+use zerocopy::{AsBytes, FromBytes};
 
 use crate::{
     cprintln,
@@ -26,7 +29,12 @@ use crate::{
 };
 
 const FHT_MAJOR_VERSION: u16 = 1;
-const FHT_MINOR_VERSION: u16 = 0;
+const FHT_MINOR_VERSION: u16 = 2;
+
+extern "C" {
+    static MAN1_ORG: u32;
+    static IDEVID_CERT_ORG: u8;
+}
 
 struct FhtDataStore {}
 impl FhtDataStore {
@@ -92,6 +100,15 @@ impl FhtDataStore {
                 | ColdResetEntry48::FmcPubKeyY as u32,
         )
     }
+    /// The IDevID certificate size is stored in a 32-bit DataVault sticky
+    /// register.
+    pub const fn idev_cert_size_store() -> HandOffDataHandle {
+        HandOffDataHandle(
+            ((Vault::DataVault as u32) << 12)
+                | (DataVaultRegister::Sticky32BitReg as u32) << 8
+                | ColdResetEntry4::IdevCertSize as u32,
+        )
+    }
     /// The RT SVN is stored in a 32-bit DataVault non-sticky register.
     pub fn rt_svn_data_store() -> HandOffDataHandle {
         HandOffDataHandle(
@@ -119,7 +136,44 @@ impl FhtDataStore {
     }
 }
 
+/// Locate the auxiliary TOC entries of the verified manifest
+///
+/// # Arguments
+///
+/// * `env` - ROM Environment
+///
+/// # Returns
+///
+/// * `(u32, u32)` - Address and number of the auxiliary TOC entries
+fn aux_toc(env: &RomEnv) -> (u32, u32) {
+    let manifest = unsafe { &MAN1_ORG as *const u32 as *const u8 };
+
+    // The manifest is only known to be verified if it is in MAN_1
+    if env.data_vault().map(|d| d.manifest_addr()) != manifest as u32 {
+        return (FHT_INVALID_ADDRESS, 0);
+    }
+
+    let range = ImageManifest::header_range();
+    let header = unsafe {
+        let ptr = manifest.add(range.start as usize);
+        core::slice::from_raw_parts(ptr, range.len())
+    };
+    let Some(header) = ImageHeader::read_from(header) else {
+        return (FHT_INVALID_ADDRESS, 0);
+    };
+
+    let len = header
+        .toc_len
+        .saturating_sub(MIN_TOC_ENTRY_COUNT)
+        .min(MAX_AUX_TOC_ENTRY_COUNT);
+    let addr = manifest as u32 + ImageManifest::toc_range(MIN_TOC_ENTRY_COUNT).end;
+
+    (addr, len)
+}
+
 pub fn make_fht(env: &RomEnv) -> FirmwareHandoffTable {
+    let (aux_toc_addr, aux_toc_len) = aux_toc(env);
+
     FirmwareHandoffTable {
         fht_marker: FHT_MARKER,
         fht_major_ver: FHT_MAJOR_VERSION,
@@ -144,6 +198,10 @@ pub fn make_fht(env: &RomEnv) -> FirmwareHandoffTable {
         rt_cert_sig_s_dv_hdl: FHT_INVALID_HANDLE,
         rt_tci_dv_hdl: FhtDataStore::rt_tci_data_store(),
         rt_svn_dv_hdl: FhtDataStore::rt_svn_data_store(),
+        aux_toc_addr,
+        aux_toc_len,
+        idev_cert_addr: unsafe { &IDEVID_CERT_ORG as *const u8 as u32 },
+        idev_cert_size_dv_hdl: FhtDataStore::idev_cert_size_store(),
         ..Default::default()
     }
 }
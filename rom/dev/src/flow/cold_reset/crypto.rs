@@ -13,8 +13,17 @@ Abstract:
 --*/
 
 use crate::rom_env::RomEnv;
+use caliptra_common::crypto;
+use caliptra_common::crypto::Ecc384KeyPair;
 use caliptra_drivers::*;
 use caliptra_x509::Ecdsa384Signature;
+use core::num::NonZeroUsize;
+
+/// Number of words in a CSRNG request
+const CSRNG_NUM_WORDS: NonZeroUsize = match NonZeroUsize::new(12) {
+    Some(num_words) => num_words,
+    None => unreachable!(),
+};
 
 /// ECDSA-384 Signature Adapter
 ///
@@ -34,19 +43,29 @@ impl Ecdsa384SignatureAdapter for Ecc384Signature {
     }
 }
 
-/// DICE  Layer Key Pair
-#[derive(Debug)]
-pub struct Ecc384KeyPair {
-    /// Private Key
-    pub priv_key: KeyId,
-
-    /// Public Key
-    pub pub_key: Ecc384PubKey,
-}
-
 pub enum Crypto {}
 
 impl Crypto {
+    /// Generate random words with the CSRNG
+    ///
+    /// # Arguments
+    ///
+    /// * `env` - ROM Environment
+    ///
+    /// # Returns
+    ///
+    /// * `Array4x12` - Random words
+    pub fn csrng_generate(env: &RomEnv) -> CaliptraResult<Array4x12> {
+        let mut words = [0u32; 12];
+        env.csrng().map(|c| -> CaliptraResult<()> {
+            for (dst, src) in words.iter_mut().zip(c.generate(CSRNG_NUM_WORDS)?) {
+                *dst = src;
+            }
+            Ok(())
+        })?;
+        Ok(Array4x12::from(words))
+    }
+
     /// Calculate SHA1 Digest
     ///
     /// # Arguments
@@ -95,14 +114,14 @@ impl Crypto {
         Ok(digest)
     }
 
-    /// Calculate HMAC-348
+    /// Calculate HMAC-384
     ///
     /// # Arguments
     ///
-    /// * `env` - ROM Environment
-    /// * `key` - HMAC384 key
+    /// * `env`  - ROM Environment
+    /// * `key`  - HMAC384 key
     /// * `data` - Input data to hash
-    /// * `tag` - Key slot to store the tag
+    /// * `tag`  - Key slot to store the tag
     ///
     /// # Returns
     ///
@@ -113,24 +132,16 @@ impl Crypto {
         data: Hmac384Data,
         tag: KeyId,
     ) -> CaliptraResult<KeyId> {
-        // Tag
-        let mut usage = KeyUsage::default();
-        usage.set_hmac_key(true);
-        usage.set_ecc_key_gen_seed(true);
-        let tag_args = Hmac384Tag::Key(KeyWriteArgs::new(tag, usage));
-
-        // Calculate the CDI
-        env.hmac384().map(|h| h.hmac(key, data, tag_args))?;
-
-        Ok(tag)
+        env.hmac384()
+            .map(|h| crypto::hmac384_mac(h, key, data, tag))
     }
 
     /// Generate ECC Key Pair
     ///
     /// # Arguments
     ///
-    /// * `env`   - ROM Environment
-    /// * `seed` - Key slot to retrieve the seed from
+    /// * `env`      - ROM Environment
+    /// * `seed`     - Key slot to retrieve the seed from
     /// * `priv_key` - Key slot to store the private key
     ///
     /// # Returns
@@ -141,20 +152,9 @@ impl Crypto {
         seed: KeyId,
         priv_key: KeyId,
     ) -> CaliptraResult<Ecc384KeyPair> {
-        // [TODO] Add Nonce to the ecc384_key_gen function
-        let seed = Ecc384Seed::Key(KeyReadArgs::new(seed));
-
-        let mut usage = KeyUsage::default();
-        usage.set_ecc_private_key(true);
-
-        let key_out = Ecc384PrivKeyOut::Key(KeyWriteArgs::new(priv_key, usage));
-
-        Ok(Ecc384KeyPair {
-            priv_key,
-            pub_key: env
-                .ecc384()
-                .map(|e| e.key_pair(seed, &Array4x12::default(), key_out))?,
-        })
+        let iv = Self::csrng_generate(env)?;
+        env.ecc384()
+            .map(|e| crypto::ecc384_key_gen(e, seed, priv_key, &iv))
     }
 
     /// Sign data using ECC Private Key
@@ -163,9 +163,9 @@ impl Crypto {
     ///
     /// # Arguments
     ///
-    /// * `env` - ROM Environment
+    /// * `env`      - ROM Environment
     /// * `priv_key` - Key slot to retrieve the private key
-    /// * `data` - Input data to hash
+    /// * `data`     - Input data to hash
     ///
     /// # Returns
     ///
@@ -175,10 +175,11 @@ impl Crypto {
         priv_key: KeyId,
         data: &[u8],
     ) -> CaliptraResult<Ecc384Signature> {
-        let digest = Self::sha384_digest(env, data)?;
-        let priv_key_args = KeyReadArgs::new(priv_key);
-        let priv_key = Ecc384PrivKeyIn::Key(priv_key_args);
-        env.ecc384().map(|ecc| ecc.sign(priv_key, &digest))
+        let iv = Self::csrng_generate(env)?;
+        env.sha384().map(|sha| {
+            env.ecc384()
+                .map(|ecc| crypto::ecdsa384_sign(sha, ecc, priv_key, data, &iv))
+        })
     }
 
     /// Verify the ECC Signature
@@ -187,10 +188,10 @@ impl Crypto {
     ///
     /// # Arguments
     ///
-    /// * `env` - ROM Environment
+    /// * `env`     - ROM Environment
     /// * `pub_key` - Public key to verify the signature
-    /// * `data` - Input data to hash
-    /// * `sig` - Signature to verify
+    /// * `data`    - Input data to hash
+    /// * `sig`     - Signature to verify
     ///
     /// # Returns
     ///
@@ -201,7 +202,9 @@ impl Crypto {
         data: &[u8],
         sig: &Ecc384Signature,
     ) -> CaliptraResult<bool> {
-        let digest = Self::sha384_digest(env, data)?;
-        env.ecc384().map(|e| e.verify(pub_key, &digest, sig))
+        env.sha384().map(|sha| {
+            env.ecc384()
+                .map(|ecc| crypto::ecdsa384_verify(sha, ecc, pub_key, data, sig))
+        })
     }
 }
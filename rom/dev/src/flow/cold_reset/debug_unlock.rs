@@ -0,0 +1,240 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+    debug_unlock.rs
+
+Abstract:
+
+    File contains the implementation of the debug unlock flow used to
+    re-enable debug on a locked device in Manufacturing or Production
+    lifecycle.
+
+--*/
+
+use core::mem::{size_of, ManuallyDrop};
+use core::num::NonZeroU32;
+
+use super::crypto::Crypto;
+use crate::cprintln;
+use crate::{rom_env::RomEnv, rom_err_def};
+use caliptra_api::mailbox::DEBUG_UNLOCK_LABEL;
+use caliptra_api::CommandId;
+use caliptra_drivers::{
+    report_fw_error_non_fatal, Array4x12, CaliptraResult, Ecc384PubKey, Ecc384Signature, Lifecycle,
+    MailboxRecvTxn,
+};
+use caliptra_image_types::{ImageEccSignature, ImageOwnerPubKeys};
+use caliptra_registers::mbox::enums::MboxStatusE;
+use zerocopy::{AsBytes, FromBytes};
+
+rom_err_def! {
+    DebugUnlock,
+    DebugUnlockErr
+    {
+        OwnerKeyNotProvisioned = 0x1,
+        ChallengeRejected = 0x2,
+        InvalidTokenSize = 0x3,
+        TokenReadFailure = 0x4,
+        OwnerKeyMismatch = 0x5,
+        InvalidSignature = 0x6,
+    }
+}
+
+/// Debug unlock token sent by the SoC with the DEBUG_UNLOCK_TOKEN command
+#[repr(C)]
+#[derive(AsBytes, FromBytes, Default, Debug)]
+struct DebugUnlockToken {
+    /// Owner public keys, must match the owner public key hash fuses
+    owner_pub_keys: ImageOwnerPubKeys,
+
+    /// Signature over `DEBUG_UNLOCK_LABEL` and the challenge with the owner
+    /// ECC key
+    signature: ImageEccSignature,
+}
+
+/// Debug Unlock Flow
+pub enum DebugUnlockFlow {}
+
+impl DebugUnlockFlow {
+    /// Execute the debug unlock flow
+    ///
+    /// The outcome is always recorded in the data vault, so that warm and
+    /// update resets see the state established on cold reset.
+    ///
+    /// # Arguments
+    ///
+    /// * `env` - ROM Environment
+    pub fn run(env: &RomEnv) -> CaliptraResult<()> {
+        let unlocked = Self::requested(env) && Self::unlock(env)?;
+        env.data_vault().map(|d| d.set_debug_unlocked(unlocked));
+        Ok(())
+    }
+
+    /// Returns true if debug unlock was requested and can be granted
+    ///
+    /// # Arguments
+    ///
+    /// * `env` - ROM Environment
+    fn requested(env: &RomEnv) -> bool {
+        if !env.mfg_state().map(|m| m.debug_unlock_req()) {
+            return false;
+        }
+
+        let lifecycle = env.dev_state().map(|d| d.lifecycle());
+        let debug_locked = env.dev_state().map(|d| d.debug_locked());
+        if !matches!(lifecycle, Lifecycle::Manufacturing | Lifecycle::Production) || !debug_locked {
+            cprintln!("[dbg] Ignoring debug unlock request, device is not locked");
+            return false;
+        }
+
+        true
+    }
+
+    /// Verify a debug unlock token from the SoC
+    ///
+    /// # Arguments
+    ///
+    /// * `env` - ROM Environment
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - True if the token was verified, false if the SoC declined
+    ///   the unlock or the token was rejected
+    fn unlock(env: &RomEnv) -> CaliptraResult<bool> {
+        cprintln!("[dbg] ++");
+
+        let owner_pk_hash = env.fuse_bank().map(|f| f.owner_pub_key_hash());
+        if owner_pk_hash == Array4x12::default() {
+            raise_err!(OwnerKeyNotProvisioned)
+        }
+
+        // Generate a fresh challenge with the CSRNG
+        let challenge: [u32; 12] = Crypto::csrng_generate(env)?.into();
+        Self::send_challenge(env, challenge.as_bytes())?;
+
+        let Some(mut txn) = Self::recv_token(env)? else {
+            cprintln!("[dbg] Debug unlock declined");
+            return Ok(false);
+        };
+        if txn.dlen() != size_of::<DebugUnlockToken>() as u32 {
+            return Self::reject_token(txn, err_u32!(InvalidTokenSize));
+        }
+
+        let mut buf = [0u32; size_of::<DebugUnlockToken>() / 4];
+        txn.copy_request(&mut buf)?;
+        let token =
+            DebugUnlockToken::read_from(buf.as_bytes()).ok_or(err_u32!(TokenReadFailure))?;
+
+        let digest = Crypto::sha384_digest(env, token.owner_pub_keys.as_bytes())?;
+        if digest != owner_pk_hash {
+            return Self::reject_token(txn, err_u32!(OwnerKeyMismatch));
+        }
+
+        let pub_key = &token.owner_pub_keys.ecc_pub_key;
+        let pub_key = Ecc384PubKey {
+            x: pub_key.x.into(),
+            y: pub_key.y.into(),
+        };
+        let sig = Ecc384Signature {
+            r: token.signature.r.into(),
+            s: token.signature.s.into(),
+        };
+        // The owner key signs the label followed by the challenge
+        let mut signed = [0u8; DEBUG_UNLOCK_LABEL.len() + size_of::<[u32; 12]>()];
+        let (label, data) = signed.split_at_mut(DEBUG_UNLOCK_LABEL.len());
+        label.copy_from_slice(&DEBUG_UNLOCK_LABEL);
+        data.copy_from_slice(challenge.as_bytes());
+        if !Crypto::ecdsa384_verify(env, &pub_key, &signed, &sig)? {
+            return Self::reject_token(txn, err_u32!(InvalidSignature));
+        }
+
+        txn.complete(true)?;
+
+        cprintln!("[dbg] Debug unlocked");
+        cprintln!("[dbg] --");
+
+        Ok(true)
+    }
+
+    /// Fail the token command and report `err` as a non-fatal error
+    ///
+    /// The cold boot continues with the device debug locked.
+    ///
+    /// # Arguments
+    ///
+    /// * `txn` - Token mailbox transaction
+    /// * `err` - Reason the token was rejected
+    fn reject_token(
+        mut txn: ManuallyDrop<MailboxRecvTxn>,
+        err: NonZeroU32,
+    ) -> CaliptraResult<bool> {
+        cprintln!(
+            "[dbg] Debug unlock token rejected: 0x{:08X}",
+            u32::from(err)
+        );
+        report_fw_error_non_fatal(err.into());
+        txn.complete(false)?;
+        Ok(false)
+    }
+
+    /// Send the challenge to the SoC
+    ///
+    /// # Arguments
+    ///
+    /// * `env`       - ROM Environment
+    /// * `challenge` - Challenge to send
+    fn send_challenge(env: &RomEnv, challenge: &[u8]) -> CaliptraResult<()> {
+        loop {
+            if let Some(mut txn) = env.mbox().map(|m| m.try_start_send_txn()) {
+                txn.send_request(CommandId::DebugUnlockChallenge.into(), challenge)?;
+
+                // Wait for the SoC to consume the challenge
+                while !txn.is_response_ready() {}
+
+                let accepted = txn.status() == MboxStatusE::CmdComplete;
+
+                // Release access to the mailbox
+                txn.complete()?;
+
+                if !accepted {
+                    raise_err!(ChallengeRejected)
+                }
+
+                cprintln!("[dbg] Challenge sent");
+                break Ok(());
+            }
+        }
+    }
+
+    /// Wait for the debug unlock token
+    ///
+    /// # Arguments
+    ///
+    /// * `env` - ROM Environment
+    ///
+    /// # Returns
+    ///
+    /// Mailbox transaction handle, or `None` if the SoC sent any other
+    /// command, declining the unlock. The transaction is ManuallyDrop so that
+    /// it is only failed by the fatal error handler, after the error has been
+    /// reported.
+    fn recv_token(env: &RomEnv) -> CaliptraResult<Option<ManuallyDrop<MailboxRecvTxn>>> {
+        loop {
+            if let Some(mut txn) = env.mbox().map(|m| m.try_start_recv_txn()) {
+                if txn.cmd() != u32::from(CommandId::DebugUnlockToken) {
+                    cprintln!(
+                        "[dbg] Command 0x{:08x} received instead of token",
+                        txn.cmd()
+                    );
+                    txn.complete(false)?;
+                    break Ok(None);
+                }
+
+                break Ok(Some(ManuallyDrop::new(txn)));
+            }
+        }
+    }
+}
@@ -16,7 +16,7 @@ Abstract:
 use crate::rom_env::RomEnv;
 use caliptra_drivers::{CaliptraResult, Ecc384PubKey, KeyId};
 
-use super::crypto::Ecc384KeyPair;
+use caliptra_common::crypto::Ecc384KeyPair;
 
 /// DICE Layer Input
 #[derive(Debug)]
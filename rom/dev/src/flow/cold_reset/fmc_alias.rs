@@ -15,21 +15,23 @@ Abstract:
 
 use core::mem::ManuallyDrop;
 
-use super::crypto::{Crypto, Ecc384KeyPair};
+use super::crypto::Crypto;
 use super::dice::{DiceInput, DiceLayer, DiceOutput};
 use super::x509::X509;
 use crate::flow::cold_reset::{copy_tbs, TbsType};
 use crate::flow::cold_reset::{KEY_ID_CDI, KEY_ID_FMC_PRIV_KEY};
 use crate::print::HexBytes;
-use crate::verifier::RomImageVerificationEnv;
+use crate::verifier::{load_image_chunks, load_image_section, RomImageVerificationEnv};
 use crate::{cprint, cprintln, pcr};
 use crate::{rom_env::RomEnv, rom_err_def};
+use caliptra_api::CommandId;
+use caliptra_common::crypto::Ecc384KeyPair;
 use caliptra_common::dice;
 use caliptra_drivers::{
     Array4x12, CaliptraResult, ColdResetEntry4, ColdResetEntry48, Hmac384Data, Hmac384Key, KeyId,
     KeyReadArgs, Lifecycle, MailboxRecvTxn, ResetReason, WarmResetEntry4, WarmResetEntry48,
 };
-use caliptra_image_types::{ImageManifest, IMAGE_BYTE_SIZE};
+use caliptra_image_types::{ImageManifest, IMAGE_CHUNK_BYTE_SIZE, IMAGE_MANIFEST_BYTE_SIZE};
 use caliptra_image_verify::{ImageVerificationInfo, ImageVerifier};
 use caliptra_x509::{FmcAliasCertTbs, FmcAliasCertTbsParams, NotAfter, NotBefore};
 use zerocopy::{AsBytes, FromBytes};
@@ -68,20 +70,32 @@ impl DiceLayer for FmcAliasLayer {
         // Load the manifest
         let manifest = Self::load_manifest(&txn)?;
 
+        // Images larger than the mailbox are sent as the manifest alone,
+        // followed by the image sections in separate transactions
+        let chunked = txn.dlen() as usize == IMAGE_MANIFEST_BYTE_SIZE;
+
         // Verify the image
-        let info = Self::verify_image(env, &manifest)?;
+        let info = Self::verify_image(env, &manifest, txn.dlen(), chunked)?;
 
-        // populate data vault
-        Self::populate_data_vault(env, &info);
+        if chunked {
+            // Release the mailbox for the image sections
+            txn.complete(true)?;
 
-        // Extend PCR0
-        pcr::extend_pcr0(env)?;
+            // Load and verify the image sections
+            load_image_chunks(env, &manifest)?;
+        } else {
+            // Load the image
+            Self::load_image(env, &manifest)?;
 
-        // Load the image
-        Self::load_image(env, &manifest, &txn)?;
+            // Complete the mailbox transaction indicating success.
+            txn.complete(true)?;
+        }
+
+        // The whole image is verified, populate data vault
+        Self::populate_data_vault(env, &info);
 
-        // Complete the mailbox transaction indicating success.
-        txn.complete(true)?;
+        // Extend PCR0
+        pcr::extend_pcr0(env, manifest.aux_toc_entries())?;
 
         // At this point PCR0 & PCR1 must have the same value. We use the value
         // of PCR1 as the measurement for deriving the CDI
@@ -109,26 +123,12 @@ impl DiceLayer for FmcAliasLayer {
             subj_key_id,
         };
 
-        // if there is a valid value in the manifest for the not_before and not_after
-        // we take it from there.
-
+        // Use the validity window of the verified image if it has one
         let mut nb = NotBefore::default();
         let mut nf = NotAfter::default();
-        let null_time = [0u8; 15];
-
-        if manifest.header.vendor_not_after != null_time
-            && manifest.header.vendor_not_before != null_time
-        {
-            nf.not_after = manifest.header.vendor_not_after;
-            nb.not_before = manifest.header.vendor_not_before;
-        }
-
-        //The owner values takes preference
-        if manifest.header.owner_data.owner_not_after != null_time
-            && manifest.header.owner_data.owner_not_before != null_time
-        {
-            nf.not_after = manifest.header.owner_data.owner_not_after;
-            nb.not_before = manifest.header.owner_data.owner_not_before;
+        if info.not_before != [0u8; 15] {
+            nb.not_before = info.not_before;
+            nf.not_after = info.not_after;
         }
 
         // Generate Local Device ID Certificate
@@ -141,9 +141,6 @@ impl DiceLayer for FmcAliasLayer {
 }
 
 impl FmcAliasLayer {
-    /// Download firmware mailbox command ID.
-    const MBOX_DOWNLOAD_FIRMWARE_CMD_ID: u32 = 0x46574C44;
-
     /// Download the image
     ///
     /// # Arguments
@@ -164,7 +161,7 @@ impl FmcAliasLayer {
         loop {
             cprint!(".");
             if let Some(mut txn) = env.mbox().map(|m| m.try_start_recv_txn()) {
-                if txn.cmd() != Self::MBOX_DOWNLOAD_FIRMWARE_CMD_ID {
+                if txn.cmd() != u32::from(CommandId::FirmwareLoad) {
                     cprintln!("Invalid command 0x{:08x} received", txn.cmd());
                     txn.complete(false)?;
                     continue;
@@ -173,7 +170,7 @@ impl FmcAliasLayer {
                 // transaction will be completed by either report_error() (on
                 // failure) or by a manual complete call upon success.
                 let txn = ManuallyDrop::new(txn);
-                if txn.dlen() == 0 || txn.dlen() > IMAGE_BYTE_SIZE as u32 {
+                if txn.dlen() == 0 || txn.dlen() > IMAGE_CHUNK_BYTE_SIZE as u32 {
                     cprintln!("Invalid Image of size {} bytes" txn.dlen());
                     raise_err!(InvalidImageSize);
                 }
@@ -205,19 +202,32 @@ impl FmcAliasLayer {
     ///
     /// # Arguments
     ///
-    /// * `env` - ROM Environment
+    /// * `env`        - ROM Environment
+    /// * `manifest`   - Manifest
+    /// * `image_size` - Size of the image in the mailbox
+    /// * `chunked`    - Only the manifest is in the mailbox
     fn verify_image(
         env: &RomEnv,
         manifest: &ImageManifest,
+        image_size: u32,
+        chunked: bool,
     ) -> CaliptraResult<ImageVerificationInfo> {
-        let venv = RomImageVerificationEnv::new(env);
+        let venv = RomImageVerificationEnv::new(env, image_size);
         let verifier = ImageVerifier::new(venv);
-        let info = verifier.verify(manifest, (), ResetReason::ColdReset)?;
+        let info = if chunked {
+            verifier.verify_manifest(manifest, (), ResetReason::ColdReset)?
+        } else {
+            verifier.verify(manifest, (), ResetReason::ColdReset)?
+        };
 
         cprintln!(
             "[afmc] Image verified using Vendor ECC Key Index {}",
             info.vendor_ecc_pub_key_idx
         );
+        cprintln!(
+            "[afmc] Image verified using Vendor LMS Key Index {}",
+            info.vendor_lms_pub_key_idx
+        );
 
         Ok(info)
     }
@@ -228,12 +238,7 @@ impl FmcAliasLayer {
     ///
     /// * `env`      - ROM Environment
     /// * `manifest` - Manifest
-    /// * `txn`      - Mailbox Receive Transaction
-    fn load_image(
-        _env: &RomEnv,
-        manifest: &ImageManifest,
-        txn: &MailboxRecvTxn,
-    ) -> CaliptraResult<()> {
+    fn load_image(_env: &RomEnv, manifest: &ImageManifest) -> CaliptraResult<()> {
         cprintln!(
             "[afmc] Loading FMC at address 0x{:08x} len {}",
             manifest.fmc.load_addr,
@@ -245,7 +250,7 @@ impl FmcAliasLayer {
             core::slice::from_raw_parts_mut(addr, manifest.fmc.size as usize / 4)
         };
 
-        txn.copy_request(fmc_dest)?;
+        load_image_section(&manifest.fmc, fmc_dest)?;
 
         cprintln!(
             "[afmc] Loading Runtime at address 0x{:08x} len {}",
@@ -258,7 +263,7 @@ impl FmcAliasLayer {
             core::slice::from_raw_parts_mut(addr, manifest.runtime.size as usize / 4)
         };
 
-        txn.copy_request(runtime_dest)?;
+        load_image_section(&manifest.runtime, runtime_dest)?;
 
         Ok(())
     }
@@ -297,6 +302,13 @@ impl FmcAliasLayer {
             )
         });
 
+        env.data_vault().map(|d| {
+            d.write_cold_reset_entry4(
+                ColdResetEntry4::VendorLmsPubKeyIndex,
+                info.vendor_lms_pub_key_idx,
+            )
+        });
+
         env.data_vault().map(|d| {
             d.write_warm_reset_entry48(WarmResetEntry48::RtTci, &info.runtime.digest.into())
         });
@@ -373,7 +385,8 @@ impl FmcAliasLayer {
 
         let flags = Self::make_flags(
             env.dev_state().map(|d| d.lifecycle()),
-            env.dev_state().map(|d| d.debug_locked()),
+            env.dev_state().map(|d| d.debug_locked())
+                && !env.data_vault().map(|d| d.debug_unlocked()),
         );
 
         let svn = env.data_vault().map(|d| d.fmc_svn()) as u8;
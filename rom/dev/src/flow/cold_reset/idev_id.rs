@@ -21,8 +21,12 @@ use crate::flow::cold_reset::{KEY_ID_CDI, KEY_ID_FE, KEY_ID_IDEVID_PRIV_KEY, KEY
 use crate::print::HexBytes;
 use crate::rom_env::RomEnv;
 use crate::rom_err_def;
+use caliptra_api::CommandId;
+use caliptra_common::crypto::Ecc384KeyPair;
 use caliptra_drivers::*;
 use caliptra_x509::*;
+use core::mem::{size_of, ManuallyDrop};
+use zerocopy::AsBytes;
 
 type InitDevIdCsr<'a> = Certificate<'a, { MAX_CSR_SIZE }>;
 
@@ -41,6 +45,29 @@ const IDEVID_CDI_KEY: Array4x12 = Array4xN::<12, 48>([
 /// Maximum Certificate Signing Request Size
 const MAX_CSR_SIZE: usize = 512;
 
+/// Maximum IDevID Certificate Size, must match `IDEVID_CERT_SIZE` in rom.ld
+const MAX_CERT_SIZE: usize = 1024;
+
+/// Manufacturing HSM public key size in bytes
+const HSM_PUB_KEY_SIZE: usize = 2 * SCALAR_SIZE;
+
+/// ECC-384 scalar size in bytes
+const SCALAR_SIZE: usize = size_of::<Ecc384Scalar>();
+
+/// DER tags walked while locating the certificate subject public key
+const DER_TAG_SEQUENCE: u8 = 0x30;
+const DER_TAG_INTEGER: u8 = 0x02;
+const DER_TAG_BIT_STRING: u8 = 0x03;
+const DER_TAG_VERSION: u8 = 0xA0;
+
+/// DER encoded ecdsa-with-SHA384 algorithm identifier contents
+const ECDSA_WITH_SHA384_OID: [u8; 10] =
+    [0x06, 0x08, 0x2A, 0x86, 0x48, 0xCE, 0x3D, 0x04, 0x03, 0x03];
+
+extern "C" {
+    static mut IDEVID_CERT_ORG: u8;
+}
+
 rom_err_def! {
     InitDevId,
     InitDevIdErr
@@ -49,6 +76,12 @@ rom_err_def! {
         CsrBuilderBuild= 0x2,
         CsrInvalid = 0x3,
         CsrVerify = 0x4,
+        CertHsmIdNotProvisioned = 0x5,
+        CertHsmIdMismatch = 0x6,
+        CertInvalidSize = 0x7,
+        CertInvalid = 0x8,
+        CertPubKeyMismatch = 0x9,
+        CertInvalidSignature = 0xA,
     }
 }
 
@@ -72,6 +105,10 @@ impl DiceLayer for InitDevIdLayer {
         cprintln!("[idev] SUBJECT.KEYID = {}", KEY_ID_IDEVID_PRIV_KEY as u8);
         cprintln!("[idev] UDS.KEYID = {}", KEY_ID_UDS as u8);
 
+        // The SoC may clear the manufacturing service register once it has
+        // downloaded the CSR, so latch the provisioning request first
+        let provision_cert = env.mfg_state().map(|m| m.provision_idev_id_cert());
+
         // Decrypt the UDS
         Self::decrypt_uds(env, KEY_ID_UDS)?;
 
@@ -103,6 +140,14 @@ impl DiceLayer for InitDevIdLayer {
         // Generate the Initial DevID Certificate Signing Request (CSR)
         Self::generate_csr(env, &output)?;
 
+        // Accept the Initial DevID Certificate signed by the manufacturing HSM
+        let cert_size = if provision_cert {
+            Self::provision_cert(env, &output.subj_key_pair.pub_key)?
+        } else {
+            0
+        };
+        env.data_vault().map(|d| d.set_idev_cert_size(cert_size));
+
         cprintln!("[idev] --");
 
         // Return the DICE Layer Output
@@ -269,7 +314,10 @@ impl InitDevIdLayer {
             // Create Mailbox send transaction to send the CSR
             if let Some(mut txn) = env.mbox().map(|m| m.try_start_send_txn()) {
                 // Copy the CSR to mailbox
-                txn.send_request(0, csr.get().ok_or(err_u32!(CsrInvalid))?)?;
+                txn.send_request(
+                    CommandId::GetIdevCsr.into(),
+                    csr.get().ok_or(err_u32!(CsrInvalid))?,
+                )?;
 
                 // Signal the JTAG/SOC that Initial Device ID CSR is ready
                 env.flow_status().map(|f| f.set_idevid_csr_ready());
@@ -287,4 +335,252 @@ impl InitDevIdLayer {
             }
         }
     }
+
+    /// Receive and store the Initial Device ID Certificate signed by the
+    /// manufacturing HSM
+    ///
+    /// # Arguments
+    ///
+    /// * `env`     - ROM Environment
+    /// * `pub_key` - Initial Device ID public key
+    ///
+    /// # Returns
+    ///
+    /// * `u32` - Size of the stored certificate, zero if none was accepted
+    fn provision_cert(env: &RomEnv, pub_key: &Ecc384PubKey) -> CaliptraResult<u32> {
+        if env.dev_state().map(|d| d.lifecycle()) != Lifecycle::Manufacturing {
+            cprintln!("[idev] Ignoring cert provisioning, device is not in manufacturing");
+            return Ok(0);
+        }
+
+        let hsm_id = env.fuse_bank().map(|f| f.idevid_manuf_hsm_id());
+        if hsm_id == Array4x4::default() {
+            raise_err!(CertHsmIdNotProvisioned)
+        }
+
+        cprintln!("[idev] Cert provisioning requested");
+
+        let mut txn = Self::recv_cert(env)?;
+        let mut buf = [0u32; (HSM_PUB_KEY_SIZE + MAX_CERT_SIZE) / size_of::<u32>()];
+        txn.copy_request(&mut buf)?;
+
+        let (hsm_pub_key, cert) = buf
+            .as_bytes()
+            .get(..txn.dlen() as usize)
+            .and_then(|req| Some((req.get(..HSM_PUB_KEY_SIZE)?, req.get(HSM_PUB_KEY_SIZE..)?)))
+            .ok_or(err_u32!(CertInvalidSize))?;
+
+        // The fuses hold the leading 128 bits of the SHA-384 digest of the
+        // HSM public key
+        let digest: [u32; 12] = Crypto::sha384_digest(env, hsm_pub_key)?.into();
+        if Array4x4::from([digest[0], digest[1], digest[2], digest[3]]) != hsm_id {
+            raise_err!(CertHsmIdMismatch)
+        }
+
+        // The certificate must be signed by the fused HSM key
+        let (x, y) = hsm_pub_key.split_at(SCALAR_SIZE);
+        let hsm_pub_key = Ecc384PubKey {
+            x: scalar_from_be_bytes(x).ok_or(err_u32!(CertInvalidSize))?,
+            y: scalar_from_be_bytes(y).ok_or(err_u32!(CertInvalidSize))?,
+        };
+        let (tbs, sig) = Self::cert_signature(cert).ok_or(err_u32!(CertInvalid))?;
+        if !Crypto::ecdsa384_verify(env, &hsm_pub_key, tbs, &sig)? {
+            raise_err!(CertInvalidSignature)
+        }
+
+        // The certificate must certify the freshly derived IDevID key
+        let cert_pub_key = Self::cert_pub_key(cert).ok_or(err_u32!(CertInvalid))?;
+        if cert_pub_key != pub_key.to_der() {
+            raise_err!(CertPubKeyMismatch)
+        }
+
+        let dst = unsafe {
+            let ptr = &mut IDEVID_CERT_ORG as *mut u8;
+            core::slice::from_raw_parts_mut(ptr, cert.len())
+        };
+        dst.copy_from_slice(cert);
+
+        txn.complete(true)?;
+
+        cprintln!("[idev] Cert provisioned");
+
+        Ok(cert.len() as u32)
+    }
+
+    /// Wait for the Initial Device ID Certificate
+    ///
+    /// # Arguments
+    ///
+    /// * `env` - ROM Environment
+    ///
+    /// # Returns
+    ///
+    /// Mailbox transaction handle. The transaction is ManuallyDrop so that it
+    /// is only failed by the fatal error handler, after the error has been
+    /// reported.
+    fn recv_cert(env: &RomEnv) -> CaliptraResult<ManuallyDrop<MailboxRecvTxn>> {
+        loop {
+            if let Some(mut txn) = env.mbox().map(|m| m.try_start_recv_txn()) {
+                if txn.cmd() != u32::from(CommandId::ProvisionIdevCert) {
+                    cprintln!("[idev] Invalid command 0x{:08x} received", txn.cmd());
+                    txn.complete(false)?;
+                    continue;
+                }
+
+                let txn = ManuallyDrop::new(txn);
+                let dlen = txn.dlen() as usize;
+                if dlen <= HSM_PUB_KEY_SIZE || dlen > HSM_PUB_KEY_SIZE + MAX_CERT_SIZE {
+                    raise_err!(CertInvalidSize)
+                }
+
+                break Ok(txn);
+            }
+        }
+    }
+
+    /// Locate the to-be-signed certificate and the ECDSA-384 signature of a
+    /// DER X.509 certificate
+    ///
+    /// # Arguments
+    ///
+    /// * `cert` - DER encoded certificate
+    ///
+    /// # Returns
+    ///
+    /// * `(&[u8], Ecc384Signature)` - DER encoded TBS certificate and its
+    ///   signature
+    fn cert_signature(cert: &[u8]) -> Option<(&[u8], Ecc384Signature)> {
+        let (cert, rest) = der_expect(cert, DER_TAG_SEQUENCE)?;
+        if !rest.is_empty() {
+            return None;
+        }
+
+        // The signature covers the whole TBS element, tag and length included
+        let (_, rest) = der_expect(cert, DER_TAG_SEQUENCE)?;
+        let tbs = &cert[..cert.len() - rest.len()];
+
+        let (alg, rest) = der_expect(rest, DER_TAG_SEQUENCE)?;
+        if alg != ECDSA_WITH_SHA384_OID {
+            return None;
+        }
+
+        let (sig, rest) = der_expect(rest, DER_TAG_BIT_STRING)?;
+        if !rest.is_empty() {
+            return None;
+        }
+        let sig = match sig.split_first() {
+            Some((0, sig)) => sig,
+            _ => return None,
+        };
+
+        let (sig, _) = der_expect(sig, DER_TAG_SEQUENCE)?;
+        let (r, sig) = der_expect(sig, DER_TAG_INTEGER)?;
+        let (s, _) = der_expect(sig, DER_TAG_INTEGER)?;
+
+        Some((
+            tbs,
+            Ecc384Signature {
+                r: scalar_from_be_bytes(r)?,
+                s: scalar_from_be_bytes(s)?,
+            },
+        ))
+    }
+
+    /// Locate the subject public key of a DER X.509 certificate
+    ///
+    /// # Arguments
+    ///
+    /// * `cert` - DER encoded certificate
+    ///
+    /// # Returns
+    ///
+    /// * `&[u8]` - Uncompressed subject public key point
+    fn cert_pub_key(cert: &[u8]) -> Option<&[u8]> {
+        let (cert, rest) = der_expect(cert, DER_TAG_SEQUENCE)?;
+        if !rest.is_empty() {
+            return None;
+        }
+        let (tbs, _) = der_expect(cert, DER_TAG_SEQUENCE)?;
+
+        // Skip the optional version
+        let tbs = match der_expect(tbs, DER_TAG_VERSION) {
+            Some((_, rest)) => rest,
+            None => tbs,
+        };
+
+        // Skip the serial number, signature algorithm, issuer, validity and
+        // subject
+        let (_, tbs) = der_expect(tbs, DER_TAG_INTEGER)?;
+        let (_, tbs) = der_expect(tbs, DER_TAG_SEQUENCE)?;
+        let (_, tbs) = der_expect(tbs, DER_TAG_SEQUENCE)?;
+        let (_, tbs) = der_expect(tbs, DER_TAG_SEQUENCE)?;
+        let (_, tbs) = der_expect(tbs, DER_TAG_SEQUENCE)?;
+
+        let (spki, _) = der_expect(tbs, DER_TAG_SEQUENCE)?;
+        let (_, spki) = der_expect(spki, DER_TAG_SEQUENCE)?;
+        let (key, _) = der_expect(spki, DER_TAG_BIT_STRING)?;
+
+        // The key is a whole number of bytes
+        match key.split_first() {
+            Some((0, key)) => Some(key),
+            _ => None,
+        }
+    }
+}
+
+/// Convert a big endian integer to an ECC-384 scalar
+///
+/// # Arguments
+///
+/// * `int` - Big endian integer, possibly shorter than a scalar or with
+///   leading zero bytes, as in a DER INTEGER
+///
+/// # Returns
+///
+/// * `Ecc384Scalar` - Scalar, None if the integer does not fit
+fn scalar_from_be_bytes(int: &[u8]) -> Option<Ecc384Scalar> {
+    let start = int.iter().position(|&b| b != 0).unwrap_or(int.len());
+    let int = &int[start..];
+
+    let mut scalar = [0u8; SCALAR_SIZE];
+    let offset = SCALAR_SIZE.checked_sub(int.len())?;
+    scalar[offset..].copy_from_slice(int);
+    Some(Ecc384Scalar::from(scalar))
+}
+
+/// Split a DER element with the expected tag off the front of `data`
+///
+/// # Arguments
+///
+/// * `data` - DER encoded data
+/// * `tag`  - Expected tag
+///
+/// # Returns
+///
+/// * `(&[u8], &[u8])` - Contents of the element and the remaining data
+fn der_expect(data: &[u8], tag: u8) -> Option<(&[u8], &[u8])> {
+    let (&actual, data) = data.split_first()?;
+    if actual != tag {
+        return None;
+    }
+
+    let (&len, mut data) = data.split_first()?;
+    let len = match len {
+        0..=0x7F => len as usize,
+        0x81..=0x82 => {
+            let mut n = 0usize;
+            for _ in 0..(len & 0x7F) {
+                let (&byte, rest) = data.split_first()?;
+                n = n << 8 | byte as usize;
+                data = rest;
+            }
+            n
+        }
+        _ => return None,
+    };
+
+    if len > data.len() {
+        return None;
+    }
+    Some(data.split_at(len))
 }
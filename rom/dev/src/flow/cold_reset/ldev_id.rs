@@ -22,6 +22,7 @@ use crate::flow::cold_reset::{KEY_ID_CDI, KEY_ID_FE, KEY_ID_LDEVID_PRIV_KEY};
 use crate::print::HexBytes;
 use crate::rom_env::RomEnv;
 use crate::rom_err_def;
+use caliptra_common::crypto::Ecc384KeyPair;
 use caliptra_drivers::*;
 use caliptra_x509::*;
 
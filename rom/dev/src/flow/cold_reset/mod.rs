@@ -13,6 +13,7 @@ Abstract:
 --*/
 
 mod crypto;
+mod debug_unlock;
 mod dice;
 mod fmc_alias;
 mod idev_id;
@@ -20,6 +21,7 @@ mod ldev_id;
 mod x509;
 
 use crate::fht;
+use crate::flow::cold_reset::debug_unlock::DebugUnlockFlow;
 use crate::flow::cold_reset::dice::*;
 use crate::flow::cold_reset::fmc_alias::FmcAliasLayer;
 use crate::flow::cold_reset::idev_id::InitDevIdLayer;
@@ -57,6 +59,9 @@ impl ColdResetFlow {
     pub fn run(env: &RomEnv) -> CaliptraResult<FirmwareHandoffTable> {
         cprintln!("[cold-reset] ++");
 
+        // Debug unlock must be settled before anything is measured
+        DebugUnlockFlow::run(env)?;
+
         // Compose the three dice layers into one function
         let dice_fn = compose_layers(
             InitDevIdLayer::derive,
@@ -14,6 +14,7 @@ Abstract:
 use super::crypto::Crypto;
 use crate::cprintln;
 use crate::rom_env::RomEnv;
+use caliptra_common::x509;
 use caliptra_drivers::*;
 
 /// Wrapper to hold certificate buffer and length
@@ -68,9 +69,7 @@ impl X509 {
     ///
     /// `[u8; 64]` - X509 Subject Identifier serial number
     pub fn subj_sn(env: &RomEnv, pub_key: &Ecc384PubKey) -> CaliptraResult<[u8; 64]> {
-        let data = pub_key.to_der();
-        let digest = Crypto::sha256_digest(env, &data)?;
-        Ok(Self::hex(&digest.into()))
+        env.sha256().map(|sha| x509::subj_sn(sha, pub_key))
     }
 
     /// Get Initial Device ID Cert Subject Key Identifier
@@ -121,10 +120,7 @@ impl X509 {
     ///
     /// `[u8; 20]` - X509 Subject Key Identifier
     pub fn subj_key_id(env: &RomEnv, pub_key: &Ecc384PubKey) -> CaliptraResult<[u8; 20]> {
-        let data = pub_key.to_der();
-        let digest: [u8; 32] = Crypto::sha256_digest(env, &data)?.into();
-
-        Ok(digest[..20].try_into().unwrap())
+        env.sha256().map(|sha| x509::subj_key_id(sha, pub_key))
     }
 
     /// Get Cert Serial Number
@@ -138,37 +134,6 @@ impl X509 {
     ///
     /// `[u8; 20]` - X509 Serial Number
     pub fn cert_sn(env: &RomEnv, pub_key: &Ecc384PubKey) -> CaliptraResult<[u8; 20]> {
-        let data = pub_key.to_der();
-        let mut digest: [u8; 32] = Crypto::sha256_digest(env, &data)?.into();
-        digest[0] &= !0x80;
-        Ok(digest[..20].try_into().unwrap())
-    }
-
-    /// Return the hex representation of the input `buf`
-    ///
-    /// # Arguments
-    ///
-    /// `buf` - Buffer
-    ///
-    /// # Returns
-    ///
-    /// `[u8; 64]` - Hex representation of the buffer
-    fn hex(buf: &[u8; 32]) -> [u8; 64] {
-        fn ch(byte: u8) -> u8 {
-            match byte & 0x0F {
-                b @ 0..=9 => 48 + b,
-                b @ 10..=15 => 55 + b,
-                _ => unreachable!(),
-            }
-        }
-
-        let mut hex = [0u8; 64];
-
-        for (index, byte) in buf.iter().enumerate() {
-            hex[index << 1] = ch((byte & 0xF0) >> 4);
-            hex[(index << 1) + 1] = ch(byte & 0x0F);
-        }
-
-        hex
+        env.sha256().map(|sha| x509::cert_sn(sha, pub_key))
     }
 }
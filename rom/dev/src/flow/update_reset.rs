@@ -11,11 +11,19 @@ Abstract:
     File contains the implementation of update reset flow.
 
 --*/
-use crate::{cprintln, fht, rom_env::RomEnv, rom_err_def, verifier::RomImageVerificationEnv};
-
+use crate::{
+    cprintln, fht, pcr,
+    rom_env::RomEnv,
+    rom_err_def,
+    verifier::{load_image_chunks, load_image_section, RomImageVerificationEnv},
+};
+
+use caliptra_api::CommandId;
 use caliptra_common::FirmwareHandoffTable;
-use caliptra_drivers::{CaliptraResult, MailboxRecvTxn, ResetReason};
-use caliptra_image_types::ImageManifest;
+use caliptra_drivers::{
+    CaliptraResult, MailboxRecvTxn, ResetReason, WarmResetEntry4, WarmResetEntry48,
+};
+use caliptra_image_types::{ImageManifest, IMAGE_MANIFEST_BYTE_SIZE};
 use caliptra_image_verify::{ImageVerificationInfo, ImageVerifier};
 use zerocopy::{AsBytes, FromBytes};
 
@@ -24,6 +32,12 @@ extern "C" {
     static mut MAN1_ORG: u32;
 }
 
+/// Size of the MAN_1 and MAN_2 regions, must match `MAN1_SIZE` and
+/// `MAN2_SIZE` in rom.ld
+const MAN_SIZE: usize = 4 << 10;
+
+const _: () = assert!(IMAGE_MANIFEST_BYTE_SIZE <= MAN_SIZE);
+
 rom_err_def! {
     UpdateReset,
     UpdateResetErr
@@ -38,8 +52,6 @@ rom_err_def! {
 pub struct UpdateResetFlow {}
 
 impl UpdateResetFlow {
-    const MBOX_DOWNLOAD_FIRMWARE_CMD_ID: u32 = 0x46574C44;
-
     /// Execute update reset flow
     ///
     /// # Arguments
@@ -48,28 +60,50 @@ impl UpdateResetFlow {
     pub fn run(env: &RomEnv) -> CaliptraResult<FirmwareHandoffTable> {
         cprintln!("[update-reset] ++");
 
-        let Some(recv_txn) = env.mbox().map(|m| m.try_start_recv_txn()) else {
+        let Some(mut recv_txn) = env.mbox().map(|m| m.try_start_recv_txn()) else {
             cprintln!("Failed To Get Mailbox Transaction");
             raise_err!(MailboxAccessFailure)
         };
 
-        if recv_txn.cmd() != Self::MBOX_DOWNLOAD_FIRMWARE_CMD_ID {
+        if recv_txn.cmd() != u32::from(CommandId::FirmwareLoad) {
             cprintln!("Invalid command 0x{:08x} received", recv_txn.cmd());
             raise_err!(InvalidFirmwareCommand)
         }
 
         let manifest = Self::load_manifest(&recv_txn)?;
 
-        let info = Self::verify_image(env, &manifest)?;
+        // Images larger than the mailbox are sent as the manifest alone,
+        // followed by the image sections in separate transactions
+        let chunked = recv_txn.dlen() as usize == IMAGE_MANIFEST_BYTE_SIZE;
+
+        let info = Self::verify_image(env, &manifest, recv_txn.dlen(), chunked)?;
 
         cprintln!(
             "[update-reset] Image verified using Vendor ECC Key Index {}",
             info.vendor_ecc_pub_key_idx
         );
+        cprintln!(
+            "[update-reset] Image verified using Vendor LMS Key Index {}",
+            info.vendor_lms_pub_key_idx
+        );
 
-        Self::load_image(env, &manifest, recv_txn)?;
+        if chunked {
+            // Release the mailbox for the image sections
+            recv_txn.complete(true)?;
+            drop(recv_txn);
 
-        Self::copy_regions(&manifest);
+            load_image_chunks(env, &manifest)?;
+        } else {
+            Self::load_image(env, &manifest, recv_txn)?;
+        }
+
+        // The whole image is verified
+        Self::populate_data_vault(env, &info);
+
+        // The FHT points at the auxiliary TOC entries of the new manifest
+        pcr::extend_pcr0_aux(env, manifest.aux_toc_entries())?;
+
+        Self::copy_regions();
         cprintln!("[update-reset Success] --");
         Ok(fht::make_fht(env))
     }
@@ -80,53 +114,45 @@ impl UpdateResetFlow {
     ///
     /// * `env` - ROM Environment
     /// * 'manifest'- Manifest
+    /// * `image_size` - Size of the image in the mailbox
+    /// * `chunked`    - Only the manifest is in the mailbox
     ///
     fn verify_image(
         env: &RomEnv,
         manifest: &ImageManifest,
+        image_size: u32,
+        chunked: bool,
     ) -> CaliptraResult<ImageVerificationInfo> {
-        let venv = RomImageVerificationEnv::new(env);
+        let venv = RomImageVerificationEnv::new(env, image_size);
 
         let verifier = ImageVerifier::new(venv);
 
-        let info = verifier.verify(manifest, (), ResetReason::UpdateReset)?;
+        let info = if chunked {
+            verifier.verify_manifest(manifest, (), ResetReason::UpdateReset)?
+        } else {
+            verifier.verify(manifest, (), ResetReason::UpdateReset)?
+        };
 
         Ok(info)
     }
 
     ///
-    /// Copy the verified MAN_2 region to MAN_1
+    /// Copy the verified manifest from MAN_2 to MAN_1
     ///
-    /// # Arguments
+    /// The image sections are not staged in DCCM; only the manifest is
+    /// copied, leaving the regions following MAN_1 intact.
     ///
-    /// * `manifest` - Manifest
-    ///
-    fn copy_regions(manifest: &ImageManifest) {
+    fn copy_regions() {
         cprintln!("[update-reset] Copying MAN_2 To MAN_1");
 
         let dst = unsafe {
             let ptr = &mut MAN1_ORG as *mut u32;
-            core::slice::from_raw_parts_mut(
-                ptr,
-                (core::mem::size_of::<ImageManifest>()
-                    + manifest.fmc.size as usize
-                    + manifest.runtime.size as usize
-                    + 3)
-                    / 4,
-            )
+            core::slice::from_raw_parts_mut(ptr, IMAGE_MANIFEST_BYTE_SIZE / 4)
         };
 
         let src = unsafe {
-            let ptr = &mut MAN2_ORG as *mut u32;
-
-            core::slice::from_raw_parts_mut(
-                ptr,
-                (core::mem::size_of::<ImageManifest>()
-                    + manifest.fmc.size as usize
-                    + manifest.runtime.size as usize
-                    + 3)
-                    / 4,
-            )
+            let ptr = &MAN2_ORG as *const u32;
+            core::slice::from_raw_parts(ptr, IMAGE_MANIFEST_BYTE_SIZE / 4)
         };
         dst.clone_from_slice(src);
     }
@@ -143,6 +169,15 @@ impl UpdateResetFlow {
         manifest: &ImageManifest,
         mut txn: MailboxRecvTxn,
     ) -> CaliptraResult<()> {
+        // The verifier has checked that the FMC is unchanged, the image in
+        // ICCM is rewritten with the same contents to reach the runtime.
+        let fmc_dest = unsafe {
+            let addr = (manifest.fmc.load_addr) as *mut u32;
+            core::slice::from_raw_parts_mut(addr, manifest.fmc.size as usize / 4)
+        };
+
+        load_image_section(&manifest.fmc, fmc_dest)?;
+
         cprintln!(
             "[update-reset] Loading Runtime at address 0x{:08x} len {}",
             manifest.runtime.load_addr,
@@ -154,7 +189,7 @@ impl UpdateResetFlow {
             core::slice::from_raw_parts_mut(addr, manifest.runtime.size as usize / 4)
         };
 
-        txn.copy_request(runtime_dest)?;
+        load_image_section(&manifest.runtime, runtime_dest)?;
 
         //Call the complete here to reset the execute bit
         txn.complete(true)?;
@@ -166,6 +201,29 @@ impl UpdateResetFlow {
         Ok(())
     }
 
+    /// Populate the Runtime entries of the data vault
+    ///
+    /// # Arguments
+    ///
+    /// * `env`  - ROM Environment
+    /// * `info` - Image Verification Info
+    fn populate_data_vault(env: &RomEnv, info: &ImageVerificationInfo) {
+        env.data_vault().map(|d| {
+            d.write_warm_reset_entry48(WarmResetEntry48::RtTci, &info.runtime.digest.into())
+        });
+
+        env.data_vault()
+            .map(|d| d.write_warm_reset_entry4(WarmResetEntry4::RtSvn, info.runtime.svn));
+
+        env.data_vault().map(|d| {
+            d.write_warm_reset_entry4(WarmResetEntry4::RtLoadAddr, info.runtime.load_addr)
+        });
+
+        env.data_vault().map(|d| {
+            d.write_warm_reset_entry4(WarmResetEntry4::RtEntryPoint, info.runtime.entry_point)
+        });
+    }
+
     /// Load the manifest
     ///
     /// # Returns
@@ -11,15 +11,40 @@ Abstract:
     File contains the implementation of warm reset flow.
 
 --*/
-use crate::{cprintln, fht, rom_env::RomEnv};
+use crate::{cprintln, fht, rom_env::RomEnv, rom_err_def};
 use caliptra_common::FirmwareHandoffTable;
-use caliptra_drivers::CaliptraResult;
+use caliptra_drivers::{Array4x12, CaliptraResult, ColdResetEntry4, ColdResetEntry48};
+use caliptra_image_types::{ImageManifest, ImageTocEntry};
+use zerocopy::{AsBytes, FromBytes};
+
+extern "C" {
+    static mut MAN1_ORG: u32;
+}
+
+rom_err_def! {
+    WarmReset,
+    WarmResetErr
+    {
+        ColdResetEntryUnlocked = 0x1,
+        ManifestAddrMismatch = 0x2,
+        ManifestReadFailure = 0x3,
+        FmcTocMismatch = 0x4,
+        FmcDigestMismatch = 0x5,
+        RuntimeTocMismatch = 0x6,
+        RuntimeDigestMismatch = 0x7,
+        ImageOutOfBounds = 0x8,
+    }
+}
 
 /// Warm Reset Flow
 pub struct WarmResetFlow {}
 
 impl WarmResetFlow {
-    /// Execute update reset flow
+    /// Execute warm reset flow
+    ///
+    /// The firmware loaded on cold reset is still in ICCM. It is only handed
+    /// control again if it matches the measurements recorded in the data
+    /// vault; any failure prevents the jump to FMC.
     ///
     /// # Arguments
     ///
@@ -28,10 +53,136 @@ impl WarmResetFlow {
     pub fn run(env: &RomEnv) -> CaliptraResult<FirmwareHandoffTable> {
         cprintln!("[warm-reset] ++");
 
-        // TODO: Implement
+        Self::check_cold_reset_entries(env)?;
+
+        let manifest = Self::load_manifest(env)?;
+
+        Self::verify_fmc(env, &manifest.fmc)?;
+        cprintln!("[warm-reset] FMC verified");
+
+        Self::verify_runtime(env, &manifest.runtime)?;
+        cprintln!("[warm-reset] Runtime verified");
 
         cprintln!("[warm-reset] --");
 
         Ok(fht::make_fht(env))
     }
+
+    /// Check that the cold reset entries of the data vault are still locked
+    ///
+    /// # Arguments
+    ///
+    /// * `env` - ROM Environment
+    fn check_cold_reset_entries(env: &RomEnv) -> CaliptraResult<()> {
+        let entries48 = (0u8..).map_while(|i| ColdResetEntry48::try_from(i).ok());
+        for entry in entries48 {
+            if !env.data_vault().map(|d| d.cold_reset_entry48_lock(entry)) {
+                cprintln!("[warm-reset] Cold reset entry48 {} unlocked", entry as u8);
+                raise_err!(ColdResetEntryUnlocked)
+            }
+        }
+
+        let entries4 = (0u8..).map_while(|i| ColdResetEntry4::try_from(i).ok());
+        for entry in entries4 {
+            if !env.data_vault().map(|d| d.cold_reset_entry4_lock(entry)) {
+                cprintln!("[warm-reset] Cold reset entry4 {} unlocked", entry as u8);
+                raise_err!(ColdResetEntryUnlocked)
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Load the manifest of the image verified on cold reset
+    ///
+    /// # Arguments
+    ///
+    /// * `env` - ROM Environment
+    ///
+    /// # Returns
+    ///
+    /// * `Manifest` - Caliptra Image Bundle Manifest
+    fn load_manifest(env: &RomEnv) -> CaliptraResult<ImageManifest> {
+        let slice = unsafe {
+            let ptr = &MAN1_ORG as *const u32;
+            core::slice::from_raw_parts(ptr, core::mem::size_of::<ImageManifest>() / 4)
+        };
+
+        if env.data_vault().map(|d| d.manifest_addr()) != slice.as_ptr() as u32 {
+            raise_err!(ManifestAddrMismatch)
+        }
+
+        ImageManifest::read_from(slice.as_bytes()).ok_or(err_u32!(ManifestReadFailure))
+    }
+
+    /// Verify the FMC in ICCM against the cold reset entries
+    ///
+    /// # Arguments
+    ///
+    /// * `env` - ROM Environment
+    /// * `toc` - FMC TOC entry of the manifest
+    fn verify_fmc(env: &RomEnv, toc: &ImageTocEntry) -> CaliptraResult<()> {
+        let data_vault = env.data_vault();
+        if toc.load_addr != data_vault.map(|d| d.fmc_load_addr())
+            || toc.entry_point != data_vault.map(|d| d.fmc_entry_point())
+            || toc.svn != data_vault.map(|d| d.fmc_svn())
+            || Array4x12::from(toc.digest) != data_vault.map(|d| d.fmc_tci())
+        {
+            raise_err!(FmcTocMismatch)
+        }
+
+        if Self::iccm_digest(env, toc)? != data_vault.map(|d| d.fmc_tci()) {
+            raise_err!(FmcDigestMismatch)
+        }
+
+        Ok(())
+    }
+
+    /// Verify the Runtime in ICCM against the warm reset entries
+    ///
+    /// # Arguments
+    ///
+    /// * `env` - ROM Environment
+    /// * `toc` - Runtime TOC entry of the manifest
+    fn verify_runtime(env: &RomEnv, toc: &ImageTocEntry) -> CaliptraResult<()> {
+        let data_vault = env.data_vault();
+        if toc.load_addr != data_vault.map(|d| d.rt_load_addr())
+            || toc.entry_point != data_vault.map(|d| d.rt_entry_point())
+            || toc.svn != data_vault.map(|d| d.rt_svn())
+            || Array4x12::from(toc.digest) != data_vault.map(|d| d.rt_tci())
+        {
+            raise_err!(RuntimeTocMismatch)
+        }
+
+        if Self::iccm_digest(env, toc)? != data_vault.map(|d| d.rt_tci()) {
+            raise_err!(RuntimeDigestMismatch)
+        }
+
+        Ok(())
+    }
+
+    /// Calculate the digest of an image loaded in ICCM
+    ///
+    /// # Arguments
+    ///
+    /// * `env` - ROM Environment
+    /// * `toc` - TOC entry of the image
+    ///
+    /// # Returns
+    ///
+    /// * `Array4x12` - Digest of the image
+    fn iccm_digest(env: &RomEnv, toc: &ImageTocEntry) -> CaliptraResult<Array4x12> {
+        let iccm = env.iccm_range();
+        let end = toc.load_addr.checked_add(toc.size);
+        if !iccm.contains(&toc.load_addr) || !matches!(end, Some(end) if end <= iccm.end) {
+            raise_err!(ImageOutOfBounds)
+        }
+
+        let image =
+            unsafe { core::slice::from_raw_parts(toc.load_addr as *const u8, toc.size as usize) };
+
+        let mut digest = Array4x12::default();
+        env.sha384().map(|s| s.digest(image, &mut digest))?;
+        Ok(digest)
+    }
 }
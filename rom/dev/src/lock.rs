@@ -13,7 +13,7 @@ Abstract:
 --*/
 
 use caliptra_drivers::{
-    ColdResetEntry4, ColdResetEntry48, ResetReason, WarmResetEntry4, WarmResetEntry48,
+    ColdResetEntry4, ColdResetEntry48, PcrId, ResetReason, WarmResetEntry4, WarmResetEntry48,
 };
 
 use crate::{cprintln, rom_env::RomEnv};
@@ -29,9 +29,12 @@ pub fn lock_registers(env: &RomEnv, reset_reason: ResetReason) {
     if reset_reason == ResetReason::ColdReset {
         lock_cold_reset_reg(env);
         lock_warm_reset_reg(env);
-    } else if reset_reason == ResetReason::WarmReset {
+    } else if reset_reason == ResetReason::WarmReset || reset_reason == ResetReason::UpdateReset {
         lock_warm_reset_reg(env);
-    } else if reset_reason == ResetReason::UpdateReset {
+
+        // The reset unlocked the ROM journey PCR
+        cprintln!("[state] Locking PCR0");
+        env.pcr_bank().map(|p| p.set_pcr_lock(PcrId::PcrId0));
     }
 
     cprintln!("[state] Locking ICCM");
@@ -67,6 +70,10 @@ fn lock_cold_reset_reg(env: &RomEnv) {
     // Lock the Vendor Public Key Index in data vault until next cold reset
     env.data_vault()
         .map(|d| d.lock_cold_reset_entry4(ColdResetEntry4::VendorPubKeyIndex));
+
+    // Lock the Vendor LMS Public Key Index in data vault until next cold reset
+    env.data_vault()
+        .map(|d| d.lock_cold_reset_entry4(ColdResetEntry4::VendorLmsPubKeyIndex));
 }
 
 /// Lock registers on a warm reset
@@ -17,7 +17,7 @@ Abstract:
 use crate::lock::lock_registers;
 use core::hint::black_box;
 
-use caliptra_drivers::{report_fw_error_non_fatal, Mailbox};
+use caliptra_drivers::{handle_fatal_error, FatalErrorTrap};
 use rom_env::RomEnv;
 
 #[cfg(not(feature = "std"))]
@@ -56,7 +56,10 @@ Running Caliptra ROM ...
 pub extern "C" fn rom_entry() -> ! {
     cprintln!("{}", BANNER);
 
-    let env = rom_env::RomEnv::default();
+    let env = match rom_env::RomEnv::new() {
+        Ok(env) => env,
+        Err(err) => report_error(err.into()),
+    };
 
     let _lifecyle = match env.dev_state().map(|d| d.lifecycle()) {
         caliptra_drivers::Lifecycle::Unprovisioned => "Unprovisioned",
@@ -120,9 +123,7 @@ extern "C" fn exception_handler(exception: &exception::ExceptionRecord) {
         exception.mepc
     );
 
-    // TODO: Signal non-fatal error to SOC
-
-    report_error(GlobalErr::Exception.into());
+    report_trap_error(GlobalErr::Exception.into(), exception);
 }
 
 #[no_mangle]
@@ -135,11 +136,7 @@ extern "C" fn nmi_handler(exception: &exception::ExceptionRecord) {
         exception.mepc
     );
 
-    // TODO: Signal error to SOC
-    // - Signal Fatal error for ICCM/DCCM double bit faults
-    // - Signal Non=-Fatal error for all other errors
-
-    report_error(GlobalErr::Nmi.into());
+    report_trap_error(GlobalErr::Nmi.into(), exception);
 }
 
 #[panic_handler]
@@ -149,22 +146,17 @@ fn rom_panic(_: &core::panic::PanicInfo) -> ! {
     cprintln!("Panic!!");
     panic_is_possible();
 
-    // TODO: Signal non-fatal error to SOC
-
     report_error(GlobalErr::Panic.into());
 }
 
-#[allow(clippy::empty_loop)]
 fn report_error(code: u32) -> ! {
     cprintln!("ROM Error: 0x{:08X}", code);
-    report_fw_error_non_fatal(code);
+    handle_fatal_error(code, None)
+}
 
-    loop {
-        // SoC firmware might be stuck waiting for Caliptra to finish
-        // executing this pending mailbox transaction. Notify them that
-        // we've failed.
-        unsafe { Mailbox::abort_pending_soc_to_uc_transactions() };
-    }
+fn report_trap_error(code: u32, exception: &exception::ExceptionRecord) -> ! {
+    cprintln!("ROM Error: 0x{:08X}", code);
+    handle_fatal_error(code, Some(&FatalErrorTrap::from(exception)))
 }
 
 #[no_mangle]
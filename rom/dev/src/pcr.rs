@@ -23,13 +23,15 @@ Note:
 
 use crate::rom_env::RomEnv;
 use caliptra_drivers::{Array4x12, CaliptraResult, PcrId};
+use caliptra_image_types::ImageTocEntry;
 
 /// Extend PCR0
 ///
 /// # Arguments
 ///
 /// * `env` - ROM Environment
-pub fn extend_pcr0(env: &RomEnv) -> CaliptraResult<()> {
+/// * `aux` - Verified auxiliary TOC entries
+pub fn extend_pcr0(env: &RomEnv, aux: &[ImageTocEntry]) -> CaliptraResult<()> {
     let sha = env.sha384();
     let pcr_bank = env.pcr_bank();
 
@@ -49,8 +51,12 @@ pub fn extend_pcr0(env: &RomEnv) -> CaliptraResult<()> {
         sha.map(|s| pcr_bank.map(|p| p.extend_pcr(PcrId::PcrId0, s, bytes)))
     };
 
+    // A verified debug unlock token overrides the hardware debug lock
+    let debug_locked =
+        env.dev_state().map(|d| d.debug_locked()) && !env.data_vault().map(|d| d.debug_unlocked());
+
     extend_u8(env.dev_state().map(|d| d.lifecycle()) as u8)?;
-    extend_u8(env.dev_state().map(|d| d.debug_locked()) as u8)?;
+    extend_u8(debug_locked as u8)?;
     extend_u8(env.fuse_bank().map(|f| f.anti_rollback_disable()) as u8)?;
     extend(env.fuse_bank().map(|f| f.vendor_pub_key_hash()))?;
     extend(env.data_vault().map(|d| d.owner_pk_hash()))?;
@@ -58,7 +64,34 @@ pub fn extend_pcr0(env: &RomEnv) -> CaliptraResult<()> {
     extend(env.data_vault().map(|d| d.fmc_tci()))?;
     extend_u8(env.data_vault().map(|d| d.fmc_svn()) as u8)?;
 
+    extend_pcr0_aux(env, aux)?;
+
     // TODO: Check PCR0 != 0
 
     Ok(())
 }
+
+/// Extend PCR0 with the auxiliary TOC entries
+///
+/// Auxiliary images are measured but never executed. They are measured on
+/// cold reset and again on update reset, as the new manifest may carry
+/// different auxiliary images.
+///
+/// # Arguments
+///
+/// * `env` - ROM Environment
+/// * `aux` - Verified auxiliary TOC entries
+pub fn extend_pcr0_aux(env: &RomEnv, aux: &[ImageTocEntry]) -> CaliptraResult<()> {
+    let sha = env.sha384();
+    let pcr_bank = env.pcr_bank();
+
+    for toc in aux {
+        let id = &toc.id.to_le_bytes();
+        sha.map(|s| pcr_bank.map(|p| p.extend_pcr(PcrId::PcrId0, s, id)))?;
+
+        let digest: &[u8; 48] = &Array4x12::from(toc.digest).into();
+        sha.map(|s| pcr_bank.map(|p| p.extend_pcr(PcrId::PcrId0, s, digest)))?;
+    }
+
+    Ok(())
+}
@@ -16,14 +16,28 @@ Abstract:
 --*/
 
 use crate::env_cell::EnvCell;
+use crate::{cprintln, rom_err_def};
 use caliptra_drivers::{
-    DataVault, DeobfuscationEngine, DeviceState, Ecc384, FlowStatus, FuseBank, Hmac384, KeyVault,
-    Mailbox, MfgState, PcrBank, ResetService, Sha1, Sha256, Sha384, Sha384Acc,
+    memory_layout, CaliptraResult, Csrng, CsrngSeed, DataVault, DeobfuscationEngine, DeviceState,
+    Ecc384, FlowStatus, FuseBank, Hmac384, KeyVault, Mailbox, MfgState, PcrBank, ResetService,
+    Sha1, Sha256, Sha384, Sha384Acc,
 };
 use core::ops::Range;
 
-const ICCM_START: u32 = 0x40000000;
-const ICCM_SIZE: u32 = 128 << 10;
+rom_err_def! {
+    RomEnv,
+    RomEnvErr
+    {
+        CsrngHealthCheck = 0x1,
+    }
+}
+
+/// Seed making the CSRNG output reproducible in test builds
+#[cfg(feature = "deterministic-rng")]
+const DETERMINISTIC_SEED: [u32; 12] = [
+    0x43414c49, 0x50545241, 0x2d544553, 0x542d5345, 0x45443030, 0x30303030, 0x30303030, 0x30303030,
+    0x30303030, 0x30303030, 0x30303030, 0x30303031,
+];
 
 /// Rom Context
 pub struct RomEnv {
@@ -74,11 +88,18 @@ pub struct RomEnv {
 
     /// PCR Bank
     pcr_bank: EnvCell<PcrBank>,
+
+    /// Cryptographically Secure Random Number Generator
+    csrng: EnvCell<Csrng>,
 }
 
-impl Default for RomEnv {
-    fn default() -> Self {
-        Self {
+impl RomEnv {
+    /// Create the ROM Environment
+    ///
+    /// The CSRNG is instantiated and its health checks are run before any
+    /// other service is used.
+    pub fn new() -> CaliptraResult<Self> {
+        Ok(Self {
             doe: EnvCell::new(DeobfuscationEngine::default()),
             reset: EnvCell::new(ResetService::default()),
             sha1: EnvCell::new(Sha1::default()),
@@ -95,11 +116,38 @@ impl Default for RomEnv {
             flow_status: EnvCell::new(FlowStatus::default()),
             fuse_bank: EnvCell::new(FuseBank::default()),
             pcr_bank: EnvCell::new(PcrBank::default()),
+            csrng: EnvCell::new(Self::init_csrng()?),
+        })
+    }
+
+    /// Instantiate the CSRNG
+    ///
+    /// # Returns
+    ///
+    /// * `Csrng` - CSRNG handle that passed the entropy source health checks
+    fn init_csrng() -> CaliptraResult<Csrng> {
+        #[cfg(not(feature = "deterministic-rng"))]
+        let seed = CsrngSeed::EntropySrc;
+
+        #[cfg(feature = "deterministic-rng")]
+        let seed = CsrngSeed::Constant(&DETERMINISTIC_SEED);
+
+        // This is the only CSRNG handle in ROM
+        let csrng = unsafe { Csrng::with_seed(seed)? };
+
+        let counts = csrng.health_counts();
+        if counts.total != 0 {
+            cprintln!(
+                "[state] CSRNG health check failures = {}, alerts = 0x{:08X}",
+                counts.total,
+                u32::from(counts.specific)
+            );
+            raise_err!(CsrngHealthCheck)
         }
+
+        Ok(csrng)
     }
-}
 
-impl RomEnv {
     /// Get deobfuscation engine reference
     pub fn doe(&self) -> &EnvCell<DeobfuscationEngine> {
         &self.doe
@@ -180,11 +228,16 @@ impl RomEnv {
         &self.pcr_bank
     }
 
+    /// Get CSRNG
+    pub fn csrng(&self) -> &EnvCell<Csrng> {
+        &self.csrng
+    }
+
     /// Get ICCM Range
     pub fn iccm_range(&self) -> Range<u32> {
         Range {
-            start: ICCM_START,
-            end: ICCM_START + ICCM_SIZE,
+            start: memory_layout::ICCM_ORG,
+            end: memory_layout::ICCM_ORG + memory_layout::ICCM_SIZE,
         }
     }
 }
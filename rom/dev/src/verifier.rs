@@ -12,22 +12,252 @@ Abstract:
 
 --*/
 
+use caliptra_api::CommandId;
 use caliptra_drivers::*;
 use caliptra_image_types::*;
 use caliptra_image_verify::ImageVerificationEnv;
+use core::mem::ManuallyDrop;
 use core::ops::Range;
 
-use crate::rom_env::RomEnv;
+use crate::{rom_env::RomEnv, rom_err_def};
+
+extern "C" {
+    static MBOX_ORG: u32;
+}
+
+rom_err_def! {
+    Verifier,
+    VerifierErr
+    {
+        ImageRangeInvalid = 0x1,
+        ImageSectionUnaligned = 0x2,
+        ImageChunkCmdInvalid = 0x3,
+        ImageChunkSizeInvalid = 0x4,
+        ImageSectionDigestMismatch = 0x5,
+        ImageTooLarge = 0x6,
+    }
+}
 
 /// ROM Verification Environemnt
+///
+/// The image is verified in place in the mailbox SRAM.
 pub(crate) struct RomImageVerificationEnv<'a> {
     env: &'a RomEnv,
+
+    /// Size of the image in the mailbox
+    image_size: u32,
 }
 
 impl<'a> RomImageVerificationEnv<'a> {
     /// Create and instance `RomImageVerificationEnv`
-    pub fn new(env: &'a RomEnv) -> Self {
-        Self { env }
+    ///
+    /// # Arguments
+    ///
+    /// * `env`        - ROM Environment
+    /// * `image_size` - Size of the image in the mailbox
+    pub fn new(env: &'a RomEnv, image_size: u32) -> Self {
+        Self { env, image_size }
+    }
+}
+
+/// Copy a verified image section from the mailbox SRAM
+///
+/// The section is read from the same mailbox location that was hashed by the
+/// SHA384 accelerator during verification, hence only verified bytes reach
+/// the destination.
+///
+/// # Arguments
+///
+/// * `toc`  - Verified TOC entry of the section
+/// * `dest` - Destination buffer
+pub(crate) fn load_image_section(toc: &ImageTocEntry, dest: &mut [u32]) -> CaliptraResult<()> {
+    if toc.offset % 4 != 0 {
+        raise_err!(ImageSectionUnaligned)
+    }
+
+    let src = unsafe {
+        let ptr = (&MBOX_ORG as *const u32).add(toc.offset as usize / 4);
+        core::slice::from_raw_parts(ptr, dest.len())
+    };
+
+    dest.copy_from_slice(src);
+
+    Ok(())
+}
+
+/// Receive and load the image sections following a manifest-only firmware load
+///
+/// The rest of the image is received in order in FIRMWARE_LOAD_CHUNK
+/// mailbox transactions. The FMC and runtime are copied to their load
+/// addresses and each section is hashed as it is received; the digest of
+/// every section is checked against its verified TOC entry. The last
+/// transaction is completed only once all the sections are verified.
+///
+/// The image following the manifest may not be larger than ICCM. If any
+/// section fails verification, the FMC and runtime load regions are erased
+/// so that no unverified code is left in ICCM.
+///
+/// # Arguments
+///
+/// * `env`      - ROM Environment
+/// * `manifest` - Verified manifest
+pub(crate) fn load_image_chunks(env: &RomEnv, manifest: &ImageManifest) -> CaliptraResult<()> {
+    let result = recv_image_sections(env, manifest);
+
+    if result.is_err() {
+        for toc in [&manifest.fmc, &manifest.runtime] {
+            let dest = unsafe {
+                core::slice::from_raw_parts_mut(toc.load_addr as *mut u8, toc.size as usize)
+            };
+            dest.fill(0);
+        }
+    }
+
+    result
+}
+
+/// Receive, load and verify the image sections in FIRMWARE_LOAD_CHUNK
+/// transactions
+///
+/// # Arguments
+///
+/// * `env`      - ROM Environment
+/// * `manifest` - Verified manifest
+fn recv_image_sections(env: &RomEnv, manifest: &ImageManifest) -> CaliptraResult<()> {
+    // The last section ends the image
+    let last = manifest
+        .aux_toc_entries()
+        .last()
+        .unwrap_or(&manifest.runtime);
+    let len = last
+        .image_range()
+        .end
+        .saturating_sub(IMAGE_MANIFEST_BYTE_SIZE as u32);
+    let iccm = env.iccm_range();
+    if len > iccm.end - iccm.start {
+        raise_err!(ImageTooLarge)
+    }
+
+    // Sections were verified to be in this order and not to overlap
+    let exes = [&manifest.fmc, &manifest.runtime].map(|toc| (toc, true));
+    let aux = manifest.aux_toc_entries().iter().map(|toc| (toc, false));
+
+    let mut txn: Option<ManuallyDrop<MailboxRecvTxn>> = None;
+    let mut chunk: &[u8] = &[];
+    let mut offset = IMAGE_MANIFEST_BYTE_SIZE as u32;
+
+    for (section, load) in exes.into_iter().chain(aux) {
+        let range = section.image_range();
+        if range.start < offset || range.end < range.start {
+            raise_err!(ImageRangeInvalid)
+        }
+
+        // Auxiliary images are only measured
+        let mut dest = if load {
+            Some(unsafe {
+                core::slice::from_raw_parts_mut(section.load_addr as *mut u8, section.size as usize)
+            })
+        } else {
+            None
+        };
+
+        let mut digest = Array4x12::default();
+        env.sha384().map(|sha| {
+            let mut op = sha.digest_init(&mut digest)?;
+
+            while offset < range.end {
+                if chunk.is_empty() {
+                    chunk = recv_image_chunk(env, &mut txn)?;
+                }
+
+                // Skip any padding before the section
+                if offset < range.start {
+                    let skip = core::cmp::min((range.start - offset) as usize, chunk.len());
+                    chunk = chunk.get(skip..).ok_or(err_u32!(ImageRangeInvalid))?;
+                    offset += skip as u32;
+                    continue;
+                }
+
+                let len = core::cmp::min((range.end - offset) as usize, chunk.len());
+                let data = chunk.get(..len).ok_or(err_u32!(ImageRangeInvalid))?;
+
+                match dest.as_deref_mut() {
+                    Some(dest) => {
+                        // Hash the copy so the loaded bytes are the measured ones
+                        let pos = (offset - range.start) as usize;
+                        let dest = dest
+                            .get_mut(pos..pos + len)
+                            .ok_or(err_u32!(ImageRangeInvalid))?;
+                        dest.copy_from_slice(data);
+                        op.update(dest)?;
+                    }
+                    None => op.update(data)?,
+                }
+
+                chunk = chunk.get(len..).ok_or(err_u32!(ImageRangeInvalid))?;
+                offset += len as u32;
+            }
+
+            op.finalize()
+        })?;
+
+        if digest != Array4x12::from(section.digest) {
+            raise_err!(ImageSectionDigestMismatch)
+        }
+    }
+
+    // No data may follow the last section
+    if !chunk.is_empty() {
+        raise_err!(ImageChunkSizeInvalid)
+    }
+
+    if let Some(mut txn) = txn {
+        txn.complete(true)?;
+    }
+
+    Ok(())
+}
+
+/// Receive the next chunk of an image
+///
+/// The previous chunk transaction, if any, is completed first.
+///
+/// # Arguments
+///
+/// * `env` - ROM Environment
+/// * `txn` - Current chunk transaction
+///
+/// # Returns
+///
+/// * Contents of the chunk in the mailbox SRAM
+fn recv_image_chunk(
+    env: &RomEnv,
+    txn: &mut Option<ManuallyDrop<MailboxRecvTxn>>,
+) -> CaliptraResult<&'static [u8]> {
+    if let Some(mut prev) = txn.take() {
+        prev.complete(true)?;
+    }
+
+    loop {
+        if let Some(next) = env.mbox().map(|m| m.try_start_recv_txn()) {
+            // The transaction is completed by report_error() on failure
+            let next = ManuallyDrop::new(next);
+            if next.cmd() != u32::from(CommandId::FirmwareLoadChunk) {
+                raise_err!(ImageChunkCmdInvalid)
+            }
+
+            let dlen = next.dlen();
+            if dlen == 0 || dlen > IMAGE_CHUNK_BYTE_SIZE as u32 {
+                raise_err!(ImageChunkSizeInvalid)
+            }
+
+            *txn = Some(next);
+
+            let data = unsafe {
+                core::slice::from_raw_parts(&MBOX_ORG as *const u32 as *const u8, dlen as usize)
+            };
+            return Ok(data);
+        }
     }
 }
 
@@ -41,6 +271,12 @@ impl<'a> ImageVerificationEnv for RomImageVerificationEnv<'a> {
         offset: u32,
         len: u32,
     ) -> CaliptraResult<ImageDigest> {
+        // Only hash data received in the mailbox
+        match offset.checked_add(len) {
+            Some(end) if end <= self.image_size => {}
+            _ => raise_err!(ImageRangeInvalid),
+        }
+
         loop {
             if let Some(mut txn) = self.env.sha384_acc().map(|s| s.try_start_operation()) {
                 let mut digest = Array4x12::default();
@@ -50,6 +286,13 @@ impl<'a> ImageVerificationEnv for RomImageVerificationEnv<'a> {
         }
     }
 
+    /// Calculate Digest using SHA-384 Engine
+    fn sha384_digest_data(&self, data: &[u8]) -> CaliptraResult<ImageDigest> {
+        let mut digest = Array4x12::default();
+        self.env.sha384().map(|s| s.digest(data, &mut digest))?;
+        Ok(digest.0)
+    }
+
     /// ECC-384 Verification routine
     fn ecc384_verify(
         &self,
@@ -79,6 +322,59 @@ impl<'a> ImageVerificationEnv for RomImageVerificationEnv<'a> {
         self.env.ecc384().map(|e| e.verify(&pub_key, &digest, &sig))
     }
 
+    /// LMS Verification routine
+    fn lms_verify(
+        &self,
+        _image: Self::Image,
+        digest: &ImageDigest,
+        pub_key: &ImageLmsPubKey,
+        sig: &ImageLmsSignature,
+    ) -> CaliptraResult<bool> {
+        if pub_key.tree_type != IMAGE_LMS_TREE_TYPE
+            || pub_key.otstype != IMAGE_LMS_OTS_TYPE
+            || sig.tree_type != pub_key.tree_type
+            || sig.ots.otstype != pub_key.otstype
+        {
+            return Ok(false);
+        }
+
+        // The header digest is signed in its big-endian byte representation
+        let mut message = [0u8; SHA384_DIGEST_BYTE_SIZE];
+        for (bytes, word) in message.chunks_exact_mut(4).zip(digest.iter()) {
+            bytes.copy_from_slice(&word.to_be_bytes());
+        }
+
+        let mut y = [HashValue::<LMS_DIGEST_BYTE_SIZE>::default(); LMOTS_P];
+        for (node, sig_node) in y.iter_mut().zip(sig.ots.y.iter()) {
+            *node = HashValue::from(sig_node);
+        }
+
+        let mut path = [HashValue::<LMS_DIGEST_BYTE_SIZE>::default(); LMS_TREE_HEIGHT];
+        for (node, sig_node) in path.iter_mut().zip(sig.tree_path.iter()) {
+            *node = HashValue::from(sig_node);
+        }
+
+        let lms_sig = LmsSignature {
+            q: sig.q,
+            lmots_signature: LmotsSignature {
+                ots_type: LmotsAlgorithmType::LmotsSha256N24W8,
+                nonce: sig.ots.nonce,
+                y,
+            },
+            sig_type: LmsAlgorithmType::LmsSha256N24H5,
+            lms_path: &path,
+        };
+
+        verify_lms_signature(
+            LMS_TREE_HEIGHT as u8,
+            &message,
+            &pub_key.id,
+            sig.q,
+            &HashValue::from(&pub_key.digest),
+            &lms_sig,
+        )
+    }
+
     /// Retrieve Vendor Public Key Digest
     fn vendor_pub_key_digest(&self, _image: Self::Image) -> ImageDigest {
         self.env.fuse_bank().map(|f| f.vendor_pub_key_hash()).into()
@@ -89,6 +385,13 @@ impl<'a> ImageVerificationEnv for RomImageVerificationEnv<'a> {
         self.env.fuse_bank().map(|f| f.vendor_pub_key_revocation())
     }
 
+    /// Retrieve Vendor LMS Public Key Revocation Bitmask
+    fn vendor_lms_pub_key_revocation(&self, _image: Self::Image) -> VendorPubKeyRevocation {
+        self.env
+            .fuse_bank()
+            .map(|f| f.vendor_lms_pub_key_revocation())
+    }
+
     /// Retrieve Owner Public Key Digest from fuses
     fn owner_pub_key_digest_fuses(&self) -> ImageDigest {
         self.env.fuse_bank().map(|f| f.owner_pub_key_hash()).into()
@@ -109,6 +412,11 @@ impl<'a> ImageVerificationEnv for RomImageVerificationEnv<'a> {
         self.env.data_vault().map(|dv| dv.vendor_pk_index())
     }
 
+    /// Get the vendor LMS key index saved in data vault on cold boot
+    fn vendor_lms_pub_key_idx_dv(&self) -> u32 {
+        self.env.data_vault().map(|dv| dv.vendor_lms_pk_index())
+    }
+
     /// Get the owner public key digest saved in the dv on cold boot
     fn owner_pub_key_digest_dv(&self) -> ImageDigest {
         self.env.data_vault().map(|dv| dv.owner_pk_hash()).into()
@@ -132,4 +440,9 @@ impl<'a> ImageVerificationEnv for RomImageVerificationEnv<'a> {
     fn iccm_range(&self) -> Range<u32> {
         self.env.iccm_range()
     }
+
+    // Get the trusted time supplied by the SoC
+    fn trusted_time(&self) -> Option<u64> {
+        self.env.dev_state().map(|d| d.trusted_time())
+    }
 }
@@ -2,6 +2,7 @@
 
 use std::mem;
 
+use caliptra_api::CommandId;
 use caliptra_builder::{ImageOptions, APP_WITH_UART, FMC_WITH_UART, ROM_WITH_UART};
 use caliptra_hw_model::{BootParams, Fuses, HwModel, InitParams, SecurityState};
 use caliptra_hw_model::{DefaultHwModel, ModelError};
@@ -50,6 +51,7 @@ pub fn get_data<'a>(to_match: &str, haystack: &'a str) -> &'a str {
 pub fn get_csr(hw: &mut DefaultHwModel) -> Result<Vec<u8>, ModelError> {
     hw.step_until(|m| m.soc_ifc().cptra_flow_status().read().status() == 0x0800_0000);
     let mut txn = hw.wait_for_mailbox_receive()?;
+    assert_eq!(txn.req.cmd, u32::from(CommandId::GetIdevCsr));
     let result = mem::take(&mut txn.req.data);
     txn.respond_success();
     hw.soc_ifc().cptra_dbg_manuf_service_reg().write(|_| 0);
@@ -0,0 +1,312 @@
+// Licensed under the Apache-2.0 license
+
+use caliptra_api::mailbox::{GetFmcAliasCertReq, QuotePcrsReq, DEBUG_UNLOCK_LABEL};
+use caliptra_api::{CommandId, MailboxReqHeader};
+use caliptra_builder::{
+    FwId, ImageOptions, APP_WITH_UART, FMC_WITH_UART, ROM_WITH_UART,
+    ROM_WITH_UART_DETERMINISTIC_RNG,
+};
+use caliptra_drivers::state::MfgFlags;
+use caliptra_hw_model::{
+    BootParams, DefaultHwModel, DeviceLifecycle, Fuses, HwModel, InitParams, ModelError,
+    SecurityState,
+};
+use caliptra_image_fake_keys::{
+    OWNER_KEY_PRIVATE, OWNER_KEY_PUBLIC, VENDOR_KEY_0_PRIVATE, VENDOR_KEY_0_PUBLIC,
+};
+use caliptra_image_gen::{ImageGenerator, ImageGeneratorCrypto};
+use caliptra_image_openssl::OsslCrypto;
+use caliptra_image_types::{ImageBundle, ImageEccPrivKey, ImageEccPubKey, ImageOwnerPubKeys};
+use zerocopy::AsBytes;
+
+// [TODO] Use the error codes from the common library.
+const DEBUG_UNLOCK_OWNER_KEY_MISMATCH: u32 = 0x07000005;
+const DEBUG_UNLOCK_INVALID_SIGNATURE: u32 = 0x07000006;
+
+/// DICE TcbInfo flag set while the device is debug locked
+const FLAG_BIT_DEBUG: u32 = 1 << 3;
+
+fn build_locked_hw_model(debug_unlock: bool) -> (DefaultHwModel, ImageBundle) {
+    build_locked_hw_model_with_rom(&ROM_WITH_UART, debug_unlock)
+}
+
+fn build_locked_hw_model_with_rom(
+    rom_id: &FwId,
+    debug_unlock: bool,
+) -> (DefaultHwModel, ImageBundle) {
+    let image_bundle = caliptra_builder::build_and_sign_image(
+        &FMC_WITH_UART,
+        &APP_WITH_UART,
+        ImageOptions::default(),
+    )
+    .unwrap();
+
+    let gen = ImageGenerator::new(OsslCrypto::default());
+    let fuses = Fuses {
+        life_cycle: DeviceLifecycle::Manufacturing,
+        owner_pk_hash: gen
+            .owner_pubkey_digest(&image_bundle.manifest.preamble)
+            .unwrap(),
+        ..Default::default()
+    };
+
+    let mut security_state = SecurityState::from(fuses.life_cycle as u32);
+    security_state.set_debug_locked(true);
+
+    let rom = caliptra_builder::build_firmware_rom(rom_id).unwrap();
+    let mut hw = caliptra_hw_model::new(BootParams {
+        init_params: InitParams {
+            rom: &rom,
+            security_state,
+            ..Default::default()
+        },
+        fuses,
+        fw_image: None,
+    })
+    .unwrap();
+
+    if debug_unlock {
+        hw.soc_ifc()
+            .cptra_dbg_manuf_service_reg()
+            .write(|_| MfgFlags::DEBUG_UNLOCK.bits());
+    }
+
+    (hw, image_bundle)
+}
+
+fn get_challenge(hw: &mut DefaultHwModel) -> Vec<u8> {
+    let mut txn = hw.wait_for_mailbox_receive().unwrap();
+    assert_eq!(txn.req.cmd, u32::from(CommandId::DebugUnlockChallenge));
+    let challenge = std::mem::take(&mut txn.req.data);
+    txn.respond_success();
+    challenge
+}
+
+/// Make a debug unlock token carrying `pub_keys`, signing `data` with
+/// `priv_key`
+fn make_token_with_key(
+    pub_keys: &ImageOwnerPubKeys,
+    data: &[u8],
+    priv_key: &ImageEccPrivKey,
+    pub_key: &ImageEccPubKey,
+) -> Vec<u8> {
+    let crypto = OsslCrypto::default();
+    let digest = crypto.sha384_digest(data).unwrap();
+    let sig = crypto.ecdsa384_sign(&digest, priv_key, pub_key).unwrap();
+
+    let mut token = pub_keys.as_bytes().to_vec();
+    token.extend_from_slice(sig.as_bytes());
+    token
+}
+
+fn make_token(image_bundle: &ImageBundle, challenge: &[u8]) -> Vec<u8> {
+    let mut data = DEBUG_UNLOCK_LABEL.to_vec();
+    data.extend_from_slice(challenge);
+    make_token_with_key(
+        &image_bundle.manifest.preamble.owner_pub_keys,
+        &data,
+        &OWNER_KEY_PRIVATE,
+        &OWNER_KEY_PUBLIC,
+    )
+}
+
+fn send_token(hw: &mut DefaultHwModel, token: &[u8]) -> Result<Option<Vec<u8>>, ModelError> {
+    // ROM releases the mailbox once it has seen the challenge response
+    loop {
+        match hw.mailbox_execute(CommandId::DebugUnlockToken.into(), token) {
+            Err(ModelError::UnableToLockMailbox) => hw.step(),
+            result => break result,
+        }
+    }
+}
+
+/// Boot to runtime and return PCR0 and the DICE TcbInfo flags of the FMC
+/// Alias certificate
+fn boot_measurements(hw: &mut DefaultHwModel, image_bundle: &ImageBundle) -> ([u8; 48], u32) {
+    hw.upload_firmware(&image_bundle.to_bytes().unwrap())
+        .unwrap();
+    hw.step_until_output_contains("Caliptra RT listening for mailbox commands...")
+        .unwrap();
+
+    let quote = hw
+        .mailbox_execute_req(QuotePcrsReq {
+            hdr: MailboxReqHeader::default(),
+            nonce: [0u8; 32],
+        })
+        .unwrap();
+    let cert = hw
+        .mailbox_execute_req(GetFmcAliasCertReq::default())
+        .unwrap();
+
+    (quote.pcrs[0], tcb_info_flags(cert.cert().unwrap()))
+}
+
+/// Find the flags of the DICE TcbInfo extension in a certificate
+fn tcb_info_flags(cert: &[u8]) -> u32 {
+    // [7] IMPLICIT BIT STRING flags followed by [10] IMPLICIT BIT STRING
+    // flags mask
+    let pos = cert
+        .windows(10)
+        .position(|w| w[..3] == [0x87, 0x05, 0x00] && w[7..] == [0x8A, 0x05, 0x00])
+        .unwrap();
+    u32::from_be_bytes(cert[pos + 3..pos + 7].try_into().unwrap())
+}
+
+/// Check that a rejected token is reported as the non-fatal error `err`, and
+/// that the device boots debug locked
+fn assert_boots_locked(hw: &mut DefaultHwModel, image_bundle: &ImageBundle, err: Option<u32>) {
+    if let Some(err) = err {
+        assert_eq!(hw.soc_ifc().cptra_fw_error_non_fatal().read(), err);
+    }
+    assert_eq!(hw.soc_ifc().cptra_fw_error_fatal().read(), 0);
+
+    let (_, flags) = boot_measurements(hw, image_bundle);
+    assert_ne!(flags & FLAG_BIT_DEBUG, 0);
+}
+
+#[test]
+fn test_debug_unlock() {
+    let (mut hw, image_bundle) = build_locked_hw_model(false);
+    let (locked_pcr0, locked_flags) = boot_measurements(&mut hw, &image_bundle);
+    assert_ne!(locked_flags & FLAG_BIT_DEBUG, 0);
+
+    let (mut hw, image_bundle) = build_locked_hw_model(true);
+
+    let challenge = get_challenge(&mut hw);
+    assert_eq!(challenge.len(), 48);
+
+    let token = make_token(&image_bundle, &challenge);
+    assert_eq!(send_token(&mut hw, &token), Ok(None));
+
+    hw.step_until_output_contains("[dbg] Debug unlocked")
+        .unwrap();
+
+    // The device is measured as not debug locked
+    let (unlocked_pcr0, unlocked_flags) = boot_measurements(&mut hw, &image_bundle);
+    assert_ne!(unlocked_pcr0, locked_pcr0);
+    assert_eq!(unlocked_flags, locked_flags & !FLAG_BIT_DEBUG);
+}
+
+#[test]
+fn test_debug_unlock_stale_challenge() {
+    let (mut hw, image_bundle) = build_locked_hw_model(true);
+
+    let mut challenge = get_challenge(&mut hw);
+    challenge[0] ^= 1;
+
+    let token = make_token(&image_bundle, &challenge);
+    assert_eq!(
+        send_token(&mut hw, &token),
+        Err(ModelError::MailboxCmdFailed)
+    );
+
+    assert_boots_locked(&mut hw, &image_bundle, Some(DEBUG_UNLOCK_INVALID_SIGNATURE));
+}
+
+#[test]
+fn test_debug_unlock_missing_label() {
+    let (mut hw, image_bundle) = build_locked_hw_model(true);
+
+    // A signature over the challenge alone is not a token
+    let challenge = get_challenge(&mut hw);
+    let token = make_token_with_key(
+        &image_bundle.manifest.preamble.owner_pub_keys,
+        &challenge,
+        &OWNER_KEY_PRIVATE,
+        &OWNER_KEY_PUBLIC,
+    );
+    assert_eq!(
+        send_token(&mut hw, &token),
+        Err(ModelError::MailboxCmdFailed)
+    );
+
+    assert_boots_locked(&mut hw, &image_bundle, Some(DEBUG_UNLOCK_INVALID_SIGNATURE));
+}
+
+#[test]
+fn test_debug_unlock_wrong_key() {
+    let (mut hw, image_bundle) = build_locked_hw_model(true);
+
+    let challenge = get_challenge(&mut hw);
+    let mut data = DEBUG_UNLOCK_LABEL.to_vec();
+    data.extend_from_slice(&challenge);
+
+    // The owner public keys signed with another key
+    let token = make_token_with_key(
+        &image_bundle.manifest.preamble.owner_pub_keys,
+        &data,
+        &VENDOR_KEY_0_PRIVATE,
+        &VENDOR_KEY_0_PUBLIC,
+    );
+    assert_eq!(
+        send_token(&mut hw, &token),
+        Err(ModelError::MailboxCmdFailed)
+    );
+
+    assert_boots_locked(&mut hw, &image_bundle, Some(DEBUG_UNLOCK_INVALID_SIGNATURE));
+}
+
+#[test]
+fn test_debug_unlock_unfused_key() {
+    let (mut hw, image_bundle) = build_locked_hw_model(true);
+
+    let challenge = get_challenge(&mut hw);
+    let mut data = DEBUG_UNLOCK_LABEL.to_vec();
+    data.extend_from_slice(&challenge);
+
+    // A valid signature with a key that does not match the fuses
+    let pub_keys = ImageOwnerPubKeys {
+        ecc_pub_key: VENDOR_KEY_0_PUBLIC,
+        ..image_bundle.manifest.preamble.owner_pub_keys
+    };
+    let token = make_token_with_key(
+        &pub_keys,
+        &data,
+        &VENDOR_KEY_0_PRIVATE,
+        &VENDOR_KEY_0_PUBLIC,
+    );
+    assert_eq!(
+        send_token(&mut hw, &token),
+        Err(ModelError::MailboxCmdFailed)
+    );
+
+    assert_boots_locked(
+        &mut hw,
+        &image_bundle,
+        Some(DEBUG_UNLOCK_OWNER_KEY_MISMATCH),
+    );
+}
+
+#[test]
+fn test_debug_unlock_declined() {
+    let (mut hw, image_bundle) = build_locked_hw_model(true);
+
+    // Any other command declines the unlock
+    get_challenge(&mut hw);
+    loop {
+        match hw.mailbox_execute(CommandId::DebugUnlockChallenge.into(), &[]) {
+            Err(ModelError::UnableToLockMailbox) => hw.step(),
+            result => {
+                assert_eq!(result, Err(ModelError::MailboxCmdFailed));
+                break;
+            }
+        }
+    }
+
+    hw.step_until_output_contains("[dbg] Debug unlock declined")
+        .unwrap();
+    assert_boots_locked(&mut hw, &image_bundle, None);
+}
+
+#[test]
+fn test_debug_unlock_challenge_deterministic_rng() {
+    let challenge = |rom_id: &FwId| {
+        let (mut hw, _) = build_locked_hw_model_with_rom(rom_id, true);
+        get_challenge(&mut hw)
+    };
+
+    // The CSRNG is seeded from a constant instead of the entropy source
+    let deterministic = challenge(&ROM_WITH_UART_DETERMINISTIC_RNG);
+    assert_eq!(deterministic, challenge(&ROM_WITH_UART_DETERMINISTIC_RNG));
+    assert_ne!(deterministic, challenge(&ROM_WITH_UART));
+}
@@ -1,13 +1,18 @@
 // Licensed under the Apache-2.0 license
 
+use caliptra_api::CommandId;
 use caliptra_builder::ImageOptions;
 use caliptra_hw_model::{Fuses, HwModel, ModelError};
-use caliptra_image_types::IMAGE_BYTE_SIZE;
+use caliptra_image_gen::ImageGeneratorAuxImage;
+use caliptra_image_types::{IMAGE_CHUNK_BYTE_SIZE, IMAGE_MANIFEST_BYTE_SIZE};
 
 pub mod helpers;
 
 // [TODO] Use the error codes from the common library.
 const INVALID_IMAGE_SIZE: u32 = 0x02000003;
+const IMAGE_CHUNK_CMD_INVALID: u32 = 0x06000003;
+const IMAGE_SECTION_DIGEST_MISMATCH: u32 = 0x06000005;
+const IMAGE_TOO_LARGE: u32 = 0x06000006;
 
 #[test]
 fn test_zero_firmware_size() {
@@ -27,8 +32,6 @@ fn test_zero_firmware_size() {
 
 #[test]
 fn test_firmware_gt_max_size() {
-    const FW_LOAD_CMD_OPCODE: u32 = 0x4657_4C44;
-
     // Firmware size > 128 KB.
 
     let (mut hw, _image_bundle) =
@@ -37,9 +40,13 @@ fn test_firmware_gt_max_size() {
     // Manually put the oversize data in the mailbox because
     // HwModel::upload_firmware won't let us.
     assert!(!hw.soc_mbox().lock().read().lock());
-    hw.soc_mbox().cmd().write(|_| FW_LOAD_CMD_OPCODE);
-    hw.soc_mbox().dlen().write(|_| (IMAGE_BYTE_SIZE + 1) as u32);
-    for i in 0..((IMAGE_BYTE_SIZE + 1 + 3) / 4) {
+    hw.soc_mbox()
+        .cmd()
+        .write(|_| CommandId::FirmwareLoad.into());
+    hw.soc_mbox()
+        .dlen()
+        .write(|_| (IMAGE_CHUNK_BYTE_SIZE + 1) as u32);
+    for i in 0..((IMAGE_CHUNK_BYTE_SIZE + 1 + 3) / 4) {
         hw.soc_mbox().datain().write(|_| i as u32);
     }
     hw.soc_mbox().execute().write(|w| w.execute(true));
@@ -53,3 +60,81 @@ fn test_firmware_gt_max_size() {
         INVALID_IMAGE_SIZE
     );
 }
+
+#[test]
+fn test_chunked_firmware_load() {
+    let (mut hw, image_bundle) =
+        helpers::build_hw_model_and_image_bundle(Fuses::default(), ImageOptions::default());
+
+    hw.upload_firmware_chunked(&image_bundle.to_bytes().unwrap(), 4096)
+        .unwrap();
+    hw.step_until_output_contains("[exit] Launching FMC")
+        .unwrap();
+}
+
+#[test]
+fn test_chunked_firmware_load_digest_mismatch() {
+    let (mut hw, image_bundle) =
+        helpers::build_hw_model_and_image_bundle(Fuses::default(), ImageOptions::default());
+
+    // Corrupt the last byte of the runtime.
+    let mut image = image_bundle.to_bytes().unwrap();
+    *image.last_mut().unwrap() ^= 1;
+
+    assert_eq!(
+        hw.upload_firmware_chunked(&image, 4096).unwrap_err(),
+        ModelError::MailboxCmdFailed
+    );
+    assert_eq!(
+        hw.soc_ifc().cptra_fw_error_non_fatal().read(),
+        IMAGE_SECTION_DIGEST_MISMATCH
+    );
+}
+
+#[test]
+fn test_chunked_firmware_load_invalid_cmd() {
+    let (mut hw, image_bundle) =
+        helpers::build_hw_model_and_image_bundle(Fuses::default(), ImageOptions::default());
+
+    let image = image_bundle.to_bytes().unwrap();
+    let (manifest, sections) = image.split_at(IMAGE_MANIFEST_BYTE_SIZE);
+    assert_eq!(
+        hw.mailbox_execute(CommandId::FirmwareLoad.into(), manifest),
+        Ok(None)
+    );
+
+    // The image sections must be sent as chunks.
+    assert_eq!(
+        hw.mailbox_execute(CommandId::FirmwareLoad.into(), sections),
+        Err(ModelError::MailboxCmdFailed)
+    );
+    assert_eq!(
+        hw.soc_ifc().cptra_fw_error_non_fatal().read(),
+        IMAGE_CHUNK_CMD_INVALID
+    );
+}
+
+#[test]
+fn test_chunked_firmware_load_larger_than_iccm() {
+    // The auxiliary image alone fills ICCM
+    let image_options = ImageOptions {
+        aux: vec![ImageGeneratorAuxImage {
+            id: 3,
+            rev: Default::default(),
+            content: vec![0xA5; 128 * 1024],
+        }],
+        ..Default::default()
+    };
+    let (mut hw, image_bundle) =
+        helpers::build_hw_model_and_image_bundle(Fuses::default(), image_options);
+
+    assert_eq!(
+        hw.upload_firmware(&image_bundle.to_bytes().unwrap())
+            .unwrap_err(),
+        ModelError::MailboxCmdFailed
+    );
+    assert_eq!(
+        hw.soc_ifc().cptra_fw_error_non_fatal().read(),
+        IMAGE_TOO_LARGE
+    );
+}
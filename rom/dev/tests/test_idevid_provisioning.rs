@@ -0,0 +1,202 @@
+// Licensed under the Apache-2.0 license
+
+use caliptra_api::{mailbox::GetIdevIdCertReq, CommandId};
+use caliptra_builder::ImageOptions;
+use caliptra_drivers::state::MfgFlags;
+use caliptra_hw_model::{DefaultHwModel, DeviceLifecycle, Fuses, HwModel, ModelError};
+use caliptra_image_types::ImageBundle;
+use openssl::asn1::{Asn1Integer, Asn1Time};
+use openssl::bn::{BigNum, BigNumContext};
+use openssl::ec::{EcGroup, EcKey, PointConversionForm};
+use openssl::hash::MessageDigest;
+use openssl::nid::Nid;
+use openssl::pkey::{PKey, Private};
+use openssl::sha::sha384;
+use openssl::x509::{X509NameBuilder, X509Req, X509};
+
+pub mod helpers;
+
+// [TODO] Use the error codes from the common library.
+const INIT_DEV_ID_CERT_HSM_ID_MISMATCH: u32 = 0x00000006;
+const INIT_DEV_ID_CERT_PUB_KEY_MISMATCH: u32 = 0x00000009;
+const INIT_DEV_ID_CERT_INVALID_SIGNATURE: u32 = 0x0000000A;
+
+fn generate_key() -> PKey<Private> {
+    let group = EcGroup::from_curve_name(Nid::SECP384R1).unwrap();
+    PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap()
+}
+
+/// HSM public key as sent to ROM, X followed by Y
+fn hsm_pub_key(hsm_key: &PKey<Private>) -> Vec<u8> {
+    let ec_key = hsm_key.ec_key().unwrap();
+    let mut ctx = BigNumContext::new().unwrap();
+    let point = ec_key
+        .public_key()
+        .to_bytes(ec_key.group(), PointConversionForm::UNCOMPRESSED, &mut ctx)
+        .unwrap();
+    point[1..].to_vec()
+}
+
+/// HSM identifier fused for `hsm_key`, the leading 128 bits of the SHA-384
+/// digest of its public key
+fn hsm_id(hsm_key: &PKey<Private>) -> [u32; 4] {
+    let digest = sha384(&hsm_pub_key(hsm_key));
+    let mut id = [0u32; 4];
+    for (word, bytes) in id.iter_mut().zip(digest.chunks_exact(4)) {
+        *word = u32::from_be_bytes(bytes.try_into().unwrap());
+    }
+    id
+}
+
+fn build_provisioning_hw_model(hsm_key: &PKey<Private>) -> (DefaultHwModel, ImageBundle) {
+    let fuses = Fuses {
+        life_cycle: DeviceLifecycle::Manufacturing,
+        idevid_manuf_hsm_id: hsm_id(hsm_key),
+        ..Default::default()
+    };
+    let (mut hw, image_bundle) =
+        helpers::build_hw_model_and_image_bundle(fuses, ImageOptions::default());
+
+    let flags = MfgFlags::GENERATE_IDEVID_CSR | MfgFlags::PROVISION_IDEVID_CERT;
+    hw.soc_ifc()
+        .cptra_dbg_manuf_service_reg()
+        .write(|_| flags.bits());
+
+    (hw, image_bundle)
+}
+
+/// Issue a certificate for the CSR subject with `hsm_key`
+fn hsm_sign_cert(hsm_key: &PKey<Private>, csr: &[u8]) -> Vec<u8> {
+    let req = X509Req::from_der(csr).unwrap();
+
+    let mut issuer = X509NameBuilder::new().unwrap();
+    issuer
+        .append_entry_by_text("CN", "Caliptra Test HSM")
+        .unwrap();
+    let issuer = issuer.build();
+
+    let mut builder = X509::builder().unwrap();
+    builder.set_version(2).unwrap();
+    let serial_number = BigNum::from_u32(1).unwrap();
+    builder
+        .set_serial_number(&Asn1Integer::from_bn(&serial_number).unwrap())
+        .unwrap();
+    builder.set_issuer_name(&issuer).unwrap();
+    builder.set_subject_name(req.subject_name()).unwrap();
+    builder
+        .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+        .unwrap();
+    builder
+        .set_not_after(&Asn1Time::days_from_now(1).unwrap())
+        .unwrap();
+    builder.set_pubkey(&req.public_key().unwrap()).unwrap();
+    builder.sign(hsm_key, MessageDigest::sha384()).unwrap();
+
+    builder.build().to_der().unwrap()
+}
+
+fn send_cert(
+    hw: &mut DefaultHwModel,
+    hsm_key: &PKey<Private>,
+    cert: &[u8],
+) -> Result<(), ModelError> {
+    let mut req = hsm_pub_key(hsm_key);
+    req.extend_from_slice(cert);
+
+    // ROM releases the mailbox once the CSR has been downloaded
+    loop {
+        match hw.mailbox_execute(CommandId::ProvisionIdevCert.into(), &req) {
+            Err(ModelError::UnableToLockMailbox) => hw.step(),
+            result => break result.map(|_| ()),
+        }
+    }
+}
+
+#[test]
+fn test_idevid_cert_provisioning() {
+    let hsm_key = generate_key();
+    let (mut hw, image_bundle) = build_provisioning_hw_model(&hsm_key);
+
+    let csr = helpers::get_csr(&mut hw).unwrap();
+    let cert = hsm_sign_cert(&hsm_key, &csr);
+    send_cert(&mut hw, &hsm_key, &cert).unwrap();
+
+    hw.step_until_output_contains("[idev] Cert provisioned")
+        .unwrap();
+
+    hw.step_until(|m| m.soc_ifc().cptra_flow_status().read().ready_for_fw());
+    hw.upload_firmware(&image_bundle.to_bytes().unwrap())
+        .unwrap();
+
+    hw.step_until_output_contains("Caliptra RT listening for mailbox commands...")
+        .unwrap();
+
+    let resp = hw.mailbox_execute_req(GetIdevIdCertReq::default()).unwrap();
+    assert_eq!(resp.cert().unwrap(), cert);
+}
+
+#[test]
+fn test_idevid_cert_hsm_id_mismatch() {
+    let hsm_key = generate_key();
+    let (mut hw, _) = build_provisioning_hw_model(&hsm_key);
+
+    // A certificate issued by an HSM other than the fused one
+    let csr = helpers::get_csr(&mut hw).unwrap();
+    let other_key = generate_key();
+    let cert = hsm_sign_cert(&other_key, &csr);
+
+    assert_eq!(
+        send_cert(&mut hw, &other_key, &cert),
+        Err(ModelError::MailboxCmdFailed)
+    );
+
+    assert_eq!(
+        hw.soc_ifc().cptra_fw_error_fatal().read(),
+        INIT_DEV_ID_CERT_HSM_ID_MISMATCH
+    );
+}
+
+#[test]
+fn test_idevid_cert_invalid_signature() {
+    let hsm_key = generate_key();
+    let (mut hw, _) = build_provisioning_hw_model(&hsm_key);
+
+    // The fused HSM key is presented but did not sign the certificate
+    let csr = helpers::get_csr(&mut hw).unwrap();
+    let cert = hsm_sign_cert(&generate_key(), &csr);
+
+    assert_eq!(
+        send_cert(&mut hw, &hsm_key, &cert),
+        Err(ModelError::MailboxCmdFailed)
+    );
+
+    assert_eq!(
+        hw.soc_ifc().cptra_fw_error_fatal().read(),
+        INIT_DEV_ID_CERT_INVALID_SIGNATURE
+    );
+}
+
+#[test]
+fn test_idevid_cert_pub_key_mismatch() {
+    let hsm_key = generate_key();
+    let (mut hw, _) = build_provisioning_hw_model(&hsm_key);
+
+    helpers::get_csr(&mut hw).unwrap();
+
+    // Certify a key other than the IDevID key
+    let other_key = generate_key();
+    let mut req = X509Req::builder().unwrap();
+    req.set_pubkey(&other_key).unwrap();
+    req.sign(&other_key, MessageDigest::sha384()).unwrap();
+    let cert = hsm_sign_cert(&hsm_key, &req.build().to_der().unwrap());
+
+    assert_eq!(
+        send_cert(&mut hw, &hsm_key, &cert),
+        Err(ModelError::MailboxCmdFailed)
+    );
+
+    assert_eq!(
+        hw.soc_ifc().cptra_fw_error_fatal().read(),
+        INIT_DEV_ID_CERT_PUB_KEY_MISMATCH
+    );
+}
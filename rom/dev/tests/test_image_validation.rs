@@ -11,9 +11,14 @@ use caliptra_image_elf::ElfExecutable;
 use caliptra_image_fake_keys::{
     VENDOR_CONFIG_KEY_0, VENDOR_CONFIG_KEY_1, VENDOR_CONFIG_KEY_2, VENDOR_CONFIG_KEY_3,
 };
-use caliptra_image_gen::{ImageGenerator, ImageGeneratorConfig, ImageGeneratorVendorConfig};
+use caliptra_image_gen::{
+    ImageGenerator, ImageGeneratorConfig, ImageGeneratorCrypto, ImageGeneratorVendorConfig,
+};
 use caliptra_image_openssl::OsslCrypto;
-use caliptra_image_types::{ImageBundle, ImageManifest, VENDOR_ECC_KEY_COUNT};
+use caliptra_image_types::{
+    ImageBundle, ImageManifest, ImageTocEntry, ImageTocEntryType, VENDOR_ECC_KEY_COUNT,
+    VENDOR_LMS_KEY_COUNT,
+};
 use openssl::asn1::Asn1Integer;
 use openssl::asn1::Asn1Time;
 use openssl::bn::BigNum;
@@ -40,6 +45,7 @@ const TOC_DIGEST_MISMATCH: u32 = 0x0B000012;
 const FMC_RUNTIME_OVERLAP: u32 = 0x0B000017;
 const FMC_RUNTIME_INCORRECT_ORDER: u32 = 0x0B000018;
 const FMC_DIGEST_MISMATCH: u32 = 0x0B000014;
+const RUNTIME_DIGEST_FAILURE: u32 = 0x0B000015;
 const RUNTIME_DIGEST_MISMATCH: u32 = 0x0B000016;
 const OWNER_PUB_KEY_DIGEST_INVALID_ARG: u32 = 0x0B000019;
 const OWNER_PUB_KEY_DIGEST_MISMATCH: u32 = 0x0B000007;
@@ -61,6 +67,15 @@ const RUNTIME_ENTRY_POINT_UNALIGNED: u32 = 0x0B00002B;
 const RUNTIME_SVN_GREATER_THAN_MAX_SUPPORTED: u32 = 0x0B00002C;
 const RUNTIME_SVN_LESS_THAN_MIN_SUPPORTED: u32 = 0x0B00002D;
 const RUNTIME_SVN_LESS_THAN_FUSE: u32 = 0x0B00002E;
+const VENDOR_LMS_PUB_KEY_INDEX_OUT_OF_BOUNDS: u32 = 0x0B00002F;
+const VENDOR_LMS_PUB_KEY_REVOKED: u32 = 0x0B000030;
+const VENDOR_LMS_SIGNATURE_INVALID: u32 = 0x0B000033;
+const OWNER_LMS_SIGNATURE_INVALID: u32 = 0x0B000035;
+const VENDOR_IMAGE_EXPIRED: u32 = 0x0B000039;
+const OWNER_IMAGE_NOT_YET_VALID: u32 = 0x0B00003B;
+const TRUSTED_TIME_UNAVAILABLE: u32 = 0x0B000041;
+const AUX_IMAGE_INCORRECT_ORDER: u32 = 0x0B00003E;
+const AUX_DIGEST_MISMATCH: u32 = 0x0B000040;
 
 const ICCM_START_ADDR: u32 = 0x40000000;
 const ICCM_END_ADDR: u32 = ICCM_START_ADDR + (128 * 1024) - 1;
@@ -261,6 +276,194 @@ fn test_preamble_vendor_pubkey_out_of_bounds() {
     );
 }
 
+#[test]
+fn test_preamble_vendor_lms_pubkey_revocation() {
+    let rom = caliptra_builder::build_firmware_rom(&ROM_WITH_UART).unwrap();
+    const LAST_KEY_IDX: u32 = VENDOR_LMS_KEY_COUNT - 1;
+    const VENDOR_CONFIG_LIST: [ImageGeneratorVendorConfig; VENDOR_LMS_KEY_COUNT as usize] = [
+        VENDOR_CONFIG_KEY_0,
+        VENDOR_CONFIG_KEY_1,
+        VENDOR_CONFIG_KEY_2,
+        VENDOR_CONFIG_KEY_3,
+    ];
+
+    for vendor_config in VENDOR_CONFIG_LIST {
+        let mut image_options = ImageOptions::default();
+        let key_idx = vendor_config.lms_key_idx;
+        image_options.vendor_config = vendor_config;
+
+        let fuses = caliptra_hw_model::Fuses {
+            lms_revocation: U4::try_from(1u32 << key_idx).unwrap(),
+            ..Default::default()
+        };
+
+        let mut hw = caliptra_hw_model::new(BootParams {
+            init_params: InitParams {
+                rom: &rom,
+                ..Default::default()
+            },
+            fuses,
+            fw_image: None,
+        })
+        .unwrap();
+
+        let image_bundle =
+            caliptra_builder::build_and_sign_image(&FMC_WITH_UART, &APP_WITH_UART, image_options)
+                .unwrap();
+
+        if key_idx == LAST_KEY_IDX {
+            // Last key is never revoked.
+            hw.upload_firmware(&image_bundle.to_bytes().unwrap())
+                .unwrap();
+            hw.step_until_output_contains("Caliptra RT listening for mailbox commands...")
+                .unwrap();
+        } else {
+            assert_eq!(
+                ModelError::MailboxCmdFailed,
+                hw.upload_firmware(&image_bundle.to_bytes().unwrap())
+                    .unwrap_err()
+            );
+            assert_eq!(
+                hw.soc_ifc().cptra_fw_error_non_fatal().read(),
+                VENDOR_LMS_PUB_KEY_REVOKED
+            );
+        }
+    }
+}
+
+#[test]
+fn test_preamble_vendor_lms_pubkey_out_of_bounds() {
+    let (mut hw, mut image_bundle) =
+        helpers::build_hw_model_and_image_bundle(Fuses::default(), ImageOptions::default());
+    image_bundle.manifest.preamble.vendor_lms_pub_key_idx = VENDOR_LMS_KEY_COUNT;
+
+    assert_eq!(
+        ModelError::MailboxCmdFailed,
+        hw.upload_firmware(&image_bundle.to_bytes().unwrap())
+            .unwrap_err()
+    );
+    assert_eq!(
+        hw.soc_ifc().cptra_fw_error_non_fatal().read(),
+        VENDOR_LMS_PUB_KEY_INDEX_OUT_OF_BOUNDS
+    );
+}
+
+#[test]
+fn test_header_verify_vendor_lms_signature_invalid() {
+    let (mut hw, mut image_bundle) =
+        helpers::build_hw_model_and_image_bundle(Fuses::default(), ImageOptions::default());
+
+    // Modify the nonce of the vendor LMS signature.
+    image_bundle.manifest.preamble.vendor_sigs.lms_sig.ots.nonce[0] ^= 0x01;
+
+    assert_eq!(
+        ModelError::MailboxCmdFailed,
+        hw.upload_firmware(&image_bundle.to_bytes().unwrap())
+            .unwrap_err()
+    );
+    assert_eq!(
+        hw.soc_ifc().cptra_fw_error_non_fatal().read(),
+        VENDOR_LMS_SIGNATURE_INVALID
+    );
+}
+
+#[test]
+fn test_header_verify_owner_lms_signature_invalid() {
+    let (mut hw, mut image_bundle) =
+        helpers::build_hw_model_and_image_bundle(Fuses::default(), ImageOptions::default());
+
+    // Modify the tree path of the owner LMS signature.
+    image_bundle.manifest.preamble.owner_sigs.lms_sig.tree_path[0][0] ^= 0x01;
+
+    assert_eq!(
+        ModelError::MailboxCmdFailed,
+        hw.upload_firmware(&image_bundle.to_bytes().unwrap())
+            .unwrap_err()
+    );
+    assert_eq!(
+        hw.soc_ifc().cptra_fw_error_non_fatal().read(),
+        OWNER_LMS_SIGNATURE_INVALID
+    );
+}
+
+#[test]
+fn test_header_vendor_validity_expired() {
+    let mut image_options = ImageOptions::default();
+    image_options.vendor_config.not_before = *b"20230101000000Z";
+    image_options.vendor_config.not_after = *b"20231231235959Z";
+    let (mut hw, image_bundle) =
+        helpers::build_hw_model_and_image_bundle(Fuses::default(), image_options);
+
+    // 2024-01-01T00:00:00Z
+    set_trusted_time(&mut hw, 1704067200);
+
+    assert_eq!(
+        ModelError::MailboxCmdFailed,
+        hw.upload_firmware(&image_bundle.to_bytes().unwrap())
+            .unwrap_err()
+    );
+    assert_eq!(
+        hw.soc_ifc().cptra_fw_error_non_fatal().read(),
+        VENDOR_IMAGE_EXPIRED
+    );
+}
+
+#[test]
+fn test_header_owner_validity_not_yet_valid() {
+    let mut image_options = ImageOptions::default();
+    image_options.vendor_config.not_before = *b"20230101000000Z";
+    image_options.vendor_config.not_after = *b"20231231235959Z";
+    let mut owner_config = image_options.owner_config.unwrap();
+    owner_config.not_before = *b"20230701000000Z";
+    owner_config.not_after = *b"20231231235959Z";
+    image_options.owner_config = Some(owner_config);
+    let (mut hw, image_bundle) =
+        helpers::build_hw_model_and_image_bundle(Fuses::default(), image_options);
+
+    // 2023-06-01T00:00:00Z
+    set_trusted_time(&mut hw, 1685577600);
+
+    assert_eq!(
+        ModelError::MailboxCmdFailed,
+        hw.upload_firmware(&image_bundle.to_bytes().unwrap())
+            .unwrap_err()
+    );
+    assert_eq!(
+        hw.soc_ifc().cptra_fw_error_non_fatal().read(),
+        OWNER_IMAGE_NOT_YET_VALID
+    );
+}
+
+#[test]
+fn test_header_validity_production_without_trusted_time() {
+    let gen = ImageGenerator::new(OsslCrypto::default());
+    let (_hw, image_bundle) =
+        helpers::build_hw_model_and_image_bundle(Fuses::default(), ImageOptions::default());
+    let vendor_pubkey_digest = gen
+        .vendor_pubkey_digest(&image_bundle.manifest.preamble)
+        .unwrap();
+
+    let fuses = Fuses {
+        life_cycle: DeviceLifecycle::Production,
+        key_manifest_pk_hash: vendor_pubkey_digest,
+        ..Default::default()
+    };
+    let mut image_options = ImageOptions::default();
+    image_options.vendor_config.not_before = *b"20230101000000Z";
+    image_options.vendor_config.not_after = *b"20231231235959Z";
+    let (mut hw, image_bundle) = helpers::build_hw_model_and_image_bundle(fuses, image_options);
+
+    assert_eq!(
+        ModelError::MailboxCmdFailed,
+        hw.upload_firmware(&image_bundle.to_bytes().unwrap())
+            .unwrap_err()
+    );
+    assert_eq!(
+        hw.soc_ifc().cptra_fw_error_non_fatal().read(),
+        TRUSTED_TIME_UNAVAILABLE
+    );
+}
+
 #[test]
 fn test_header_verify_vendor_sig_zero_pubkey() {
     let (mut hw, mut image_bundle) =
@@ -745,6 +948,49 @@ fn test_toc_invalid_entry_count() {
     );
 }
 
+#[test]
+fn test_toc_aux_digest_mismatch() {
+    let (mut hw, mut image_bundle) =
+        helpers::build_hw_model_and_image_bundle(Fuses::default(), ImageOptions::default());
+
+    add_aux_image(&mut image_bundle, 3, vec![0xA5; 64]);
+    image_bundle.manifest.aux[0].digest[0] ^= 1;
+    update_toc_digest(&mut image_bundle);
+    update_header(&mut image_bundle);
+
+    assert_eq!(
+        ModelError::MailboxCmdFailed,
+        hw.upload_firmware(&image_bundle.to_bytes().unwrap())
+            .unwrap_err()
+    );
+    assert_eq!(
+        hw.soc_ifc().cptra_fw_error_non_fatal().read(),
+        AUX_DIGEST_MISMATCH
+    );
+}
+
+#[test]
+fn test_toc_aux_incorrect_order() {
+    let (mut hw, mut image_bundle) =
+        helpers::build_hw_model_and_image_bundle(Fuses::default(), ImageOptions::default());
+
+    // Place the auxiliary image inside the runtime section.
+    add_aux_image(&mut image_bundle, 3, vec![0xA5; 64]);
+    image_bundle.manifest.aux[0].offset = image_bundle.manifest.runtime.offset;
+    update_toc_digest(&mut image_bundle);
+    update_header(&mut image_bundle);
+
+    assert_eq!(
+        ModelError::MailboxCmdFailed,
+        hw.upload_firmware(&generate_image_bytes(&mut image_bundle))
+            .unwrap_err()
+    );
+    assert_eq!(
+        hw.soc_ifc().cptra_fw_error_non_fatal().read(),
+        AUX_IMAGE_INCORRECT_ORDER
+    );
+}
+
 #[test]
 fn test_toc_invalid_toc_digest() {
     let (mut hw, mut image_bundle) =
@@ -1103,6 +1349,34 @@ fn test_runtime_digest_mismatch() {
     );
 }
 
+#[test]
+fn test_runtime_range_beyond_image() {
+    let (mut hw, mut image_bundle) =
+        helpers::build_hw_model_and_image_bundle(Fuses::default(), ImageOptions::default());
+
+    // Extend the runtime section past the end of the image in the mailbox.
+    let fmc_new_offset = image_bundle.manifest.fmc.offset;
+    let fmc_new_size = image_bundle.manifest.fmc.size;
+    let runtime_new_offset = image_bundle.manifest.runtime.offset;
+    let runtime_new_size = image_bundle.manifest.runtime.size + 4;
+    let image = update_fmc_runtime_ranges(
+        &mut image_bundle,
+        fmc_new_offset,
+        fmc_new_size,
+        runtime_new_offset,
+        runtime_new_size,
+    );
+
+    assert_eq!(
+        ModelError::MailboxCmdFailed,
+        hw.upload_firmware(&image).unwrap_err()
+    );
+    assert_eq!(
+        hw.soc_ifc().cptra_fw_error_non_fatal().read(),
+        RUNTIME_DIGEST_FAILURE
+    );
+}
+
 #[test]
 fn test_runtime_invalid_load_addr_before_iccm() {
     let (mut hw, mut image_bundle) =
@@ -1435,6 +1709,18 @@ fn cert_test() {
     fmcalias_cert(&ldevid_cert, &output);
 }
 
+fn set_trusted_time(hw: &mut impl HwModel, time: u64) {
+    let soc_ifc = hw.soc_ifc();
+    soc_ifc
+        .cptra_generic_input_wires()
+        .at(0)
+        .write(|_| time as u32);
+    soc_ifc
+        .cptra_generic_input_wires()
+        .at(1)
+        .write(|_| (time >> 32) as u32);
+}
+
 fn update_header(image_bundle: &mut ImageBundle) {
     let opts = ImageOptions::default();
     let config = ImageGeneratorConfig {
@@ -1442,6 +1728,7 @@ fn update_header(image_bundle: &mut ImageBundle) {
         runtime: ElfExecutable::default(),
         vendor_config: opts.vendor_config,
         owner_config: opts.owner_config,
+        aux: Vec::new(),
     };
 
     let gen = ImageGenerator::new(OsslCrypto::default());
@@ -1450,11 +1737,45 @@ fn update_header(image_bundle: &mut ImageBundle) {
         .gen_preamble(
             &config,
             image_bundle.manifest.preamble.vendor_ecc_pub_key_idx,
+            image_bundle.manifest.preamble.vendor_lms_pub_key_idx,
             &digest,
         )
         .unwrap();
 }
 
+fn add_aux_image(image_bundle: &mut ImageBundle, id: u32, content: Vec<u8>) {
+    let manifest = &mut image_bundle.manifest;
+    let idx = manifest.aux_toc_entries().len();
+    let offset = manifest
+        .aux_toc_entries()
+        .last()
+        .unwrap_or(&manifest.runtime)
+        .image_range()
+        .end;
+
+    manifest.aux[idx] = ImageTocEntry {
+        id,
+        r#type: ImageTocEntryType::Data.into(),
+        offset,
+        size: content.len() as u32,
+        digest: OsslCrypto::default().sha384_digest(&content).unwrap(),
+        ..Default::default()
+    };
+    manifest.header.toc_len += 1;
+    image_bundle.aux.push(content);
+}
+
+fn update_toc_digest(image_bundle: &mut ImageBundle) {
+    let gen = ImageGenerator::new(OsslCrypto::default());
+    image_bundle.manifest.header.toc_digest = gen
+        .toc_digest(
+            &image_bundle.manifest.fmc,
+            &image_bundle.manifest.runtime,
+            image_bundle.manifest.aux_toc_entries(),
+        )
+        .unwrap();
+}
+
 fn update_fmc_runtime_ranges(
     image_bundle: &mut ImageBundle,
     fmc_new_offset: u32,
@@ -1471,7 +1792,11 @@ fn update_fmc_runtime_ranges(
 
     // Update TOC digest.
     image_bundle.manifest.header.toc_digest = gen
-        .toc_digest(&image_bundle.manifest.fmc, &image_bundle.manifest.runtime)
+        .toc_digest(
+            &image_bundle.manifest.fmc,
+            &image_bundle.manifest.runtime,
+            image_bundle.manifest.aux_toc_entries(),
+        )
         .unwrap();
 
     // Update Header.
@@ -1492,7 +1817,11 @@ fn update_load_addr(image_bundle: &mut ImageBundle, is_fmc: bool, new_load_addr:
 
     // Update TOC digest.
     image_bundle.manifest.header.toc_digest = gen
-        .toc_digest(&image_bundle.manifest.fmc, &image_bundle.manifest.runtime)
+        .toc_digest(
+            &image_bundle.manifest.fmc,
+            &image_bundle.manifest.runtime,
+            image_bundle.manifest.aux_toc_entries(),
+        )
         .unwrap();
 
     // Update Header.
@@ -1517,7 +1846,11 @@ fn update_entry_point(
 
     // Update TOC digest.
     image_bundle.manifest.header.toc_digest = gen
-        .toc_digest(&image_bundle.manifest.fmc, &image_bundle.manifest.runtime)
+        .toc_digest(
+            &image_bundle.manifest.fmc,
+            &image_bundle.manifest.runtime,
+            image_bundle.manifest.aux_toc_entries(),
+        )
         .unwrap();
 
     // Update Header.
@@ -1532,6 +1865,9 @@ fn generate_image_bytes(image_bundle: &mut ImageBundle) -> Vec<u8> {
     image.extend_from_slice(image_bundle.manifest.as_bytes());
     image.extend_from_slice(&image_bundle.fmc);
     image.extend_from_slice(&image_bundle.runtime);
+    for aux in &image_bundle.aux {
+        image.extend_from_slice(aux);
+    }
     image
 }
 
@@ -1,5 +1,6 @@
 // Licensed under the Apache-2.0 license
 
+use caliptra_api::CrashRecord;
 use caliptra_builder::ImageOptions;
 use caliptra_hw_model::{Fuses, HwModel, ModelError};
 
@@ -53,3 +54,26 @@ fn test_mailbox_command_aborted_after_report_error() {
         INVALID_IMAGE_SIZE
     );
 }
+
+#[test]
+fn test_fatal_error_crash_record() {
+    let (mut hw, _) =
+        helpers::build_hw_model_and_image_bundle(Fuses::default(), ImageOptions::default());
+    assert_eq!(Err(ModelError::MailboxCmdFailed), hw.upload_firmware(&[]));
+
+    assert_eq!(
+        hw.soc_ifc().cptra_fw_error_fatal().read(),
+        INVALID_IMAGE_SIZE
+    );
+
+    let record = CrashRecord::from(hw.soc_ifc().cptra_fw_extended_error_info().read());
+    assert!(record.is_valid());
+    assert_eq!(record.error_code, INVALID_IMAGE_SIZE);
+
+    // The error was not caused by a trap
+    assert_eq!(record.mcause, 0);
+    assert_eq!(record.mepc, 0);
+
+    // Cold reset
+    assert_eq!(record.reset_reason, 0);
+}
@@ -0,0 +1,96 @@
+// Licensed under the Apache-2.0 license
+
+use caliptra_api::CrashRecord;
+use caliptra_builder::{FwId, ImageOptions, APP_WITH_UART, ROM_WITH_UART};
+use caliptra_hw_model::{BootParams, DefaultHwModel, Fuses, HwModel, InitParams, SecurityState};
+
+/// Test FMC that handles the mailbox commands below instead of exiting
+const TEST_FMC_INTERACTIVE: FwId = FwId {
+    crate_name: "caliptra-rom-test-fmc",
+    bin_name: "caliptra-rom-test-fmc",
+    features: &["emu", "interactive"],
+};
+
+const TEST_CMD_WARM_RESET: u32 = 0x1000_0000;
+const TEST_CMD_CORRUPT_FMC_TOC: u32 = 0x1000_0001;
+const TEST_CMD_CORRUPT_RUNTIME_TOC: u32 = 0x1000_0002;
+
+// [TODO] Use the error codes from the common library.
+const FMC_TOC_MISMATCH: u32 = 0x05000004;
+const RUNTIME_TOC_MISMATCH: u32 = 0x05000006;
+
+/// Value of CPTRA_RESET_REASON on a warm reset
+const WARM_RESET: u32 = 0x2;
+
+fn boot_interactive_fmc() -> DefaultHwModel {
+    let fuses = Fuses::default();
+    let rom = caliptra_builder::build_firmware_rom(&ROM_WITH_UART).unwrap();
+    let image = caliptra_builder::build_and_sign_image(
+        &TEST_FMC_INTERACTIVE,
+        &APP_WITH_UART,
+        ImageOptions::default(),
+    )
+    .unwrap();
+    let mut hw = caliptra_hw_model::new(BootParams {
+        init_params: InitParams {
+            rom: &rom,
+            security_state: SecurityState::from(fuses.life_cycle as u32),
+            ..Default::default()
+        },
+        fuses,
+        fw_image: Some(&image.to_bytes().unwrap()),
+    })
+    .unwrap();
+
+    hw.step_until_output_contains("[fmc] Waiting for mailbox commands")
+        .unwrap();
+    hw.output().take(usize::MAX);
+    hw
+}
+
+/// Run a test FMC command that triggers a warm reset and return the fatal
+/// error reported by ROM
+fn warm_reset_fatal_error(cmd: u32) -> u32 {
+    let mut hw = boot_interactive_fmc();
+    assert_eq!(hw.mailbox_execute(cmd, &[]), Ok(None));
+
+    hw.step_until(|m| m.soc_ifc().cptra_fw_error_fatal().read() != 0);
+    let error = hw.soc_ifc().cptra_fw_error_fatal().read();
+
+    let record = CrashRecord::from(hw.soc_ifc().cptra_fw_extended_error_info().read());
+    assert!(record.is_valid());
+    assert_eq!(record.error_code, error);
+    assert_eq!(record.reset_reason, WARM_RESET);
+
+    // FMC is not launched again
+    assert!(!hw.output().peek().contains("[exit] Launching FMC"));
+    error
+}
+
+#[test]
+fn test_warm_reset_success() {
+    let mut hw = boot_interactive_fmc();
+    assert_eq!(hw.mailbox_execute(TEST_CMD_WARM_RESET, &[]), Ok(None));
+
+    hw.step_until_output_contains("[warm-reset] Runtime verified")
+        .unwrap();
+    hw.step_until_output_contains("[fmc] Waiting for mailbox commands")
+        .unwrap();
+    assert_eq!(hw.soc_ifc().cptra_fw_error_fatal().read(), 0);
+}
+
+#[test]
+fn test_warm_reset_corrupted_fmc_toc() {
+    assert_eq!(
+        warm_reset_fatal_error(TEST_CMD_CORRUPT_FMC_TOC),
+        FMC_TOC_MISMATCH
+    );
+}
+
+#[test]
+fn test_warm_reset_corrupted_runtime_toc() {
+    assert_eq!(
+        warm_reset_fatal_error(TEST_CMD_CORRUPT_RUNTIME_TOC),
+        RUNTIME_TOC_MISMATCH
+    );
+}
@@ -13,4 +13,19 @@ done
 openssl ecparam -name secp384r1 -genkey -noout -out $1/own-priv-key.pem
 openssl ec -in  $1/own-priv-key.pem -pubout -out $1/own-pub-key.pem
 
+# LMS private key: LMS_SHA256_N24_H5 and LMOTS_SHA256_N24_W8 types, random
+# identifier and seed, next unused leaf 0
+gen_lms_priv_key() {
+  printf '\x0a\x00\x00\x00\x08\x00\x00\x00' > $1
+  openssl rand 40 >> $1
+  printf '\x00\x00\x00\x00' >> $1
+}
+
+for value in {0..3}
+do
+  gen_lms_priv_key $1/vnd-lms-priv-key-$value.bin
+done
+
+gen_lms_priv_key $1/own-lms-priv-key.bin
+
 
@@ -25,6 +25,11 @@ use caliptra_drivers::Mailbox;
 use caliptra_x509::{Ecdsa384CertBuilder, Ecdsa384Signature, FmcAliasCertTbs, LocalDevIdCertTbs};
 use zerocopy::FromBytes;
 
+#[cfg(feature = "interactive")]
+use caliptra_image_types::ImageManifest;
+#[cfg(feature = "interactive")]
+use zerocopy::AsBytes;
+
 #[cfg(not(feature = "std"))]
 core::arch::global_asm!(include_str!("start.S"));
 
@@ -57,9 +62,73 @@ pub extern "C" fn fmc_entry() -> ! {
 
     create_certs();
 
+    #[cfg(feature = "interactive")]
+    process_mailbox_commands();
+
+    #[cfg(not(feature = "interactive"))]
     caliptra_drivers::ExitCtrl::exit(0)
 }
 
+/// Trigger a warm reset
+#[cfg(feature = "interactive")]
+const TEST_CMD_WARM_RESET: u32 = 0x1000_0000;
+
+/// Corrupt the FMC TOC entry of the manifest in DCCM and trigger a warm reset
+#[cfg(feature = "interactive")]
+const TEST_CMD_CORRUPT_FMC_TOC: u32 = 0x1000_0001;
+
+/// Corrupt the Runtime TOC entry of the manifest in DCCM and trigger a warm
+/// reset
+#[cfg(feature = "interactive")]
+const TEST_CMD_CORRUPT_RUNTIME_TOC: u32 = 0x1000_0002;
+
+#[cfg(feature = "interactive")]
+#[allow(clippy::empty_loop)]
+fn process_mailbox_commands() -> ! {
+    cprintln!("[fmc] Waiting for mailbox commands");
+    loop {
+        let Some(mut txn) = Mailbox::default().try_start_recv_txn() else {
+            continue;
+        };
+        let cmd = txn.cmd();
+        match cmd {
+            TEST_CMD_WARM_RESET => {}
+            TEST_CMD_CORRUPT_FMC_TOC => corrupt_manifest(|m| m.fmc.digest[0] ^= 1),
+            TEST_CMD_CORRUPT_RUNTIME_TOC => corrupt_manifest(|m| m.runtime.digest[0] ^= 1),
+            _ => {
+                let _ = txn.complete(false);
+                continue;
+            }
+        }
+        let _ = txn.complete(true);
+        drop(txn);
+
+        cprintln!("[fmc] Triggering warm reset");
+        caliptra_registers::soc_ifc::RegisterBlock::soc_ifc_reg()
+            .cptra_generic_output_wires()
+            .at(1)
+            .write(|_| 1);
+        loop {}
+    }
+}
+
+/// Modify the manifest left in DCCM by ROM
+#[cfg(feature = "interactive")]
+fn corrupt_manifest(f: impl FnOnce(&mut ImageManifest)) {
+    extern "C" {
+        static mut MAN1_ORG: u8;
+    }
+
+    let slice = unsafe {
+        let ptr = &mut MAN1_ORG as *mut u8;
+        core::slice::from_raw_parts_mut(ptr, core::mem::size_of::<ImageManifest>())
+    };
+
+    let mut manifest = ImageManifest::read_from(&slice[..]).unwrap();
+    f(&mut manifest);
+    slice.copy_from_slice(manifest.as_bytes());
+}
+
 #[no_mangle]
 #[inline(never)]
 #[allow(clippy::empty_loop)]
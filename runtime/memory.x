@@ -5,7 +5,12 @@ META_ORG  = ROM_SIZE;
 ICCM_ORG  = 0x40002000;
 DCCM_ORG  = 0x50000000;
 FHT_ORG   = 0x50002000;
-DATA_ORG  = 0x50003000;
+LDEVID_TBS_ORG   = 0x50003000;
+FMCALIAS_TBS_ORG = 0x50003400;
+PCR_LOG_ORG      = 0x50003800;
+DPE_ORG          = 0x50004000;
+IDEVID_CERT_ORG  = 0x50005000;
+DATA_ORG  = 0x50005400;
 STACK_ORG = 0x5001C000;
 ESTACK_ORG  = 0x5001F800;
 NSTACK_ORG  = 0x5001FC00;
@@ -18,7 +23,12 @@ DCCM_SIZE   = 128K;
 MAN1_SIZE   = 4K;
 MAN2_SIZE   = 4K;
 FHT_SIZE    = 4K;
-DATA_SIZE   = 100K;
+LDEVID_TBS_SIZE   = 1K;
+FMCALIAS_TBS_SIZE = 1K;
+PCR_LOG_SIZE      = 2K;
+DPE_SIZE          = 4K;
+IDEVID_CERT_SIZE  = 1K;
+DATA_SIZE   = 91K;
 STACK_SIZE  = 14K;
 ESTACK_SIZE = 1K;
 NSTACK_SIZE = 1K;
@@ -0,0 +1,123 @@
+// Licensed under the Apache-2.0 license
+
+use crate::{parse_request, send_response, RuntimeErr};
+
+use caliptra_api::mailbox::{
+    GetCertResp, GetFmcAliasCertReq, GetIdevIdCertReq, GetLdevIdCertReq, GetRtAliasCertReq,
+};
+use caliptra_common::{x509, FirmwareHandoffTable, FHT_INVALID_ADDRESS};
+use caliptra_drivers::{CaliptraResult, DataVault, Ecc384Signature, FuseBank, Sha256};
+use caliptra_registers::mbox::enums::MboxStatusE;
+use caliptra_x509::{
+    Ecdsa384CertBuilder, Ecdsa384Signature, FmcAliasCertTbs, LocalDevIdCertTbs, NotAfter,
+    NotBefore, RtAliasCertTbs, RtAliasCertTbsParams,
+};
+use zerocopy::FromBytes;
+
+use core::mem::size_of;
+
+extern "C" {
+    static LDEVID_TBS_ORG: u8;
+    static FMCALIAS_TBS_ORG: u8;
+}
+
+/// Return the IDevID certificate provisioned by the manufacturing HSM
+pub fn handle_get_idev_id_cert(
+    fht: &FirmwareHandoffTable,
+    cmd_bytes: &[u8],
+) -> CaliptraResult<MboxStatusE> {
+    let _req: GetIdevIdCertReq = parse_request(cmd_bytes)?;
+
+    // The certificate was stored by ROM and left in DCCM
+    let size = DataVault::default().idev_cert_size() as usize;
+    if fht.idev_cert_addr == FHT_INVALID_ADDRESS || size == 0 {
+        raise_err!(IdevCertNotProvisioned)
+    }
+
+    let mut resp = GetCertResp::new_zeroed();
+    let cert = unsafe { core::slice::from_raw_parts(fht.idev_cert_addr as *const u8, size) };
+    resp.data
+        .get_mut(..size)
+        .ok_or(err_u32!(InsufficientMemory))?
+        .copy_from_slice(cert);
+    resp.data_size = size as u32;
+
+    send_response(&mut resp)
+}
+
+/// Return the LDevID certificate
+pub fn handle_get_ldev_id_cert(cmd_bytes: &[u8]) -> CaliptraResult<MboxStatusE> {
+    let _req: GetLdevIdCertReq = parse_request(cmd_bytes)?;
+
+    // The TBS was generated by ROM and left in DCCM
+    let tbs = unsafe {
+        let ptr = &LDEVID_TBS_ORG as *const u8;
+        core::slice::from_raw_parts(ptr, size_of::<LocalDevIdCertTbs>())
+    };
+    let sig = DataVault::default().ldev_dice_signature();
+
+    write_cert_resp(tbs, &sig)
+}
+
+/// Return the FMC Alias certificate
+pub fn handle_get_fmc_alias_cert(cmd_bytes: &[u8]) -> CaliptraResult<MboxStatusE> {
+    let _req: GetFmcAliasCertReq = parse_request(cmd_bytes)?;
+
+    // The TBS was generated by ROM and left in DCCM
+    let tbs = unsafe {
+        let ptr = &FMCALIAS_TBS_ORG as *const u8;
+        core::slice::from_raw_parts(ptr, size_of::<FmcAliasCertTbs>())
+    };
+    let sig = DataVault::default().fmc_dice_signature();
+
+    write_cert_resp(tbs, &sig)
+}
+
+/// Return the RT Alias certificate
+pub fn handle_get_rt_alias_cert(cmd_bytes: &[u8]) -> CaliptraResult<MboxStatusE> {
+    let _req: GetRtAliasCertReq = parse_request(cmd_bytes)?;
+
+    let dv = DataVault::default();
+    let issuer_pub_key = dv.fmc_pub_key();
+    let pub_key = dv.rt_pub_key();
+    let sha256 = Sha256::default();
+
+    // Rebuild the TBS signed by FMC
+    let params = RtAliasCertTbsParams {
+        ueid: &FuseBank::default().ueid(),
+        subject_sn: &x509::subj_sn(&sha256, &pub_key)?,
+        subject_key_id: &x509::subj_key_id(&sha256, &pub_key)?,
+        issuer_sn: &x509::subj_sn(&sha256, &issuer_pub_key)?,
+        authority_key_id: &x509::subj_key_id(&sha256, &issuer_pub_key)?,
+        serial_number: &x509::cert_sn(&sha256, &pub_key)?,
+        public_key: &pub_key.to_der(),
+        tcb_info_rt_tci: &dv.rt_tci().into(),
+        not_before: &NotBefore::default().not_before,
+        not_after: &NotAfter::default().not_after,
+    };
+    let tbs = RtAliasCertTbs::new(&params);
+
+    write_cert_resp(tbs.tbs(), &dv.rt_dice_signature())
+}
+
+/// Build the certificate from `tbs` and `sig` and write it to the mailbox
+///
+/// # Arguments
+///
+/// * `tbs` - DER encoded `To Be Signed` portion of the certificate
+/// * `sig` - Signature over `tbs`
+fn write_cert_resp(tbs: &[u8], sig: &Ecc384Signature) -> CaliptraResult<MboxStatusE> {
+    let sig = Ecdsa384Signature {
+        r: sig.r.into(),
+        s: sig.s.into(),
+    };
+    let builder = Ecdsa384CertBuilder::new(tbs, &sig).ok_or(err_u32!(InternalErr))?;
+
+    let mut resp = GetCertResp::new_zeroed();
+    let len = builder
+        .build(&mut resp.data)
+        .ok_or(err_u32!(InsufficientMemory))?;
+    resp.data_size = len as u32;
+
+    send_response(&mut resp)
+}
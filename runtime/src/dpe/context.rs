@@ -0,0 +1,300 @@
+// Licensed under the Apache-2.0 license
+
+use crate::RuntimeErr;
+
+use caliptra_api::dpe::{
+    ContextHandle, DpeStatus, DEFAULT_CONTEXT_HANDLE, INVALID_CONTEXT_HANDLE, MAX_TCI_NODES,
+    TCI_TYPE_RT_JOURNEY,
+};
+use caliptra_drivers::{CaliptraResult, PcrBank, PcrId, Sha384};
+use zerocopy::{AsBytes, FromBytes, LayoutVerified};
+
+use core::mem::size_of;
+
+extern "C" {
+    static mut DPE_ORG: u32;
+}
+
+/// Size of the DPE region in DCCM
+const DPE_SIZE: usize = 4096;
+
+/// Hardware PCR holding the journey of TCI node 0. Node `i` uses PCR `4 + i`.
+const FIRST_TCI_PCR: usize = 4;
+
+/// Parent index of a root node
+const NO_PARENT: u16 = 0xFFFF;
+
+/// TCI node stored in DCCM
+#[repr(C)]
+#[derive(Debug, Clone, Copy, AsBytes, FromBytes)]
+pub struct TciNode {
+    /// Hardware PCR holding the journey of this node
+    pub pcr_index: u16,
+
+    /// Index of the parent node, `NO_PARENT` for a root node
+    pub parent_index: u16,
+
+    /// Node tag
+    pub tag: u32,
+
+    /// Handle of the context referring to this node
+    pub handle: ContextHandle,
+
+    /// `TciNode::FLAG_*`
+    pub flags: u32,
+
+    /// Latest `INPUT_DATA` extended into this node
+    pub latest_tci: [u8; 48],
+
+    /// 4-byte type of the TCI
+    pub tci_type: u32,
+}
+
+impl TciNode {
+    /// Node is in use
+    pub const FLAG_ALLOCATED: u32 = 1 << 0;
+
+    /// Node has no usable context and is kept for its descendants
+    pub const FLAG_RETIRED: u32 = 1 << 1;
+
+    /// TCI was measured by Runtime Firmware itself
+    pub const FLAG_INTERNAL: u32 = 1 << 31;
+
+    fn is_allocated(&self) -> bool {
+        self.flags & Self::FLAG_ALLOCATED != 0
+    }
+
+    /// Node holds a usable context
+    pub fn is_active(&self) -> bool {
+        self.is_allocated() && self.flags & Self::FLAG_RETIRED == 0
+    }
+
+    /// Hardware PCR holding the journey of this node
+    pub fn pcr(&self) -> CaliptraResult<PcrId> {
+        u8::try_from(self.pcr_index)
+            .ok()
+            .and_then(|id| PcrId::try_from(id).ok())
+            .ok_or(err_u32!(InternalErr))
+    }
+}
+
+/// DPE state stored in DCCM
+#[repr(C)]
+#[derive(AsBytes, FromBytes)]
+pub struct DpeState {
+    /// Secret mixed into generated context handles
+    pub handle_seed: [u8; 48],
+
+    /// Number of context handles generated
+    pub handle_counter: u32,
+
+    /// TCI nodes
+    pub nodes: [TciNode; MAX_TCI_NODES],
+}
+
+/// Get the DPE state
+pub fn dpe_state() -> CaliptraResult<LayoutVerified<&'static mut [u8], DpeState>> {
+    let slice = unsafe {
+        let ptr = core::ptr::addr_of_mut!(DPE_ORG);
+        core::slice::from_raw_parts_mut(ptr, DPE_SIZE / size_of::<u32>())
+    };
+    let (state, _) = LayoutVerified::<_, DpeState>::new_from_prefix(slice.as_bytes_mut())
+        .ok_or(err_u32!(InternalErr))?;
+    Ok(state)
+}
+
+impl DpeState {
+    /// Clear all nodes and the PCRs they use
+    pub fn reset(&mut self) -> CaliptraResult<()> {
+        self.handle_counter = 0;
+        for index in 0..MAX_TCI_NODES {
+            self.free(index)?;
+        }
+        Ok(())
+    }
+
+    /// Find the active node referred to by `handle`
+    pub fn find(&self, handle: &ContextHandle) -> Result<usize, DpeStatus> {
+        self.nodes
+            .iter()
+            .position(|node| node.is_active() && node.handle == *handle)
+            .ok_or(DpeStatus::InvalidHandle)
+    }
+
+    /// Check whether the default context exists
+    pub fn has_default(&self) -> bool {
+        self.find(&DEFAULT_CONTEXT_HANDLE).is_ok()
+    }
+
+    /// Find an unused node
+    pub fn alloc(&self) -> Result<usize, DpeStatus> {
+        self.nodes
+            .iter()
+            .position(|node| !node.is_allocated())
+            .ok_or(DpeStatus::MaxTcis)
+    }
+
+    pub fn node(&self, index: usize) -> CaliptraResult<&TciNode> {
+        self.nodes.get(index).ok_or(err_u32!(InternalErr))
+    }
+
+    pub fn node_mut(&mut self, index: usize) -> CaliptraResult<&mut TciNode> {
+        self.nodes.get_mut(index).ok_or(err_u32!(InternalErr))
+    }
+
+    /// Index of the parent of node `index`
+    pub fn parent(&self, index: usize) -> Option<usize> {
+        let node = self.nodes.get(index)?;
+        match node.parent_index {
+            NO_PARENT => None,
+            parent => Some(parent as usize),
+        }
+    }
+
+    /// Initialize node `index` with an empty journey
+    ///
+    /// # Arguments
+    ///
+    /// * `index`    - Unused node returned by `alloc`
+    /// * `parent`   - Index of the parent node
+    /// * `handle`   - Handle of the new context
+    /// * `tci_type` - 4-byte type of the TCI
+    /// * `flags`    - Additional `TciNode::FLAG_*`
+    pub fn create(
+        &mut self,
+        index: usize,
+        parent: Option<usize>,
+        handle: ContextHandle,
+        tci_type: u32,
+        flags: u32,
+    ) -> CaliptraResult<()> {
+        let node = self.node_mut(index)?;
+        *node = TciNode {
+            pcr_index: (FIRST_TCI_PCR + index) as u16,
+            parent_index: parent.map_or(NO_PARENT, |p| p as u16),
+            tag: 0,
+            handle,
+            flags: TciNode::FLAG_ALLOCATED | flags,
+            latest_tci: [0u8; 48],
+            tci_type,
+        };
+        PcrBank::default().erase_pcr(node.pcr()?)
+    }
+
+    /// Extend `data` into the journey of node `index`
+    pub fn extend(&mut self, index: usize, data: &[u8; 48]) -> CaliptraResult<()> {
+        let node = self.node_mut(index)?;
+        node.latest_tci = *data;
+        PcrBank::default().extend_pcr(node.pcr()?, &Sha384::default(), data)
+    }
+
+    /// Destroy node `index` and its descendants
+    ///
+    /// Retired ancestors left without descendants are destroyed as well.
+    pub fn destroy(&mut self, index: usize) -> CaliptraResult<()> {
+        let mut next = self.parent(index);
+
+        let mut doomed = [false; MAX_TCI_NODES];
+        for (i, doom) in doomed.iter_mut().enumerate() {
+            *doom = i == index || self.is_descendant(i, index);
+        }
+        for (i, doom) in doomed.iter().enumerate() {
+            if *doom {
+                self.free(i)?;
+            }
+        }
+
+        for _ in 0..MAX_TCI_NODES {
+            let Some(ancestor) = next else {
+                break;
+            };
+            let node = self.node(ancestor)?;
+            if node.is_active() || self.has_children(ancestor) {
+                break;
+            }
+            next = self.parent(ancestor);
+            self.free(ancestor)?;
+        }
+        Ok(())
+    }
+
+    /// Check that the node table kept across an impactless update is
+    /// well-formed
+    ///
+    /// Every allocated node must use its own PCR and chain to a root node
+    /// through allocated parents.
+    pub fn is_valid(&self) -> bool {
+        for (index, node) in self.nodes.iter().enumerate() {
+            if !node.is_allocated() {
+                continue;
+            }
+            if node.pcr_index as usize != FIRST_TCI_PCR + index {
+                return false;
+            }
+            let mut next = self.parent(index);
+            let mut reached_root = false;
+            for _ in 0..MAX_TCI_NODES {
+                match next {
+                    Some(parent) if self.is_allocated(parent) => next = self.parent(parent),
+                    Some(_) => return false,
+                    None => {
+                        reached_root = true;
+                        break;
+                    }
+                }
+            }
+            if !reached_root {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Find the node measuring Runtime Firmware
+    pub fn find_rt_node(&self) -> Option<usize> {
+        self.nodes.iter().position(|node| {
+            node.is_allocated()
+                && node.tci_type == TCI_TYPE_RT_JOURNEY
+                && node.flags & TciNode::FLAG_INTERNAL != 0
+        })
+    }
+
+    /// Retire node `index`, keeping it for the journey of its descendants
+    pub fn retire(&mut self, index: usize) -> CaliptraResult<()> {
+        let node = self.node_mut(index)?;
+        node.flags |= TciNode::FLAG_RETIRED;
+        node.handle = INVALID_CONTEXT_HANDLE;
+        Ok(())
+    }
+
+    fn is_allocated(&self, index: usize) -> bool {
+        matches!(self.nodes.get(index), Some(node) if node.is_allocated())
+    }
+
+    fn has_children(&self, index: usize) -> bool {
+        (0..MAX_TCI_NODES).any(|i| self.is_allocated(i) && self.parent(i) == Some(index))
+    }
+
+    fn is_descendant(&self, index: usize, ancestor: usize) -> bool {
+        if !self.is_allocated(index) {
+            return false;
+        }
+        let mut next = self.parent(index);
+        for _ in 0..MAX_TCI_NODES {
+            match next {
+                Some(i) if i == ancestor => return true,
+                Some(i) => next = self.parent(i),
+                None => return false,
+            }
+        }
+        false
+    }
+
+    fn free(&mut self, index: usize) -> CaliptraResult<()> {
+        let pcr =
+            PcrId::try_from((FIRST_TCI_PCR + index) as u8).map_err(|_| err_u32!(InternalErr))?;
+        PcrBank::default().erase_pcr(pcr)?;
+        *self.node_mut(index)? = TciNode::new_zeroed();
+        Ok(())
+    }
+}
@@ -0,0 +1,197 @@
+// Licensed under the Apache-2.0 license
+
+use super::context::DpeState;
+use crate::{csrng_generate, RuntimeErr};
+
+use caliptra_api::dpe::{ContextHandle, CONTEXT_HANDLE_SIZE, MAX_TCI_NODES};
+use caliptra_common::{crypto, x509, DataStore, FirmwareHandoffTable, HandOffDataHandle};
+use caliptra_drivers::{
+    Array4x12, CaliptraResult, DataVault, Ecc384, Ecc384PrivKeyIn, Ecc384PubKey, Ecc384Signature,
+    FuseBank, Hmac384, Hmac384Key, Hmac384Tag, KeyId, KeyReadArgs, KeyUsage, KeyVault,
+    KeyWriteArgs, PcrBank, Sha256, Sha384,
+};
+use caliptra_x509::{
+    DpeLeafCertTbs, DpeLeafCertTbsParams, Ecdsa384CertBuilder, Ecdsa384Signature, NotAfter,
+    NotBefore,
+};
+
+/// KeyVault slot receiving the CDI of a context
+const DPE_CDI: KeyId = KeyId::KeyId0;
+
+/// KeyVault slot receiving the private key of a context
+pub const DPE_PRIV_KEY: KeyId = KeyId::KeyId1;
+
+/// Label of the key pair whose public key seeds context handles
+const HANDLE_SEED_LABEL: &[u8] = b"DPE context handles";
+
+/// Get the KeyVault slot referred to by a hand-off handle
+fn key_vault_slot(handle: HandOffDataHandle) -> CaliptraResult<KeyId> {
+    match handle.try_into() {
+        Ok(DataStore::KeyVaultSlot(key_id)) => Ok(key_id),
+        _ => raise_err!(InternalErr),
+    }
+}
+
+/// Derive the key pair of a context into `DPE_PRIV_KEY` and run `f` with its
+/// public key
+///
+/// The CDI is `HMAC(RT CDI, label || PCRs)`, where the PCRs are the journeys
+/// of the nodes from `node` to the root. Both KeyVault slots are erased before
+/// returning.
+///
+/// # Arguments
+///
+/// * `fht`   - Firmware Handoff Table
+/// * `state` - DPE state
+/// * `node`  - Node whose path is measured, if any
+/// * `label` - Key derivation label
+/// * `f`     - Operation using the derived key
+pub fn with_derived_key<T>(
+    fht: &FirmwareHandoffTable,
+    state: &DpeState,
+    node: Option<usize>,
+    label: &[u8],
+    f: impl FnOnce(&Ecc384PubKey) -> CaliptraResult<T>,
+) -> CaliptraResult<T> {
+    let result = derive_key(fht, state, node, label).and_then(|pub_key| f(&pub_key));
+
+    let mut kv = KeyVault::default();
+    kv.erase_key(DPE_CDI)?;
+    kv.erase_key(DPE_PRIV_KEY)?;
+    result
+}
+
+fn derive_key(
+    fht: &FirmwareHandoffTable,
+    state: &DpeState,
+    node: Option<usize>,
+    label: &[u8],
+) -> CaliptraResult<Ecc384PubKey> {
+    let rt_cdi = key_vault_slot(fht.rt_cdi_kv_hdl)?;
+
+    let mut usage = KeyUsage::default();
+    usage.set_ecc_key_gen_seed(true);
+    let hmac = Hmac384::default();
+    let mut op = hmac.hmac_init(
+        Hmac384Key::Key(KeyReadArgs::new(rt_cdi)),
+        Hmac384Tag::Key(KeyWriteArgs::new(DPE_CDI, usage)),
+    )?;
+    op.update(label)?;
+
+    let pcr_bank = PcrBank::default();
+    let mut next = node;
+    for _ in 0..MAX_TCI_NODES {
+        let Some(index) = next else {
+            break;
+        };
+        let pcr: [u8; 48] = pcr_bank.read_pcr(state.node(index)?.pcr()?).into();
+        op.update(&pcr)?;
+        next = state.parent(index);
+    }
+    op.finalize()?;
+
+    let key_pair = crypto::ecc384_key_gen(
+        &Ecc384::default(),
+        DPE_CDI,
+        DPE_PRIV_KEY,
+        &csrng_generate()?,
+    )?;
+    Ok(key_pair.pub_key)
+}
+
+/// Derive the secret context handles are generated from
+///
+/// The secret is the public key of a key pair derived from the RT CDI. It is
+/// never disclosed.
+pub fn init_handle_seed(fht: &FirmwareHandoffTable, state: &mut DpeState) -> CaliptraResult<()> {
+    let seed = with_derived_key(fht, state, None, HANDLE_SEED_LABEL, |pub_key| {
+        Ok(pub_key.x.into())
+    })?;
+    state.handle_seed = seed;
+    Ok(())
+}
+
+/// Generate a new context handle
+pub fn new_handle(state: &mut DpeState) -> CaliptraResult<ContextHandle> {
+    state.handle_counter = state.handle_counter.wrapping_add(1);
+
+    let mut data = [0u8; 52];
+    data[..48].copy_from_slice(&state.handle_seed);
+    data[48..].copy_from_slice(&state.handle_counter.to_le_bytes());
+
+    let mut digest = Array4x12::default();
+    Sha384::default().digest(&data, &mut digest)?;
+    let digest: [u8; 48] = digest.into();
+
+    let mut handle = [0u8; CONTEXT_HANDLE_SIZE];
+    handle.copy_from_slice(&digest[..CONTEXT_HANDLE_SIZE]);
+    Ok(handle)
+}
+
+/// Sign `digest` with the key in `DPE_PRIV_KEY`
+pub fn sign(digest: &Array4x12) -> CaliptraResult<Ecc384Signature> {
+    Ecc384::default().sign(
+        Ecc384PrivKeyIn::Key(KeyReadArgs::new(DPE_PRIV_KEY)),
+        digest,
+        &csrng_generate()?,
+    )
+}
+
+/// Build the leaf certificate of node `index` into `buf`
+///
+/// # Arguments
+///
+/// * `fht`     - Firmware Handoff Table
+/// * `state`   - DPE state
+/// * `index`   - Certified node
+/// * `pub_key` - Certified public key
+/// * `buf`     - Buffer receiving the DER-encoded certificate
+///
+/// # Returns
+///
+/// * `usize` - Length of the certificate
+pub fn leaf_cert(
+    fht: &FirmwareHandoffTable,
+    state: &DpeState,
+    index: usize,
+    pub_key: &Ecc384PubKey,
+    buf: &mut [u8],
+) -> CaliptraResult<usize> {
+    let node = state.node(index)?;
+    let issuer_pub_key = DataVault::default().rt_pub_key();
+
+    let sha256 = Sha256::default();
+
+    let params = DpeLeafCertTbsParams {
+        ueid: &FuseBank::default().ueid(),
+        subject_sn: &x509::subj_sn(&sha256, pub_key)?,
+        subject_key_id: &x509::subj_key_id(&sha256, pub_key)?,
+        issuer_sn: &x509::subj_sn(&sha256, &issuer_pub_key)?,
+        authority_key_id: &x509::subj_key_id(&sha256, &issuer_pub_key)?,
+        serial_number: &x509::cert_sn(&sha256, pub_key)?,
+        public_key: &pub_key.to_der(),
+        tcb_info_type: &node.tci_type.to_le_bytes(),
+        tcb_info_dpe_journey: &PcrBank::default().read_pcr(node.pcr()?).into(),
+        tcb_info_dpe_current: &node.latest_tci,
+        not_before: &NotBefore::default().not_before,
+        not_after: &NotAfter::default().not_after,
+    };
+    let tbs = DpeLeafCertTbs::new(&params);
+
+    // Leaf certificates are signed with the RT Alias Key
+    let rt_priv_key = key_vault_slot(fht.rt_priv_key_kv_hdl)?;
+    let sig = crypto::ecdsa384_sign(
+        &Sha384::default(),
+        &Ecc384::default(),
+        rt_priv_key,
+        tbs.tbs(),
+        &csrng_generate()?,
+    )?;
+
+    let sig = Ecdsa384Signature {
+        r: sig.r.into(),
+        s: sig.s.into(),
+    };
+    let builder = Ecdsa384CertBuilder::new(tbs.tbs(), &sig).ok_or(err_u32!(InternalErr))?;
+    builder.build(buf).ok_or(err_u32!(InsufficientMemory))
+}
@@ -0,0 +1,308 @@
+// Licensed under the Apache-2.0 license
+
+mod context;
+mod keys;
+
+use crate::{parse_request_ref, send_response, RuntimeErr};
+use context::{dpe_state, DpeState, TciNode};
+
+use caliptra_api::dpe::{
+    CertifyKeyCmd, CertifyKeyResp, CommandHdr, ContextHandle, DeriveChildCmd, DeriveChildResp,
+    DestroyCtxCmd, DpeCommandId, DpeStatus, InitCtxCmd, NewHandleResp, ResponseHdr, RotateCtxCmd,
+    SignCmd, SignResp, DEFAULT_CONTEXT_HANDLE, DPE_PROFILE_P384_SHA384, INVALID_CONTEXT_HANDLE,
+    TCI_TYPE_RT_JOURNEY,
+};
+use caliptra_api::mailbox::{InvokeDpeReq, InvokeDpeResp};
+use caliptra_common::{cprintln, FirmwareHandoffTable};
+use caliptra_drivers::{Array4x12, CaliptraError, CaliptraResult, PcrBank, PcrId};
+use caliptra_registers::mbox::enums::MboxStatusE;
+use zerocopy::{AsBytes, FromBytes, LayoutVerified};
+
+use core::mem::size_of;
+
+/// Failure of a DPE command
+enum DpeErr {
+    /// Reported to the caller in the DPE response
+    Status(DpeStatus),
+
+    /// Fails the mailbox command
+    Fw(CaliptraError),
+}
+
+impl From<DpeStatus> for DpeErr {
+    fn from(status: DpeStatus) -> Self {
+        Self::Status(status)
+    }
+}
+
+impl From<CaliptraError> for DpeErr {
+    fn from(err: CaliptraError) -> Self {
+        Self::Fw(err)
+    }
+}
+
+type DpeResult<T> = Result<T, DpeErr>;
+
+/// Initialize the TCI node table and the default context
+///
+/// The default context is measured with the Runtime Firmware journey PCR.
+pub fn initialize(fht: &FirmwareHandoffTable) -> CaliptraResult<()> {
+    let mut state = dpe_state()?;
+    state.reset()?;
+    keys::init_handle_seed(fht, &mut state)?;
+
+    let rt_journey: [u8; 48] = PcrBank::default().read_pcr(PcrId::PcrId2).into();
+    state.create(
+        0,
+        None,
+        DEFAULT_CONTEXT_HANDLE,
+        TCI_TYPE_RT_JOURNEY,
+        TciNode::FLAG_INTERNAL,
+    )?;
+    state.extend(0, &rt_journey)
+}
+
+/// Re-measure Runtime Firmware after an impactless update
+///
+/// Contexts are kept across the update. The node measuring Runtime Firmware
+/// is extended with the new Runtime Firmware journey PCR. The state is
+/// initialized again if it is not well-formed.
+pub fn update(fht: &FirmwareHandoffTable) -> CaliptraResult<()> {
+    if !dpe_state()?.is_valid() {
+        cprintln!("[rt] DPE state is invalid, reinitializing");
+        return initialize(fht);
+    }
+
+    let mut state = dpe_state()?;
+    let Some(index) = state.find_rt_node() else {
+        return Ok(());
+    };
+    let rt_journey: [u8; 48] = PcrBank::default().read_pcr(PcrId::PcrId2).into();
+    state.extend(index, &rt_journey)
+}
+
+/// Execute the DPE command carried by an INVOKE_DPE_COMMAND request
+///
+/// Failures of the DPE command are reported in the DPE response status.
+pub fn handle_invoke_dpe(
+    fht: &FirmwareHandoffTable,
+    cmd_bytes: &[u8],
+) -> CaliptraResult<MboxStatusE> {
+    let req = parse_request_ref::<InvokeDpeReq>(cmd_bytes)?;
+    let cmd = req
+        .data
+        .get(..req.data_size as usize)
+        .ok_or(err_u32!(MailboxInvalidLength))?;
+
+    let mut resp = InvokeDpeResp::default();
+    let len = match execute(fht, cmd, &mut resp.data) {
+        Ok(len) => len,
+        Err(DpeErr::Status(status)) => {
+            resp.data.fill(0);
+            let mut hdr = resp_mut::<ResponseHdr>(&mut resp.data)?;
+            *hdr = ResponseHdr::new(status);
+            size_of::<ResponseHdr>()
+        }
+        Err(DpeErr::Fw(err)) => return Err(err),
+    };
+    resp.data_size = len as u32;
+
+    send_response(&mut resp)
+}
+
+/// Execute a DPE command and write its response to `resp`
+///
+/// # Returns
+///
+/// * `usize` - Length of the response
+fn execute(fht: &FirmwareHandoffTable, cmd: &[u8], resp: &mut [u8]) -> DpeResult<usize> {
+    let hdr = CommandHdr::read_from_prefix(cmd).ok_or(DpeStatus::InvalidCommand)?;
+    if hdr.magic != CommandHdr::MAGIC || hdr.profile != DPE_PROFILE_P384_SHA384 {
+        return Err(DpeStatus::InvalidCommand.into());
+    }
+    let cmd_id = DpeCommandId::try_from(hdr.cmd_id).map_err(|_| DpeStatus::InvalidCommand)?;
+    let args = cmd
+        .get(size_of::<CommandHdr>()..)
+        .ok_or(DpeStatus::InvalidCommand)?;
+
+    let mut state = dpe_state()?;
+    match cmd_id {
+        DpeCommandId::InitializeContext => initialize_context(&mut state, args, resp),
+        DpeCommandId::DeriveChild => derive_child(&mut state, args, resp),
+        DpeCommandId::CertifyKey => certify_key(fht, &mut state, args, resp),
+        DpeCommandId::Sign => sign(fht, &mut state, args, resp),
+        DpeCommandId::RotateContextHandle => rotate_context_handle(&mut state, args, resp),
+        DpeCommandId::DestroyContext => destroy_context(&mut state, args, resp),
+    }
+}
+
+/// Parse the arguments of a DPE command
+fn parse_cmd<T: FromBytes>(args: &[u8]) -> DpeResult<T> {
+    Ok(T::read_from(args).ok_or(DpeStatus::InvalidArgument)?)
+}
+
+/// Get the response of type `T` at the start of `resp`
+fn resp_mut<T: AsBytes + FromBytes>(
+    resp: &mut [u8],
+) -> CaliptraResult<LayoutVerified<&mut [u8], T>> {
+    let (resp, _) =
+        LayoutVerified::<_, T>::new_from_prefix(resp).ok_or(err_u32!(InsufficientMemory))?;
+    Ok(resp)
+}
+
+/// Give the context of node `index` a new handle
+///
+/// The default context keeps its handle.
+fn rotate_handle(state: &mut DpeState, index: usize) -> CaliptraResult<ContextHandle> {
+    if state.node(index)?.handle == DEFAULT_CONTEXT_HANDLE {
+        return Ok(DEFAULT_CONTEXT_HANDLE);
+    }
+    let handle = keys::new_handle(state)?;
+    state.node_mut(index)?.handle = handle;
+    Ok(handle)
+}
+
+fn initialize_context(state: &mut DpeState, args: &[u8], resp: &mut [u8]) -> DpeResult<usize> {
+    let cmd: InitCtxCmd = parse_cmd(args)?;
+    if cmd.flags & InitCtxCmd::FLAG_SIMULATION != 0 {
+        return Err(DpeStatus::ArgumentNotSupported.into());
+    }
+    // Only the default context can be initialized, and only once
+    if cmd.flags != InitCtxCmd::FLAG_DEFAULT || state.has_default() {
+        return Err(DpeStatus::InvalidArgument.into());
+    }
+
+    let index = state.alloc()?;
+    state.create(index, None, DEFAULT_CONTEXT_HANDLE, 0, 0)?;
+
+    let mut resp = resp_mut::<NewHandleResp>(resp)?;
+    resp.resp_hdr = ResponseHdr::new(DpeStatus::Ok);
+    resp.handle = DEFAULT_CONTEXT_HANDLE;
+    Ok(size_of::<NewHandleResp>())
+}
+
+fn derive_child(state: &mut DpeState, args: &[u8], resp: &mut [u8]) -> DpeResult<usize> {
+    let cmd: DeriveChildCmd = parse_cmd(args)?;
+    let supported = DeriveChildCmd::FLAG_RETAIN_PARENT | DeriveChildCmd::FLAG_MAKE_DEFAULT;
+    if cmd.flags & !supported != 0 {
+        return Err(DpeStatus::ArgumentNotSupported.into());
+    }
+    let retain_parent = cmd.flags & DeriveChildCmd::FLAG_RETAIN_PARENT != 0;
+    let make_default = cmd.flags & DeriveChildCmd::FLAG_MAKE_DEFAULT != 0;
+
+    let parent = state.find(&cmd.handle)?;
+
+    // Only one default context may exist
+    let default_taken = if cmd.handle == DEFAULT_CONTEXT_HANDLE {
+        retain_parent
+    } else {
+        state.has_default()
+    };
+    if make_default && default_taken {
+        return Err(DpeStatus::InvalidArgument.into());
+    }
+
+    let child = state.alloc()?;
+    let handle = if make_default {
+        DEFAULT_CONTEXT_HANDLE
+    } else {
+        keys::new_handle(state)?
+    };
+    let parent_handle = if retain_parent {
+        rotate_handle(state, parent)?
+    } else {
+        state.retire(parent)?;
+        INVALID_CONTEXT_HANDLE
+    };
+
+    state.create(child, Some(parent), handle, cmd.tci_type, 0)?;
+    state.extend(child, &cmd.data)?;
+
+    let mut resp = resp_mut::<DeriveChildResp>(resp)?;
+    resp.resp_hdr = ResponseHdr::new(DpeStatus::Ok);
+    resp.handle = handle;
+    resp.parent_handle = parent_handle;
+    Ok(size_of::<DeriveChildResp>())
+}
+
+fn certify_key(
+    fht: &FirmwareHandoffTable,
+    state: &mut DpeState,
+    args: &[u8],
+    resp: &mut [u8],
+) -> DpeResult<usize> {
+    let cmd: CertifyKeyCmd = parse_cmd(args)?;
+    if cmd.flags != 0 {
+        return Err(DpeStatus::ArgumentNotSupported.into());
+    }
+    let index = state.find(&cmd.handle)?;
+
+    let mut resp = resp_mut::<CertifyKeyResp>(resp)?;
+    let cert_size = keys::with_derived_key(fht, state, Some(index), &cmd.label, |pub_key| {
+        resp.derived_pubkey_x = pub_key.x.into();
+        resp.derived_pubkey_y = pub_key.y.into();
+        keys::leaf_cert(fht, state, index, pub_key, &mut resp.cert)
+    })?;
+
+    resp.resp_hdr = ResponseHdr::new(DpeStatus::Ok);
+    resp.new_context_handle = rotate_handle(state, index)?;
+    resp.cert_size = cert_size as u32;
+    Ok(size_of::<CertifyKeyResp>())
+}
+
+fn sign(
+    fht: &FirmwareHandoffTable,
+    state: &mut DpeState,
+    args: &[u8],
+    resp: &mut [u8],
+) -> DpeResult<usize> {
+    let cmd: SignCmd = parse_cmd(args)?;
+    if cmd.flags != 0 {
+        return Err(DpeStatus::ArgumentNotSupported.into());
+    }
+    let index = state.find(&cmd.handle)?;
+
+    let sig = keys::with_derived_key(fht, state, Some(index), &cmd.label, |_| {
+        keys::sign(&Array4x12::from(cmd.digest))
+    })?;
+
+    let mut resp = resp_mut::<SignResp>(resp)?;
+    resp.resp_hdr = ResponseHdr::new(DpeStatus::Ok);
+    resp.new_context_handle = rotate_handle(state, index)?;
+    resp.sig_r = sig.r.into();
+    resp.sig_s = sig.s.into();
+    Ok(size_of::<SignResp>())
+}
+
+fn rotate_context_handle(state: &mut DpeState, args: &[u8], resp: &mut [u8]) -> DpeResult<usize> {
+    let cmd: RotateCtxCmd = parse_cmd(args)?;
+    if cmd.flags & !RotateCtxCmd::FLAG_TARGET_IS_DEFAULT != 0 {
+        return Err(DpeStatus::ArgumentNotSupported.into());
+    }
+    let index = state.find(&cmd.handle)?;
+
+    let handle = if cmd.flags & RotateCtxCmd::FLAG_TARGET_IS_DEFAULT != 0 {
+        if cmd.handle != DEFAULT_CONTEXT_HANDLE && state.has_default() {
+            return Err(DpeStatus::InvalidArgument.into());
+        }
+        DEFAULT_CONTEXT_HANDLE
+    } else {
+        keys::new_handle(state)?
+    };
+    state.node_mut(index)?.handle = handle;
+
+    let mut resp = resp_mut::<NewHandleResp>(resp)?;
+    resp.resp_hdr = ResponseHdr::new(DpeStatus::Ok);
+    resp.handle = handle;
+    Ok(size_of::<NewHandleResp>())
+}
+
+fn destroy_context(state: &mut DpeState, args: &[u8], resp: &mut [u8]) -> DpeResult<usize> {
+    let cmd: DestroyCtxCmd = parse_cmd(args)?;
+    let index = state.find(&cmd.handle)?;
+    state.destroy(index)?;
+
+    let mut resp = resp_mut::<ResponseHdr>(resp)?;
+    *resp = ResponseHdr::new(DpeStatus::Ok);
+    Ok(size_of::<ResponseHdr>())
+}
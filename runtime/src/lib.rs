@@ -6,11 +6,19 @@ mod mailbox;
 
 use mailbox::Mailbox;
 
-use caliptra_common::cprintln;
-use caliptra_drivers::{caliptra_err_def, CaliptraResult};
+use caliptra_api::{
+    calc_checksum, extend_checksum, CommandId, MailboxReq, MailboxReqHeader, MailboxResp,
+};
+use caliptra_common::{cprintln, FirmwareHandoffTable};
+use caliptra_drivers::{
+    caliptra_err_def, report_fw_error_non_fatal, Array4x12, CaliptraResult, Csrng, ResetReason,
+    ResetService,
+};
 use caliptra_registers::mbox::enums::MboxStatusE;
+use zerocopy::{AsBytes, FromBytes, LayoutVerified};
 
 use core::mem::size_of;
+use core::num::NonZeroUsize;
 
 caliptra_err_def! {
     Runtime,
@@ -18,9 +26,46 @@ caliptra_err_def! {
     {
         // Internal
         InternalErr = 0x1,
+        // Command is known but not supported by this firmware
+        UnimplementedCommand = 0x2,
+        // Request does not fit in the command buffer
+        InsufficientMemory = 0x3,
+        // Command code is not recognized
+        MailboxInvalidCommand = 0x4,
+        // Request length does not match the command
+        MailboxInvalidLength = 0x5,
+        // Request checksum mismatch
+        MailboxInvalidChecksum = 0x6,
+        // SHA accelerator is in use
+        ShaAccLocked = 0x7,
+        // LMS parameter set is not supported
+        LmsUnsupportedAlgorithm = 0x8,
+        // Measurement log has no room for another entry
+        PcrLogFull = 0x9,
+        // No IDevID certificate was provisioned on cold reset
+        IdevCertNotProvisioned = 0xA,
+        // Update image length is out of bounds
+        UpdateImageInvalidLength = 0xB,
+        // Update image manifest failed verification
+        UpdateImageVerificationFailed = 0xC,
     }
 }
 
+mod certs;
+mod dpe;
+mod measurements;
+mod quote;
+mod update;
+mod verify;
+
+pub use measurements::{PcrLogEntry, PCR_LOG_MAX_ENTRIES, STASH_MEASUREMENT_PCR};
+
+/// Number of words in a CSRNG request
+const CSRNG_NUM_WORDS: NonZeroUsize = match NonZeroUsize::new(12) {
+    Some(num_words) => num_words,
+    None => unreachable!(),
+};
+
 fn wait_for_cmd() {
     // TODO: Enable interrupts?
     //#[cfg(feature = "riscv")]
@@ -29,32 +74,187 @@ fn wait_for_cmd() {
     //}
 }
 
-fn handle_command() -> CaliptraResult<()> {
+/// Parse a checksummed request of type `T` from `cmd_bytes`
+///
+/// # Arguments
+///
+/// * `cmd_bytes` - Request bytes read from the mailbox
+pub(crate) fn parse_request<T: MailboxReq>(cmd_bytes: &[u8]) -> CaliptraResult<T> {
+    if cmd_bytes.len() != size_of::<T>() {
+        raise_err!(MailboxInvalidLength)
+    }
+    let req = T::read_from(cmd_bytes).ok_or(err_u32!(MailboxInvalidLength))?;
+    if !req.verify_chksum() {
+        raise_err!(MailboxInvalidChecksum)
+    }
+    Ok(req)
+}
+
+/// Parse a checksummed request of type `T` in place
+///
+/// Used for large requests which should not be copied onto the stack.
+///
+/// # Arguments
+///
+/// * `cmd_bytes` - Request bytes read from the mailbox
+pub(crate) fn parse_request_ref<T: MailboxReq>(
+    cmd_bytes: &[u8],
+) -> CaliptraResult<LayoutVerified<&[u8], T>> {
+    let req = LayoutVerified::<_, T>::new(cmd_bytes).ok_or(err_u32!(MailboxInvalidLength))?;
+    if !req.verify_chksum() {
+        raise_err!(MailboxInvalidChecksum)
+    }
+    Ok(req)
+}
+
+/// Checksum `resp` and write it to the mailbox
+///
+/// # Arguments
+///
+/// * `resp` - Response to send back to the caller
+pub(crate) fn send_response<T: MailboxResp>(resp: &mut T) -> CaliptraResult<MboxStatusE> {
+    resp.populate_chksum();
+    Mailbox::write_response(resp.as_bytes());
+    Ok(MboxStatusE::DataReady)
+}
+
+/// Generate random words with the CSRNG
+///
+/// Used as the IV randomizing the ECC side-channel countermeasures.
+pub(crate) fn csrng_generate() -> CaliptraResult<Array4x12> {
+    // Commands are handled one at a time, so this is the only CSRNG handle
+    let mut csrng = unsafe { Csrng::new()? };
+
+    let mut words = [0u32; 12];
+    for (dst, src) in words.iter_mut().zip(csrng.generate(CSRNG_NUM_WORDS)?) {
+        *dst = src;
+    }
+    Ok(Array4x12::from(words))
+}
+
+/// Initialize runtime state on boot
+///
+/// On an impactless update, ROM only replaces the manifest in DCCM. The
+/// measurement log and DPE contexts left by the previous Runtime Firmware are
+/// kept, unless the DPE state is not well-formed and is reinitialized.
+pub fn initialize(fht: &FirmwareHandoffTable) -> CaliptraResult<()> {
+    if ResetService::default().reset_reason() == ResetReason::UpdateReset {
+        cprintln!("[rt] Impactless update applied");
+        return dpe::update(fht);
+    }
+    measurements::reset_measurement_log()?;
+    dpe::initialize(fht)
+}
+
+/// Whether requests of `cmd` may be followed by data in the mailbox
+///
+/// The checksum of these requests covers the trailing data.
+fn takes_trailing_data(cmd: CommandId) -> bool {
+    matches!(cmd, CommandId::Ecdsa384Verify | CommandId::LmsVerify)
+}
+
+/// Verify the checksum of a request that may be followed by trailing data
+///
+/// The part of the request beyond `cmd_bytes` is read from the mailbox.
+///
+/// # Arguments
+///
+/// * `cmd_id`    - Command code
+/// * `cmd_bytes` - Request bytes copied from the mailbox
+/// * `dlen`      - Request length
+fn verify_chksum_with_trailing_data(
+    cmd_id: u32,
+    cmd_bytes: &[u8],
+    dlen: usize,
+) -> CaliptraResult<()> {
+    let hdr =
+        MailboxReqHeader::read_from_prefix(cmd_bytes).ok_or(err_u32!(MailboxInvalidLength))?;
+    let mut chksum = calc_checksum(
+        cmd_id,
+        cmd_bytes
+            .get(size_of::<MailboxReqHeader>()..)
+            .ok_or(err_u32!(MailboxInvalidLength))?,
+    );
+
+    let mut remaining = dlen.saturating_sub(cmd_bytes.len());
+    while remaining > 0 {
+        let mut word = [0u32; 1];
+        Mailbox::copy_from_mbox(&mut word);
+        let len = core::cmp::min(remaining, size_of::<u32>());
+        let bytes = word[0].to_le_bytes();
+        chksum = extend_checksum(chksum, bytes.get(..len).ok_or(err_u32!(InternalErr))?);
+        remaining -= len;
+    }
+
+    if chksum != hdr.chksum {
+        raise_err!(MailboxInvalidChecksum)
+    }
+    Ok(())
+}
+
+fn handle_command(fht: &FirmwareHandoffTable) -> CaliptraResult<MboxStatusE> {
     let cmd_id = Mailbox::cmd();
-    let dlen_words = Mailbox::dlen_words() as usize;
+    let cmd = CommandId::try_from(cmd_id).map_err(|_| err_u32!(MailboxInvalidCommand))?;
+
+    let dlen = Mailbox::dlen() as usize;
     let mut buf = [0u32; 1024];
-    Mailbox::copy_from_mbox(buf.get_mut(..dlen_words).ok_or(err_u32!(InternalErr))?);
 
-    // TODO: Actually handle command
-    cprintln!("[rt] Received command={}, len={}", cmd_id, Mailbox::dlen());
+    let words = match cmd {
+        // The update request is left in the mailbox for ROM to consume
+        CommandId::FirmwareLoad => 0,
+        // Requests must fit in the command buffer, except for commands taking
+        // trailing data: only their head is copied and handlers read the rest
+        // from mailbox SRAM directly.
+        _ if dlen > buf.as_bytes().len() && !takes_trailing_data(cmd) => {
+            raise_err!(InsufficientMemory)
+        }
+        _ => core::cmp::min(Mailbox::dlen_words() as usize, buf.len()),
+    };
+    Mailbox::copy_from_mbox(buf.get_mut(..words).ok_or(err_u32!(InternalErr))?);
+    let cmd_bytes = buf
+        .as_bytes()
+        .get(..core::cmp::min(dlen, words * size_of::<u32>()))
+        .ok_or(err_u32!(InternalErr))?;
 
-    // Write response
-    let out_buf = [0xFFFFFFFFu32; 4];
-    Mailbox::set_dlen((out_buf.len() * size_of::<u32>()) as u32);
-    Mailbox::copy_to_mbox(&out_buf);
+    cprintln!("[rt] Received command=0x{:08X}, len={}", cmd_id, dlen);
 
-    Ok(())
+    if takes_trailing_data(cmd) {
+        verify_chksum_with_trailing_data(cmd_id, cmd_bytes, dlen)?;
+    }
+
+    match cmd {
+        CommandId::Ecdsa384Verify => verify::handle_ecdsa_verify(cmd_bytes),
+        CommandId::LmsVerify => verify::handle_lms_verify(cmd_bytes),
+        CommandId::StashMeasurement => measurements::handle_stash_measurement(cmd_bytes),
+        CommandId::GetIdevIdCert => certs::handle_get_idev_id_cert(fht, cmd_bytes),
+        CommandId::GetLdevIdCert => certs::handle_get_ldev_id_cert(cmd_bytes),
+        CommandId::GetFmcAliasCert => certs::handle_get_fmc_alias_cert(cmd_bytes),
+        CommandId::GetRtAliasCert => certs::handle_get_rt_alias_cert(cmd_bytes),
+        CommandId::QuotePcrs => quote::handle_quote_pcrs(fht, cmd_bytes),
+        CommandId::InvokeDpe => dpe::handle_invoke_dpe(fht, cmd_bytes),
+        CommandId::FirmwareLoad => update::handle_firmware_load(),
+        CommandId::FirmwareLoadChunk
+        | CommandId::GetIdevCsr
+        | CommandId::ProvisionIdevCert
+        | CommandId::DebugUnlockChallenge
+        | CommandId::DebugUnlockToken => {
+            raise_err!(MailboxInvalidCommand)
+        }
+    }
 }
 
-pub fn handle_mailbox_commands() {
+pub fn handle_mailbox_commands(fht: &FirmwareHandoffTable) {
     loop {
         wait_for_cmd();
 
         if Mailbox::is_cmd_ready() {
-            if handle_command().is_ok() {
-                Mailbox::set_status(MboxStatusE::DataReady);
-            } else {
-                Mailbox::set_status(MboxStatusE::CmdFailure);
+            match handle_command(fht) {
+                Ok(status) => Mailbox::set_status(status),
+                Err(e) => {
+                    cprintln!("[rt] Command failed: 0x{:08X}", u32::from(e));
+                    report_fw_error_non_fatal(e.into());
+                    Mailbox::set_status(MboxStatusE::CmdFailure);
+                }
             }
         }
     }
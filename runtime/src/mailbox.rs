@@ -23,7 +23,7 @@ impl Mailbox {
 
     // Get the length of the current mailbox data in words
     pub fn dlen_words() -> u32 {
-        (Self::dlen() + 7) / 8
+        (Self::dlen() + 3) / 4
     }
 
     pub fn cmd() -> u32 {
@@ -36,12 +36,24 @@ impl Mailbox {
         }
     }
 
-    pub fn copy_to_mbox(buf: &[u32]) {
-        for word in buf {
-            mbox::RegisterBlock::mbox_csr().datain().write(|_| *word);
+    pub fn copy_to_mbox(buf: &[u8]) {
+        for chunk in buf.chunks(4) {
+            let mut word = [0u8; 4];
+            for (dest, src) in word.iter_mut().zip(chunk) {
+                *dest = *src;
+            }
+            mbox::RegisterBlock::mbox_csr()
+                .datain()
+                .write(|_| u32::from_le_bytes(word));
         }
     }
 
+    // Write a response to the mailbox
+    pub fn write_response(buf: &[u8]) {
+        Self::set_dlen(buf.len() as u32);
+        Self::copy_to_mbox(buf);
+    }
+
     pub fn set_status(status: MboxStatusE) {
         mbox::RegisterBlock::mbox_csr()
             .status()
@@ -16,7 +16,7 @@ Abstract:
 
 use caliptra_common::cprintln;
 use caliptra_cpu::TrapRecord;
-use caliptra_drivers::{report_fw_error_non_fatal, Mailbox};
+use caliptra_drivers::{handle_fatal_error, FatalErrorTrap};
 use core::hint::black_box;
 
 #[cfg(feature = "std")]
@@ -35,9 +35,13 @@ const BANNER: &str = r#"
 pub extern "C" fn entry_point() -> ! {
     cprintln!("{}", BANNER);
 
-    if let Some(_fht) = caliptra_common::FirmwareHandoffTable::try_load() {
+    if let Some(fht) = caliptra_common::FirmwareHandoffTable::try_load() {
+        if let Err(e) = caliptra_runtime::initialize(&fht) {
+            report_error(e.into());
+        }
+
         cprintln!("Caliptra RT listening for mailbox commands...");
-        caliptra_runtime::handle_mailbox_commands();
+        caliptra_runtime::handle_mailbox_commands(&fht);
 
         caliptra_drivers::ExitCtrl::exit(0)
     } else {
@@ -56,8 +60,7 @@ extern "C" fn exception_handler(trap_record: &TrapRecord) {
         trap_record.mepc
     );
 
-    // Signal non-fatal error to SOC
-    report_error(0xdead);
+    report_trap_error(0xdead, trap_record);
 }
 
 #[no_mangle]
@@ -71,7 +74,7 @@ extern "C" fn nmi_handler(trap_record: &TrapRecord) {
         trap_record.mepc
     );
 
-    report_error(0xdead);
+    report_trap_error(0xdead, trap_record);
 }
 
 #[panic_handler]
@@ -82,20 +85,17 @@ fn runtime_panic(_: &core::panic::PanicInfo) -> ! {
     cprintln!("RT Panic!!");
     panic_is_possible();
 
-    // TODO: Signal non-fatal error to SOC
     report_error(0xdead);
 }
 
-#[allow(clippy::empty_loop)]
 fn report_error(code: u32) -> ! {
     cprintln!("RT Error: 0x{:08X}", code);
-    report_fw_error_non_fatal(code);
-    loop {
-        // SoC firmware might be stuck waiting for Caliptra to finish
-        // executing this pending mailbox transaction. Notify them that
-        // we've failed.
-        unsafe { Mailbox::abort_pending_soc_to_uc_transactions() };
-    }
+    handle_fatal_error(code, None)
+}
+
+fn report_trap_error(code: u32, trap_record: &TrapRecord) -> ! {
+    cprintln!("RT Error: 0x{:08X}", code);
+    handle_fatal_error(code, Some(&FatalErrorTrap::from(trap_record)))
 }
 
 #[no_mangle]
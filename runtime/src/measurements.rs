@@ -0,0 +1,97 @@
+// Licensed under the Apache-2.0 license
+
+use crate::{parse_request, send_response, RuntimeErr};
+
+use caliptra_api::mailbox::{StashMeasurementReq, StashMeasurementResp};
+use caliptra_drivers::{CaliptraResult, PcrBank, PcrId, Sha384};
+use caliptra_registers::mbox::enums::MboxStatusE;
+use zerocopy::{AsBytes, FromBytes, LayoutVerified};
+
+use core::mem::size_of;
+
+extern "C" {
+    static mut PCR_LOG_ORG: u32;
+}
+
+/// PCR extended with stashed measurements
+pub const STASH_MEASUREMENT_PCR: PcrId = PcrId::PcrId31;
+
+/// Size of the measurement log region in DCCM
+const PCR_LOG_SIZE: usize = 2048;
+
+/// Maximum number of entries in the measurement log
+pub const PCR_LOG_MAX_ENTRIES: usize = (PCR_LOG_SIZE - size_of::<u32>()) / size_of::<PcrLogEntry>();
+
+/// Measurement log entry
+///
+/// The PCR is extended with the entry, excluding `pcr_id`:
+/// `PCR = SHA384(PCR || measurement || metadata || svn)`
+#[repr(C)]
+#[derive(Debug, Clone, Copy, AsBytes, FromBytes, PartialEq, Eq)]
+pub struct PcrLogEntry {
+    /// PCR the measurement was extended into
+    pub pcr_id: u32,
+
+    /// Measurement
+    pub measurement: [u8; 48],
+
+    /// Measurement identifier
+    pub metadata: [u8; 4],
+
+    /// Security Version Number of the measured component
+    pub svn: u32,
+}
+
+/// Measurement log stored in DCCM
+#[repr(C)]
+#[derive(AsBytes, FromBytes)]
+struct PcrLog {
+    /// Number of valid entries
+    num_entries: u32,
+
+    /// Log entries
+    entries: [PcrLogEntry; PCR_LOG_MAX_ENTRIES],
+}
+
+/// Get the measurement log
+fn pcr_log() -> CaliptraResult<LayoutVerified<&'static mut [u8], PcrLog>> {
+    let slice = unsafe {
+        let ptr = core::ptr::addr_of_mut!(PCR_LOG_ORG);
+        core::slice::from_raw_parts_mut(ptr, PCR_LOG_SIZE / size_of::<u32>())
+    };
+    let (log, _) = LayoutVerified::<_, PcrLog>::new_from_prefix(slice.as_bytes_mut())
+        .ok_or(err_u32!(InternalErr))?;
+    Ok(log)
+}
+
+/// Clear the measurement log and the PCR it is replayed against
+pub fn reset_measurement_log() -> CaliptraResult<()> {
+    PcrBank::default().erase_pcr(STASH_MEASUREMENT_PCR)?;
+    pcr_log()?.num_entries = 0;
+    Ok(())
+}
+
+/// Extend a measurement into the stash PCR and log it
+pub fn handle_stash_measurement(cmd_bytes: &[u8]) -> CaliptraResult<MboxStatusE> {
+    let req: StashMeasurementReq = parse_request(cmd_bytes)?;
+
+    let mut log = pcr_log()?;
+    let index = log.num_entries as usize;
+    let entry = log.entries.get_mut(index).ok_or(err_u32!(PcrLogFull))?;
+
+    *entry = PcrLogEntry {
+        pcr_id: STASH_MEASUREMENT_PCR.into(),
+        measurement: req.measurement,
+        metadata: req.metadata,
+        svn: req.svn,
+    };
+
+    let data = entry
+        .as_bytes()
+        .get(size_of::<u32>()..)
+        .ok_or(err_u32!(InternalErr))?;
+    PcrBank::default().extend_pcr(STASH_MEASUREMENT_PCR, &Sha384::default(), data)?;
+    log.num_entries += 1;
+
+    send_response(&mut StashMeasurementResp::default())
+}
@@ -0,0 +1,78 @@
+// Licensed under the Apache-2.0 license
+
+use crate::{csrng_generate, parse_request, send_response, RuntimeErr};
+
+use caliptra_api::mailbox::{QuotePcrsReq, QuotePcrsResp};
+use caliptra_common::{DataStore, FirmwareHandoffTable};
+use caliptra_drivers::{
+    Array4x12, CaliptraResult, Ecc384, Ecc384PrivKeyIn, KeyReadArgs, PcrBank, PcrId, Sha384,
+};
+use caliptra_registers::mbox::enums::MboxStatusE;
+
+const PCR_IDS: [PcrId; QuotePcrsResp::NUM_PCRS] = [
+    PcrId::PcrId0,
+    PcrId::PcrId1,
+    PcrId::PcrId2,
+    PcrId::PcrId3,
+    PcrId::PcrId4,
+    PcrId::PcrId5,
+    PcrId::PcrId6,
+    PcrId::PcrId7,
+    PcrId::PcrId8,
+    PcrId::PcrId9,
+    PcrId::PcrId10,
+    PcrId::PcrId11,
+    PcrId::PcrId12,
+    PcrId::PcrId13,
+    PcrId::PcrId14,
+    PcrId::PcrId15,
+    PcrId::PcrId16,
+    PcrId::PcrId17,
+    PcrId::PcrId18,
+    PcrId::PcrId19,
+    PcrId::PcrId20,
+    PcrId::PcrId21,
+    PcrId::PcrId22,
+    PcrId::PcrId23,
+    PcrId::PcrId24,
+    PcrId::PcrId25,
+    PcrId::PcrId26,
+    PcrId::PcrId27,
+    PcrId::PcrId28,
+    PcrId::PcrId29,
+    PcrId::PcrId30,
+    PcrId::PcrId31,
+];
+
+/// Return all PCRs and the caller's nonce, signed with the RT Alias Key
+pub fn handle_quote_pcrs(
+    fht: &FirmwareHandoffTable,
+    cmd_bytes: &[u8],
+) -> CaliptraResult<MboxStatusE> {
+    let req: QuotePcrsReq = parse_request(cmd_bytes)?;
+
+    let priv_key = match fht.rt_priv_key_kv_hdl.try_into() {
+        Ok(DataStore::KeyVaultSlot(key_id)) => key_id,
+        _ => raise_err!(InternalErr),
+    };
+
+    let mut resp = QuotePcrsResp::default();
+    let pcr_bank = PcrBank::default();
+    for (pcr, id) in resp.pcrs.iter_mut().zip(PCR_IDS) {
+        *pcr = pcr_bank.read_pcr(id).into();
+    }
+    resp.nonce = req.nonce;
+
+    let mut digest = Array4x12::default();
+    Sha384::default().digest(resp.signed_data(), &mut digest)?;
+
+    let sig = Ecc384::default().sign(
+        Ecc384PrivKeyIn::Key(KeyReadArgs::new(priv_key)),
+        &digest,
+        &csrng_generate()?,
+    )?;
+    resp.signature_r = sig.r.into();
+    resp.signature_s = sig.s.into();
+
+    send_response(&mut resp)
+}
@@ -0,0 +1,259 @@
+// Licensed under the Apache-2.0 license
+
+use crate::{mailbox::Mailbox, RuntimeErr};
+
+use caliptra_common::cprintln;
+use caliptra_drivers::{
+    memory_layout, verify_lms_signature, Array4x12, CaliptraResult, DataVault, DeviceState, Ecc384,
+    Ecc384PubKey, Ecc384Signature, FuseBank, HashValue, Lifecycle, LmotsAlgorithmType,
+    LmotsSignature, LmsAlgorithmType, LmsSignature, ResetReason, Sha384, Sha384Acc,
+    VendorPubKeyRevocation,
+};
+use caliptra_image_types::{
+    ImageDigest, ImageEccPubKey, ImageEccSignature, ImageLmsPubKey, ImageLmsSignature,
+    ImageManifest, IMAGE_CHUNK_BYTE_SIZE, IMAGE_LMS_OTS_TYPE, IMAGE_LMS_TREE_TYPE,
+    IMAGE_MANIFEST_BYTE_SIZE, LMOTS_P, LMS_DIGEST_BYTE_SIZE, LMS_TREE_HEIGHT,
+    SHA384_DIGEST_BYTE_SIZE,
+};
+use caliptra_image_verify::{ImageVerificationEnv, ImageVerifier};
+use caliptra_registers::{mbox::enums::MboxStatusE, soc_ifc};
+use core::ops::Range;
+use zerocopy::LayoutVerified;
+
+/// Handle a FIRMWARE_LOAD request
+///
+/// The manifest of the new image is verified against the fuses and the data
+/// vault before the update reset is started, so that a malformed or wrongly
+/// signed image fails the command and the current firmware keeps running.
+/// ROM verifies the whole image again after the reset.
+///
+/// The request is only inspected in mailbox SRAM, it must not be read from
+/// the mailbox before calling this function. The function does not return
+/// once the update reset is started.
+pub fn handle_firmware_load() -> CaliptraResult<MboxStatusE> {
+    let dlen = Mailbox::dlen();
+    if !(IMAGE_MANIFEST_BYTE_SIZE..=IMAGE_CHUNK_BYTE_SIZE).contains(&(dlen as usize)) {
+        raise_err!(UpdateImageInvalidLength)
+    }
+
+    let image =
+        unsafe { core::slice::from_raw_parts(memory_layout::MBOX_ORG as *const u8, dlen as usize) };
+    let manifest = LayoutVerified::<_, ImageManifest>::new_from_prefix(image)
+        .ok_or(err_u32!(UpdateImageInvalidLength))?
+        .0;
+
+    let verifier = ImageVerifier::new(RtImageVerificationEnv { image_size: dlen });
+    if let Err(e) = verifier.verify_manifest(&manifest, (), ResetReason::UpdateReset) {
+        cprintln!(
+            "[rt] Update image verification failed: 0x{:08X}",
+            u32::from(e)
+        );
+        raise_err!(UpdateImageVerificationFailed)
+    }
+
+    cprintln!("[rt] Received firmware update");
+    handle_impactless_update()
+}
+
+/// Start an impactless update with the FIRMWARE_LOAD request in the mailbox
+///
+/// The request is left pending: after the update reset, ROM reads the new
+/// image from the mailbox, verifies and loads it, then completes the
+/// transaction.
+#[allow(clippy::empty_loop)]
+fn handle_impactless_update() -> ! {
+    let soc_ifc = soc_ifc::RegisterBlock::soc_ifc_reg();
+    soc_ifc
+        .internal_fw_update_reset()
+        .write(|w| w.core_rst(true));
+
+    // The reset takes effect within a few cycles
+    loop {}
+}
+
+/// Runtime Verification Environment
+///
+/// The manifest of an update image is verified in place in the mailbox SRAM.
+struct RtImageVerificationEnv {
+    /// Size of the image in the mailbox
+    image_size: u32,
+}
+
+impl ImageVerificationEnv for RtImageVerificationEnv {
+    type Image = ();
+
+    /// Calculate Digest using SHA-384 Accelerator
+    fn sha384_digest(
+        &self,
+        _image: Self::Image,
+        offset: u32,
+        len: u32,
+    ) -> CaliptraResult<ImageDigest> {
+        // Only hash data received in the mailbox
+        match offset.checked_add(len) {
+            Some(end) if end <= self.image_size => {}
+            _ => raise_err!(UpdateImageInvalidLength),
+        }
+
+        let mut op = Sha384Acc::default()
+            .try_start_operation()
+            .ok_or(err_u32!(ShaAccLocked))?;
+
+        let mut digest = Array4x12::default();
+        op.digest(len, offset, false, &mut digest)?;
+        Ok(digest.0)
+    }
+
+    /// Calculate Digest using SHA-384 Engine
+    fn sha384_digest_data(&self, data: &[u8]) -> CaliptraResult<ImageDigest> {
+        let mut digest = Array4x12::default();
+        Sha384::default().digest(data, &mut digest)?;
+        Ok(digest.0)
+    }
+
+    /// ECC-384 Verification routine
+    fn ecc384_verify(
+        &self,
+        _image: Self::Image,
+        digest: &ImageDigest,
+        pub_key: &ImageEccPubKey,
+        sig: &ImageEccSignature,
+    ) -> CaliptraResult<bool> {
+        let pub_key = Ecc384PubKey {
+            x: pub_key.x.into(),
+            y: pub_key.y.into(),
+        };
+        let digest: Array4x12 = digest.into();
+        let sig = Ecc384Signature {
+            r: sig.r.into(),
+            s: sig.s.into(),
+        };
+
+        Ecc384::default().verify(&pub_key, &digest, &sig)
+    }
+
+    /// LMS Verification routine
+    fn lms_verify(
+        &self,
+        _image: Self::Image,
+        digest: &ImageDigest,
+        pub_key: &ImageLmsPubKey,
+        sig: &ImageLmsSignature,
+    ) -> CaliptraResult<bool> {
+        if pub_key.tree_type != IMAGE_LMS_TREE_TYPE
+            || pub_key.otstype != IMAGE_LMS_OTS_TYPE
+            || sig.tree_type != pub_key.tree_type
+            || sig.ots.otstype != pub_key.otstype
+        {
+            return Ok(false);
+        }
+
+        // The header digest is signed in its big-endian byte representation
+        let mut message = [0u8; SHA384_DIGEST_BYTE_SIZE];
+        for (bytes, word) in message.chunks_exact_mut(4).zip(digest.iter()) {
+            bytes.copy_from_slice(&word.to_be_bytes());
+        }
+
+        let mut y = [HashValue::<LMS_DIGEST_BYTE_SIZE>::default(); LMOTS_P];
+        for (node, sig_node) in y.iter_mut().zip(sig.ots.y.iter()) {
+            *node = HashValue::from(sig_node);
+        }
+
+        let mut path = [HashValue::<LMS_DIGEST_BYTE_SIZE>::default(); LMS_TREE_HEIGHT];
+        for (node, sig_node) in path.iter_mut().zip(sig.tree_path.iter()) {
+            *node = HashValue::from(sig_node);
+        }
+
+        let lms_sig = LmsSignature {
+            q: sig.q,
+            lmots_signature: LmotsSignature {
+                ots_type: LmotsAlgorithmType::LmotsSha256N24W8,
+                nonce: sig.ots.nonce,
+                y,
+            },
+            sig_type: LmsAlgorithmType::LmsSha256N24H5,
+            lms_path: &path,
+        };
+
+        verify_lms_signature(
+            LMS_TREE_HEIGHT as u8,
+            &message,
+            &pub_key.id,
+            sig.q,
+            &HashValue::from(&pub_key.digest),
+            &lms_sig,
+        )
+    }
+
+    /// Retrieve Vendor Public Key Digest
+    fn vendor_pub_key_digest(&self, _image: Self::Image) -> ImageDigest {
+        FuseBank::default().vendor_pub_key_hash().into()
+    }
+
+    /// Retrieve Vendor Public Key Revocation Bitmask
+    fn vendor_pub_key_revocation(&self, _image: Self::Image) -> VendorPubKeyRevocation {
+        FuseBank::default().vendor_pub_key_revocation()
+    }
+
+    /// Retrieve Vendor LMS Public Key Revocation Bitmask
+    fn vendor_lms_pub_key_revocation(&self, _image: Self::Image) -> VendorPubKeyRevocation {
+        FuseBank::default().vendor_lms_pub_key_revocation()
+    }
+
+    /// Retrieve Owner Public Key Digest from fuses
+    fn owner_pub_key_digest_fuses(&self) -> ImageDigest {
+        FuseBank::default().owner_pub_key_hash().into()
+    }
+
+    /// Retrieve Anti-Rollback disable fuse value
+    fn anti_rollback_disable(&self, _image: Self::Image) -> bool {
+        FuseBank::default().anti_rollback_disable()
+    }
+
+    /// Retrieve Device Lifecycle state
+    fn dev_lifecycle(&self, _image: Self::Image) -> Lifecycle {
+        DeviceState::default().lifecycle()
+    }
+
+    /// Get the vendor key index saved in data vault on cold boot
+    fn vendor_pub_key_idx_dv(&self) -> u32 {
+        DataVault::default().vendor_pk_index()
+    }
+
+    /// Get the vendor LMS key index saved in data vault on cold boot
+    fn vendor_lms_pub_key_idx_dv(&self) -> u32 {
+        DataVault::default().vendor_lms_pk_index()
+    }
+
+    /// Get the owner public key digest saved in the dv on cold boot
+    fn owner_pub_key_digest_dv(&self) -> ImageDigest {
+        DataVault::default().owner_pk_hash().into()
+    }
+
+    // Get the fmc digest from the data vault on cold boot
+    fn get_fmc_digest_dv(&self) -> ImageDigest {
+        DataVault::default().fmc_tci().into()
+    }
+
+    // Get Fuse FMC Key Manifest SVN
+    fn fmc_svn(&self) -> u32 {
+        FuseBank::default().fmc_svn()
+    }
+
+    // Get Runtime fuse SVN
+    fn runtime_svn(&self) -> u32 {
+        FuseBank::default().runtime_svn()
+    }
+
+    fn iccm_range(&self) -> Range<u32> {
+        Range {
+            start: memory_layout::ICCM_ORG,
+            end: memory_layout::ICCM_ORG + memory_layout::ICCM_SIZE,
+        }
+    }
+
+    // Get the trusted time supplied by the SoC
+    fn trusted_time(&self) -> Option<u64> {
+        DeviceState::default().trusted_time()
+    }
+}
@@ -0,0 +1,214 @@
+// Licensed under the Apache-2.0 license
+
+use crate::mailbox::Mailbox;
+use crate::{send_response, RuntimeErr};
+
+use caliptra_api::mailbox::{EcdsaVerifyReq, LmsVerifyReq, MailboxRespHeader};
+use caliptra_drivers::{
+    get_lms_parameters, lookup_lmots_algorithm_type, lookup_lms_algorithm_type,
+    verify_lms_signature, Array4x12, CaliptraResult, Ecc384, Ecc384PubKey, Ecc384Signature,
+    HashValue, LmotsAlgorithmType, LmotsSignature, LmsAlgorithmType, LmsIdentifier, LmsSignature,
+    Sha384Acc,
+};
+use caliptra_registers::mbox::enums::MboxStatusE;
+use zerocopy::{FromBytes, LayoutVerified};
+
+use core::mem::size_of;
+
+/// Verify an ECDSA-384 signature
+///
+/// If `EcdsaVerifyReq::FLAG_SHA384_MESSAGE` is set, the digest is computed
+/// over the data following the request with the SHA accelerator and the
+/// `digest` field is ignored. Otherwise no data may follow the request.
+pub fn handle_ecdsa_verify(cmd_bytes: &[u8]) -> CaliptraResult<MboxStatusE> {
+    // The checksum, covering the trailing data, was verified by the caller
+    let req_len = size_of::<EcdsaVerifyReq>();
+    let req = EcdsaVerifyReq::read_from_prefix(cmd_bytes).ok_or(err_u32!(MailboxInvalidLength))?;
+
+    let dlen = Mailbox::dlen();
+    let digest = if req.flags & EcdsaVerifyReq::FLAG_SHA384_MESSAGE != 0 {
+        mbox_sha384_digest(req_len as u32, dlen.saturating_sub(req_len as u32))?
+    } else {
+        if dlen != req_len as u32 {
+            raise_err!(MailboxInvalidLength)
+        }
+        Array4x12::from(req.digest)
+    };
+
+    let pub_key = Ecc384PubKey {
+        x: req.pub_key_x.into(),
+        y: req.pub_key_y.into(),
+    };
+    let sig = Ecc384Signature {
+        r: req.signature_r.into(),
+        s: req.signature_s.into(),
+    };
+
+    let result = if Ecc384::default().verify(&pub_key, &digest, &sig)? {
+        MailboxRespHeader::RESULT_SUCCESS
+    } else {
+        MailboxRespHeader::RESULT_BAD_SIG
+    };
+
+    send_response(&mut MailboxRespHeader { chksum: 0, result })
+}
+
+/// Verify an LMS signature
+///
+/// The message follows the request in the mailbox. If
+/// `LmsVerifyReq::FLAG_SHA384_MESSAGE` is set, the SHA-384 digest of the
+/// message is verified instead, computed with the SHA accelerator.
+pub fn handle_lms_verify(cmd_bytes: &[u8]) -> CaliptraResult<MboxStatusE> {
+    // The checksum, covering the message, was verified by the caller
+    let req_len = size_of::<LmsVerifyReq>();
+    let (req, _) = LayoutVerified::<_, LmsVerifyReq>::new_from_prefix(cmd_bytes)
+        .ok_or(err_u32!(MailboxInvalidLength))?;
+
+    let signature = req
+        .signature
+        .get(..req.signature_size as usize)
+        .ok_or(err_u32!(MailboxInvalidLength))?;
+
+    let dlen = Mailbox::dlen();
+    let verified = if req.flags & LmsVerifyReq::FLAG_SHA384_MESSAGE != 0 {
+        let digest: [u8; 48] =
+            mbox_sha384_digest(req_len as u32, dlen.saturating_sub(req_len as u32))?.into();
+        lms_verify(&digest, &req.pub_key, signature)?
+    } else {
+        // The message must fit in the command buffer
+        let message = cmd_bytes
+            .get(req_len..dlen as usize)
+            .ok_or(err_u32!(InsufficientMemory))?;
+        lms_verify(message, &req.pub_key, signature)?
+    };
+
+    let result = if verified {
+        MailboxRespHeader::RESULT_SUCCESS
+    } else {
+        MailboxRespHeader::RESULT_BAD_SIG
+    };
+
+    send_response(&mut MailboxRespHeader { chksum: 0, result })
+}
+
+/// Maximum LMS tree height
+const LMS_MAX_TREE_HEIGHT: usize = 25;
+
+/// Reader over a serialized RFC 8554 structure
+struct LmsReader<'a>(&'a [u8]);
+
+impl<'a> LmsReader<'a> {
+    fn array<const N: usize>(&mut self) -> CaliptraResult<[u8; N]> {
+        let bytes = self.0.get(..N).ok_or(err_u32!(MailboxInvalidLength))?;
+        self.0 = self.0.get(N..).ok_or(err_u32!(MailboxInvalidLength))?;
+        bytes.try_into().map_err(|_| err_u32!(MailboxInvalidLength))
+    }
+
+    fn u32(&mut self) -> CaliptraResult<u32> {
+        Ok(u32::from_be_bytes(self.array()?))
+    }
+}
+
+/// Parse a serialized LMS public key and signature and verify `message`
+///
+/// # Arguments
+///
+/// * `message`   - Signed message
+/// * `pub_key`   - Serialized LMS public key, possibly zero padded
+/// * `signature` - Serialized LMS signature
+fn lms_verify(message: &[u8], pub_key: &[u8], signature: &[u8]) -> CaliptraResult<bool> {
+    let mut key = LmsReader(pub_key);
+    let lms_type =
+        lookup_lms_algorithm_type(key.u32()?).ok_or(err_u32!(LmsUnsupportedAlgorithm))?;
+    let ots_type =
+        lookup_lmots_algorithm_type(key.u32()?).ok_or(err_u32!(LmsUnsupportedAlgorithm))?;
+    let id: LmsIdentifier = key.array()?;
+
+    // Only the parameter sets whose signatures fit on the stack are supported
+    match ots_type {
+        LmotsAlgorithmType::LmotsSha256N32W4 => {
+            lms_verify_sig::<32, 67>(message, lms_type, ots_type, &id, key, signature)
+        }
+        LmotsAlgorithmType::LmotsSha256N32W8 => {
+            lms_verify_sig::<32, 34>(message, lms_type, ots_type, &id, key, signature)
+        }
+        LmotsAlgorithmType::LmotsSha256N24W4 => {
+            lms_verify_sig::<24, 51>(message, lms_type, ots_type, &id, key, signature)
+        }
+        LmotsAlgorithmType::LmotsSha256N24W8 => {
+            lms_verify_sig::<24, 26>(message, lms_type, ots_type, &id, key, signature)
+        }
+        _ => raise_err!(LmsUnsupportedAlgorithm),
+    }
+}
+
+/// Verify `message` against a signature using `N`-byte hashes and `P`
+/// Winternitz chains
+fn lms_verify_sig<const N: usize, const P: usize>(
+    message: &[u8],
+    lms_type: LmsAlgorithmType,
+    ots_type: LmotsAlgorithmType,
+    id: &LmsIdentifier,
+    mut key: LmsReader,
+    signature: &[u8],
+) -> CaliptraResult<bool> {
+    let (width, height) =
+        get_lms_parameters(&lms_type).map_err(|_| err_u32!(LmsUnsupportedAlgorithm))?;
+    if width as usize != N {
+        raise_err!(LmsUnsupportedAlgorithm)
+    }
+    let pub_key = HashValue::<N>::new(key.array()?);
+
+    let mut sig = LmsReader(signature);
+    let q = sig.u32()?;
+    if sig.u32()? != ots_type as u32 {
+        return Ok(false);
+    }
+    let nonce = sig.array::<N>()?;
+    let mut y = [HashValue::<N>::default(); P];
+    for node in y.iter_mut() {
+        *node = HashValue::new(sig.array()?);
+    }
+    if sig.u32()? != lms_type as u32 {
+        return Ok(false);
+    }
+    let mut path = [HashValue::<N>::default(); LMS_MAX_TREE_HEIGHT];
+    let path = path
+        .get_mut(..height as usize)
+        .ok_or(err_u32!(LmsUnsupportedAlgorithm))?;
+    for node in path.iter_mut() {
+        *node = HashValue::new(sig.array()?);
+    }
+    if !sig.0.is_empty() {
+        raise_err!(MailboxInvalidLength)
+    }
+
+    // Leaf index out of range for the tree
+    if q >= 1 << height {
+        return Ok(false);
+    }
+
+    let lms_sig = LmsSignature {
+        q,
+        lmots_signature: LmotsSignature { ots_type, nonce, y },
+        sig_type: lms_type,
+        lms_path: path,
+    };
+    verify_lms_signature(height, message, id, q, &pub_key, &lms_sig)
+}
+
+/// Compute the SHA-384 digest of the data in mailbox SRAM
+///
+/// # Arguments
+///
+/// * `offset` - Offset of the data in the mailbox
+/// * `len`    - Length of the data
+fn mbox_sha384_digest(offset: u32, len: u32) -> CaliptraResult<Array4x12> {
+    let mut op = Sha384Acc::default()
+        .try_start_operation()
+        .ok_or(err_u32!(ShaAccLocked))?;
+
+    let mut digest = Array4x12::default();
+    op.digest(len, offset, false, &mut digest)?;
+    Ok(digest)
+}
@@ -21,6 +21,19 @@ fn main() {
                 } else {
                     println!("cargo:rerun-if-changed=../../test-harness/scripts/rom.ld");
                     println!("cargo:rustc-link-arg=-Ttest-harness/scripts/rom.ld");
+
+                    // DCCM regions the runtime library expects, at the
+                    // addresses of the runtime memory map
+                    println!("cargo:rerun-if-changed=../memory.x");
+                    let memory_x = include_str!("../memory.x");
+                    for sym in ["LDEVID_TBS_ORG", "FMCALIAS_TBS_ORG", "PCR_LOG_ORG", "DPE_ORG"] {
+                        let addr = memory_x
+                            .lines()
+                            .find_map(|l| l.strip_prefix(sym)?.trim_start().strip_prefix('='))
+                            .and_then(|v| v.trim().strip_suffix(';'))
+                            .unwrap_or_else(|| panic!("{sym} not found in memory.x"));
+                        println!("cargo:rustc-link-arg=--defsym={sym}={addr}");
+                    }
                 }
             }
         }
@@ -15,10 +15,11 @@ Abstract:
 #![no_std]
 #![no_main]
 
+use caliptra_common::FirmwareHandoffTable;
 use caliptra_test_harness::test_suite;
 
 fn test_mbox_cmd() {
-    caliptra_runtime::handle_mailbox_commands();
+    caliptra_runtime::handle_mailbox_commands(&FirmwareHandoffTable::default());
 }
 
 test_suite! {
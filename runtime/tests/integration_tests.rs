@@ -1,7 +1,29 @@
 // Licensed under the Apache-2.0 license.
 
+use caliptra_api::dpe::{
+    CertifyKeyCmd, CertifyKeyResp, CommandHdr, DeriveChildCmd, DeriveChildResp, DestroyCtxCmd,
+    DpeCommandId, DpeStatus, NewHandleResp, ResponseHdr, RotateCtxCmd, SignCmd, SignResp,
+    DEFAULT_CONTEXT_HANDLE, INVALID_CONTEXT_HANDLE,
+};
+use caliptra_api::mailbox::{
+    EcdsaVerifyReq, GetFmcAliasCertReq, GetLdevIdCertReq, GetRtAliasCertReq, InvokeDpeReq,
+    LmsVerifyReq, MailboxRespHeader, QuotePcrsReq, StashMeasurementReq,
+};
+use caliptra_api::{CommandId, MailboxReq, MailboxReqHeader, MailboxResp};
 use caliptra_builder::{FwId, ImageOptions, APP_WITH_UART, FMC_WITH_UART, ROM_WITH_UART};
-use caliptra_hw_model::{BootParams, DefaultHwModel, HwModel, InitParams};
+use caliptra_hw_model::{BootParams, DefaultHwModel, HwModel, InitParams, ModelError};
+use caliptra_image_fake_keys::{VENDOR_LMS_KEY_0_PRIVATE, VENDOR_LMS_KEY_0_PUBLIC};
+use caliptra_image_gen::{lms, ImageGeneratorAuxImage};
+use caliptra_image_openssl::OsslCrypto;
+use caliptra_image_types::{ImageLmsPubKey, ImageLmsSignature};
+use caliptra_runtime::{RuntimeErr, PCR_LOG_MAX_ENTRIES, STASH_MEASUREMENT_PCR};
+use openssl::bn::{BigNum, BigNumContext};
+use openssl::ec::{EcGroup, EcKey};
+use openssl::ecdsa::EcdsaSig;
+use openssl::nid::Nid;
+use openssl::sha::sha384;
+use openssl::x509::X509;
+use zerocopy::{AsBytes, FromBytes};
 
 // Run test_bin as a ROM image. The is used for faster tests that can run
 // against verilator
@@ -60,70 +82,760 @@ fn run_rt_test(test_bin_name: Option<&str>) -> DefaultHwModel {
     model
 }
 
-fn send_mailbox_command(model: &mut DefaultHwModel, cmd_id: u32, arg_buf: &[u32]) -> Vec<u32> {
-    model.soc_mbox().lock().read().lock();
-    model.soc_mbox().cmd().write(|_| cmd_id);
+#[test]
+fn test_standard() {
+    // Test that the normal runtime firmware boots.
+    // Ultimately, this will be useful for exercising Caliptra end-to-end
+    // via the mailbox.
+    let mut model = run_rt_test(None);
+
     model
-        .soc_mbox()
-        .dlen()
-        .write(|_| u32::try_from(arg_buf.len()).unwrap() * 4);
+        .step_until_output_contains("Caliptra RT listening for mailbox commands...")
+        .unwrap();
+}
 
-    for word in arg_buf {
-        model.soc_mbox().datain().write(|_| *word);
+#[test]
+fn test_boot() {
+    let mut model = run_rt_test(Some("boot"));
+
+    model.step_until_exit_success().unwrap();
+}
+
+#[test]
+fn test_mbox() {
+    let mut model = run_rom_test("mbox");
+
+    model.step_until(|m| m.soc_mbox().status().read().mbox_fsm_ps().mbox_idle());
+
+    // Unknown commands are rejected
+    let resp = model.mailbox_execute(0x0, &[0u8; 16]);
+    assert_eq!(resp, Err(ModelError::MailboxCmdFailed));
+    assert_eq!(
+        model.soc_ifc().cptra_fw_error_non_fatal().read(),
+        u32::from(RuntimeErr::MailboxInvalidCommand)
+    );
+
+    assert!(model.soc_mbox().status().read().mbox_fsm_ps().mbox_idle());
+}
+
+#[test]
+fn test_mbox_request_validation() {
+    let mut model = run_rom_test("mbox");
+
+    model.step_until(|m| m.soc_mbox().status().read().mbox_fsm_ps().mbox_idle());
+
+    let cmd = u32::from(CommandId::StashMeasurement);
+    let mut req = StashMeasurementReq {
+        hdr: MailboxReqHeader::default(),
+        metadata: [0x1, 0x2, 0x3, 0x4],
+        measurement: [0xAB; 48],
+        svn: 1,
+    };
+
+    // Bad checksum
+    req.populate_chksum();
+    req.hdr.chksum = req.hdr.chksum.wrapping_add(1);
+    let resp = model.mailbox_execute(cmd, req.as_bytes());
+    assert_eq!(resp, Err(ModelError::MailboxCmdFailed));
+    assert_eq!(
+        model.soc_ifc().cptra_fw_error_non_fatal().read(),
+        u32::from(RuntimeErr::MailboxInvalidChecksum)
+    );
+
+    // Truncated request
+    req.populate_chksum();
+    let resp = model.mailbox_execute(cmd, &req.as_bytes()[..req.as_bytes().len() - 4]);
+    assert_eq!(resp, Err(ModelError::MailboxCmdFailed));
+    assert_eq!(
+        model.soc_ifc().cptra_fw_error_non_fatal().read(),
+        u32::from(RuntimeErr::MailboxInvalidLength)
+    );
+
+    // Request larger than the command buffer
+    let mut buf = req.as_bytes().to_vec();
+    buf.resize(4096 + 4, 0);
+    let resp = model.mailbox_execute(cmd, &buf);
+    assert_eq!(resp, Err(ModelError::MailboxCmdFailed));
+    assert_eq!(
+        model.soc_ifc().cptra_fw_error_non_fatal().read(),
+        u32::from(RuntimeErr::InsufficientMemory)
+    );
+}
+
+#[test]
+fn test_stash_measurement() {
+    let mut model = run_rt_test(None);
+
+    model
+        .step_until_output_contains("Caliptra RT listening for mailbox commands...")
+        .unwrap();
+
+    for i in 0..PCR_LOG_MAX_ENTRIES {
+        let req = StashMeasurementReq {
+            hdr: MailboxReqHeader::default(),
+            metadata: (i as u32).to_be_bytes(),
+            measurement: [i as u8; 48],
+            svn: i as u32,
+        };
+        let resp = model.mailbox_execute_req(req).unwrap();
+        assert_eq!(resp.hdr.result, MailboxRespHeader::RESULT_SUCCESS);
     }
+
+    // The log is full
+    let req = StashMeasurementReq {
+        hdr: MailboxReqHeader::default(),
+        metadata: [0xFF; 4],
+        measurement: [0xFF; 48],
+        svn: 0,
+    };
+    assert_eq!(
+        model.mailbox_execute_req(req),
+        Err(ModelError::MailboxCmdFailed)
+    );
+    assert_eq!(
+        model.soc_ifc().cptra_fw_error_non_fatal().read(),
+        u32::from(RuntimeErr::PcrLogFull)
+    );
+}
+
+#[test]
+fn test_cert_chain() {
+    let mut model = run_rt_test(None);
+
+    model
+        .step_until_output_contains("Caliptra RT listening for mailbox commands...")
+        .unwrap();
+
+    let ldev_resp = model
+        .mailbox_execute_req(GetLdevIdCertReq::default())
+        .unwrap();
+    let ldev_cert = X509::from_der(ldev_resp.cert().unwrap()).unwrap();
+
+    let fmc_resp = model
+        .mailbox_execute_req(GetFmcAliasCertReq::default())
+        .unwrap();
+    let fmc_cert = X509::from_der(fmc_resp.cert().unwrap()).unwrap();
+
+    let rt_resp = model
+        .mailbox_execute_req(GetRtAliasCertReq::default())
+        .unwrap();
+    let rt_cert = X509::from_der(rt_resp.cert().unwrap()).unwrap();
+
+    // Each certificate is signed by the previous layer's alias key
+    assert!(fmc_cert.verify(&ldev_cert.public_key().unwrap()).unwrap());
+    assert!(rt_cert.verify(&fmc_cert.public_key().unwrap()).unwrap());
+}
+
+#[test]
+fn test_quote_pcrs() {
+    let mut model = run_rt_test(None);
+
     model
-        .soc_mbox()
-        .execute()
-        .write(|exec_reg| exec_reg.execute(true));
+        .step_until_output_contains("Caliptra RT listening for mailbox commands...")
+        .unwrap();
 
-    model.step_until(|m| !m.soc_mbox().status().read().status().cmd_busy());
+    let stash = StashMeasurementReq {
+        hdr: MailboxReqHeader::default(),
+        metadata: [0x1, 0x2, 0x3, 0x4],
+        measurement: [0xAB; 48],
+        svn: 7,
+    };
+    model.mailbox_execute_req(stash).unwrap();
+
+    let nonce = [0x5A; 32];
+    let quote = model
+        .mailbox_execute_req(QuotePcrsReq {
+            hdr: MailboxReqHeader::default(),
+            nonce,
+        })
+        .unwrap();
+    assert_eq!(quote.nonce, nonce);
 
-    let dlen_bytes = model.soc_mbox().dlen().read() as usize;
-    let dlen_words = (dlen_bytes + 3) / 4;
-    let mut resp = vec![0u32; dlen_words];
+    // Replay the measurement log
+    let mut data = vec![0u8; 48];
+    data.extend_from_slice(&stash.measurement);
+    data.extend_from_slice(&stash.metadata);
+    data.extend_from_slice(&stash.svn.to_le_bytes());
+    assert_eq!(quote.pcrs[STASH_MEASUREMENT_PCR as usize], sha384(&data));
 
-    for dest_word in resp.iter_mut() {
-        *dest_word = model.soc_mbox().dataout().read();
+    // The quote is signed by the RT Alias Key
+    let rt_resp = model
+        .mailbox_execute_req(GetRtAliasCertReq::default())
+        .unwrap();
+    let rt_cert = X509::from_der(rt_resp.cert().unwrap()).unwrap();
+    let rt_pub_key = rt_cert.public_key().unwrap().ec_key().unwrap();
+
+    let sig = EcdsaSig::from_private_components(
+        BigNum::from_slice(&quote.signature_r).unwrap(),
+        BigNum::from_slice(&quote.signature_s).unwrap(),
+    )
+    .unwrap();
+    assert!(sig
+        .verify(&sha384(quote.signed_data()), &rt_pub_key)
+        .unwrap());
+}
+
+/// Build an ECDSA384_SIGNATURE_VERIFY request for `data` signed with a
+/// freshly generated key
+fn ecdsa_verify_req(data: &[u8]) -> EcdsaVerifyReq {
+    let group = EcGroup::from_curve_name(Nid::SECP384R1).unwrap();
+    let key = EcKey::generate(&group).unwrap();
+    let digest = sha384(data);
+    let sig = EcdsaSig::sign(&digest, &key).unwrap();
+
+    let mut ctx = BigNumContext::new().unwrap();
+    let mut x = BigNum::new().unwrap();
+    let mut y = BigNum::new().unwrap();
+    key.public_key()
+        .affine_coordinates(&group, &mut x, &mut y, &mut ctx)
+        .unwrap();
+
+    EcdsaVerifyReq {
+        hdr: MailboxReqHeader::default(),
+        flags: 0,
+        digest,
+        pub_key_x: x.to_vec_padded(48).unwrap().try_into().unwrap(),
+        pub_key_y: y.to_vec_padded(48).unwrap().try_into().unwrap(),
+        signature_r: sig.r().to_vec_padded(48).unwrap().try_into().unwrap(),
+        signature_s: sig.s().to_vec_padded(48).unwrap().try_into().unwrap(),
     }
+}
+
+#[test]
+fn test_ecdsa_verify() {
+    let mut model = run_rt_test(None);
 
     model
-        .soc_mbox()
-        .execute()
-        .write(|exec_reg| exec_reg.execute(false));
-    model.step(); // Step once to get mailbox to idel state
+        .step_until_output_contains("Caliptra RT listening for mailbox commands...")
+        .unwrap();
+
+    let req = ecdsa_verify_req(b"Caliptra ECDSA verify test");
 
-    resp
+    // Valid signature over the supplied digest
+    let resp = model.mailbox_execute_req(req).unwrap();
+    assert_eq!(resp.result, MailboxRespHeader::RESULT_SUCCESS);
+
+    // Corrupted signature
+    let mut bad_req = req;
+    bad_req.signature_s[47] ^= 0x1;
+    let resp = model.mailbox_execute_req(bad_req).unwrap();
+    assert_eq!(resp.result, MailboxRespHeader::RESULT_BAD_SIG);
 }
 
 #[test]
-fn test_standard() {
-    // Test that the normal runtime firmware boots.
-    // Ultimately, this will be useful for exercising Caliptra end-to-end
-    // via the mailbox.
+fn test_ecdsa_verify_mbox_data() {
     let mut model = run_rt_test(None);
 
     model
         .step_until_output_contains("Caliptra RT listening for mailbox commands...")
         .unwrap();
+
+    // Data larger than the runtime's command buffer is hashed in place
+    let data: Vec<u8> = (0..8192u32).map(|i| i as u8).collect();
+    let mut req = ecdsa_verify_req(&data);
+    req.flags = EcdsaVerifyReq::FLAG_SHA384_MESSAGE;
+
+    let mut execute = |req: &mut EcdsaVerifyReq, data: &[u8]| {
+        // The digest is computed by Caliptra
+        req.digest = [0u8; 48];
+        req.populate_chksum_with_data(data);
+        let mut buf = req.as_bytes().to_vec();
+        buf.extend_from_slice(data);
+        let resp = model
+            .mailbox_execute(CommandId::Ecdsa384Verify.into(), &buf)
+            .unwrap()
+            .unwrap();
+        let resp = MailboxRespHeader::read_from(resp.as_slice()).unwrap();
+        assert!(resp.verify_chksum());
+        resp.result
+    };
+
+    assert_eq!(execute(&mut req, &data), MailboxRespHeader::RESULT_SUCCESS);
+
+    // Tampered data
+    let mut bad_data = data.clone();
+    bad_data[4096] ^= 0x1;
+    assert_eq!(
+        execute(&mut req, &bad_data),
+        MailboxRespHeader::RESULT_BAD_SIG
+    );
+
+    // The checksum covers the data
+    req.populate_chksum_with_data(&data);
+    let mut buf = req.as_bytes().to_vec();
+    buf.extend_from_slice(&bad_data);
+    let resp = model.mailbox_execute(CommandId::Ecdsa384Verify.into(), &buf);
+    assert_eq!(resp, Err(ModelError::MailboxCmdFailed));
+    assert_eq!(
+        model.soc_ifc().cptra_fw_error_non_fatal().read(),
+        u32::from(RuntimeErr::MailboxInvalidChecksum)
+    );
+
+    // Data is rejected without the SHA384_MESSAGE flag
+    req.flags = 0;
+    req.populate_chksum_with_data(&data);
+    let mut buf = req.as_bytes().to_vec();
+    buf.extend_from_slice(&data);
+    let resp = model.mailbox_execute(CommandId::Ecdsa384Verify.into(), &buf);
+    assert_eq!(resp, Err(ModelError::MailboxCmdFailed));
+    assert_eq!(
+        model.soc_ifc().cptra_fw_error_non_fatal().read(),
+        u32::from(RuntimeErr::MailboxInvalidLength)
+    );
+}
+
+/// Serialize an LMS public key as described in RFC 8554, section 5.3
+fn lms_pub_key_bytes(pub_key: &ImageLmsPubKey) -> Vec<u8> {
+    let mut bytes = vec![];
+    bytes.extend_from_slice(&pub_key.tree_type.to_be_bytes());
+    bytes.extend_from_slice(&pub_key.otstype.to_be_bytes());
+    bytes.extend_from_slice(&pub_key.id);
+    bytes.extend_from_slice(&pub_key.digest);
+    bytes
+}
+
+/// Serialize an LMS signature as described in RFC 8554, section 5.4
+fn lms_signature_bytes(sig: &ImageLmsSignature) -> Vec<u8> {
+    let mut bytes = vec![];
+    bytes.extend_from_slice(&sig.q.to_be_bytes());
+    bytes.extend_from_slice(&sig.ots.otstype.to_be_bytes());
+    bytes.extend_from_slice(&sig.ots.nonce);
+    for y in sig.ots.y.iter() {
+        bytes.extend_from_slice(y);
+    }
+    bytes.extend_from_slice(&sig.tree_type.to_be_bytes());
+    for node in sig.tree_path.iter() {
+        bytes.extend_from_slice(node);
+    }
+    bytes
+}
+
+/// Build an LMS verify request for `message` signed over `signed` with the
+/// test vendor LMS key
+fn lms_verify_req(flags: u32, signed: &[u8], message: &[u8]) -> Vec<u8> {
+    let pub_key = lms_pub_key_bytes(&VENDOR_LMS_KEY_0_PUBLIC);
+    let signature = lms_signature_bytes(
+        &lms::sign(&OsslCrypto::default(), signed, &VENDOR_LMS_KEY_0_PRIVATE).unwrap(),
+    );
+
+    let mut req = LmsVerifyReq {
+        flags,
+        signature_size: signature.len() as u32,
+        ..Default::default()
+    };
+    req.pub_key[..pub_key.len()].copy_from_slice(&pub_key);
+    req.signature[..signature.len()].copy_from_slice(&signature);
+    req.populate_chksum_with_data(message);
+
+    let mut buf = req.as_bytes().to_vec();
+    buf.extend_from_slice(message);
+    buf
 }
 
 #[test]
-fn test_boot() {
-    let mut model = run_rt_test(Some("boot"));
+fn test_lms_verify() {
+    let mut model = run_rt_test(None);
 
-    model.step_until_exit_success().unwrap();
+    model
+        .step_until_output_contains("Caliptra RT listening for mailbox commands...")
+        .unwrap();
+
+    let mut execute = |req: &[u8]| {
+        let resp = model
+            .mailbox_execute(CommandId::LmsVerify.into(), req)
+            .unwrap()
+            .unwrap();
+        let resp = MailboxRespHeader::read_from(resp.as_slice()).unwrap();
+        assert!(resp.verify_chksum());
+        resp.result
+    };
+
+    let message = b"this is the message I want signed";
+    assert_eq!(
+        execute(&lms_verify_req(0, message, message)),
+        MailboxRespHeader::RESULT_SUCCESS
+    );
+
+    // Different message
+    assert_eq!(
+        execute(&lms_verify_req(0, message, b"this is a different message")),
+        MailboxRespHeader::RESULT_BAD_SIG
+    );
+
+    // Signature over the SHA-384 digest of the message
+    assert_eq!(
+        execute(&lms_verify_req(
+            LmsVerifyReq::FLAG_SHA384_MESSAGE,
+            &sha384(message),
+            message
+        )),
+        MailboxRespHeader::RESULT_SUCCESS
+    );
+
+    // The digest of a different message
+    assert_eq!(
+        execute(&lms_verify_req(
+            LmsVerifyReq::FLAG_SHA384_MESSAGE,
+            &sha384(message),
+            b"this is a different message"
+        )),
+        MailboxRespHeader::RESULT_BAD_SIG
+    );
+
+    // The signature is over the message itself, not its digest
+    assert_eq!(
+        execute(&lms_verify_req(
+            LmsVerifyReq::FLAG_SHA384_MESSAGE,
+            message,
+            message
+        )),
+        MailboxRespHeader::RESULT_BAD_SIG
+    );
+}
+
+/// Execute DPE command `cmd_id` and return the DPE response
+fn invoke_dpe(model: &mut DefaultHwModel, cmd_id: DpeCommandId, cmd: &[u8]) -> Vec<u8> {
+    let mut data = CommandHdr::new(cmd_id).as_bytes().to_vec();
+    data.extend_from_slice(cmd);
+    let resp = model
+        .mailbox_execute_req(InvokeDpeReq::new(&data).unwrap())
+        .unwrap();
+    assert_eq!(resp.hdr.result, MailboxRespHeader::RESULT_SUCCESS);
+    resp.resp().unwrap().to_vec()
+}
+
+fn dpe_status(resp: &[u8]) -> u32 {
+    let hdr = ResponseHdr::read_from_prefix(resp).unwrap();
+    assert_eq!(hdr.magic, ResponseHdr::MAGIC);
+    hdr.status
+}
+
+fn ec_pub_key(x: &[u8], y: &[u8]) -> EcKey<openssl::pkey::Public> {
+    let group = EcGroup::from_curve_name(Nid::SECP384R1).unwrap();
+    EcKey::from_public_key_affine_coordinates(
+        &group,
+        &BigNum::from_slice(x).unwrap(),
+        &BigNum::from_slice(y).unwrap(),
+    )
+    .unwrap()
 }
 
 #[test]
-fn test_mbox() {
-    let mut model = run_rom_test("mbox");
+fn test_dpe() {
+    let mut model = run_rt_test(None);
 
-    model.step_until(|m| m.soc_mbox().status().read().mbox_fsm_ps().mbox_idle());
+    model
+        .step_until_output_contains("Caliptra RT listening for mailbox commands...")
+        .unwrap();
 
-    let cmd_buf = [0u32; 4];
-    let resp = send_mailbox_command(&mut model, 0x0, &cmd_buf);
+    let rt_resp = model
+        .mailbox_execute_req(GetRtAliasCertReq::default())
+        .unwrap();
+    let rt_cert = X509::from_der(rt_resp.cert().unwrap()).unwrap();
 
-    assert_eq!(resp, vec![0xFFFFFFFFu32; 4]);
+    // The default context is initialized on boot
+    let certify = CertifyKeyCmd {
+        handle: DEFAULT_CONTEXT_HANDLE,
+        flags: 0,
+        label: [0x11; 48],
+    };
+    let resp = invoke_dpe(&mut model, DpeCommandId::CertifyKey, certify.as_bytes());
+    assert_eq!(dpe_status(&resp), u32::from(DpeStatus::Ok));
+    let resp = CertifyKeyResp::read_from(resp.as_slice()).unwrap();
+    assert_eq!(resp.new_context_handle, DEFAULT_CONTEXT_HANDLE);
 
-    assert!(model.soc_mbox().status().read().mbox_fsm_ps().mbox_idle());
+    // The leaf certificate is signed by the RT Alias Key and certifies the
+    // derived key
+    let leaf_cert = X509::from_der(resp.cert().unwrap()).unwrap();
+    assert!(leaf_cert.verify(&rt_cert.public_key().unwrap()).unwrap());
+    let default_pubkey_x = resp.derived_pubkey_x;
+    let leaf_key = ec_pub_key(&resp.derived_pubkey_x, &resp.derived_pubkey_y);
+    assert!(leaf_cert
+        .public_key()
+        .unwrap()
+        .public_eq(&openssl::pkey::PKey::from_ec_key(leaf_key.clone()).unwrap()));
+
+    // Sign with the same key
+    let digest = sha384(b"message");
+    let sign = SignCmd {
+        handle: DEFAULT_CONTEXT_HANDLE,
+        label: certify.label,
+        flags: 0,
+        digest,
+    };
+    let resp = invoke_dpe(&mut model, DpeCommandId::Sign, sign.as_bytes());
+    assert_eq!(dpe_status(&resp), u32::from(DpeStatus::Ok));
+    let resp = SignResp::read_from(resp.as_slice()).unwrap();
+    assert_eq!(resp.new_context_handle, DEFAULT_CONTEXT_HANDLE);
+    let sig = EcdsaSig::from_private_components(
+        BigNum::from_slice(&resp.sig_r).unwrap(),
+        BigNum::from_slice(&resp.sig_s).unwrap(),
+    )
+    .unwrap();
+    assert!(sig.verify(&digest, &leaf_key).unwrap());
+
+    // Derive a child, keeping the default context
+    let derive = DeriveChildCmd {
+        handle: DEFAULT_CONTEXT_HANDLE,
+        data: [0x22; 48],
+        flags: DeriveChildCmd::FLAG_RETAIN_PARENT,
+        tci_type: u32::from_le_bytes(*b"TEST"),
+    };
+    let resp = invoke_dpe(&mut model, DpeCommandId::DeriveChild, derive.as_bytes());
+    assert_eq!(dpe_status(&resp), u32::from(DpeStatus::Ok));
+    let resp = DeriveChildResp::read_from(resp.as_slice()).unwrap();
+    assert_eq!(resp.parent_handle, DEFAULT_CONTEXT_HANDLE);
+    let child = resp.handle;
+    assert_ne!(child, DEFAULT_CONTEXT_HANDLE);
+    assert_ne!(child, INVALID_CONTEXT_HANDLE);
+
+    // The child has its own key and its handle changes on use
+    let certify_child = CertifyKeyCmd {
+        handle: child,
+        ..certify
+    };
+    let resp = invoke_dpe(
+        &mut model,
+        DpeCommandId::CertifyKey,
+        certify_child.as_bytes(),
+    );
+    assert_eq!(dpe_status(&resp), u32::from(DpeStatus::Ok));
+    let resp = CertifyKeyResp::read_from(resp.as_slice()).unwrap();
+    assert_ne!(resp.derived_pubkey_x, default_pubkey_x);
+    assert_ne!(resp.new_context_handle, child);
+    let leaf_cert = X509::from_der(resp.cert().unwrap()).unwrap();
+    assert!(leaf_cert.verify(&rt_cert.public_key().unwrap()).unwrap());
+    let child = resp.new_context_handle;
+
+    // The old handle is no longer valid
+    let resp = invoke_dpe(
+        &mut model,
+        DpeCommandId::CertifyKey,
+        certify_child.as_bytes(),
+    );
+    assert_eq!(dpe_status(&resp), u32::from(DpeStatus::InvalidHandle));
+
+    let rotate = RotateCtxCmd {
+        handle: child,
+        flags: 0,
+    };
+    let resp = invoke_dpe(
+        &mut model,
+        DpeCommandId::RotateContextHandle,
+        rotate.as_bytes(),
+    );
+    assert_eq!(dpe_status(&resp), u32::from(DpeStatus::Ok));
+    let resp = NewHandleResp::read_from(resp.as_slice()).unwrap();
+    assert_ne!(resp.handle, child);
+    let child = resp.handle;
+
+    // Destroying the child leaves the default context usable
+    let destroy = DestroyCtxCmd { handle: child };
+    let resp = invoke_dpe(&mut model, DpeCommandId::DestroyContext, destroy.as_bytes());
+    assert_eq!(dpe_status(&resp), u32::from(DpeStatus::Ok));
+
+    let resp = invoke_dpe(&mut model, DpeCommandId::DestroyContext, destroy.as_bytes());
+    assert_eq!(dpe_status(&resp), u32::from(DpeStatus::InvalidHandle));
+
+    let resp = invoke_dpe(&mut model, DpeCommandId::CertifyKey, certify.as_bytes());
+    assert_eq!(dpe_status(&resp), u32::from(DpeStatus::Ok));
+    let resp = CertifyKeyResp::read_from(resp.as_slice()).unwrap();
+    assert_eq!(resp.derived_pubkey_x, default_pubkey_x);
+}
+
+#[test]
+fn test_impactless_update() {
+    let mut model = run_rt_test(None);
+
+    model
+        .step_until_output_contains("Caliptra RT listening for mailbox commands...")
+        .unwrap();
+
+    let stash = StashMeasurementReq {
+        hdr: MailboxReqHeader::default(),
+        metadata: [0x1, 0x2, 0x3, 0x4],
+        measurement: [0xAB; 48],
+        svn: 7,
+    };
+    model.mailbox_execute_req(stash).unwrap();
+
+    let derive = DeriveChildCmd {
+        handle: DEFAULT_CONTEXT_HANDLE,
+        data: [0x22; 48],
+        flags: DeriveChildCmd::FLAG_RETAIN_PARENT,
+        tci_type: u32::from_le_bytes(*b"TEST"),
+    };
+    let resp = invoke_dpe(&mut model, DpeCommandId::DeriveChild, derive.as_bytes());
+    let child = DeriveChildResp::read_from(resp.as_slice()).unwrap().handle;
+
+    let certify = CertifyKeyCmd {
+        handle: child,
+        flags: 0,
+        label: [0x11; 48],
+    };
+    let resp = invoke_dpe(&mut model, DpeCommandId::CertifyKey, certify.as_bytes());
+    let resp = CertifyKeyResp::read_from(resp.as_slice()).unwrap();
+    let child = resp.new_context_handle;
+    let pubkey_x = resp.derived_pubkey_x;
+
+    let quote_req = QuotePcrsReq {
+        hdr: MailboxReqHeader::default(),
+        nonce: [0x5A; 32],
+    };
+    let before = model.mailbox_execute_req(quote_req).unwrap();
+
+    // Update to a Runtime Firmware with a different SVN
+    let image = caliptra_builder::build_and_sign_image(
+        &FMC_WITH_UART,
+        &APP_WITH_UART,
+        ImageOptions {
+            app_svn: 1,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        model.mailbox_execute(
+            u32::from(CommandId::FirmwareLoad),
+            &image.to_bytes().unwrap()
+        ),
+        Ok(None)
+    );
+
+    model
+        .step_until_output_contains("Caliptra RT listening for mailbox commands...")
+        .unwrap();
+
+    // Updating the manifest leaves the DPE state intact
+    assert!(!model
+        .output()
+        .peek()
+        .contains("DPE state is invalid, reinitializing"));
+
+    // The journey and current PCRs measure the new firmware, stashed
+    // measurements are kept
+    let after = model.mailbox_execute_req(quote_req).unwrap();
+    assert_ne!(after.pcrs[2], before.pcrs[2]);
+    assert_ne!(after.pcrs[3], before.pcrs[3]);
+    assert_eq!(
+        after.pcrs[STASH_MEASUREMENT_PCR as usize],
+        before.pcrs[STASH_MEASUREMENT_PCR as usize]
+    );
+
+    // DPE contexts are kept and derive keys from the new firmware journey
+    let certify = CertifyKeyCmd {
+        handle: child,
+        ..certify
+    };
+    let resp = invoke_dpe(&mut model, DpeCommandId::CertifyKey, certify.as_bytes());
+    assert_eq!(dpe_status(&resp), u32::from(DpeStatus::Ok));
+    let resp = CertifyKeyResp::read_from(resp.as_slice()).unwrap();
+    assert_ne!(resp.derived_pubkey_x, pubkey_x);
+
+    // The measurement log still holds the entry stashed before the update
+    for _ in 1..PCR_LOG_MAX_ENTRIES {
+        let resp = model.mailbox_execute_req(stash).unwrap();
+        assert_eq!(resp.hdr.result, MailboxRespHeader::RESULT_SUCCESS);
+    }
+    assert_eq!(
+        model.mailbox_execute_req(stash),
+        Err(ModelError::MailboxCmdFailed)
+    );
+    assert_eq!(
+        model.soc_ifc().cptra_fw_error_non_fatal().read(),
+        u32::from(RuntimeErr::PcrLogFull)
+    );
+}
+
+#[test]
+fn test_update_reset_measures_aux_images() {
+    let mut model = run_rt_test(None);
+
+    model
+        .step_until_output_contains("Caliptra RT listening for mailbox commands...")
+        .unwrap();
+
+    let quote_req = QuotePcrsReq {
+        hdr: MailboxReqHeader::default(),
+        nonce: [0x5A; 32],
+    };
+    let before = model.mailbox_execute_req(quote_req).unwrap();
+
+    let aux = ImageGeneratorAuxImage {
+        id: 3,
+        rev: Default::default(),
+        content: vec![0xA5; 64],
+    };
+    let image = caliptra_builder::build_and_sign_image(
+        &FMC_WITH_UART,
+        &APP_WITH_UART,
+        ImageOptions {
+            aux: vec![aux.clone()],
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        model.mailbox_execute(
+            u32::from(CommandId::FirmwareLoad),
+            &image.to_bytes().unwrap()
+        ),
+        Ok(None)
+    );
+
+    model
+        .step_until_output_contains("Caliptra RT listening for mailbox commands...")
+        .unwrap();
+
+    // ROM extends the journey PCR0 with the auxiliary image of the new
+    // manifest
+    let after = model.mailbox_execute_req(quote_req).unwrap();
+    let mut data = before.pcrs[0].to_vec();
+    data.extend_from_slice(&aux.id.to_le_bytes());
+    let mut data = sha384(&data).to_vec();
+    data.extend_from_slice(&sha384(&aux.content));
+    assert_eq!(after.pcrs[0], sha384(&data));
+}
+
+#[test]
+fn test_update_reset_rejects_invalid_image() {
+    let mut model = run_rt_test(None);
+
+    model
+        .step_until_output_contains("Caliptra RT listening for mailbox commands...")
+        .unwrap();
+
+    // The image must at least hold a manifest
+    assert_eq!(
+        model.mailbox_execute(u32::from(CommandId::FirmwareLoad), &[0u8; 64]),
+        Err(ModelError::MailboxCmdFailed)
+    );
+    assert_eq!(
+        model.soc_ifc().cptra_fw_error_non_fatal().read(),
+        u32::from(RuntimeErr::UpdateImageInvalidLength)
+    );
+
+    // The TOC entries are covered by the signed header
+    let mut image =
+        caliptra_builder::build_and_sign_image(&FMC_WITH_UART, &APP_WITH_UART, Default::default())
+            .unwrap();
+    image.manifest.runtime.digest[0] ^= 1;
+    assert_eq!(
+        model.mailbox_execute(
+            u32::from(CommandId::FirmwareLoad),
+            &image.to_bytes().unwrap()
+        ),
+        Err(ModelError::MailboxCmdFailed)
+    );
+    assert_eq!(
+        model.soc_ifc().cptra_fw_error_non_fatal().read(),
+        u32::from(RuntimeErr::UpdateImageVerificationFailed)
+    );
+
+    // The current firmware keeps handling commands
+    let quote_req = QuotePcrsReq {
+        hdr: MailboxReqHeader::default(),
+        nonce: [0x5A; 32],
+    };
+    model.mailbox_execute_req(quote_req).unwrap();
 }
@@ -12,14 +12,16 @@ Abstract:
 
 --*/
 
+use caliptra_api::CommandId;
 use caliptra_emu_bus::Clock;
 use caliptra_emu_cpu::{Cpu, RvInstr, StepAction};
 use caliptra_emu_periph::{
     CaliptraRootBus, CaliptraRootBusArgs, MailboxInternal, ReadyForFwCb, TbServicesCb,
     UploadUpdateFwCb,
 };
-use caliptra_hw_model::BusMmio;
+use caliptra_hw_model::{firmware_load_txns, BusMmio};
 use caliptra_hw_model_types::{DeviceLifecycle, SecurityState};
+use caliptra_image_types::IMAGE_CHUNK_BYTE_SIZE;
 use clap::{arg, value_parser, ArgAction};
 use std::fs::File;
 use std::io;
@@ -35,9 +37,6 @@ use gdb::gdb_state;
 
 use tock_registers::register_bitfields;
 
-/// Firmware Load Command Opcode
-const FW_LOAD_CMD_OPCODE: u32 = 0x4657_4C44;
-
 /// The number of CPU clock cycles it takes to write the firmware to the mailbox.
 const FW_WRITE_TICKS: u64 = 1000;
 
@@ -249,15 +248,20 @@ fn main() -> io::Result<()> {
             // Lock the mailbox
             while !args.mailbox.try_acquire_lock() {}
 
-            let firmware_buffer = current_fw_buf.clone();
-            args.schedule_later(FW_WRITE_TICKS, move |mailbox: &mut MailboxInternal| {
-                upload_fw_to_mailbox(mailbox, firmware_buffer);
-            });
+            args.schedule_chunks_later(FW_WRITE_TICKS, fw_upload_fn(current_fw_buf.clone()));
         }),
         security_state,
-        upload_update_fw: UploadUpdateFwCb::new(move |mailbox: &mut MailboxInternal| {
-            while !mailbox.try_acquire_lock() {}
-            upload_fw_to_mailbox(mailbox, update_fw_buf.clone());
+        upload_update_fw: UploadUpdateFwCb::new({
+            let mut upload = fw_upload_fn(update_fw_buf);
+            let mut pending = false;
+            move |mailbox: &mut MailboxInternal| {
+                // The emulator locks the mailbox for the remaining chunks
+                if !pending {
+                    while !mailbox.try_acquire_lock() {}
+                }
+                pending = upload(mailbox);
+                pending
+            }
         }),
         ..Default::default()
     };
@@ -375,9 +379,24 @@ fn change_dword_endianess(data: &mut Vec<u8>) {
     }
 }
 
-fn upload_fw_to_mailbox(mailbox: &mut MailboxInternal, firmware_buffer: Rc<Vec<u8>>) {
+/// Returns a callback uploading the mailbox transactions that load
+/// `firmware`, one transaction per call
+fn fw_upload_fn(firmware: Rc<Vec<u8>>) -> impl FnMut(&mut MailboxInternal) -> bool {
+    let mut next = 0;
+    move |mailbox: &mut MailboxInternal| {
+        let txns = firmware_load_txns(&firmware, IMAGE_CHUNK_BYTE_SIZE);
+        let (cmd, data) = txns[next];
+        upload_fw_to_mailbox(mailbox, cmd, data);
+
+        // Start over for the next upload once all transactions are written
+        next = (next + 1) % txns.len();
+        next != 0
+    }
+}
+
+fn upload_fw_to_mailbox(mailbox: &mut MailboxInternal, cmd: CommandId, firmware_buffer: &[u8]) {
     // Write the cmd to mailbox.
-    let _ = mailbox.write_cmd(FW_LOAD_CMD_OPCODE);
+    let _ = mailbox.write_cmd(cmd.into());
 
     // Write dlen.
     let _ = mailbox.write_dlen(firmware_buffer.len() as u32).is_ok();
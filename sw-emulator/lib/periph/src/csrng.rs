@@ -0,0 +1,401 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+    csrng.rs
+
+Abstract:
+
+    File contains CSRNG and Entropy Source peripheral implementations.
+
+--*/
+
+use caliptra_emu_bus::{Bus, BusError};
+use caliptra_emu_types::{RvAddr, RvData, RvSize};
+use std::collections::VecDeque;
+
+/// Seed used when the CSRNG is seeded from the entropy source. The emulator
+/// does not model a physical noise source.
+const EMULATED_ENTROPY: [u32; 12] = [
+    0x33f6_3b65,
+    0xf57a_d687,
+    0x6569_3560,
+    0xe743_cc50,
+    0x1051_8e4b,
+    0xf4ec_beba,
+    0x71dc_56aa,
+    0xa08b_3943,
+    0x1173_1d9d,
+    0xf763_fc5d,
+    0x27e4_ed3e,
+    0x4b7d_e947,
+];
+
+/// Multi-bit boolean values
+const MULTI_BIT_TRUE: u32 = 6;
+const MULTI_BIT_FALSE: u32 = 9;
+
+/// Command header fields
+const ACMD_INSTANTIATE: u32 = 1;
+const ACMD_RESEED: u32 = 2;
+const ACMD_GENERATE: u32 = 3;
+const ACMD_UPDATE: u32 = 4;
+const ACMD_UNINSTANTIATE: u32 = 5;
+
+/// Words generated per 128-bit block
+const WORDS_PER_GENERATE_BLOCK: usize = 4;
+
+/// Command being received through CMD_REQ
+struct PendingCmd {
+    header: u32,
+    words: Vec<u32>,
+}
+
+impl PendingCmd {
+    fn acmd(&self) -> u32 {
+        self.header & 0xf
+    }
+
+    fn clen(&self) -> usize {
+        ((self.header >> 4) & 0xf) as usize
+    }
+
+    fn flag0(&self) -> u32 {
+        (self.header >> 8) & 0xf
+    }
+
+    fn glen(&self) -> usize {
+        ((self.header >> 12) & 0x1fff) as usize
+    }
+}
+
+/// Cryptographically Secure Random Number Generator
+///
+/// The output is a deterministic function of the seed. It follows the CSRNG
+/// command interface but is not a NIST SP 800-90A DRBG.
+pub struct Csrng {
+    ctrl: u32,
+    state: Option<u64>,
+    cmd: Option<PendingCmd>,
+    genbits: VecDeque<u32>,
+}
+
+impl Csrng {
+    /// Control Register
+    const ADDR_CTRL: RvAddr = 0x0000_0014;
+
+    /// Command Request Register
+    const ADDR_CMD_REQ: RvAddr = 0x0000_0018;
+
+    /// Software Command Status Register
+    const ADDR_SW_CMD_STS: RvAddr = 0x0000_001c;
+
+    /// Generated Bits Valid Register
+    const ADDR_GENBITS_VLD: RvAddr = 0x0000_0020;
+
+    /// Generated Bits Register
+    const ADDR_GENBITS: RvAddr = 0x0000_0024;
+
+    pub fn new() -> Self {
+        Self {
+            // enable, sw_app_enable and read_int_state are all false
+            ctrl: 0x999,
+            state: None,
+            cmd: None,
+            genbits: VecDeque::new(),
+        }
+    }
+
+    /// Memory map size.
+    pub fn mmap_size(&self) -> RvAddr {
+        4096
+    }
+
+    fn write_cmd_req(&mut self, val: u32) {
+        let mut cmd = match self.cmd.take() {
+            Some(mut cmd) => {
+                cmd.words.push(val);
+                cmd
+            }
+            None => PendingCmd {
+                header: val,
+                words: vec![],
+            },
+        };
+
+        if cmd.words.len() < cmd.clen() {
+            self.cmd = Some(cmd);
+            return;
+        }
+
+        match cmd.acmd() {
+            ACMD_INSTANTIATE | ACMD_RESEED => {
+                let seed = if cmd.flag0() == MULTI_BIT_TRUE {
+                    &cmd.words[..]
+                } else {
+                    &EMULATED_ENTROPY[..]
+                };
+                self.state = Some(Self::absorb(0, seed));
+            }
+            ACMD_GENERATE => {
+                if let Some(state) = self.state.as_mut() {
+                    for _ in 0..cmd.glen() * WORDS_PER_GENERATE_BLOCK {
+                        self.genbits.push_back(Self::next(state));
+                    }
+                }
+            }
+            ACMD_UPDATE => {
+                if let Some(state) = self.state {
+                    self.state = Some(Self::absorb(state, &cmd.words));
+                }
+            }
+            ACMD_UNINSTANTIATE => {
+                self.state = None;
+                self.genbits.clear();
+            }
+            _ => {}
+        }
+        cmd.words.clear();
+    }
+
+    fn absorb(mut state: u64, words: &[u32]) -> u64 {
+        for word in words {
+            state ^= u64::from(*word);
+            Self::next(&mut state);
+        }
+        state
+    }
+
+    /// SplitMix64 step
+    fn next(state: &mut u64) -> u32 {
+        *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = *state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        (z ^ (z >> 31)) as u32
+    }
+}
+
+impl Default for Csrng {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Bus for Csrng {
+    /// Read data of specified size from given address
+    ///
+    /// # Arguments
+    ///
+    /// * `size` - Size of the read
+    /// * `addr` - Address to read from
+    ///
+    /// # Error
+    ///
+    /// * `RvException` - Exception with cause `RvExceptionCause::LoadAccessFault`
+    ///                   or `RvExceptionCause::LoadAddrMisaligned`
+    fn read(&mut self, size: RvSize, addr: RvAddr) -> Result<RvData, BusError> {
+        match (size, addr) {
+            (RvSize::Word, Csrng::ADDR_CTRL) => Ok(self.ctrl),
+            // Commands complete immediately and never fail
+            (RvSize::Word, Csrng::ADDR_SW_CMD_STS) => Ok(1),
+            (RvSize::Word, Csrng::ADDR_GENBITS_VLD) => Ok(!self.genbits.is_empty() as RvData),
+            (RvSize::Word, Csrng::ADDR_GENBITS) => Ok(self.genbits.pop_front().unwrap_or(0)),
+            (RvSize::Word, _) => Ok(0),
+            _ => Err(BusError::LoadAccessFault),
+        }
+    }
+
+    /// Write data of specified size to given address
+    ///
+    /// # Arguments
+    ///
+    /// * `size` - Size of the write
+    /// * `addr` - Address to write
+    /// * `val` - Data to write
+    ///
+    /// # Error
+    ///
+    /// * `RvException` - Exception with cause `RvExceptionCause::StoreAccessFault`
+    ///                   or `RvExceptionCause::StoreAddrMisaligned`
+    fn write(&mut self, size: RvSize, addr: RvAddr, val: RvData) -> Result<(), BusError> {
+        match (size, addr) {
+            (RvSize::Word, Csrng::ADDR_CTRL) => self.ctrl = val,
+            (RvSize::Word, Csrng::ADDR_CMD_REQ) => self.write_cmd_req(val),
+            (RvSize::Word, _) => {}
+            _ => Err(BusError::StoreAccessFault)?,
+        }
+        Ok(())
+    }
+}
+
+/// Entropy Source
+///
+/// Only the registers needed to enable the module are modeled. Health tests
+/// never fail.
+pub struct EntropySrc {
+    module_enable: u32,
+    conf: u32,
+}
+
+impl EntropySrc {
+    /// Module Enable Register
+    const ADDR_MODULE_ENABLE: RvAddr = 0x0000_0020;
+
+    /// Configuration Register
+    const ADDR_CONF: RvAddr = 0x0000_0024;
+
+    /// Debug Status Register
+    const ADDR_DEBUG_STATUS: RvAddr = 0x0000_00d0;
+
+    /// Main state machine boot done bit in the debug status register
+    const MAIN_SM_BOOT_DONE: u32 = 1 << 17;
+
+    pub fn new() -> Self {
+        Self {
+            module_enable: MULTI_BIT_FALSE,
+            conf: 0,
+        }
+    }
+
+    /// Memory map size.
+    pub fn mmap_size(&self) -> RvAddr {
+        4096
+    }
+}
+
+impl Default for EntropySrc {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Bus for EntropySrc {
+    /// Read data of specified size from given address
+    ///
+    /// # Arguments
+    ///
+    /// * `size` - Size of the read
+    /// * `addr` - Address to read from
+    ///
+    /// # Error
+    ///
+    /// * `RvException` - Exception with cause `RvExceptionCause::LoadAccessFault`
+    ///                   or `RvExceptionCause::LoadAddrMisaligned`
+    fn read(&mut self, size: RvSize, addr: RvAddr) -> Result<RvData, BusError> {
+        match (size, addr) {
+            (RvSize::Word, EntropySrc::ADDR_MODULE_ENABLE) => Ok(self.module_enable),
+            (RvSize::Word, EntropySrc::ADDR_CONF) => Ok(self.conf),
+            (RvSize::Word, EntropySrc::ADDR_DEBUG_STATUS) => {
+                Ok(if self.module_enable == MULTI_BIT_TRUE {
+                    EntropySrc::MAIN_SM_BOOT_DONE
+                } else {
+                    0
+                })
+            }
+            (RvSize::Word, _) => Ok(0),
+            _ => Err(BusError::LoadAccessFault),
+        }
+    }
+
+    /// Write data of specified size to given address
+    ///
+    /// # Arguments
+    ///
+    /// * `size` - Size of the write
+    /// * `addr` - Address to write
+    /// * `val` - Data to write
+    ///
+    /// # Error
+    ///
+    /// * `RvException` - Exception with cause `RvExceptionCause::StoreAccessFault`
+    ///                   or `RvExceptionCause::StoreAddrMisaligned`
+    fn write(&mut self, size: RvSize, addr: RvAddr, val: RvData) -> Result<(), BusError> {
+        match (size, addr) {
+            (RvSize::Word, EntropySrc::ADDR_MODULE_ENABLE) => self.module_enable = val,
+            (RvSize::Word, EntropySrc::ADDR_CONF) => self.conf = val,
+            (RvSize::Word, _) => {}
+            _ => Err(BusError::StoreAccessFault)?,
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cmd(csrng: &mut Csrng, header: u32, words: &[u32]) {
+        csrng
+            .write(RvSize::Word, Csrng::ADDR_CMD_REQ, header)
+            .unwrap();
+        for word in words {
+            csrng
+                .write(RvSize::Word, Csrng::ADDR_CMD_REQ, *word)
+                .unwrap();
+        }
+    }
+
+    fn generate(csrng: &mut Csrng, blocks: u32) -> Vec<u32> {
+        cmd(csrng, (blocks << 12) | ACMD_GENERATE, &[]);
+        let mut words = vec![];
+        while csrng.read(RvSize::Word, Csrng::ADDR_GENBITS_VLD).unwrap() != 0 {
+            words.push(csrng.read(RvSize::Word, Csrng::ADDR_GENBITS).unwrap());
+        }
+        words
+    }
+
+    #[test]
+    fn test_generate() {
+        let mut csrng = Csrng::new();
+
+        // Nothing is generated before instantiation
+        assert!(generate(&mut csrng, 1).is_empty());
+
+        cmd(&mut csrng, ACMD_INSTANTIATE, &[]);
+        let first = generate(&mut csrng, 2);
+        assert_eq!(first.len(), 8);
+        assert_ne!(first, generate(&mut csrng, 2));
+
+        cmd(&mut csrng, ACMD_UNINSTANTIATE, &[]);
+        assert!(generate(&mut csrng, 1).is_empty());
+    }
+
+    #[test]
+    fn test_constant_seed() {
+        let mut csrng = Csrng::new();
+        let header = (MULTI_BIT_TRUE << 8) | (2 << 4) | ACMD_INSTANTIATE;
+
+        cmd(&mut csrng, header, &[1, 2]);
+        let first = generate(&mut csrng, 1);
+
+        cmd(&mut csrng, header, &[1, 2]);
+        assert_eq!(first, generate(&mut csrng, 1));
+
+        cmd(&mut csrng, header, &[1, 3]);
+        assert_ne!(first, generate(&mut csrng, 1));
+    }
+
+    #[test]
+    fn test_entropy_src_boot_done() {
+        let mut entropy_src = EntropySrc::new();
+        assert_eq!(
+            entropy_src
+                .read(RvSize::Word, EntropySrc::ADDR_DEBUG_STATUS)
+                .unwrap(),
+            0
+        );
+
+        entropy_src
+            .write(RvSize::Word, EntropySrc::ADDR_MODULE_ENABLE, MULTI_BIT_TRUE)
+            .unwrap();
+        assert_eq!(
+            entropy_src
+                .read(RvSize::Word, EntropySrc::ADDR_DEBUG_STATUS)
+                .unwrap(),
+            EntropySrc::MAIN_SM_BOOT_DONE
+        );
+    }
+}
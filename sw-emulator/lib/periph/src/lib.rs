@@ -15,6 +15,7 @@ Abstract:
 extern crate arrayref;
 
 mod asym_ecc384;
+mod csrng;
 mod doe;
 mod emu_ctrl;
 mod hash_sha256;
@@ -30,6 +31,7 @@ mod soc_reg;
 mod uart;
 
 pub use asym_ecc384::AsymEcc384;
+pub use csrng::{Csrng, EntropySrc};
 pub use doe::Doe;
 pub use emu_ctrl::EmuCtrl;
 pub use hash_sha256::HashSha256;
@@ -40,8 +42,8 @@ pub use key_vault::KeyUsage;
 pub use key_vault::KeyVault;
 pub use mailbox::{MailboxExternal, MailboxInternal, MailboxRam};
 pub use root_bus::{
-    ActionCb, CaliptraRootBus, CaliptraRootBusArgs, ReadyForFwCb, SocToCaliptraBus, TbServicesCb,
-    UploadUpdateFwCb,
+    ActionCb, CaliptraRootBus, CaliptraRootBusArgs, FwUploadFn, ReadyForFwCb, SocToCaliptraBus,
+    TbServicesCb, UploadUpdateFwCb,
 };
 pub use sha512_acc::Sha512Accelerator;
 pub use soc_reg::SocRegistersInternal;
@@ -13,9 +13,9 @@ Abstract:
 --*/
 
 use crate::{
-    iccm::Iccm, soc_reg::SocRegistersExternal, AsymEcc384, Doe, EmuCtrl, HashSha256, HashSha512,
-    HmacSha384, KeyVault, MailboxExternal, MailboxInternal, MailboxRam, Sha512Accelerator,
-    SocRegistersInternal, Uart,
+    iccm::Iccm, soc_reg::SocRegistersExternal, AsymEcc384, Csrng, Doe, EmuCtrl, EntropySrc,
+    HashSha256, HashSha512, HmacSha384, KeyVault, MailboxExternal, MailboxInternal, MailboxRam,
+    Sha512Accelerator, SocRegistersInternal, Uart,
 };
 use caliptra_emu_bus::{Clock, Ram, Rom};
 use caliptra_emu_derive::Bus;
@@ -49,7 +49,12 @@ impl From<Box<dyn FnMut(u8) + 'static>> for TbServicesCb {
     }
 }
 
-type ReadyForFwCbSchedFn<'a> = dyn FnOnce(u64, Box<dyn FnOnce(&mut MailboxInternal)>) + 'a;
+/// Writes a firmware load transaction to the locked mailbox. Returns true if
+/// it must be called again, with the mailbox locked, once the microcontroller
+/// completes the transaction successfully.
+pub type FwUploadFn = Box<dyn FnMut(&mut MailboxInternal) -> bool>;
+
+type ReadyForFwCbSchedFn<'a> = dyn FnOnce(u64, FwUploadFn) + 'a;
 pub struct ReadyForFwCbArgs<'a> {
     pub mailbox: &'a mut MailboxInternal,
     pub(crate) sched_fn: Box<ReadyForFwCbSchedFn<'a>>,
@@ -59,6 +64,22 @@ impl<'a> ReadyForFwCbArgs<'a> {
         self,
         ticks_from_now: u64,
         cb: impl FnOnce(&mut MailboxInternal) + 'static,
+    ) {
+        let mut cb = Some(cb);
+        self.schedule_chunks_later(ticks_from_now, move |mailbox| {
+            if let Some(cb) = cb.take() {
+                cb(mailbox);
+            }
+            false
+        });
+    }
+
+    /// Schedule a firmware upload made of several mailbox transactions, see
+    /// [`FwUploadFn`].
+    pub fn schedule_chunks_later(
+        self,
+        ticks_from_now: u64,
+        cb: impl FnMut(&mut MailboxInternal) -> bool + 'static,
     ) {
         (self.sched_fn)(ticks_from_now, Box::new(cb));
     }
@@ -92,19 +113,20 @@ impl From<Box<dyn FnMut(ReadyForFwCbArgs) + 'static>> for ReadyForFwCb {
     }
 }
 
-type UploadUpdateFwFn = Box<dyn FnMut(&mut MailboxInternal)>;
-pub struct UploadUpdateFwCb(pub UploadUpdateFwFn);
+/// Uploads the update firmware to the mailbox on update reset, see
+/// [`FwUploadFn`]. The mailbox is not locked for the first transaction.
+pub struct UploadUpdateFwCb(pub FwUploadFn);
 impl UploadUpdateFwCb {
-    pub fn new(f: impl FnMut(&mut MailboxInternal) + 'static) -> Self {
+    pub fn new(f: impl FnMut(&mut MailboxInternal) -> bool + 'static) -> Self {
         Self(Box::new(f))
     }
-    pub(crate) fn take(&mut self) -> UploadUpdateFwFn {
+    pub(crate) fn take(&mut self) -> FwUploadFn {
         std::mem::take(self).0
     }
 }
 impl Default for UploadUpdateFwCb {
     fn default() -> Self {
-        Self(Box::new(|_| {}))
+        Self(Box::new(|_| false))
     }
 }
 impl std::fmt::Debug for UploadUpdateFwCb {
@@ -114,8 +136,8 @@ impl std::fmt::Debug for UploadUpdateFwCb {
             .finish()
     }
 }
-impl From<Box<dyn FnMut(&mut MailboxInternal) + 'static>> for UploadUpdateFwCb {
-    fn from(value: Box<dyn FnMut(&mut MailboxInternal)>) -> Self {
+impl From<FwUploadFn> for UploadUpdateFwCb {
+    fn from(value: FwUploadFn) -> Self {
         Self(value)
     }
 }
@@ -192,6 +214,12 @@ pub struct CaliptraRootBus {
     #[peripheral(offset = 0x2000_1000, mask = 0x0000_0fff)]
     pub uart: Uart,
 
+    #[peripheral(offset = 0x2000_2000, mask = 0x0000_0fff)]
+    pub csrng: Csrng,
+
+    #[peripheral(offset = 0x2000_3000, mask = 0x0000_0fff)]
+    pub entropy_src: EntropySrc,
+
     #[peripheral(offset = 0x2000_f000, mask = 0x0000_0fff)]
     pub ctrl: EmuCtrl,
 
@@ -235,6 +263,8 @@ impl CaliptraRootBus {
             iccm,
             dccm: Ram::new(vec![0; Self::DCCM_SIZE]),
             uart: Uart::new(),
+            csrng: Csrng::new(),
+            entropy_src: EntropySrc::new(),
             ctrl: EmuCtrl::new(),
             soc_reg,
             mailbox_sram: mailbox_ram.clone(),
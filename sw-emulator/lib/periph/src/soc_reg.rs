@@ -13,7 +13,7 @@ Abstract:
 --*/
 
 use crate::helpers::{bytes_from_words_le, words_from_bytes_le};
-use crate::root_bus::ReadyForFwCbArgs;
+use crate::root_bus::{FwUploadFn, ReadyForFwCbArgs};
 use crate::{CaliptraRootBusArgs, Iccm, MailboxInternal};
 use caliptra_emu_bus::BusError::{LoadAccessFault, StoreAccessFault};
 use caliptra_emu_bus::{
@@ -28,10 +28,9 @@ use tock_registers::interfaces::{Readable, Writeable};
 use tock_registers::register_bitfields;
 use tock_registers::registers::InMemoryRegister;
 
-// Second parameter is schedule(ticks_from_now: u64, cb: Box<dyn FnOnce(&mut
-// Mailbox)>), which is called to schedule firmware writing in the future
+// Second parameter is schedule(ticks_from_now: u64, cb: FwUploadFn), which is
+// called to schedule firmware writing in the future
 type ReadyForFwCallback = Box<dyn FnMut(ReadyForFwCbArgs)>;
-type UploadUpdateFwCallback = Box<dyn FnMut(&mut MailboxInternal)>;
 type BootFsmGoCallback = Box<dyn FnMut()>;
 
 mod constants {
@@ -83,6 +82,7 @@ mod constants {
     pub const FUSE_IDEVID_MANUF_HSM_ID_START: u32 = 0x32c;
     pub const FUSE_IDEVID_MANUF_HSM_ID_SIZE: usize = 16;
     pub const FUSE_LIFE_CYCLE_START: u32 = 0x33c;
+    pub const FUSE_LMS_REVOCATION_START: u32 = 0x340;
     pub const INTERNAL_OBF_KEY_SIZE: usize = 32;
     pub const INTERNAL_ICCM_LOCK_START: u32 = 0x620;
     pub const INTERNAL_FW_UPDATE_RESET_START: u32 = 0x624;
@@ -413,6 +413,9 @@ struct SocRegistersImpl {
     #[register(offset = 0x033c)]
     fuse_life_cycle: u32,
 
+    #[register(offset = 0x0340)]
+    fuse_lms_revocation: u32,
+
     /// INTERNAL_OBF_KEY Register
     internal_obf_key: ReadOnlyMemory<INTERNAL_OBF_KEY_SIZE>,
 
@@ -443,12 +446,18 @@ struct SocRegistersImpl {
 
     /// Firmware Write Complete action
     op_fw_write_complete_action: Option<ActionHandle>,
-    #[allow(clippy::type_complexity)]
-    op_fw_write_complete_cb: Option<Box<dyn FnOnce(&mut MailboxInternal)>>,
+    op_fw_write_complete_cb: Option<FwUploadFn>,
 
     /// Firmware Read Complete action
     op_fw_read_complete_action: Option<ActionHandle>,
 
+    /// Upload of the next firmware load transaction once the current one
+    /// completes
+    op_fw_next_chunk_cb: Option<FwUploadFn>,
+
+    /// The update firmware has more firmware load transactions to upload
+    op_update_fw_chunk_pending: bool,
+
     /// Reset Trigger action
     op_reset_trigger_action: Option<ActionHandle>,
 
@@ -457,7 +466,7 @@ struct SocRegistersImpl {
 
     ready_for_fw_cb: ReadyForFwCallback,
 
-    upload_update_fw: UploadUpdateFwCallback,
+    upload_update_fw: FwUploadFn,
 
     bootfsm_go_cb: BootFsmGoCallback,
 
@@ -525,6 +534,7 @@ impl SocRegistersImpl {
             fuse_idevid_cert_attr: Default::default(),
             fuse_idevid_manuf_hsm_id: Default::default(),
             fuse_life_cycle: Default::default(),
+            fuse_lms_revocation: Default::default(),
             internal_obf_key: ReadOnlyMemory::new_with_data(Self::DOE_KEY),
             internal_iccm_lock: ReadWriteRegister::new(0),
             internal_fw_update_reset: ReadWriteRegister::new(0),
@@ -536,6 +546,8 @@ impl SocRegistersImpl {
             op_fw_write_complete_action: None,
             op_fw_write_complete_cb: None,
             op_fw_read_complete_action: None,
+            op_fw_next_chunk_cb: None,
+            op_update_fw_chunk_pending: false,
             op_reset_trigger_action: None,
             tb_services_cb: args.tb_services_cb.take(),
             ready_for_fw_cb: args.ready_for_fw_cb.take(),
@@ -599,10 +611,9 @@ impl SocRegistersImpl {
             Err(BusError::StoreAccessFault)?
         }
 
-        // [TODO] Enable warm reset after design agreement.
-        // // Schedule warm reset timer action.
-        // self.op_reset_trigger_action =
-        //     Some(self.timer.schedule_reset_in(0, TimerActionType::WarmReset));
+        // Schedule warm reset timer action.
+        self.op_reset_trigger_action =
+            Some(self.timer.schedule_action_in(0, TimerAction::WarmReset));
 
         Ok(())
     }
@@ -631,7 +642,7 @@ impl SocRegistersImpl {
             let op_fw_write_complete_action = &mut self.op_fw_write_complete_action;
             let op_fw_write_complete_cb = &mut self.op_fw_write_complete_cb;
             let timer = &self.timer;
-            let sched_fn = move |ticks_from_now: u64, cb: Box<dyn FnOnce(&mut MailboxInternal)>| {
+            let sched_fn = move |ticks_from_now: u64, cb: FwUploadFn| {
                 *op_fw_write_complete_action = Some(timer.schedule_poll_in(ticks_from_now));
                 *op_fw_write_complete_cb = Some(cb);
             };
@@ -705,8 +716,10 @@ impl SocRegistersImpl {
     /// Called by Bus::poll() to indicate that time has passed
     fn bus_poll(&mut self) {
         if self.timer.fired(&mut self.op_fw_write_complete_action) {
-            if let Some(cb) = self.op_fw_write_complete_cb.take() {
-                (cb)(&mut self.mailbox);
+            if let Some(mut cb) = self.op_fw_write_complete_cb.take() {
+                if (cb)(&mut self.mailbox) {
+                    self.op_fw_next_chunk_cb = Some(cb);
+                }
                 // Schedule a future call to poll() to check on the fw read operation completion.
                 self.op_fw_read_complete_action =
                     Some(self.timer.schedule_poll_in(Self::FW_READ_TICKS));
@@ -716,8 +729,18 @@ impl SocRegistersImpl {
         if self.timer.fired(&mut self.op_fw_read_complete_action) {
             // Receiver sets status as CMD_COMPLETE after reading the mailbox data.
             if !self.mailbox.is_status_cmd_busy() {
+                let complete = self.mailbox.is_status_cmd_complete();
+
                 // Reset the execute bit
                 self.mailbox.write_execute(0).unwrap();
+
+                // Stop uploading the remaining chunks if the receiver failed
+                if complete {
+                    self.upload_next_fw_chunk();
+                } else {
+                    self.op_fw_next_chunk_cb = None;
+                    self.op_update_fw_chunk_pending = false;
+                }
             } else {
                 self.op_fw_read_complete_action =
                     Some(self.timer.schedule_poll_in(Self::FW_READ_TICKS));
@@ -725,6 +748,24 @@ impl SocRegistersImpl {
         }
     }
 
+    /// Upload the next transaction of a firmware image loaded in chunks
+    fn upload_next_fw_chunk(&mut self) {
+        if let Some(mut cb) = self.op_fw_next_chunk_cb.take() {
+            while !self.mailbox.try_acquire_lock() {}
+            if (cb)(&mut self.mailbox) {
+                self.op_fw_next_chunk_cb = Some(cb);
+            }
+        } else if self.op_update_fw_chunk_pending {
+            while !self.mailbox.try_acquire_lock() {}
+            self.op_update_fw_chunk_pending = (self.upload_update_fw)(&mut self.mailbox);
+        } else {
+            return;
+        }
+
+        // Schedule a future call to poll() to check on the fw read operation completion.
+        self.op_fw_read_complete_action = Some(self.timer.schedule_poll_in(Self::FW_READ_TICKS));
+    }
+
     /// Called by Bus::warm_reset() to indicate a warm reset
     fn bus_warm_reset(&mut self) {
         // Set the reaset reason to 'WARM_RESET'
@@ -738,7 +779,12 @@ impl SocRegistersImpl {
     /// Called by Bus::update_reset() to indicate an update reset
     fn bus_update_reset(&mut self) {
         // Upload the update firmware in the mailbox.
-        (self.upload_update_fw)(&mut self.mailbox);
+        self.op_update_fw_chunk_pending = (self.upload_update_fw)(&mut self.mailbox);
+        if self.op_update_fw_chunk_pending {
+            // Upload the remaining chunks once ROM has read the manifest
+            self.op_fw_read_complete_action =
+                Some(self.timer.schedule_poll_in(Self::FW_READ_TICKS));
+        }
 
         // Set the reaset reason to 'FW_UPD_RESET'
         self.cptra_reset_reason
@@ -753,6 +799,7 @@ impl SocRegistersImpl {
 mod tests {
     use super::*;
     use crate::{root_bus::TbServicesCb, MailboxRam};
+    use caliptra_api::CommandId;
     use std::{
         fs::File,
         io::{Read, Write},
@@ -793,6 +840,10 @@ mod tests {
         path: &mut PathBuf,
         soc_reg: &mut SocRegistersInternal,
     ) {
+        assert_eq!(
+            mailbox.read_cmd().unwrap(),
+            u32::from(CommandId::GetIdevCsr)
+        );
         download_to_file(mailbox, path, "caliptra_idevid_csr.der");
 
         soc_reg
@@ -868,7 +919,7 @@ mod tests {
         //
 
         // Add csr data to the mailbox.
-        send_data_to_mailbox(&mut mailbox, 0xDEADBEEF, &data);
+        send_data_to_mailbox(&mut mailbox, CommandId::GetIdevCsr.into(), &data);
         mailbox.set_status_data_ready().unwrap();
         mailbox.write_execute(1).unwrap();
 
@@ -983,4 +1034,30 @@ mod tests {
 
         assert_eq!(&*output.borrow(), &vec![b'h', b'i', 0xff]);
     }
+
+    #[test]
+    fn test_warm_reset_trigger() {
+        let clock = Clock::new();
+        let mailbox_ram = MailboxRam::new();
+        let mailbox = MailboxInternal::new(mailbox_ram);
+        let mut soc_reg: SocRegistersInternal = SocRegistersInternal::new(
+            &clock,
+            mailbox,
+            Iccm::new(&clock),
+            CaliptraRootBusArgs::default(),
+        );
+
+        soc_reg
+            .write(RvSize::Word, CPTRA_GENERIC_OUTPUT_WIRES_START + 4, 1)
+            .unwrap();
+
+        let actions = clock.increment_and_process_timer_actions(1, &mut soc_reg);
+        assert!(actions.contains(&TimerAction::WarmReset));
+        assert_eq!(
+            soc_reg
+                .read(RvSize::Word, CPTRA_RESET_REASON_START)
+                .unwrap(),
+            ResetReason::WARM_RESET::SET.value
+        );
+    }
 }
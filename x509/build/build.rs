@@ -16,6 +16,7 @@ Abstract:
 mod cert;
 mod code_gen;
 mod csr;
+mod der;
 mod tbs;
 mod x509;
 
@@ -33,6 +34,7 @@ fn main() {
     gen_local_devid_cert(out_dir);
     gen_fmc_alias_cert(out_dir);
     gen_rt_alias_cert(out_dir);
+    gen_dpe_leaf_cert(out_dir);
 }
 
 /// Generated Initial DeviceId Cert Signing request Template
@@ -70,14 +72,14 @@ fn gen_fmc_alias_cert(out_dir: &str) {
             FwidParam {
                 name: "TCB_INFO_FMC_TCI",
                 fwid: Fwid {
-                    hash_alg: asn1::oid!(/*sha384*/ 2, 16, 840, 1, 101, 3, 4, 2, 2),
+                    hash_alg: &[/*sha384*/ 2, 16, 840, 1, 101, 3, 4, 2, 2],
                     digest: &[0xCD; 48],
                 },
             },
             FwidParam {
                 name: "TCB_INFO_OWNER_PK_HASH",
                 fwid: Fwid {
-                    hash_alg: asn1::oid!(/*sha384*/ 2, 16, 840, 1, 101, 3, 4, 2, 2),
+                    hash_alg: &[/*sha384*/ 2, 16, 840, 1, 101, 3, 4, 2, 2],
                     digest: &[0xEF; 48],
                 },
             },
@@ -100,10 +102,42 @@ fn gen_rt_alias_cert(out_dir: &str) {
         .add_rt_dice_tcb_info_ext(&[FwidParam {
             name: "TCB_INFO_RT_TCI",
             fwid: Fwid {
-                hash_alg: asn1::oid!(/*sha384*/ 2, 16, 840, 1, 101, 3, 4, 2, 2),
+                hash_alg: &[/*sha384*/ 2, 16, 840, 1, 101, 3, 4, 2, 2],
                 digest: &[0xCD; 48],
             },
         }]);
     let template = bldr.tbs_template("Caliptra Rt Alias", "Caliptra FMC");
     CodeGen::gen_code("RtAliasCertTbs", template, out_dir);
 }
+
+fn gen_dpe_leaf_cert(out_dir: &str) {
+    let mut usage = KeyUsage::default();
+    // Add DigitalSignature to allow signing with the DPE leaf key
+    usage.set_digital_signature(true);
+    let bldr = cert::CertTemplateBuilder::<EcdsaSha384Algo>::new()
+        // Basic Constraints : CA = false
+        .add_basic_constraints_ext(false, 0)
+        .add_key_usage_ext(usage)
+        .add_ueid_ext(&[0xFF; 8])
+        .add_dpe_dice_tcb_info_ext(
+            &[0xC0, 0xC1, 0xC2, 0xC3],
+            &[
+                FwidParam {
+                    name: "TCB_INFO_DPE_JOURNEY",
+                    fwid: Fwid {
+                        hash_alg: &[/*sha384*/ 2, 16, 840, 1, 101, 3, 4, 2, 2],
+                        digest: &[0xCD; 48],
+                    },
+                },
+                FwidParam {
+                    name: "TCB_INFO_DPE_CURRENT",
+                    fwid: Fwid {
+                        hash_alg: &[/*sha384*/ 2, 16, 840, 1, 101, 3, 4, 2, 2],
+                        digest: &[0xEF; 48],
+                    },
+                },
+            ],
+        );
+    let template = bldr.tbs_template("Caliptra DPE Leaf", "Caliptra Rt Alias");
+    CodeGen::gen_code("DpeLeafCertTbs", template, out_dir);
+}
@@ -13,12 +13,9 @@ Abstract:
 
 --*/
 
+use crate::der;
 use crate::tbs::{TbsParam, TbsTemplate};
 use crate::x509::{self, AsymKey, FwidParam, KeyUsage, SigningAlgorithm};
-use openssl::asn1::Asn1Time;
-use openssl::bn::BigNum;
-use openssl::stack::Stack;
-use openssl::x509::{X509Builder, X509Extension, X509NameBuilder};
 
 /// Certificate Template Param
 struct CertTemplateParam {
@@ -29,8 +26,7 @@ struct CertTemplateParam {
 /// Certificate Template Builder
 pub struct CertTemplateBuilder<Algo: SigningAlgorithm> {
     algo: Algo,
-    builder: X509Builder,
-    exts: Stack<X509Extension>,
+    exts: Vec<Vec<u8>>,
     params: Vec<CertTemplateParam>,
 }
 
@@ -39,8 +35,7 @@ impl<Algo: SigningAlgorithm> CertTemplateBuilder<Algo> {
     pub fn new() -> Self {
         Self {
             algo: Algo::default(),
-            builder: X509Builder::new().unwrap(),
-            exts: Stack::new().unwrap(),
+            exts: vec![],
             params: vec![],
         }
     }
@@ -53,8 +48,7 @@ impl<Algo: SigningAlgorithm> CertTemplateBuilder<Algo> {
     /// * `path_len` - Certificate path length
     pub fn add_basic_constraints_ext(mut self, ca: bool, path_len: u32) -> Self {
         self.exts
-            .push(x509::make_basic_constraints_ext(ca, path_len))
-            .unwrap();
+            .push(x509::make_basic_constraints_ext(ca, path_len));
         self
     }
 
@@ -64,7 +58,7 @@ impl<Algo: SigningAlgorithm> CertTemplateBuilder<Algo> {
     ///
     /// * `usage` - Key Usage
     pub fn add_key_usage_ext(mut self, usage: KeyUsage) -> Self {
-        self.exts.push(x509::make_key_usage_ext(usage)).unwrap();
+        self.exts.push(x509::make_key_usage_ext(usage));
         self
     }
 
@@ -74,7 +68,7 @@ impl<Algo: SigningAlgorithm> CertTemplateBuilder<Algo> {
     ///
     /// * `ueid` - Unique Endpoint Identifier
     pub fn add_ueid_ext(mut self, ueid: &[u8]) -> Self {
-        self.exts.push(x509::make_tcg_ueid_ext(ueid)).unwrap();
+        self.exts.push(x509::make_tcg_ueid_ext(ueid));
 
         let param = CertTemplateParam {
             tbs_param: TbsParam::new("UEID", 0, ueid.len()),
@@ -91,8 +85,7 @@ impl<Algo: SigningAlgorithm> CertTemplateBuilder<Algo> {
         let min_svn: u8 = 0xC5;
 
         self.exts
-            .push(x509::make_fmc_dice_tcb_info_ext(flags, svn, min_svn, fwids))
-            .unwrap();
+            .push(x509::make_fmc_dice_tcb_info_ext(flags, svn, min_svn, fwids));
 
         self.params.push(CertTemplateParam {
             tbs_param: TbsParam::new("tcb_info_flags", 0, std::mem::size_of_val(&flags)),
@@ -120,9 +113,26 @@ impl<Algo: SigningAlgorithm> CertTemplateBuilder<Algo> {
     }
 
     pub fn add_rt_dice_tcb_info_ext(mut self, fwids: &[FwidParam]) -> Self {
+        self.exts.push(x509::make_rt_dice_tcb_info_ext(fwids));
+
+        for fwid in fwids.iter() {
+            self.params.push(CertTemplateParam {
+                tbs_param: TbsParam::new(fwid.name, 0, fwid.fwid.digest.len()),
+                needle: fwid.fwid.digest.to_vec(),
+            });
+        }
+
+        self
+    }
+
+    pub fn add_dpe_dice_tcb_info_ext(mut self, tcb_type: &[u8], fwids: &[FwidParam]) -> Self {
         self.exts
-            .push(x509::make_rt_dice_tcb_info_ext(fwids))
-            .unwrap();
+            .push(x509::make_dpe_dice_tcb_info_ext(tcb_type, fwids));
+
+        self.params.push(CertTemplateParam {
+            tbs_param: TbsParam::new("TCB_INFO_TYPE", 0, tcb_type.len()),
+            needle: tcb_type.to_vec(),
+        });
 
         for fwid in fwids.iter() {
             self.params.push(CertTemplateParam {
@@ -140,11 +150,7 @@ impl<Algo: SigningAlgorithm> CertTemplateBuilder<Algo> {
     ///
     /// * `key_id` - Key Id
     fn add_subj_key_id_ext(&mut self, key_id: &[u8]) {
-        self.exts
-            .push(x509::make_subj_key_id_ext(
-                &self.builder.x509v3_context(None, None),
-            ))
-            .unwrap();
+        self.exts.push(x509::make_subj_key_id_ext(key_id));
 
         let param = CertTemplateParam {
             tbs_param: TbsParam::new("SUBJECT_KEY_ID", 0, key_id.len()),
@@ -159,7 +165,7 @@ impl<Algo: SigningAlgorithm> CertTemplateBuilder<Algo> {
     ///
     /// * `key_id` - Key Id
     fn add_auth_key_id_ext(&mut self, key_id: &[u8]) {
-        self.exts.push(x509::make_auth_key_id_ext(key_id)).unwrap();
+        self.exts.push(x509::make_auth_key_id_ext(key_id));
 
         let param = CertTemplateParam {
             tbs_param: TbsParam::new("AUTHORITY_KEY_ID", 0, key_id.len()),
@@ -174,13 +180,8 @@ impl<Algo: SigningAlgorithm> CertTemplateBuilder<Algo> {
         let subject_key = self.algo.gen_key();
         let issuer_key = self.algo.gen_key();
 
-        // Set Version
-        self.builder.set_version(2).unwrap();
-
         // Set the valid from time
         let not_before = "20230101000000Z";
-        let valid_from = Asn1Time::from_str(not_before).unwrap();
-        self.builder.set_not_before(&valid_from).unwrap();
         let param = CertTemplateParam {
             tbs_param: TbsParam::new("NOT_BEFORE", 0, not_before.len()),
             needle: not_before.as_bytes().to_vec(),
@@ -190,8 +191,6 @@ impl<Algo: SigningAlgorithm> CertTemplateBuilder<Algo> {
 
         // Set the valid to time
         let not_after = "99991231235959Z";
-        let valid_to = Asn1Time::from_str(not_after).unwrap();
-        self.builder.set_not_after(&valid_to).unwrap();
         let param = CertTemplateParam {
             tbs_param: TbsParam::new("NOT_AFTER", 0, not_after.len()),
             needle: not_after.as_bytes().to_vec(),
@@ -200,9 +199,6 @@ impl<Algo: SigningAlgorithm> CertTemplateBuilder<Algo> {
 
         // Set the serial number
         let serial_number_bytes = [0x7Fu8; 20];
-        let serial_number = BigNum::from_slice(&serial_number_bytes).unwrap();
-        let serial_number = serial_number.to_asn1_integer().unwrap();
-        self.builder.set_serial_number(&serial_number).unwrap();
         let param = CertTemplateParam {
             tbs_param: TbsParam::new("SERIAL_NUMBER", 0, serial_number_bytes.len()),
             needle: serial_number_bytes.to_vec(),
@@ -210,7 +206,6 @@ impl<Algo: SigningAlgorithm> CertTemplateBuilder<Algo> {
         self.params.push(param);
 
         // Set Subject Public Key
-        self.builder.set_pubkey(subject_key.priv_key()).unwrap();
         let param = CertTemplateParam {
             tbs_param: TbsParam::new("PUBLIC_KEY", 0, subject_key.pub_key().len()),
             needle: subject_key.pub_key().to_vec(),
@@ -218,13 +213,7 @@ impl<Algo: SigningAlgorithm> CertTemplateBuilder<Algo> {
         self.params.push(param);
 
         // Set the subject name
-        let mut subject_name = X509NameBuilder::new().unwrap();
-        subject_name.append_entry_by_text("CN", subject_cn).unwrap();
-        subject_name
-            .append_entry_by_text("serialNumber", &subject_key.hex_str())
-            .unwrap();
-        let subject_name = subject_name.build();
-        self.builder.set_subject_name(&subject_name).unwrap();
+        let subject_name = x509::make_name(subject_cn, &subject_key.hex_str());
         let param = CertTemplateParam {
             tbs_param: TbsParam::new("SUBJECT_SN", 0, subject_key.hex_str().len()),
             needle: subject_key.hex_str().into_bytes(),
@@ -232,13 +221,7 @@ impl<Algo: SigningAlgorithm> CertTemplateBuilder<Algo> {
         self.params.push(param);
 
         // Set the issuer name
-        let mut issuer_name = X509NameBuilder::new().unwrap();
-        issuer_name.append_entry_by_text("CN", issuer_cn).unwrap();
-        issuer_name
-            .append_entry_by_text("serialNumber", &issuer_key.hex_str())
-            .unwrap();
-        let issuer_name = issuer_name.build();
-        self.builder.set_issuer_name(&issuer_name).unwrap();
+        let issuer_name = x509::make_name(issuer_cn, &issuer_key.hex_str());
         let param = CertTemplateParam {
             tbs_param: TbsParam::new("ISSUER_SN", 0, issuer_key.hex_str().len()),
             needle: issuer_key.hex_str().into_bytes(),
@@ -251,24 +234,21 @@ impl<Algo: SigningAlgorithm> CertTemplateBuilder<Algo> {
         // Add Authority Key Identifier
         self.add_auth_key_id_ext(&issuer_key.sha1());
 
-        // Add the requested extensions
-        for ext in self.exts {
-            self.builder.append_extension(ext).unwrap();
-        }
-
-        // Sign the Certificate
-        self.builder
-            .sign(subject_key.priv_key(), self.algo.digest())
-            .unwrap();
-
-        // Generate the Certificate
-        let cert = self.builder.build();
-
-        // Serialize the Certificate to DER
-        let der = cert.to_der().unwrap();
-
-        // Retrieve the To be signed portion from the Certificate
-        let mut tbs = x509::get_tbs(der);
+        // Encode the To be signed portion of the Certificate
+        let mut tbs = der::seq(&[
+            // Version: v3
+            der::explicit(0, der::uint(&[2])),
+            der::uint(&serial_number_bytes),
+            self.algo.algo_id(),
+            issuer_name,
+            der::seq(&[
+                der::generalized_time(not_before),
+                der::generalized_time(not_after),
+            ]),
+            subject_name,
+            subject_key.pub_key_info(),
+            der::explicit(3, der::seq(&self.exts)),
+        ]);
 
         // Match long params first to ensure a subset is not sanitized by a short param.
         self.params
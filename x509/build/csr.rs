@@ -13,10 +13,12 @@ Abstract:
 
 --*/
 
+use crate::der;
 use crate::tbs::{TbsParam, TbsTemplate};
 use crate::x509::{self, AsymKey, KeyUsage, SigningAlgorithm};
-use openssl::stack::Stack;
-use openssl::x509::{X509Extension, X509NameBuilder, X509ReqBuilder};
+
+/// pkcs-9-at-extensionRequest
+const EXTENSION_REQUEST_OID: &[u32] = &[1, 2, 840, 113549, 1, 9, 14];
 
 /// CSR Template Parameter
 struct CsrTemplateParam {
@@ -27,8 +29,7 @@ struct CsrTemplateParam {
 /// CSR Template Builder
 pub struct CsrTemplateBuilder<Algo: SigningAlgorithm> {
     algo: Algo,
-    builder: X509ReqBuilder,
-    exts: Stack<X509Extension>,
+    exts: Vec<Vec<u8>>,
     params: Vec<CsrTemplateParam>,
 }
 
@@ -37,8 +38,7 @@ impl<Algo: SigningAlgorithm> CsrTemplateBuilder<Algo> {
     pub fn new() -> Self {
         Self {
             algo: Algo::default(),
-            builder: X509ReqBuilder::new().unwrap(),
-            exts: Stack::new().unwrap(),
+            exts: vec![],
             params: vec![],
         }
     }
@@ -51,8 +51,7 @@ impl<Algo: SigningAlgorithm> CsrTemplateBuilder<Algo> {
 
     pub fn add_basic_constraints_ext(mut self, ca: bool, path_len: u32) -> Self {
         self.exts
-            .push(x509::make_basic_constraints_ext(ca, path_len))
-            .unwrap();
+            .push(x509::make_basic_constraints_ext(ca, path_len));
         self
     }
 
@@ -62,7 +61,7 @@ impl<Algo: SigningAlgorithm> CsrTemplateBuilder<Algo> {
     ///
     /// * `usage` - Key Usage
     pub fn add_key_usage_ext(mut self, usage: KeyUsage) -> Self {
-        self.exts.push(x509::make_key_usage_ext(usage)).unwrap();
+        self.exts.push(x509::make_key_usage_ext(usage));
         self
     }
 
@@ -72,7 +71,7 @@ impl<Algo: SigningAlgorithm> CsrTemplateBuilder<Algo> {
     ///
     /// * `ueid` - Unique Endpoint Identifier
     pub fn add_ueid_ext(mut self, ueid: &[u8]) -> Self {
-        self.exts.push(x509::make_tcg_ueid_ext(ueid)).unwrap();
+        self.exts.push(x509::make_tcg_ueid_ext(ueid));
 
         let param = CsrTemplateParam {
             tbs_param: TbsParam::new("UEID", 0, ueid.len()),
@@ -88,11 +87,7 @@ impl<Algo: SigningAlgorithm> CsrTemplateBuilder<Algo> {
         // Generate key pair
         let key = self.algo.gen_key();
 
-        // Set Version
-        self.builder.set_version(0).unwrap();
-
         // Set Public Key
-        self.builder.set_pubkey(key.priv_key()).unwrap();
         let param = CsrTemplateParam {
             tbs_param: TbsParam::new("PUBLIC_KEY", 0, key.pub_key().len()),
             needle: key.pub_key().to_vec(),
@@ -100,35 +95,28 @@ impl<Algo: SigningAlgorithm> CsrTemplateBuilder<Algo> {
         self.params.push(param);
 
         // Set the subject name
-        let mut subject_name = X509NameBuilder::new().unwrap();
-        subject_name.append_entry_by_text("CN", subject_cn).unwrap();
-        subject_name
-            .append_entry_by_text("serialNumber", &key.hex_str())
-            .unwrap();
-        let subject_name = subject_name.build();
-        self.builder.set_subject_name(&subject_name).unwrap();
+        let subject_name = x509::make_name(subject_cn, &key.hex_str());
         let param = CsrTemplateParam {
             tbs_param: TbsParam::new("SUBJECT_SN", 0, key.hex_str().len()),
             needle: key.hex_str().into_bytes(),
         };
         self.params.push(param);
 
-        // Add the requested extensions
-        self.builder.add_extensions(&self.exts).unwrap();
-
-        // Sign the CSR
-        self.builder
-            .sign(key.priv_key(), self.algo.digest())
-            .unwrap();
-
-        // Generate the CSR
-        let csr = self.builder.build();
-
-        // Serialize the CSR to DER
-        let der = csr.to_der().unwrap();
-
-        // Retrieve the To be signed portion from the CSR
-        let mut tbs = x509::get_tbs(der);
+        // Encode the To be signed portion of the CSR
+        let mut tbs = der::seq(&[
+            // Version: v1
+            der::uint(&[0]),
+            subject_name,
+            key.pub_key_info(),
+            // Attributes: the requested extensions
+            der::implicit(
+                0,
+                der::set(&[der::seq(&[
+                    der::oid(EXTENSION_REQUEST_OID),
+                    der::set(&[der::seq(&self.exts)]),
+                ])]),
+            ),
+        ]);
 
         // Calculate the offset of parameters and sanitize the TBS section
         let params = self
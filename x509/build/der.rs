@@ -0,0 +1,155 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+    der.rs
+
+Abstract:
+
+    File contains a minimal DER encoder used to build the X509 templates.
+
+--*/
+
+/// DER Boolean Tag
+const BOOLEAN_TAG: u8 = 0x01;
+
+/// DER Integer Tag
+const INTEGER_TAG: u8 = 0x02;
+
+/// DER Bit String Tag
+const BIT_STRING_TAG: u8 = 0x03;
+
+/// DER Octet String Tag
+const OCTET_STRING_TAG: u8 = 0x04;
+
+/// DER Object Identifier Tag
+const OID_TAG: u8 = 0x06;
+
+/// DER UTF8 String Tag
+const UTF8_STRING_TAG: u8 = 0x0C;
+
+/// DER Printable String Tag
+const PRINTABLE_STRING_TAG: u8 = 0x13;
+
+/// DER Generalized Time Tag
+const GENERALIZED_TIME_TAG: u8 = 0x18;
+
+/// DER Sequence Tag
+const SEQUENCE_TAG: u8 = 0x30;
+
+/// DER Set Tag
+const SET_TAG: u8 = 0x31;
+
+/// DER Context Specific Class
+const CONTEXT_CLASS: u8 = 0x80;
+
+/// DER Constructed Flag
+const CONSTRUCTED: u8 = 0x20;
+
+/// Encode a Tag-Length-Value
+pub fn tlv(tag: u8, value: &[u8]) -> Vec<u8> {
+    let mut der = vec![tag];
+    match value.len() {
+        len @ 0..=0x7F => der.push(len as u8),
+        len @ 0x80..=0xFF => der.extend([0x81, len as u8]),
+        len @ 0x100..=0xFFFF => der.extend([0x82, (len >> u8::BITS) as u8, len as u8]),
+        _ => panic!("Unsupported DER Length"),
+    }
+    der.extend_from_slice(value);
+    der
+}
+
+/// Encode a boolean
+pub fn boolean(val: bool) -> Vec<u8> {
+    tlv(BOOLEAN_TAG, &[if val { 0xFF } else { 0x00 }])
+}
+
+/// Encode a big-endian unsigned integer
+pub fn uint(val: &[u8]) -> Vec<u8> {
+    let mut val = match val.iter().position(|byte| *byte != 0) {
+        Some(idx) => val[idx..].to_vec(),
+        None => vec![0],
+    };
+    if val[0] & 0x80 != 0 {
+        val.insert(0, 0);
+    }
+    tlv(INTEGER_TAG, &val)
+}
+
+/// Encode a bit string
+///
+/// # Arguments
+///
+/// * `val`         - Bits
+/// * `unused_bits` - Number of unused bits in the last byte
+pub fn bit_string(val: &[u8], unused_bits: u8) -> Vec<u8> {
+    let mut bits = vec![unused_bits];
+    bits.extend_from_slice(val);
+    tlv(BIT_STRING_TAG, &bits)
+}
+
+/// Encode an octet string
+pub fn octet_string(val: &[u8]) -> Vec<u8> {
+    tlv(OCTET_STRING_TAG, val)
+}
+
+/// Encode an object identifier
+pub fn oid(arcs: &[u32]) -> Vec<u8> {
+    assert!(arcs.len() >= 2);
+    let mut val = vec![];
+    for arc in [arcs[0] * 40 + arcs[1]].iter().chain(&arcs[2..]) {
+        let mut base128 = vec![(arc & 0x7F) as u8];
+        let mut arc = arc >> 7;
+        while arc != 0 {
+            base128.insert(0, (arc & 0x7F) as u8 | 0x80);
+            arc >>= 7;
+        }
+        val.extend(base128);
+    }
+    tlv(OID_TAG, &val)
+}
+
+/// Encode a UTF8 string
+pub fn utf8_string(val: &str) -> Vec<u8> {
+    tlv(UTF8_STRING_TAG, val.as_bytes())
+}
+
+/// Encode a printable string
+pub fn printable_string(val: &str) -> Vec<u8> {
+    assert!(val
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || " '()+,-./:=?".contains(c)));
+    tlv(PRINTABLE_STRING_TAG, val.as_bytes())
+}
+
+/// Encode a generalized time in `YYYYMMDDHHMMSSZ` format
+pub fn generalized_time(val: &str) -> Vec<u8> {
+    assert_eq!(val.len(), 15);
+    tlv(GENERALIZED_TIME_TAG, val.as_bytes())
+}
+
+/// Encode a sequence of DER encoded elements
+pub fn seq(elems: &[Vec<u8>]) -> Vec<u8> {
+    tlv(SEQUENCE_TAG, &elems.concat())
+}
+
+/// Encode a set of DER encoded elements
+pub fn set(elems: &[Vec<u8>]) -> Vec<u8> {
+    let mut elems = elems.to_vec();
+    elems.sort();
+    tlv(SET_TAG, &elems.concat())
+}
+
+/// Replace the tag of a DER encoded element with a context specific tag
+pub fn implicit(tag: u8, der: Vec<u8>) -> Vec<u8> {
+    let mut der = der;
+    der[0] = CONTEXT_CLASS | (der[0] & CONSTRUCTED) | tag;
+    der
+}
+
+/// Wrap a DER encoded element in a context specific tag
+pub fn explicit(tag: u8, der: Vec<u8>) -> Vec<u8> {
+    tlv(CONTEXT_CLASS | CONSTRUCTED | tag, &der)
+}
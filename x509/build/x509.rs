@@ -16,22 +16,13 @@ use caliptra_common::dice;
 
 use hex::ToHex;
 
-use openssl::bn::BigNumContext;
-use openssl::ec::EcGroup;
-use openssl::ec::EcKey;
-use openssl::ec::PointConversionForm;
-use openssl::hash::MessageDigest;
-use openssl::nid::Nid;
-use openssl::pkey::PKey;
-use openssl::pkey::Private;
-use openssl::sha::Sha1;
-use openssl::sha::Sha256;
-use openssl::x509::extension::BasicConstraints;
-use openssl::x509::extension::KeyUsage as Usage;
-use openssl::x509::extension::SubjectKeyIdentifier;
-use openssl::x509::X509Extension;
-use openssl::x509::X509v3Context;
+use p384::elliptic_curve::sec1::ToEncodedPoint;
+use p384::SecretKey;
+use rand_core::OsRng;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
 
+use crate::der;
 use crate::tbs::TbsParam;
 
 const FLAG_MASK: u32 = dice::FLAG_BIT_NOT_CONFIGURED
@@ -39,26 +30,55 @@ const FLAG_MASK: u32 = dice::FLAG_BIT_NOT_CONFIGURED
     | dice::FLAG_BIT_DEBUG
     | dice::FLAG_BIT_FIXED_WIDTH;
 
+/// id-ecPublicKey
+const EC_PUBLIC_KEY_OID: &[u32] = &[1, 2, 840, 10045, 2, 1];
+
+/// secp384r1
+const SECP384R1_OID: &[u32] = &[1, 3, 132, 0, 34];
+
+/// ecdsa-with-SHA384
+const ECDSA_WITH_SHA384_OID: &[u32] = &[1, 2, 840, 10045, 4, 3, 3];
+
+/// id-at-commonName
+const COMMON_NAME_OID: &[u32] = &[2, 5, 4, 3];
+
+/// id-at-serialNumber
+const SERIAL_NUMBER_OID: &[u32] = &[2, 5, 4, 5];
+
+/// id-ce-basicConstraints
+const BASIC_CONSTRAINTS_OID: &[u32] = &[2, 5, 29, 19];
+
+/// id-ce-keyUsage
+const KEY_USAGE_OID: &[u32] = &[2, 5, 29, 15];
+
+/// id-ce-subjectKeyIdentifier
+const SUBJECT_KEY_ID_OID: &[u32] = &[2, 5, 29, 14];
+
+/// id-ce-authorityKeyIdentifier
+const AUTHORITY_KEY_ID_OID: &[u32] = &[2, 5, 29, 35];
+
+/// tcg-dice-Ueid
+const TCG_UEID_OID: &[u32] = &[2, 23, 133, 5, 4, 4];
+
+/// tcg-dice-TcbInfo
+const TCG_TCB_INFO_OID: &[u32] = &[2, 23, 133, 5, 4, 1];
+
 /// Asymmetric Key
 pub trait AsymKey: Default {
-    /// Retrieve Private Key
-    fn priv_key(&self) -> &PKey<Private>;
-
     /// Retrieve Public Key
     fn pub_key(&self) -> &[u8];
 
+    /// Retrieve the DER encoded Subject Public Key Info
+    fn pub_key_info(&self) -> Vec<u8>;
+
     /// Retrieve SHA-256 digest of the public key
     fn sha256(&self) -> [u8; 32] {
-        let mut sha = Sha256::new();
-        sha.update(self.pub_key());
-        sha.finish()
+        Sha256::digest(self.pub_key()).into()
     }
 
     /// Retrieve SHA1 digest of the public key
     fn sha1(&self) -> [u8; 20] {
-        let mut sha = Sha1::new();
-        sha.update(self.pub_key());
-        sha.finish()
+        Sha1::digest(self.pub_key()).into()
     }
 
     /// Retrieve the hex string of SHA-256 Digest of the public key
@@ -67,81 +87,63 @@ pub trait AsymKey: Default {
     }
 }
 
-/// Digest
-pub trait Digest {
-    /// Digest Algorithm
-    fn algo() -> MessageDigest;
-}
-
 /// Signing Algorithm
 pub trait SigningAlgorithm: Default {
     type AsymKey: AsymKey;
-    type Digest: Digest;
 
     /// Generate Asymmetric Key Pair
     fn gen_key(&self) -> Self::AsymKey;
 
-    /// Retrieve digest algorithm
-    fn digest(&self) -> MessageDigest {
-        Self::Digest::algo()
-    }
+    /// Retrieve the DER encoded signature algorithm identifier
+    fn algo_id(&self) -> Vec<u8>;
 }
 
 /// ECC-348 Asymmetric Key Pair
+///
+/// Templates are never signed, so only the public key is kept.
 pub struct Ecc384AsymKey {
-    priv_key: PKey<Private>,
     pub_key: Vec<u8>,
 }
 
 impl AsymKey for Ecc384AsymKey {
-    /// Retrieve Private Key
-    fn priv_key(&self) -> &PKey<Private> {
-        &self.priv_key
-    }
-
     /// Retrieve Public Key
     fn pub_key(&self) -> &[u8] {
         &self.pub_key
     }
+
+    /// Retrieve the DER encoded Subject Public Key Info
+    fn pub_key_info(&self) -> Vec<u8> {
+        der::seq(&[
+            der::seq(&[der::oid(EC_PUBLIC_KEY_OID), der::oid(SECP384R1_OID)]),
+            der::bit_string(&self.pub_key, 0),
+        ])
+    }
 }
 
 impl Default for Ecc384AsymKey {
     /// Returns the "default value" for a type.
     fn default() -> Self {
-        let ecc_group = EcGroup::from_curve_name(Nid::SECP384R1).unwrap();
-        let priv_key = EcKey::generate(&ecc_group).unwrap();
-        let mut bn_ctx = BigNumContext::new().unwrap();
-        let pub_key = priv_key
-            .public_key()
-            .to_bytes(&ecc_group, PointConversionForm::UNCOMPRESSED, &mut bn_ctx)
-            .unwrap();
+        let priv_key = SecretKey::random(&mut OsRng);
+        let pub_key = priv_key.public_key().to_encoded_point(false);
         Self {
-            priv_key: PKey::from_ec_key(priv_key).unwrap(),
-            pub_key,
+            pub_key: pub_key.as_bytes().to_vec(),
         }
     }
 }
 
-/// SHA2-384 Algorithm
-pub struct Sha384 {}
-
-impl Digest for Sha384 {
-    /// Retrieve the algorithm
-    fn algo() -> MessageDigest {
-        MessageDigest::sha384()
-    }
-}
-
 #[derive(Default)]
 pub struct EcdsaSha384Algo {}
 
 impl SigningAlgorithm for EcdsaSha384Algo {
     type AsymKey = Ecc384AsymKey;
-    type Digest = Sha384;
 
     fn gen_key(&self) -> Self::AsymKey {
         Self::AsymKey::default()
     }
+
+    fn algo_id(&self) -> Vec<u8> {
+        der::seq(&[der::oid(ECDSA_WITH_SHA384_OID)])
+    }
 }
 
 bitfield::bitfield! {
@@ -160,106 +162,81 @@ bitfield::bitfield! {
     pub decipher_only, set_decipher_only: 8;
 }
 
-impl From<KeyUsage> for Usage {
-    /// Converts to this type from the input type.
-    fn from(value: KeyUsage) -> Self {
-        let mut usage = Usage::new();
-
-        if value.digital_signature() {
-            usage.digital_signature();
-        }
-
-        if value.non_repudiation() {
-            usage.non_repudiation();
-        }
-
-        if value.key_encipherment() {
-            usage.key_encipherment();
+impl KeyUsage {
+    /// DER encode the key usage as a named bit list
+    fn to_der(self) -> Vec<u8> {
+        // Bit 0 is the most significant bit of the first byte
+        let mut bits = [0u8; 2];
+        for bit in 0..u16::BITS as usize {
+            if self.0 & (1 << bit) != 0 {
+                bits[bit / 8] |= 0x80 >> (bit % 8);
+            }
         }
 
-        if value.data_encipherment() {
-            usage.data_encipherment();
-        }
-
-        if value.key_agreement() {
-            usage.key_agreement();
-        }
-
-        if value.key_cert_sign() {
-            usage.key_cert_sign();
-        }
-
-        if value.crl_sign() {
-            usage.crl_sign();
-        }
-
-        if value.encipher_only() {
-            usage.encipher_only();
-        }
-
-        if value.decipher_only() {
-            usage.decipher_only();
-        }
+        // Trailing zero bits are removed from a named bit list
+        let len = bits
+            .iter()
+            .rposition(|byte| *byte != 0)
+            .map_or(0, |idx| idx + 1);
+        let unused_bits = bits[..len].last().map_or(0, |byte| byte.trailing_zeros());
+        der::bit_string(&bits[..len], unused_bits as u8)
+    }
+}
 
-        usage
+/// Make X509 Extension
+fn make_ext(oid: &[u32], critical: bool, value: Vec<u8>) -> Vec<u8> {
+    let mut ext = vec![der::oid(oid)];
+    if critical {
+        ext.push(der::boolean(true));
     }
+    ext.push(der::octet_string(&value));
+    der::seq(&ext)
+}
+
+/// Make X509 Name from the common name and the serial number
+pub fn make_name(cn: &str, serial_number: &str) -> Vec<u8> {
+    der::seq(&[
+        der::set(&[der::seq(&[der::oid(COMMON_NAME_OID), der::utf8_string(cn)])]),
+        der::set(&[der::seq(&[
+            der::oid(SERIAL_NUMBER_OID),
+            der::printable_string(serial_number),
+        ])]),
+    ])
 }
 
 /// Make X509 Basic Constraints Extension
-pub fn make_basic_constraints_ext(ca: bool, path_len: u32) -> X509Extension {
-    let mut ext = BasicConstraints::new();
+pub fn make_basic_constraints_ext(ca: bool, path_len: u32) -> Vec<u8> {
+    let mut constraints = vec![];
     if ca {
-        ext.ca();
+        constraints.push(der::boolean(true));
+        constraints.push(der::uint(&path_len.to_be_bytes()));
     }
-    ext.critical().pathlen(path_len).build().unwrap()
+    make_ext(BASIC_CONSTRAINTS_OID, true, der::seq(&constraints))
 }
 
 /// Make Key Usage Extension
-pub fn make_key_usage_ext(key_usage: KeyUsage) -> X509Extension {
-    let usage: Usage = key_usage.into();
-    usage.build().unwrap()
+pub fn make_key_usage_ext(key_usage: KeyUsage) -> Vec<u8> {
+    make_ext(KEY_USAGE_OID, false, key_usage.to_der())
 }
 
 /// Make TCG UEID extension
-pub fn make_tcg_ueid_ext(ueid: &[u8]) -> X509Extension {
-    #[derive(asn1::Asn1Read, asn1::Asn1Write)]
-    struct TcgUeid<'a> {
-        ueid: &'a [u8],
-    }
-
-    let tcg_ueid = TcgUeid { ueid };
-    let der = asn1::write_single(&tcg_ueid).unwrap();
-    let der_str = format!("DER:{}", hex::encode(der).to_uppercase());
-
-    X509Extension::new(None, None, "2.23.133.5.4.4", &der_str).unwrap()
+pub fn make_tcg_ueid_ext(ueid: &[u8]) -> Vec<u8> {
+    make_ext(TCG_UEID_OID, false, der::seq(&[der::octet_string(ueid)]))
 }
 
 /// Make Subject Key ID extension
-pub fn make_subj_key_id_ext(ctx: &X509v3Context) -> X509Extension {
-    SubjectKeyIdentifier::new().build(ctx).unwrap()
+pub fn make_subj_key_id_ext(key_id: &[u8]) -> Vec<u8> {
+    make_ext(SUBJECT_KEY_ID_OID, false, der::octet_string(key_id))
 }
 
 /// Make Authority Key ID extension
-pub fn make_auth_key_id_ext(key_id: &[u8]) -> X509Extension {
-    #[derive(asn1::Asn1Read, asn1::Asn1Write)]
-    struct AuthKeyId<'a> {
-        #[implicit(0)]
-        key_id: Option<&'a [u8]>,
-    }
-
-    let auth_key_id = AuthKeyId {
-        key_id: Some(key_id),
-    };
-
-    let der = asn1::write_single(&auth_key_id).unwrap();
-    let der_str = format!("DER:{}", hex::encode(der).to_uppercase());
-
-    X509Extension::new(None, None, "2.5.29.35", &der_str).unwrap()
+pub fn make_auth_key_id_ext(key_id: &[u8]) -> Vec<u8> {
+    let auth_key_id = der::seq(&[der::implicit(0, der::octet_string(key_id))]);
+    make_ext(AUTHORITY_KEY_ID_OID, false, auth_key_id)
 }
 
-#[derive(asn1::Asn1Read, asn1::Asn1Write)]
 pub struct Fwid<'a> {
-    pub(crate) hash_alg: asn1::ObjectIdentifier,
+    pub(crate) hash_alg: &'a [u32],
     pub(crate) digest: &'a [u8],
 }
 
@@ -274,107 +251,63 @@ pub fn make_fmc_dice_tcb_info_ext(
     svn: u8,
     min_svn: u8,
     fwids: &[FwidParam],
-) -> X509Extension {
+) -> Vec<u8> {
     make_dice_tcb_info_ext_helper(
-        asn1::BitString::new(flags.to_be_bytes().as_ref(), 0),
-        asn1::BitString::new(FLAG_MASK.to_be_bytes().as_ref(), 0),
+        Some(flags),
+        Some(FLAG_MASK),
         Some((1_u32 << 17) | ((svn as u32) << 8) | (min_svn as u32)),
+        None,
         fwids,
     )
 }
 
 // Make a tcg-dice-TcbInfo extension
-pub fn make_rt_dice_tcb_info_ext(fwids: &[FwidParam]) -> X509Extension {
-    make_dice_tcb_info_ext_helper(None, None, None, fwids)
+pub fn make_rt_dice_tcb_info_ext(fwids: &[FwidParam]) -> Vec<u8> {
+    make_dice_tcb_info_ext_helper(None, None, None, None, fwids)
+}
+
+// Make a tcg-dice-TcbInfo extension
+pub fn make_dpe_dice_tcb_info_ext(tcb_type: &[u8], fwids: &[FwidParam]) -> Vec<u8> {
+    make_dice_tcb_info_ext_helper(None, None, None, Some(tcb_type), fwids)
 }
 
 fn make_dice_tcb_info_ext_helper(
-    flags: Option<asn1::BitString>,
-    flags_mask: Option<asn1::BitString>,
+    flags: Option<u32>,
+    flags_mask: Option<u32>,
     svn: Option<u32>,
+    tcb_type: Option<&[u8]>,
     fwids: &[FwidParam],
-) -> X509Extension {
-    #[derive(asn1::Asn1Write)]
-    struct TcbInfo<'a> {
-        #[implicit(0)]
-        vendor: Option<asn1::Utf8String<'a>>,
-        #[implicit(1)]
-        model: Option<asn1::Utf8String<'a>>,
-        #[implicit(2)]
-        version: Option<asn1::Utf8String<'a>>,
-        #[implicit(3)]
-        svn: Option<u32>,
-        #[implicit(4)]
-        layer: Option<u64>,
-        #[implicit(5)]
-        index: Option<u64>,
-        #[implicit(6)]
-        fwids: Option<asn1::SequenceOfWriter<'a, &'a Fwid<'a>>>,
-        #[implicit(7)]
-        flags: Option<asn1::BitString<'a>>,
-        #[implicit(8)]
-        vendor_info: Option<&'a [u8]>,
-        #[implicit(9)]
-        tcb_type: Option<&'a [u8]>,
-        #[implicit(10)]
-        flags_mask: Option<asn1::BitString<'a>>,
-    }
-
-    let asn1_fwids: Vec<&Fwid> = fwids.iter().map(|f| &f.fwid).collect();
-
-    let tcb_info = TcbInfo {
-        vendor: Some(asn1::Utf8String::new("Caliptra")),
-        model: None,
-        version: None,
-        svn,
-        layer: None,
-        index: None,
-        fwids: Some(asn1::SequenceOfWriter::new(&asn1_fwids)),
-        flags,
-        vendor_info: None,
-        tcb_type: None,
-        flags_mask,
-    };
-
-    let der = asn1::write_single(&tcb_info).unwrap();
-    let der_str = format!("DER:{}", hex::encode(der).to_uppercase());
-    X509Extension::new(None, None, "2.23.133.5.4.1", &der_str).unwrap()
-}
+) -> Vec<u8> {
+    // TcbInfo fields are IMPLICIT tagged and encoded in tag order:
+    // vendor [0], svn [3], fwids [6], flags [7], tcb_type [9], flags_mask [10]
+    let mut tcb_info = vec![der::implicit(0, der::utf8_string("Caliptra"))];
 
-/// Retrieve the TBS from DER encoded vector
-///
-/// Note: Rust OpenSSL binding is missing the extensions to retrieve TBS portion of the X509
-/// artifact
-pub fn get_tbs(der: Vec<u8>) -> Vec<u8> {
-    if der[0] != 0x30 {
-        panic!("Invalid DER start tag");
+    if let Some(svn) = svn {
+        tcb_info.push(der::implicit(3, der::uint(&svn.to_be_bytes())));
     }
 
-    let der_len_offset = 1;
+    let fwids: Vec<Vec<u8>> = fwids
+        .iter()
+        .map(|f| der::seq(&[der::oid(f.fwid.hash_alg), der::octet_string(f.fwid.digest)]))
+        .collect();
+    tcb_info.push(der::implicit(6, der::seq(&fwids)));
 
-    let tbs_offset = match der[der_len_offset] {
-        0..=0x7F => der_len_offset + 1,
-        0x81 => der_len_offset + 2,
-        0x82 => der_len_offset + 3,
-        _ => panic!("Unsupported DER Length"),
-    };
+    if let Some(flags) = flags {
+        tcb_info.push(der::implicit(7, der::bit_string(&flags.to_be_bytes(), 0)));
+    }
 
-    if der[tbs_offset] != 0x30 {
-        panic!("Invalid TBS start tag");
+    if let Some(tcb_type) = tcb_type {
+        tcb_info.push(der::implicit(9, der::octet_string(tcb_type)));
     }
 
-    let tbs_len_offset = tbs_offset + 1;
-    let tbs_len = match der[tbs_len_offset] {
-        0..=0x7F => der[tbs_len_offset] as usize + 2,
-        0x81 => (der[tbs_len_offset + 1]) as usize + 3,
-        0x82 => {
-            (((der[tbs_len_offset + 1]) as usize) << u8::BITS)
-                | (((der[tbs_len_offset + 2]) as usize) + 4)
-        }
-        _ => panic!("Invalid DER Length"),
-    };
+    if let Some(flags_mask) = flags_mask {
+        tcb_info.push(der::implicit(
+            10,
+            der::bit_string(&flags_mask.to_be_bytes(), 0),
+        ));
+    }
 
-    der[tbs_offset..tbs_offset + tbs_len].to_vec()
+    make_ext(TCG_TCB_INFO_OID, false, der::seq(&tcb_info))
 }
 
 /// Initialize template parameter with its offset
@@ -0,0 +1,135 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+    dpe_leaf_cert.rs
+
+Abstract:
+
+    DPE Leaf Certificate related code.
+
+--*/
+
+// Note: All the necessary code is auto generated
+include!(concat!(env!("OUT_DIR"), "/dpe_leaf_cert_tbs.rs"));
+
+#[cfg(all(test, target_family = "unix"))]
+mod tests {
+    use openssl::ecdsa::EcdsaSig;
+    use openssl::sha::Sha384;
+    use openssl::x509::X509;
+
+    use super::*;
+    use crate::test_util::tests::*;
+    use crate::{NotAfter, NotBefore};
+
+    #[test]
+    fn test_cert_signing() {
+        let subject_key = Ecc384AsymKey::default();
+        let issuer_key = Ecc384AsymKey::default();
+        let ec_key = issuer_key.priv_key().ec_key().unwrap();
+
+        let params = DpeLeafCertTbsParams {
+            serial_number: &[0xABu8; DpeLeafCertTbsParams::SERIAL_NUMBER_LEN],
+            public_key: TryInto::<&[u8; DpeLeafCertTbsParams::PUBLIC_KEY_LEN]>::try_into(
+                subject_key.pub_key(),
+            )
+            .unwrap(),
+            subject_sn: &TryInto::<[u8; DpeLeafCertTbsParams::SUBJECT_SN_LEN]>::try_into(
+                subject_key.hex_str().into_bytes(),
+            )
+            .unwrap(),
+            issuer_sn: &TryInto::<[u8; DpeLeafCertTbsParams::ISSUER_SN_LEN]>::try_into(
+                issuer_key.hex_str().into_bytes(),
+            )
+            .unwrap(),
+            ueid: &[0xAB; DpeLeafCertTbsParams::UEID_LEN],
+            subject_key_id: &TryInto::<[u8; DpeLeafCertTbsParams::SUBJECT_KEY_ID_LEN]>::try_into(
+                subject_key.sha1(),
+            )
+            .unwrap(),
+            authority_key_id: &TryInto::<[u8; DpeLeafCertTbsParams::SUBJECT_KEY_ID_LEN]>::try_into(
+                issuer_key.sha1(),
+            )
+            .unwrap(),
+            tcb_info_type: b"RTJM",
+            tcb_info_dpe_journey: &[0xEFu8; DpeLeafCertTbsParams::TCB_INFO_DPE_JOURNEY_LEN],
+            tcb_info_dpe_current: &[0xCDu8; DpeLeafCertTbsParams::TCB_INFO_DPE_CURRENT_LEN],
+            not_before: &NotBefore::default().not_before,
+            not_after: &NotAfter::default().not_after,
+        };
+
+        let cert = DpeLeafCertTbs::new(&params);
+
+        let sig = cert
+            .sign(|b| {
+                let mut sha = Sha384::new();
+                sha.update(b);
+                EcdsaSig::sign(&sha.finish(), &ec_key)
+            })
+            .unwrap();
+
+        assert_ne!(cert.tbs(), DpeLeafCertTbs::TBS_TEMPLATE);
+        assert_eq!(
+            &cert.tbs()[DpeLeafCertTbs::PUBLIC_KEY_OFFSET
+                ..DpeLeafCertTbs::PUBLIC_KEY_OFFSET + DpeLeafCertTbs::PUBLIC_KEY_LEN],
+            params.public_key,
+        );
+        assert_eq!(
+            &cert.tbs()[DpeLeafCertTbs::SUBJECT_SN_OFFSET
+                ..DpeLeafCertTbs::SUBJECT_SN_OFFSET + DpeLeafCertTbs::SUBJECT_SN_LEN],
+            params.subject_sn,
+        );
+        assert_eq!(
+            &cert.tbs()[DpeLeafCertTbs::ISSUER_SN_OFFSET
+                ..DpeLeafCertTbs::ISSUER_SN_OFFSET + DpeLeafCertTbs::ISSUER_SN_LEN],
+            params.issuer_sn,
+        );
+        assert_eq!(
+            &cert.tbs()[DpeLeafCertTbs::UEID_OFFSET
+                ..DpeLeafCertTbs::UEID_OFFSET + DpeLeafCertTbs::UEID_LEN],
+            params.ueid,
+        );
+        assert_eq!(
+            &cert.tbs()[DpeLeafCertTbs::SUBJECT_KEY_ID_OFFSET
+                ..DpeLeafCertTbs::SUBJECT_KEY_ID_OFFSET + DpeLeafCertTbs::SUBJECT_KEY_ID_LEN],
+            params.subject_key_id,
+        );
+        assert_eq!(
+            &cert.tbs()[DpeLeafCertTbs::AUTHORITY_KEY_ID_OFFSET
+                ..DpeLeafCertTbs::AUTHORITY_KEY_ID_OFFSET + DpeLeafCertTbs::AUTHORITY_KEY_ID_LEN],
+            params.authority_key_id,
+        );
+        assert_eq!(
+            &cert.tbs()[DpeLeafCertTbs::TCB_INFO_TYPE_OFFSET
+                ..DpeLeafCertTbs::TCB_INFO_TYPE_OFFSET + DpeLeafCertTbs::TCB_INFO_TYPE_LEN],
+            params.tcb_info_type,
+        );
+        assert_eq!(
+            &cert.tbs()[DpeLeafCertTbs::TCB_INFO_DPE_JOURNEY_OFFSET
+                ..DpeLeafCertTbs::TCB_INFO_DPE_JOURNEY_OFFSET
+                    + DpeLeafCertTbs::TCB_INFO_DPE_JOURNEY_LEN],
+            params.tcb_info_dpe_journey,
+        );
+        assert_eq!(
+            &cert.tbs()[DpeLeafCertTbs::TCB_INFO_DPE_CURRENT_OFFSET
+                ..DpeLeafCertTbs::TCB_INFO_DPE_CURRENT_OFFSET
+                    + DpeLeafCertTbs::TCB_INFO_DPE_CURRENT_LEN],
+            params.tcb_info_dpe_current,
+        );
+
+        let ecdsa_sig = crate::Ecdsa384Signature {
+            r: TryInto::<[u8; 48]>::try_into(sig.r().to_vec_padded(48).unwrap()).unwrap(),
+            s: TryInto::<[u8; 48]>::try_into(sig.s().to_vec_padded(48).unwrap()).unwrap(),
+        };
+
+        let builder = crate::Ecdsa384CertBuilder::new(cert.tbs(), &ecdsa_sig).unwrap();
+        let mut buf = vec![0u8; builder.len()];
+        builder.build(&mut buf).unwrap();
+
+        let cert: X509 = X509::from_der(&buf).unwrap();
+        assert!(cert.verify(issuer_key.priv_key()).unwrap());
+    }
+}
@@ -15,6 +15,7 @@ Abstract:
 #![cfg_attr(not(feature = "std"), no_std)]
 
 mod cert_bldr;
+mod dpe_leaf_cert;
 mod fmc_alias_cert;
 mod idevid_csr;
 mod ldevid_cert;
@@ -22,6 +23,7 @@ mod rt_alias_cert;
 mod test_util;
 
 pub use cert_bldr::{Ecdsa384CertBuilder, Ecdsa384CsrBuilder, Ecdsa384Signature};
+pub use dpe_leaf_cert::{DpeLeafCertTbs, DpeLeafCertTbsParams};
 pub use fmc_alias_cert::{FmcAliasCertTbs, FmcAliasCertTbsParams};
 pub use idevid_csr::{InitDevIdCsrTbs, InitDevIdCsrTbsParams};
 pub use ldevid_cert::{LocalDevIdCertTbs, LocalDevIdCertTbsParams};